@@ -0,0 +1,158 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::normalize::normalize_pattern;
+use crate::options::Anchor;
+use crate::{Matcher, SearchError, SearchOptions, SpanMatch, is_word_bounded};
+
+/// 編集距離を許してパターンを探すマッチャー
+///
+/// パターンは正規表現ではなく文字列として扱い、置換・挿入・削除を合わせて
+/// `max_edits` 回以内の違いで一致する部分文字列を探す（Sellers のアルゴリズム）。
+#[derive(Clone)]
+pub(crate) struct ApproxMatcher {
+    /// 文字単位に分解したパターン
+    patterns: Vec<Vec<char>>,
+    /// 許容する編集距離
+    max_edits: usize,
+    /// 大文字小文字を区別するかどうか
+    case_sensitive: bool,
+    /// 単語単位でのみマッチさせるかどうか
+    whole_word: bool,
+    /// マッチの位置の固定方法
+    anchor: Anchor,
+}
+
+/// 編集距離の表の1マス
+#[derive(Clone, Copy)]
+struct Cell {
+    /// ここまでの最小の編集距離
+    cost: usize,
+    /// その編集距離を与えるマッチの開始位置（文字単位）
+    start: usize,
+}
+
+impl Cell {
+    /// 編集距離が小さい方を、同じなら開始位置が後ろ（マッチが短い）方を選ぶ
+    fn min(self, other: Cell) -> Cell {
+        if (other.cost, usize::MAX - other.start) < (self.cost, usize::MAX - self.start) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl ApproxMatcher {
+    /// パターンと許容する編集距離からマッチャーを作成する
+    pub(crate) fn new(
+        patterns: &[&str],
+        max_edits: usize,
+        options: &SearchOptions,
+    ) -> Result<Self, SearchError> {
+        let literal_options = options.clone().literal(true);
+        let patterns: Vec<Vec<char>> = patterns
+            .iter()
+            .map(|p| normalize_pattern(p, &literal_options).chars().collect())
+            .collect();
+        if let Some(pattern) = patterns.iter().find(|p| p.len() <= max_edits) {
+            return Err(SearchError::EditDistanceTooLarge {
+                pattern: pattern.iter().collect(),
+                max_edits,
+            });
+        }
+
+        Ok(Self {
+            patterns,
+            max_edits,
+            case_sensitive: options.case_sensitive,
+            whole_word: options.whole_word,
+            anchor: options.anchor,
+        })
+    }
+
+    /// 行内で指定したパターンに近い部分のバイト範囲を返す
+    ///
+    /// 重なり合う候補の中からは編集距離が最も小さいものを1つだけ選び、
+    /// 同じ距離ならパターンの長さに近いものを選ぶ。
+    pub(crate) fn find(&self, pattern_index: usize, line: &str) -> Vec<Range<usize>> {
+        let pattern = &self.patterns[pattern_index];
+        let chars: Vec<(usize, char)> = line.char_indices().collect();
+        let byte_at = |idx: usize| chars.get(idx).map_or(line.len(), |&(offset, _)| offset);
+
+        let mut column: Vec<Cell> = (0..=pattern.len())
+            .map(|cost| Cell { cost, start: 0 })
+            .collect();
+        let mut candidates = Vec::new();
+
+        for (t, &(_, c)) in chars.iter().enumerate() {
+            let mut next = Vec::with_capacity(column.len());
+            next.push(Cell {
+                cost: 0,
+                start: t + 1,
+            });
+            for i in 1..=pattern.len() {
+                let diagonal = Cell {
+                    cost: column[i - 1].cost + usize::from(!self.chars_eq(pattern[i - 1], c)),
+                    start: column[i - 1].start,
+                };
+                let deletion = Cell {
+                    cost: next[i - 1].cost + 1,
+                    start: next[i - 1].start,
+                };
+                let insertion = Cell {
+                    cost: column[i].cost + 1,
+                    start: column[i].start,
+                };
+                next.push(diagonal.min(deletion).min(insertion));
+            }
+            column = next;
+
+            let last = column[pattern.len()];
+            if last.cost <= self.max_edits {
+                let span = byte_at(last.start)..byte_at(t + 1);
+                if (!self.whole_word || is_word_bounded(line, span.clone()))
+                    && self.anchor.accepts(line, &span)
+                {
+                    let length_diff = (t + 1 - last.start).abs_diff(pattern.len());
+                    candidates.push((span, (last.cost, length_diff)));
+                }
+            }
+        }
+
+        // 重なり合う候補をまとめ、それぞれから最も良いものを選ぶ
+        let mut found: Vec<(Range<usize>, (usize, usize))> = Vec::new();
+        for (span, rank) in candidates {
+            match found.last_mut() {
+                Some((best, best_rank)) if span.start < best.end => {
+                    if rank < *best_rank {
+                        *best = span;
+                        *best_rank = rank;
+                    }
+                }
+                _ => found.push((span, rank)),
+            }
+        }
+        found.into_iter().map(|(span, _)| span).collect()
+    }
+
+    /// オプションに従って2つの文字が等しいかどうかを返す
+    fn chars_eq(&self, a: char, b: char) -> bool {
+        a == b || (!self.case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+    }
+}
+
+/// すべてのパターンについて近い部分を探し、開始位置の順に返す
+impl Matcher for ApproxMatcher {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found: Vec<SpanMatch> = (0..self.patterns.len())
+            .flat_map(|pattern_index| {
+                self.find(pattern_index, line)
+                    .into_iter()
+                    .map(move |span| SpanMatch::new(pattern_index, span))
+            })
+            .collect();
+        found.sort_by_key(|m| (m.span.start, m.pattern_index));
+        found
+    }
+}
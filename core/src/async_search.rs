@@ -0,0 +1,313 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use core::error::Error;
+#[cfg(feature = "std")]
+use core::future;
+use core::future::Future;
+use core::pin::Pin;
+use core::slice;
+use core::task::{Context, Poll, ready};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use futures_core::Stream;
+
+#[cfg(feature = "std")]
+use crate::ContentProvider;
+use crate::iter::MatchCursor;
+use crate::{FileInput, MatchResult, SearchError, SearchOptions, compile_patterns};
+
+/// 内容を取得中のファイルのフューチャー
+type Loading<'a> =
+    Pin<Box<dyn Future<Output = Result<Cow<'a, str>, Box<dyn Error + Send + Sync>>> + Send + 'a>>;
+
+/// ファイルの内容を非同期に取得するための提供元
+///
+/// `ContentProvider` の非同期版。ネットワークや非同期ファイル I/O で内容を取得する場合に
+/// 実装し、`search_async` に渡して使う。返すフューチャーは `Send` でなければならないため、
+/// `tokio::spawn` などで別のタスクから検索できる。
+///
+/// `HashMap<String, String>`（パスから内容への対応）には実装済み。
+pub trait AsyncContentProvider: Sync {
+    /// `path` のファイルの内容を返す
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - `search_async` に渡したパス
+    ///
+    /// # Returns
+    ///
+    /// ファイルの内容、または取得できなかった場合のエラーを返すフューチャー
+    fn content(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<Cow<'_, str>, Box<dyn Error + Send + Sync>>> + Send;
+}
+
+#[cfg(feature = "std")]
+impl AsyncContentProvider for HashMap<String, String> {
+    fn content(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<Cow<'_, str>, Box<dyn Error + Send + Sync>>> + Send {
+        future::ready(ContentProvider::content(self, path))
+    }
+}
+
+/// 非同期に取得した内容の検索結果を見つかった順に返す `Stream`
+///
+/// `search_async` で作る。内容の取得を待つ間は呼び出し元のタスクに制御を返し、取得できた
+/// ファイルから順に検索して結果を返す。内容を取得できなかった場合は
+/// `SearchError::ContentUnavailable` を返して終わる。
+pub struct AsyncMatches<'a, P: ?Sized> {
+    /// まだ内容を取得していないファイルのパス
+    paths: slice::Iter<'a, &'a str>,
+    /// ファイルの内容の提供元
+    provider: &'a P,
+    /// 内容を取得中のファイルのパスとフューチャー
+    loading: Option<(&'a str, Loading<'a>)>,
+    /// 検索の状態
+    cursor: MatchCursor<'a>,
+    /// 内容を取得できずに終わったかどうか
+    failed: bool,
+}
+
+impl<P: ?Sized> AsyncMatches<'_, P> {
+    /// `max_results` の上限に達して検索を打ち切ったかどうかを返す
+    pub fn truncated(&self) -> bool {
+        self.cursor.truncated
+    }
+
+    /// `timeout_ms` の時間を過ぎて検索を打ち切ったかどうかを返す
+    pub fn timed_out(&self) -> bool {
+        self.cursor.timed_out
+    }
+
+    /// `cancel_token` で打ち切りを求められて検索を打ち切ったかどうかを返す
+    pub fn cancelled(&self) -> bool {
+        self.cursor.cancelled
+    }
+}
+
+impl<P: AsyncContentProvider + ?Sized> Stream for AsyncMatches<'_, P> {
+    type Item = Result<MatchResult, SearchError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(result) = this.cursor.next_pending() {
+                return Poll::Ready(Some(Ok(result)));
+            }
+            if this.failed || this.cursor.is_stopped() {
+                return Poll::Ready(None);
+            }
+
+            let (path, loading) = match &mut this.loading {
+                Some(loading) => loading,
+                None => {
+                    let Some(&path) = this.paths.next() else {
+                        return Poll::Ready(None);
+                    };
+                    let loading: Loading<'_> = Box::pin(this.provider.content(path));
+                    this.loading.insert((path, loading))
+                }
+            };
+            let content = ready!(loading.as_mut().poll(cx));
+            let path = *path;
+            this.loading = None;
+
+            match content {
+                Ok(content) => this.cursor.search(&FileInput {
+                    path: path.to_string(),
+                    content: content.into_owned(),
+                }),
+                Err(source) => {
+                    this.failed = true;
+                    return Poll::Ready(Some(Err(SearchError::ContentUnavailable {
+                        path: path.to_string(),
+                        source: Arc::from(source),
+                    })));
+                }
+            }
+        }
+    }
+}
+
+/// ファイルの内容を `provider` から非同期に取得しながら検索し、結果を `Stream` で返す
+///
+/// `search_provider` の非同期版。`paths` の順に内容を取得して検索し、結果を見つかった順に
+/// 返す。内容の取得を待つ間は他のタスクが動けるため、tokio などのサーバーで I/O の待ち時間に
+/// ほかの処理を進められる。パターンのコンパイルはこの関数の中で済ませるため、無効な
+/// パターンは `Stream` を作る前にエラーになる。
+///
+/// `search_iter` と同じく `sort` は無視し、打ち切られたかどうかは
+/// `AsyncMatches::truncated` などで確かめられる。`async` フィーチャーで使える。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `paths` - 検索するファイルのパスのリスト
+/// * `provider` - ファイルの内容の非同期な提供元
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果の `Stream`、または正規表現パターンが無効な場合のエラー
+///
+// `HashMap` の `AsyncContentProvider` の実装は `std` フィーチャーでのみ使える
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use std::collections::HashMap;
+/// use std::future::poll_fn;
+/// use std::pin::Pin;
+///
+/// use futures_core::Stream;
+/// use simple_find_core::{SearchOptions, search_async};
+///
+/// let mut files = HashMap::new();
+/// files.insert("a.txt".to_string(), "foo\nbar".to_string());
+/// let options = SearchOptions::new();
+/// let mut matches = search_async("bar", &["a.txt"], &files, &options).unwrap();
+/// let first = futures_executor::block_on(poll_fn(|cx| Pin::new(&mut matches).poll_next(cx)));
+/// assert_eq!(first.unwrap().unwrap().line, 2);
+/// ```
+pub fn search_async<'a, P: AsyncContentProvider + ?Sized>(
+    pattern: &str,
+    paths: &'a [&'a str],
+    provider: &'a P,
+    options: &'a SearchOptions,
+) -> Result<AsyncMatches<'a, P>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(AsyncMatches {
+        paths: paths.iter(),
+        provider,
+        loading: None,
+        cursor: MatchCursor::new(compiled, options),
+        failed: false,
+    })
+}
+
+// テストは `HashMap` の実装を使う
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use std::future::poll_fn;
+    use std::sync::Mutex;
+
+    use futures_executor::block_on;
+
+    use super::*;
+    use crate::search_with_options;
+
+    /// 一度待たせてから内容を返し、取得したパスを記録する提供元
+    struct Delayed {
+        files: HashMap<String, String>,
+        requested: Mutex<Vec<String>>,
+    }
+
+    impl AsyncContentProvider for Delayed {
+        async fn content(&self, path: &str) -> Result<Cow<'_, str>, Box<dyn Error + Send + Sync>> {
+            self.requested.lock().unwrap().push(path.to_string());
+            let mut yielded = false;
+            poll_fn(|cx| {
+                if yielded {
+                    Poll::Ready(())
+                } else {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            })
+            .await;
+            ContentProvider::content(&self.files, path)
+        }
+    }
+
+    fn collect<P: AsyncContentProvider + ?Sized>(
+        matches: &mut AsyncMatches<'_, P>,
+    ) -> Vec<Result<MatchResult, SearchError>> {
+        block_on(async {
+            let mut results = Vec::new();
+            while let Some(result) = poll_fn(|cx| Pin::new(&mut *matches).poll_next(cx)).await {
+                results.push(result);
+            }
+            results
+        })
+    }
+
+    #[test]
+    fn test_search_async() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.bin".to_string(),
+                content: "foo\0".to_string(),
+            },
+            FileInput {
+                path: "c.txt".to_string(),
+                content: "foo bar".to_string(),
+            },
+        ];
+        let provider = Delayed {
+            files: files
+                .iter()
+                .map(|f| (f.path.clone(), f.content.clone()))
+                .collect(),
+            requested: Mutex::new(Vec::new()),
+        };
+        let paths = ["a.txt", "b.bin", "c.txt"];
+        let options = SearchOptions::new();
+        let mut matches = search_async("foo", &paths, &provider, &options).unwrap();
+        let results: Vec<_> = collect(&mut matches)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let expected = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(results, expected.results);
+
+        // 上限に達したら残りのファイルは取得しない
+        provider.requested.lock().unwrap().clear();
+        let options = SearchOptions::new().max_results(1);
+        let mut matches = search_async("foo", &paths, &provider, &options).unwrap();
+        assert_eq!(collect(&mut matches).len(), 1);
+        assert!(matches.truncated());
+        assert_eq!(
+            *provider.requested.lock().unwrap(),
+            vec!["a.txt".to_string()]
+        );
+
+        // 取得できなかったファイルでエラーを返して終わる
+        let options = SearchOptions::new();
+        let mut matches =
+            search_async("foo", &["missing.txt", "a.txt"], &provider, &options).unwrap();
+        let results = collect(&mut matches);
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0],
+            Err(SearchError::ContentUnavailable { ref path, .. }) if path == "missing.txt"
+        ));
+
+        assert!(search_async("(", &paths, &provider, &options).is_err());
+    }
+
+    #[test]
+    fn test_async_matches_is_send() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let files = HashMap::new();
+        let options = SearchOptions::new();
+        let matches = search_async("foo", &[], &files, &options).unwrap();
+        assert_send(&matches);
+    }
+}
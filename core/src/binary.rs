@@ -0,0 +1,38 @@
+use crate::floor_char_boundary;
+
+/// 判定に使うファイル先頭の長さ（バイト）
+const SAMPLE_LEN: usize = 8 * 1024;
+
+/// 置換文字の割合がこれを超えるとバイナリとみなす
+///
+/// 不正な UTF-8 を `String::from_utf8_lossy` や `TextDecoder` で変換すると
+/// U+FFFD（置換文字）に置き換わるため、その割合で元が不正だったかを推定する。
+const MAX_REPLACEMENT_RATIO: f64 = 0.1;
+
+/// 内容がバイナリファイルのものかどうかを推定する
+///
+/// 先頭 8 KiB に NUL 文字が含まれるか、置換文字（U+FFFD）の割合が 10% を超える場合に
+/// バイナリとみなす（git や ripgrep と同じく先頭だけを調べる）。
+///
+/// ```
+/// use simple_find_core::is_binary;
+///
+/// assert!(!is_binary("fn main() {}\n"));
+/// assert!(is_binary("\u{89}PNG\r\n\u{1a}\n\0\0\0\rIHDR"));
+/// ```
+pub fn is_binary(content: &str) -> bool {
+    let sample = &content[..floor_char_boundary(content, SAMPLE_LEN)];
+    if sample.contains('\0') {
+        return true;
+    }
+
+    let mut chars = 0;
+    let mut replacements = 0;
+    for c in sample.chars() {
+        chars += 1;
+        if c == char::REPLACEMENT_CHARACTER {
+            replacements += 1;
+        }
+    }
+    chars > 0 && replacements as f64 / chars as f64 > MAX_REPLACEMENT_RATIO
+}
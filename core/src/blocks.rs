@@ -0,0 +1,113 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::LineMatch;
+
+/// 前後のコンテキストを含めて連続する行をまとめたブロック
+pub struct ContextBlock {
+    /// ブロック内の行（行番号順、行番号は連続している）
+    pub lines: Vec<BlockLine>,
+}
+
+/// コンテキストブロック内の1行
+pub struct BlockLine {
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// 行のテキスト
+    pub text: String,
+    /// この行のマッチの `FileMatches::matches` 内の位置（コンテキスト行の場合は空）
+    pub matches: Vec<usize>,
+}
+
+/// 1ファイル分のマッチとその前後のコンテキストを、連続する行ごとのブロックにまとめる
+///
+/// ripgrep と同じく、重なったり隣り合ったりするコンテキストは1つのブロックにまとめ、
+/// 同じ行を2回含めない。マッチした行のテキストは `LineMatch::line_text` を使う。
+pub(crate) fn context_blocks(matches: &[LineMatch]) -> Vec<ContextBlock> {
+    let mut lines: BTreeMap<u32, BlockLine> = BTreeMap::new();
+    for (idx, line_match) in matches.iter().enumerate() {
+        lines
+            .entry(line_match.line)
+            .or_insert_with(|| BlockLine {
+                line: line_match.line,
+                text: line_match.line_text.to_string(),
+                matches: Vec::new(),
+            })
+            .matches
+            .push(idx);
+    }
+    for line_match in matches {
+        for context in line_match
+            .context_before
+            .iter()
+            .chain(&line_match.context_after)
+        {
+            lines.entry(context.line).or_insert_with(|| BlockLine {
+                line: context.line,
+                text: context.text.clone(),
+                matches: Vec::new(),
+            });
+        }
+    }
+
+    let mut blocks: Vec<ContextBlock> = Vec::new();
+    for (line, block_line) in lines {
+        match blocks.last_mut() {
+            Some(block) if block.lines.last().is_some_and(|last| last.line + 1 == line) => {
+                block.lines.push(block_line)
+            }
+            _ => blocks.push(ContextBlock {
+                lines: vec![block_line],
+            }),
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::{FileInput, SearchOptions, search_grouped};
+
+    #[test]
+    fn test_context_blocks_merge_overlapping_context() {
+        let content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content,
+        }];
+        let options = SearchOptions::new().before_context(1).after_context(1);
+        let output = search_grouped(r"line (2|4|9)\b", &files, &options).unwrap();
+        let blocks = &output.results[0].blocks;
+        let lines: Vec<Vec<(u32, usize)>> = blocks
+            .iter()
+            .map(|block| {
+                block
+                    .lines
+                    .iter()
+                    .map(|line| (line.line, line.matches.len()))
+                    .collect()
+            })
+            .collect();
+        // 2行目と4行目のコンテキストは3行目で重なるため1つのブロックになる
+        assert_eq!(
+            lines,
+            vec![
+                vec![(1, 0), (2, 1), (3, 0), (4, 1), (5, 0)],
+                vec![(8, 0), (9, 1), (10, 0)],
+            ]
+        );
+        assert_eq!(blocks[0].lines[2].text, "line 3");
+        assert_eq!(blocks[1].lines[1].matches, vec![2]);
+
+        // コンテキストを指定しない場合はブロックを作らない
+        let output = search_grouped("line 2", &files, &SearchOptions::new()).unwrap();
+        assert!(output.results[0].blocks.is_empty());
+    }
+}
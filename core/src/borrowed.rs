@@ -0,0 +1,187 @@
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+
+use crate::sort::sort_by_order;
+use crate::{
+    FileInput, Match, ScanProgress, SearchError, SearchOptions, SearchOutput, Stop,
+    compile_patterns, scan_file,
+};
+
+/// 検索したファイルの内容を借用する検索結果
+///
+/// `MatchResult` と違いパスや行のテキストを複製しないため、`FileInput` を保持したまま
+/// 結果を扱うネイティブの呼び出し側では確保を大きく減らせる。位置の意味は `MatchResult` の
+/// 同名のフィールドと同じで、`line_text` は切り詰めない行全体になる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MatchRef<'a> {
+    /// ファイルのパス
+    pub path: &'a str,
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// マッチしたパターンの位置（0ベース）
+    pub pattern_index: usize,
+    /// マッチ開始位置の列番号（1ベース、`SearchOptions::column_unit` の単位）
+    pub column: u32,
+    /// マッチ終了位置の列番号（1ベース、この位置の文字は含まない）
+    pub end_column: u32,
+    /// マッチした文字列（`invert_match` の場合は空）
+    pub match_text: &'a str,
+    /// ファイル先頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub byte_offset: usize,
+    /// 行頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub line_byte_offset: usize,
+    /// 行のテキスト
+    pub line_text: &'a str,
+}
+
+impl<'a> MatchRef<'a> {
+    /// 走査中のマッチを、ファイルの内容を借用する検索結果にする
+    fn new(f: &'a FileInput, m: &Match) -> Self {
+        let line_start = m.byte_offset - m.line_byte_offset;
+        Self {
+            path: &f.path,
+            line: m.line,
+            pattern_index: m.pattern_index,
+            column: m.column,
+            end_column: m.end_column,
+            match_text: &f.content[m.byte_offset..m.byte_offset + m.match_text.len()],
+            byte_offset: m.byte_offset,
+            line_byte_offset: m.line_byte_offset,
+            line_text: &f.content[line_start..line_start + m.line_text.len()],
+        }
+    }
+}
+
+/// オプションを指定してパターンでファイルを検索し、`files` を借用した結果を返す
+///
+/// 結果ごとにパスと行のテキストを複製しない `MatchRef` を返すため、`search_with_options` で
+/// 確保の大半を占める文字列の複製がなくなる。コンテキスト行・キャプチャグループ・行の切り詰めは
+/// 扱わず、関連度のスコアを付けないため `relevance` と `report` は無視する
+/// （`SortOrder::Relevance` では入力順のままになる）。それ以外のオプションは
+/// `search_with_options` と同じように働く。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、または正規表現パターンが無効な場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_borrowed};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "let x = 1;\nlet y = 2;".to_string(),
+/// }];
+/// let output = search_borrowed(r"y = \d", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(output.results[0].path, "a.txt");
+/// assert_eq!(output.results[0].line_text, "let y = 2;");
+/// // 結果は `files` の文字列をそのまま指している
+/// assert!(std::ptr::eq(output.results[0].path, files[0].path.as_str()));
+/// ```
+pub fn search_borrowed<'a>(
+    pattern: &str,
+    files: &'a [FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput<MatchRef<'a>>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+    let mut progress = ScanProgress {
+        skip: options.offset,
+        ..Default::default()
+    };
+    let mut output = SearchOutput {
+        results: Vec::new(),
+        truncated_files: Vec::new(),
+        truncated: false,
+        timed_out: false,
+        cancelled: false,
+        binary_files: Vec::new(),
+        next_offset: None,
+        report: None,
+    };
+
+    for f in files {
+        if options.skips_file(f) {
+            output.binary_files.push(f.path.clone());
+            continue;
+        }
+        let found_before = output.results.len();
+        let stop = scan_file(f, &compiled, options, &mut progress, |scan, line_idx, m| {
+            let m = scan.borrowed_match(line_idx, m);
+            output.results.push(MatchRef::new(f, &m));
+            ControlFlow::Continue(())
+        });
+        progress.found += output.results.len() - found_before;
+        match stop {
+            Some(Stop::FileLimit) => output.truncated_files.push(f.path.clone()),
+            Some(Stop::TotalLimit) => {
+                output.truncated = true;
+                break;
+            }
+            Some(Stop::Timeout) => {
+                output.timed_out = true;
+                break;
+            }
+            Some(Stop::Cancelled) => {
+                output.cancelled = true;
+                break;
+            }
+            Some(Stop::Interrupted) | None => {}
+        }
+    }
+    if output.truncated || output.timed_out || output.cancelled {
+        output.next_offset = Some(options.offset + progress.found);
+    }
+    sort_by_order(&mut output.results, options.sort);
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::{RecordSeparator, SortOrder, search_with_options};
+
+    #[test]
+    fn test_search_borrowed_matches_owned_results() {
+        let files = vec![
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "one\ntwo one\n\nthree one".to_string(),
+            },
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "one".to_string(),
+            },
+        ];
+        let options = SearchOptions::new()
+            .record_separator(RecordSeparator::Paragraph)
+            .max_count_per_file(2)
+            .sort(SortOrder::Path);
+        let owned = search_with_options("one", &files, &options).unwrap();
+        let borrowed = search_borrowed("one", &files, &options).unwrap();
+        let key = |path: &str, line, column, offset, text: &str| {
+            (path.to_string(), line, column, offset, text.to_string())
+        };
+        let owned_keys: Vec<_> = owned
+            .results
+            .iter()
+            .map(|r| key(&r.path, r.line, r.column, r.byte_offset, &r.match_text))
+            .collect();
+        let borrowed_keys: Vec<_> = borrowed
+            .results
+            .iter()
+            .map(|r| key(r.path, r.line, r.column, r.byte_offset, r.match_text))
+            .collect();
+        assert_eq!(borrowed_keys, owned_keys);
+        assert_eq!(borrowed.truncated_files, vec!["b.txt"]);
+        assert_eq!(borrowed.results[1].line_text, "one\ntwo one");
+    }
+}
@@ -0,0 +1,222 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use crate::records::{LineTerminator, RecordSeparator};
+use crate::{
+    Anchor, CompiledSearch, Normalization, PatternSet, RegexEngine, RegexFlags, SearchError,
+    SearchOptions,
+};
+
+/// デフォルトでキャッシュしておくコンパイル済みのパターンの数
+#[cfg(feature = "std")]
+const DEFAULT_CAPACITY: usize = 16;
+
+/// 最近使ったコンパイル済みのパターン
+#[cfg(feature = "std")]
+static CACHE: Mutex<RegexCache> = Mutex::new(RegexCache::new(DEFAULT_CAPACITY));
+
+/// キャッシュをロックして `f` を呼ぶ
+///
+/// `std` フィーチャーが無効な場合はロックを使えないため、キャッシュせずに `None` を返す。
+fn with_cache<R>(f: impl FnOnce(&mut RegexCache) -> R) -> Option<R> {
+    #[cfg(feature = "std")]
+    {
+        Some(f(&mut CACHE.lock().unwrap_or_else(|e| e.into_inner())))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = f;
+        None
+    }
+}
+
+/// コンパイル済みの正規表現をキャッシュしておく数を設定する
+///
+/// `search` や `search_with_options` などの関数は、同じパターンとフラグで続けて呼ばれた場合に
+/// キャッシュしておいたコンパイル結果を使い、コンパイルを省く。インタラクティブな検索で
+/// 同じパターンを何度も検索する場合に効く。デフォルトは 16 件で、最も長く使われていない
+/// ものから捨てる。`0` を指定するとキャッシュを無効にする。`std` フィーチャーが無効な場合は
+/// キャッシュを共有するためのロックがないため、常にコンパイルし直す。
+///
+/// # Arguments
+///
+/// * `capacity` - キャッシュしておくパターンの数
+///
+/// ```
+/// use simple_find_core::{FileInput, search, set_regex_cache_capacity};
+///
+/// set_regex_cache_capacity(64);
+/// let files = vec![FileInput { path: "a.txt".to_string(), content: "hello".to_string() }];
+/// for _ in 0..3 {
+///     // 2回目以降はコンパイル済みのパターンを使う
+///     assert_eq!(search("hel+o", &files, true).unwrap().len(), 1);
+/// }
+/// ```
+pub fn set_regex_cache_capacity(capacity: usize) {
+    with_cache(|cache| {
+        cache.capacity = capacity;
+        cache.evict();
+    });
+}
+
+/// キャッシュしておいたコンパイル済みの正規表現をすべて捨てる
+pub fn clear_regex_cache() {
+    with_cache(|cache| cache.entries.clear());
+}
+
+/// パターンをコンパイルする（キャッシュにあればそれを使う）
+///
+/// 打ち切る時刻は呼び出しごとに数え直す。
+pub(crate) fn compile_patterns(
+    patterns: &[&str],
+    options: &SearchOptions,
+) -> Result<CompiledSearch, SearchError> {
+    let key = CacheKey::new(patterns, options);
+    if let Some(Some(compiled)) = with_cache(|cache| cache.get(&key)) {
+        return Ok(compiled.restarted(options));
+    }
+
+    // コンパイルには時間がかかるため、ロックを外して行う
+    let compiled = CompiledSearch::new(PatternSet::new(patterns, options)?, options)?;
+    with_cache(|cache| cache.insert(key, compiled.clone()));
+    Ok(compiled)
+}
+
+/// キャッシュのキー（パターンと、コンパイル結果に影響する検索オプション）
+#[derive(PartialEq)]
+struct CacheKey {
+    patterns: Vec<String>,
+    case_sensitive: bool,
+    whole_word: bool,
+    literal: bool,
+    invert_match: bool,
+    exclude_pattern: Option<String>,
+    record_separator: RecordSeparator,
+    line_terminator: LineTerminator,
+    regex_flags: RegexFlags,
+    regex_engine: RegexEngine,
+    size_limit: usize,
+    dfa_size_limit: usize,
+    anchor: Anchor,
+    max_edit_distance: Option<usize>,
+    normalization: Normalization,
+    #[cfg(feature = "normalization")]
+    fold_diacritics: bool,
+    kana_insensitive: bool,
+    #[cfg(feature = "normalization")]
+    fold_width: bool,
+}
+
+impl CacheKey {
+    fn new(patterns: &[&str], options: &SearchOptions) -> Self {
+        Self {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            case_sensitive: options.case_sensitive,
+            whole_word: options.whole_word,
+            literal: options.literal,
+            invert_match: options.invert_match,
+            exclude_pattern: options.exclude_pattern.clone(),
+            record_separator: options.record_separator.clone(),
+            line_terminator: options.line_terminator,
+            regex_flags: options.regex_flags,
+            regex_engine: options.regex_engine,
+            size_limit: options.size_limit,
+            dfa_size_limit: options.dfa_size_limit,
+            anchor: options.anchor,
+            max_edit_distance: options.max_edit_distance,
+            normalization: options.normalization,
+            #[cfg(feature = "normalization")]
+            fold_diacritics: options.fold_diacritics,
+            kana_insensitive: options.kana_insensitive,
+            #[cfg(feature = "normalization")]
+            fold_width: options.fold_width,
+        }
+    }
+}
+
+/// 最近使ったものから順に残す、コンパイル済みのパターンのキャッシュ
+struct RegexCache {
+    /// 残しておく数
+    capacity: usize,
+    /// キーとコンパイル済みのパターン（最後が最も最近使ったもの）
+    entries: Vec<(CacheKey, CompiledSearch)>,
+}
+
+impl RegexCache {
+    #[cfg(any(test, feature = "std"))]
+    const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    /// キーに対応するコンパイル済みのパターンを返し、最も最近使ったものにする
+    fn get(&mut self, key: &CacheKey) -> Option<CompiledSearch> {
+        let idx = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(idx);
+        let compiled = entry.1.clone();
+        self.entries.push(entry);
+        Some(compiled)
+    }
+
+    /// コンパイル済みのパターンを追加し、あふれた分を古いものから捨てる
+    fn insert(&mut self, key: CacheKey, compiled: CompiledSearch) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push((key, compiled));
+        self.evict();
+    }
+
+    /// 残しておく数を超えた分を古いものから捨てる
+    fn evict(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(pattern: &str, options: &SearchOptions) -> (CacheKey, CompiledSearch) {
+        let patterns = PatternSet::new(&[pattern], options).unwrap();
+        (
+            CacheKey::new(&[pattern], options),
+            CompiledSearch::new(patterns, options).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_regex_cache_evicts_least_recently_used() {
+        let options = SearchOptions::new();
+        let mut cache = RegexCache::new(2);
+        for pattern in ["a", "b"] {
+            let (key, compiled) = compiled(pattern, &options);
+            cache.insert(key, compiled);
+        }
+        // "a" を使うと "b" が最も古くなる
+        assert!(cache.get(&CacheKey::new(&["a"], &options)).is_some());
+        let (key, compiled) = compiled("c", &options);
+        cache.insert(key, compiled);
+        assert!(cache.get(&CacheKey::new(&["b"], &options)).is_none());
+        assert!(cache.get(&CacheKey::new(&["a"], &options)).is_some());
+        assert!(cache.get(&CacheKey::new(&["c"], &options)).is_some());
+
+        // フラグが違えば別のパターンとして扱う
+        let insensitive = SearchOptions::new().case_sensitive(false);
+        assert!(cache.get(&CacheKey::new(&["a"], &insensitive)).is_none());
+        // 結果に影響しないオプションはキーに含めない
+        let limited = SearchOptions::new().max_results(1).before_context(2);
+        assert!(cache.get(&CacheKey::new(&["a"], &limited)).is_some());
+
+        cache.capacity = 0;
+        cache.evict();
+        assert!(cache.entries.is_empty());
+    }
+}
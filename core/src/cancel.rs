@@ -0,0 +1,46 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// 実行中の検索を外から打ち切るためのハンドル
+///
+/// `SearchOptions::cancel_token` に渡しておき、別のスレッドなどから `cancel` を呼ぶと、
+/// 検索は次に打ち切りを確かめたところで止まり、それまでの結果とともに
+/// `SearchOutput::cancelled` が `true` になる。複製したハンドルは同じ状態を共有する。
+/// 一度打ち切ったハンドルは元に戻せないため、検索ごとに新しく作る。
+///
+/// ```
+/// use simple_find_core::{CancelToken, FileInput, SearchOptions, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "foo".to_string(),
+/// }];
+/// let token = CancelToken::new();
+/// let options = SearchOptions::new().cancel_token(token.clone());
+/// token.cancel();
+/// let output = search_with_options("foo", &files, &options).unwrap();
+/// assert!(output.cancelled);
+/// assert!(output.results.is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    /// 打ち切りを求められたかどうか
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// 打ち切られていないハンドルを作る
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// このハンドルを渡した検索の打ち切りを求める
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 打ち切りを求められたかどうかを返す
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
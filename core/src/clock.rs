@@ -0,0 +1,23 @@
+#[cfg(not(feature = "std"))]
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+pub(crate) use web_time::Instant;
+
+/// `std` フィーチャーが無効な場合の時刻
+///
+/// 時計を読めないため、経過時間は常に 0 になる。`timeout_ms` による打ち切りも行わない。
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Instant;
+
+#[cfg(not(feature = "std"))]
+impl Instant {
+    pub(crate) fn now() -> Self {
+        Instant
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
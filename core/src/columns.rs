@@ -0,0 +1,58 @@
+#[cfg(feature = "graphemes")]
+use unicode_segmentation::UnicodeSegmentation;
+#[cfg(feature = "display-width")]
+use unicode_width::UnicodeWidthStr;
+
+/// 結果の列番号を数える単位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColumnUnit {
+    /// UTF-8 のバイト数で数える
+    #[default]
+    Bytes,
+    /// Unicode のスカラー値（`char`）の数で数える
+    Chars,
+    /// 書記素クラスタ（見た目の1文字）の数で数える（`graphemes` フィーチャーで使える）
+    #[cfg(feature = "graphemes")]
+    Graphemes,
+    /// UTF-16 のコードユニットの数で数える（JavaScript の文字列のインデックスと同じ）
+    Utf16,
+    /// 端末での表示幅で数える（`display-width` フィーチャーで使える）
+    ///
+    /// 東アジアの全角文字や絵文字は2、結合文字は0として数え、タブは次のタブ位置
+    /// （`tab_width` の倍数）まで進める。`tab_width` が0の場合は1として扱う。
+    #[cfg(feature = "display-width")]
+    DisplayWidth {
+        /// タブ位置の間隔
+        tab_width: usize,
+    },
+}
+
+impl ColumnUnit {
+    /// 行頭からのバイト位置を、この単位での1ベースの列番号に変換する
+    pub(crate) fn column(self, line: &str, byte_offset: usize) -> u32 {
+        let prefix = &line[..byte_offset];
+        let count = match self {
+            Self::Bytes => prefix.len(),
+            Self::Chars => prefix.chars().count(),
+            #[cfg(feature = "graphemes")]
+            Self::Graphemes => prefix.graphemes(true).count(),
+            Self::Utf16 => prefix.encode_utf16().count(),
+            #[cfg(feature = "display-width")]
+            Self::DisplayWidth { tab_width } => display_width(prefix, tab_width.max(1)),
+        };
+        (count + 1) as u32
+    }
+}
+
+/// タブを展開したテキストの表示幅を返す
+#[cfg(feature = "display-width")]
+fn display_width(text: &str, tab_width: usize) -> usize {
+    text.graphemes(true).fold(0, |width, grapheme| {
+        if grapheme == "\t" {
+            (width / tab_width + 1) * tab_width
+        } else {
+            width + grapheme.width()
+        }
+    })
+}
@@ -0,0 +1,135 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::MatchResult;
+
+/// 2回の検索結果の差分
+pub struct ResultDiff<'a> {
+    /// `after` にだけある結果（`after` での順）
+    pub added: Vec<&'a MatchResult>,
+    /// `before` にだけある結果（`before` での順）
+    pub removed: Vec<&'a MatchResult>,
+    /// 両方にある結果の数
+    pub unchanged: usize,
+}
+
+/// 行の移動に左右されない検索結果の識別子
+///
+/// 行番号や列番号はリファクタリングで簡単にずれるため含めず、パス・パターン・
+/// マッチした文字列・前後の空白を除いた行のテキストで見分ける。
+type Identity<'a> = (&'a str, usize, &'a str, &'a str);
+
+/// 検索結果の識別子を返す
+fn identity(result: &MatchResult) -> Identity<'_> {
+    (
+        &result.path,
+        result.pattern_index,
+        &result.match_text,
+        result.line_text.trim(),
+    )
+}
+
+/// 2回の検索結果（リファクタリングの前後など）を比べ、増えた結果と減った結果を返す
+///
+/// 結果はパス・パターン・マッチした文字列・前後の空白を除いた行のテキストで見分けるため、
+/// 行の追加や削除、インデントの変更で位置がずれただけの結果は変化として扱わない。
+/// 同じ識別子の結果が複数ある場合は数の増減を差分にする。
+/// CI で「X の新しい出現がないこと」を確かめる場合は `added` が空かどうかを見る。
+///
+/// # Arguments
+///
+/// * `before` - 前の検索結果
+/// * `after` - 後の検索結果
+///
+/// # Returns
+///
+/// 増えた結果・減った結果・変わらなかった結果の数
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, diff_results, search_with_options};
+///
+/// let before = vec![FileInput {
+///     path: "a.rs".to_string(),
+///     content: "foo.unwrap();\nbar.unwrap();".to_string(),
+/// }];
+/// let after = vec![FileInput {
+///     path: "a.rs".to_string(),
+///     content: "// moved\n    foo.unwrap();\nbaz.unwrap();".to_string(),
+/// }];
+/// let options = SearchOptions::new();
+/// let old = search_with_options(r"\.unwrap\(\)", &before, &options).unwrap().results;
+/// let new = search_with_options(r"\.unwrap\(\)", &after, &options).unwrap().results;
+/// let diff = diff_results(&old, &new);
+/// assert_eq!(&*diff.added[0].line_text, "baz.unwrap();");
+/// assert_eq!(&*diff.removed[0].line_text, "bar.unwrap();");
+/// assert_eq!(diff.unchanged, 1);
+/// ```
+pub fn diff_results<'a>(before: &'a [MatchResult], after: &'a [MatchResult]) -> ResultDiff<'a> {
+    let mut remaining: BTreeMap<Identity, usize> = BTreeMap::new();
+    for result in before {
+        *remaining.entry(identity(result)).or_default() += 1;
+    }
+
+    let mut added = Vec::new();
+    let mut unchanged = 0;
+    for result in after {
+        match remaining.get_mut(&identity(result)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                unchanged += 1;
+            }
+            _ => added.push(result),
+        }
+    }
+
+    // 残った数だけ、同じ識別子の結果のうち後ろのものを削除されたものとする
+    let mut removed = Vec::new();
+    for result in before.iter().rev() {
+        if let Some(count) = remaining.get_mut(&identity(result))
+            && *count > 0
+        {
+            *count -= 1;
+            removed.push(result);
+        }
+    }
+    removed.reverse();
+
+    ResultDiff {
+        added,
+        removed,
+        unchanged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::{FileInput, SearchOptions, search_with_options};
+
+    #[test]
+    fn test_diff_results_counts_duplicates() {
+        let search = |content: &str| {
+            let files = vec![FileInput {
+                path: "a.txt".to_string(),
+                content: content.to_string(),
+            }];
+            search_with_options("TODO", &files, &SearchOptions::new())
+                .unwrap()
+                .results
+        };
+        let before = search("TODO\nTODO\nx TODO");
+        let after = search("TODO\nx TODO\nx TODO\nx TODO");
+        let diff = diff_results(&before, &after);
+        let lines =
+            |results: &[&MatchResult]| -> Vec<u32> { results.iter().map(|r| r.line).collect() };
+        assert_eq!(lines(&diff.added), vec![3, 4]);
+        assert_eq!(lines(&diff.removed), vec![2]);
+        assert_eq!(diff.unchanged, 2);
+
+        let diff = diff_results(&before, &before);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use alloc::boxed::Box;
+#[cfg(feature = "prefilter")]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::iter::Peekable;
+
+#[cfg(feature = "prefilter")]
+use regex_syntax::hir::{
+    Capture, Class, ClassBytes, ClassBytesRange, ClassUnicode, ClassUnicodeRange, Hir, HirKind,
+    Look, Repetition,
+};
+
+use crate::engine::Regex;
+#[cfg(all(feature = "prefilter", not(feature = "regex")))]
+use crate::engine::RegexBuilderExt as _;
+#[cfg(feature = "prefilter")]
+use crate::engine::{RegexBuilder, parser_builder};
+#[cfg(feature = "prefilter")]
+use crate::normalize;
+#[cfg(feature = "prefilter")]
+use crate::records::RecordSeparator;
+use crate::records::{LineTerminator, Record};
+use crate::{PatternSet, SearchOptions, SpanMatch, capture_groups};
+
+/// ファイル全体に1回だけ適用できるように書き換えた正規表現を作る
+///
+/// 行ごとに `find_iter` を呼ぶと呼び出しのたびに探索をやり直すことになり、正規表現エンジンの
+/// リテラルによる前処理も行の長さ分しか効かない。そこで、パターンから改行にマッチする部分を
+/// 取り除き、`^` と `$` を行の先頭と末尾に変えた正規表現でファイル全体を1回で走査する。
+/// 改行をまたがないため、どのマッチも1行に収まり、行ごとに検索した場合と同じ結果になる。
+///
+/// パターンが1つの正規表現で、1行を1レコードとして `\n` で区切り、正規化や `invert_match` を
+/// 使わない場合だけ作る。改行を含むリテラルや `\A`・`\z` を使うパターンなど、書き換えると
+/// 結果が変わる場合は `None` を返し、行ごとの検索を使う。
+#[cfg(feature = "prefilter")]
+pub(crate) fn content_regex(patterns: &PatternSet, options: &SearchOptions) -> Option<Regex> {
+    let PatternSet::Regex { regexes, set: None } = patterns else {
+        return None;
+    };
+    let [regex] = regexes.as_slice() else {
+        return None;
+    };
+    if options.invert_match
+        || options.record_separator != RecordSeparator::Line
+        || !matches!(
+            options.line_terminator,
+            LineTerminator::Auto | LineTerminator::Lf
+        )
+        || normalize::is_enabled(options)
+    {
+        return None;
+    }
+
+    let hir = parser_builder(options)
+        .multi_line(true)
+        .build()
+        .parse(regex.as_str())
+        .ok()?;
+    RegexBuilder::new(&without_newline(&hir)?.to_string())
+        .size_limit(options.size_limit)
+        .dfa_size_limit(options.dfa_size_limit)
+        .build()
+        .ok()
+}
+
+/// `prefilter` フィーチャーが無効な場合はパターンを書き換えられないため、常に行ごとに検索する
+#[cfg(not(feature = "prefilter"))]
+pub(crate) fn content_regex(_patterns: &PatternSet, _options: &SearchOptions) -> Option<Regex> {
+    None
+}
+
+/// 改行にマッチしないように書き換えた式を返す
+///
+/// 文字クラスからは `\n` を取り除く。改行を含むリテラルや、行ではなくテキスト全体の先頭・
+/// 末尾を表すアサーションは書き換えられないため `None` を返す。
+#[cfg(feature = "prefilter")]
+fn without_newline(hir: &Hir) -> Option<Hir> {
+    let rewritten = match hir.kind() {
+        HirKind::Empty => Hir::empty(),
+        HirKind::Literal(literal) if literal.0.contains(&b'\n') => return None,
+        HirKind::Literal(_) => hir.clone(),
+        HirKind::Class(Class::Unicode(class)) => {
+            let mut class = class.clone();
+            class.difference(&ClassUnicode::new([ClassUnicodeRange::new('\n', '\n')]));
+            Hir::class(Class::Unicode(class))
+        }
+        HirKind::Class(Class::Bytes(class)) => {
+            let mut class = class.clone();
+            class.difference(&ClassBytes::new([ClassBytesRange::new(b'\n', b'\n')]));
+            Hir::class(Class::Bytes(class))
+        }
+        HirKind::Look(Look::Start | Look::End) => return None,
+        HirKind::Look(_) => hir.clone(),
+        HirKind::Repetition(repetition) => Hir::repetition(Repetition {
+            sub: Box::new(without_newline(&repetition.sub)?),
+            ..repetition.clone()
+        }),
+        HirKind::Capture(capture) => Hir::capture(Capture {
+            sub: Box::new(without_newline(&capture.sub)?),
+            ..capture.clone()
+        }),
+        HirKind::Concat(subs) => Hir::concat(
+            subs.iter()
+                .map(without_newline)
+                .collect::<Option<Vec<_>>>()?,
+        ),
+        HirKind::Alternation(subs) => Hir::alternation(
+            subs.iter()
+                .map(without_newline)
+                .collect::<Option<Vec<_>>>()?,
+        ),
+    };
+    Some(rewritten)
+}
+
+/// ファイル全体を走査したマッチを行ごとに振り分ける
+///
+/// マッチは必要になった分だけ順に探す。
+pub(crate) struct ContentMatches<'a> {
+    /// ファイル全体でのマッチ（範囲とキャプチャグループの列番号はファイル先頭からの位置）
+    matches: Peekable<Box<dyn Iterator<Item = SpanMatch> + 'a>>,
+}
+
+impl<'a> ContentMatches<'a> {
+    /// ファイル全体のマッチを列挙する準備をする
+    ///
+    /// 行の終端文字が `Auto` のまま `\r` を含むファイルは、行のテキストから `\r` が
+    /// 取り除かれて位置がずれるため `None` を返す。
+    pub(crate) fn new(re: &'a Regex, content: &'a str, options: &SearchOptions) -> Option<Self> {
+        if options.line_terminator == LineTerminator::Auto
+            && memchr::memchr(b'\r', content.as_bytes()).is_some()
+        {
+            return None;
+        }
+        let matches: Box<dyn Iterator<Item = SpanMatch> + 'a> = if re.captures_len() > 1 {
+            Box::new(re.captures_iter(content).map(|caps| SpanMatch {
+                pattern_index: 0,
+                span: caps.get(0).unwrap().range(),
+                captures: capture_groups(&caps),
+            }))
+        } else {
+            Box::new(re.find_iter(content).map(|m| SpanMatch {
+                pattern_index: 0,
+                span: m.range(),
+                captures: Vec::new(),
+            }))
+        };
+        Some(Self {
+            matches: matches.peekable(),
+        })
+    }
+
+    /// レコードの中のマッチを、行内の位置に直して返す
+    ///
+    /// レコードは先頭から順に渡す。飛ばしたレコードのマッチは捨てる。
+    pub(crate) fn take_line(&mut self, record: &Record) -> Vec<SpanMatch> {
+        let start = record.offset;
+        let end = start + record.text.len();
+        let mut found = Vec::new();
+        while let Some(next) = self.matches.next_if(|m| m.span.start <= end) {
+            if next.span.start < start {
+                continue;
+            }
+            let mut captures = next.captures;
+            for group in &mut captures {
+                group.column -= start as u32;
+                group.end_column -= start as u32;
+            }
+            found.push(SpanMatch {
+                pattern_index: next.pattern_index,
+                span: next.span.start - start..next.span.end - start,
+                captures,
+            });
+        }
+        found
+    }
+}
+
+#[cfg(all(test, feature = "prefilter"))]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        CompiledSearch, FileInput, RegexFlags, ScanProgress, search_file, search_with_options,
+    };
+
+    /// 全体を走査した場合と行ごとに検索した場合の結果を比べる
+    fn assert_same_as_per_line(pattern: &str, content: &str, options: &SearchOptions) {
+        let file = FileInput {
+            path: "a.txt".to_string(),
+            content: content.to_string(),
+        };
+        let patterns = PatternSet::new(&[pattern], options).unwrap();
+        let fast = CompiledSearch::new(patterns, options).unwrap();
+        assert!(
+            fast.content_regex.is_some(),
+            "no content regex for {:?}",
+            pattern
+        );
+        let patterns = PatternSet::new(&[pattern], options).unwrap();
+        let mut slow = CompiledSearch::new(patterns, options).unwrap();
+        slow.content_regex = None;
+
+        let run = |compiled: &CompiledSearch| {
+            let mut found = Vec::new();
+            let mut progress = ScanProgress::default();
+            search_file(&file, compiled, options, &mut progress, &mut found);
+            found
+                .into_iter()
+                .map(|m| m.into_result("a.txt".into()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(&fast), run(&slow), "pattern {:?}", pattern);
+    }
+
+    #[test]
+    fn test_content_regex_matches_per_line_search() {
+        let content = "foo bar\n\nbaz foo\n  \nfoo\nabc=12 def=3\n";
+        let options = SearchOptions::new();
+        for pattern in [
+            "foo",
+            "^foo",
+            "foo$",
+            "^$",
+            r"\s+",
+            r"[^a]+",
+            "x*",
+            r"(?<key>\w+)=(\d+)",
+            r"\bba",
+            ".*",
+            r"(?s).+",
+        ] {
+            assert_same_as_per_line(pattern, content, &options);
+        }
+
+        let options = SearchOptions::new()
+            .case_sensitive(false)
+            .regex_flags(RegexFlags::new().dot_all(true));
+        assert_same_as_per_line("FOO.", "foo\nFoox\n", &options);
+
+        let options = SearchOptions::new().line_terminator(LineTerminator::Lf);
+        assert_same_as_per_line(r"\s$", "a \r\nb\r\n", &options);
+    }
+
+    #[test]
+    fn test_content_regex_falls_back_when_rewrite_changes_meaning() {
+        let options = SearchOptions::new();
+        let patterns = PatternSet::new(&[r"a\nb"], &options).unwrap();
+        assert!(content_regex(&patterns, &options).is_none());
+        let patterns = PatternSet::new(&[r"\Afoo"], &options).unwrap();
+        assert!(content_regex(&patterns, &options).is_none());
+
+        // `\r` を含むファイルは行ごとに検索する
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "foo\r\nx foo".to_string(),
+        }];
+        let output = search_with_options("foo$", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[1].column, 3);
+    }
+}
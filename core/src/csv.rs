@@ -0,0 +1,116 @@
+use alloc::string::{String, ToString};
+use std::io;
+
+use crate::MatchResult;
+
+/// 先頭に出力する見出しの行
+const HEADER: [&str; 5] = ["path", "line", "column", "match_text", "line_text"];
+
+/// 検索結果を CSV（RFC 4180）として `writer` に書き出す
+///
+/// 1行目は見出し（`path,line,column,match_text,line_text`）で、続けて結果ごとに1行を出力する。
+/// 区切り文字・`"`・改行を含む値は `"` で囲み、値の中の `"` は `""` にする。
+///
+/// # Arguments
+///
+/// * `writer` - 書き出し先
+/// * `results` - 検索結果のリスト
+///
+/// # Returns
+///
+/// 書き出しに失敗した場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_with_options, write_csv};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "say \"hi\", bob".to_string(),
+/// }];
+/// let output = search_with_options("hi", &files, &SearchOptions::new()).unwrap();
+/// let mut out = Vec::new();
+/// write_csv(&mut out, &output.results).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "path,line,column,match_text,line_text\r\na.txt,1,6,hi,\"say \"\"hi\"\", bob\"\r\n"
+/// );
+/// ```
+pub fn write_csv<W: io::Write>(writer: W, results: &[MatchResult]) -> io::Result<()> {
+    write_delimited(writer, results, ',')
+}
+
+/// 検索結果を TSV（タブ区切り）として `writer` に書き出す
+///
+/// 列と値の囲み方は `write_csv` と同じで、区切り文字がタブになる。
+///
+/// # Arguments
+///
+/// * `writer` - 書き出し先
+/// * `results` - 検索結果のリスト
+///
+/// # Returns
+///
+/// 書き出しに失敗した場合のエラー
+pub fn write_tsv<W: io::Write>(writer: W, results: &[MatchResult]) -> io::Result<()> {
+    write_delimited(writer, results, '\t')
+}
+
+/// 見出しと検索結果を `delimiter` 区切りで書き出す
+fn write_delimited<W: io::Write>(
+    mut writer: W,
+    results: &[MatchResult],
+    delimiter: char,
+) -> io::Result<()> {
+    let mut row = String::new();
+    write_row(&mut row, &HEADER, delimiter);
+    writer.write_all(row.as_bytes())?;
+
+    for result in results {
+        row.clear();
+        let line = result.line.to_string();
+        let column = result.column.to_string();
+        let fields = [
+            &*result.path,
+            &line,
+            &column,
+            &result.match_text,
+            &result.line_text,
+        ];
+        write_row(&mut row, &fields, delimiter);
+        writer.write_all(row.as_bytes())?;
+    }
+    writer.flush()
+}
+
+/// 1行分の値を区切り文字でつなぎ、CRLF で終わる行として `out` に追加する
+fn write_row(out: &mut String, fields: &[&str], delimiter: char) {
+    for (idx, field) in fields.iter().enumerate() {
+        if idx > 0 {
+            out.push(delimiter);
+        }
+        if field.contains([delimiter, '"', '\r', '\n']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_row_quotes_only_when_needed() {
+        let mut out = String::new();
+        write_row(&mut out, &["a b", "x,y", "multi\nline", "tab\there"], ',');
+        assert_eq!(out, "a b,\"x,y\",\"multi\nline\",tab\there\r\n");
+
+        let mut out = String::new();
+        write_row(&mut out, &["x,y", "tab\there", "\"q\""], '\t');
+        assert_eq!(out, "x,y\t\"tab\there\"\t\"\"\"q\"\"\"\r\n");
+    }
+}
@@ -0,0 +1,133 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{FileInput, MatchResult};
+
+/// マッチの密度を数える区間の分け方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DensityBuckets {
+    /// 指定した行数ごとに区切る
+    LinesPer(u32),
+    /// ファイルをおよそ指定した数の区間に等分する
+    Count(u32),
+}
+
+/// 1ファイル分のマッチの密度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDensity {
+    /// ファイルのパス
+    pub path: String,
+    /// ファイルの行数
+    pub total_lines: u32,
+    /// 1区間の行数（最後の区間はこれより短い場合がある）
+    pub lines_per_bucket: u32,
+    /// 区間ごとのマッチ数（先頭の区間から順）
+    pub counts: Vec<u32>,
+}
+
+/// 検索結果をファイルごとに行の区間に分けて数える
+///
+/// エディタのスクロールバーの横にミニマップのような密度のバーを表示するために使う。
+/// `DensityBuckets::Count(n)` では1区間の行数を切り上げて求めるため、行数が少ない
+/// ファイルでは区間の数が `n` より少なくなる。行数は `\n` で区切った物理行で数え、
+/// それを超える行番号の結果は最後の区間に数える。
+/// ファイルは `files` の順に並び、マッチのないファイルと `files` に見つからないファイルの
+/// 結果は含めない。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+/// * `files` - 検索したファイルのリスト
+/// * `buckets` - 区間の分け方
+///
+/// # Returns
+///
+/// ファイルごとのマッチの密度のリスト
+///
+/// ```
+/// use simple_find_core::{DensityBuckets, FileInput, SearchOptions, match_density, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "x\n\n\nx x\n".to_string(),
+/// }];
+/// let output = search_with_options("x", &files, &SearchOptions::new()).unwrap();
+/// let density = match_density(&output.results, &files, DensityBuckets::LinesPer(2));
+/// assert_eq!(density[0].total_lines, 4);
+/// assert_eq!(density[0].counts, vec![1, 2]);
+/// ```
+pub fn match_density(
+    results: &[MatchResult],
+    files: &[FileInput],
+    buckets: DensityBuckets,
+) -> Vec<FileDensity> {
+    let mut lines_by_path: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for result in results {
+        lines_by_path
+            .entry(&*result.path)
+            .or_default()
+            .push(result.line);
+    }
+
+    files
+        .iter()
+        .filter_map(|f| {
+            let lines = lines_by_path.get(f.path.as_str())?;
+            let total_lines = (f.content.lines().count() as u32).max(1);
+            let lines_per_bucket = match buckets {
+                DensityBuckets::LinesPer(n) => n.max(1),
+                DensityBuckets::Count(n) => total_lines.div_ceil(n.max(1)),
+            };
+            let bucket_count = total_lines.div_ceil(lines_per_bucket) as usize;
+            let mut counts = vec![0; bucket_count];
+            for &line in lines {
+                let bucket =
+                    ((line.saturating_sub(1) / lines_per_bucket) as usize).min(bucket_count - 1);
+                counts[bucket] += 1;
+            }
+            Some(FileDensity {
+                path: f.path.clone(),
+                total_lines,
+                lines_per_bucket,
+                counts,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{SearchOptions, search_with_options};
+
+    #[test]
+    fn test_match_density_bucket_count() {
+        let content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content,
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "nothing".to_string(),
+            },
+        ];
+        let output = search_with_options(r"line (1|2|9)\b", &files, &SearchOptions::new()).unwrap();
+        let density = match_density(&output.results, &files, DensityBuckets::Count(3));
+        assert_eq!(density.len(), 1);
+        // 10行を4行ずつに区切る
+        assert_eq!(density[0].lines_per_bucket, 4);
+        assert_eq!(density[0].counts, vec![2, 0, 1]);
+
+        // 行数より多い区間を指定しても1行ずつになる
+        let density = match_density(&output.results, &files, DensityBuckets::Count(100));
+        assert_eq!(density[0].counts.len(), 10);
+        assert!(match_density(&output.results, &[], DensityBuckets::Count(3)).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// 変更箇所の前後に表示する行数
+const CONTEXT_LINES: usize = 3;
+
+/// 置換前後の1か所の行の差分
+struct Block {
+    /// 置換前の行の範囲（0ベースの行番号）
+    old: Range<usize>,
+    /// 置換後の行
+    new: Vec<String>,
+}
+
+/// 置換箇所から unified diff 形式の差分を作る
+///
+/// `changes` は元の内容でのバイト範囲と置き換える文字列の組で、位置の順に並び
+/// 互いに重ならないものとする。差分は `\n` 区切りの行単位で作る。
+pub(crate) fn unified_diff(path: &str, original: &str, changes: &[(Range<usize>, &str)]) -> String {
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut offset = 0;
+    for line in &lines {
+        line_starts.push(offset);
+        offset += line.len();
+    }
+    let line_of = |byte: usize| line_starts.partition_point(|&start| start <= byte).max(1) - 1;
+
+    // 同じ行に掛かる置換をまとめ、置換後の行を組み立てる
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut pending: Option<(Range<usize>, String, usize)> = None;
+    let mut flush = |pending: Option<(Range<usize>, String, usize)>| {
+        if let Some((old, mut text, copied)) = pending {
+            let end = line_starts.get(old.end).copied().unwrap_or(original.len());
+            text.push_str(&original[copied..end]);
+            let new = text.split_inclusive('\n').map(str::to_string).collect();
+            blocks.push(Block { old, new });
+        }
+    };
+    for (span, new_text) in changes {
+        let first = line_of(span.start);
+        let last = if span.is_empty() {
+            first
+        } else {
+            line_of(span.end - 1)
+        };
+        match &mut pending {
+            Some((old, text, copied)) if first < old.end => {
+                text.push_str(&original[*copied..span.start]);
+                text.push_str(new_text);
+                *copied = span.end;
+                old.end = old.end.max(last + 1);
+            }
+            _ => {
+                flush(pending.take());
+                let start = line_starts.get(first).copied().unwrap_or(original.len());
+                let mut text = original[start..span.start].to_string();
+                text.push_str(new_text);
+                pending = Some((first..last + 1, text, span.end));
+            }
+        }
+    }
+    flush(pending);
+
+    if blocks.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+    // 置換後の行番号と置換前の行番号の差
+    let mut delta: isize = 0;
+    let mut idx = 0;
+    while idx < blocks.len() {
+        // 間の行が前後の表示行数の合計以下なら1つの hunk にまとめる
+        let mut end = idx + 1;
+        while end < blocks.len()
+            && blocks[end].old.start - blocks[end - 1].old.end <= CONTEXT_LINES * 2
+        {
+            end += 1;
+        }
+        let hunk = &blocks[idx..end];
+        let old_start = hunk[0].old.start.saturating_sub(CONTEXT_LINES);
+        let old_end = (hunk[end - idx - 1].old.end + CONTEXT_LINES).min(lines.len());
+
+        let mut body = Vec::new();
+        let mut old_len = 0;
+        let mut new_len = 0;
+        let mut line = old_start;
+        for block in hunk {
+            for &text in &lines[line..block.old.start] {
+                body.push((' ', text));
+            }
+            for &text in &lines[block.old.clone()] {
+                body.push(('-', text));
+            }
+            for text in &block.new {
+                body.push(('+', text.as_str()));
+            }
+            old_len += block.old.start - line + block.old.len();
+            new_len += block.old.start - line + block.new.len();
+            line = block.old.end;
+        }
+        for &text in &lines[line..old_end] {
+            body.push((' ', text));
+        }
+        old_len += old_end - line;
+        new_len += old_end - line;
+
+        let new_start = old_start as isize + delta;
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start as usize, new_len)
+        ));
+        for (marker, text) in body {
+            diff.push(marker);
+            diff.push_str(text);
+            if !text.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+
+        delta += new_len as isize - old_len as isize;
+        idx = end;
+    }
+    diff
+}
+
+/// hunk の見出しに使う範囲（`開始行,行数`）を返す
+///
+/// 行数が0の場合は直前の行番号を開始行とする（GNU diff と同じ）。
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
@@ -0,0 +1,154 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::lsp::LineIndex;
+use crate::{FileInput, MatchResult};
+
+/// Monaco Editor の `IRange`（1ベースの行番号と UTF-16 の列番号、終了位置を含まない）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonacoRange {
+    /// 開始行番号（1ベース）
+    pub start_line_number: u32,
+    /// 開始列番号（1ベース、UTF-16 のコードユニット単位）
+    pub start_column: u32,
+    /// 終了行番号（1ベース）
+    pub end_line_number: u32,
+    /// 終了列番号（1ベース、UTF-16 のコードユニット単位）
+    pub end_column: u32,
+}
+
+/// Web エディタでマッチを装飾するための範囲
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorRange {
+    /// ファイルのパス
+    pub path: String,
+    /// Monaco Editor の `IRange`
+    pub monaco: MonacoRange,
+    /// CodeMirror 6 のドキュメント先頭からの開始位置（UTF-16 のコードユニット単位）
+    pub from: u32,
+    /// CodeMirror 6 のドキュメント先頭からの終了位置（UTF-16 のコードユニット単位）
+    pub to: u32,
+}
+
+/// CodeMirror 6 のオフセットを求めるための行ごとの開始位置
+///
+/// CodeMirror 6 は改行を `\n` に揃えて扱うため、`\r\n` も1文字として数える。
+struct DocOffsets {
+    /// 各行の開始位置（ドキュメント先頭からの UTF-16 のコードユニット数）
+    line_starts: Vec<u32>,
+}
+
+impl DocOffsets {
+    /// 行ごとの開始位置を求める
+    fn new(index: &LineIndex) -> Self {
+        let mut line_starts = Vec::with_capacity(index.line_count());
+        let mut offset = 0;
+        for line in 0..index.line_count() {
+            line_starts.push(offset);
+            offset += index.line_text(line).encode_utf16().count() as u32 + 1;
+        }
+        Self { line_starts }
+    }
+}
+
+/// 検索結果を Monaco Editor と CodeMirror 6 で装飾に使う範囲に変換する
+///
+/// 位置は `files` の内容から求めるため、検索時の `column_unit` や行の切り詰めに関わらず
+/// エディタが期待する UTF-16 単位になる。Monaco の範囲は `deltaDecorations` などに、
+/// `from` / `to` は CodeMirror 6 の `Decoration.mark(...).range(from, to)` にそのまま渡せる。
+/// ファイルごとの行の開始位置は1回だけ求め、`files` に見つからないファイルの結果は含めない。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+/// * `files` - 検索したファイルのリスト
+///
+/// # Returns
+///
+/// 検索結果の順の範囲のリスト
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, editor_ranges, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "first\r\n😀 target".to_string(),
+/// }];
+/// let output = search_with_options("target", &files, &SearchOptions::new()).unwrap();
+/// let ranges = editor_ranges(&output.results, &files);
+/// assert_eq!(ranges[0].monaco.start_line_number, 2);
+/// assert_eq!(ranges[0].monaco.start_column, 4);
+/// // CodeMirror は `\r\n` を1文字として数える
+/// assert_eq!((ranges[0].from, ranges[0].to), (9, 15));
+/// ```
+pub fn editor_ranges(results: &[MatchResult], files: &[FileInput]) -> Vec<EditorRange> {
+    let contents: BTreeMap<&str, &str> = files
+        .iter()
+        .map(|f| (f.path.as_str(), f.content.as_str()))
+        .collect();
+    let mut indexes: BTreeMap<&str, (LineIndex, DocOffsets)> = BTreeMap::new();
+
+    results
+        .iter()
+        .filter_map(|result| {
+            let content = contents.get(&*result.path)?;
+            let (index, offsets) = indexes.entry(&*result.path).or_insert_with(|| {
+                let index = LineIndex::new(content);
+                let offsets = DocOffsets::new(&index);
+                (index, offsets)
+            });
+            let start = index.position(result.byte_offset);
+            let end = index.position(result.byte_offset + result.match_text.len());
+            Some(EditorRange {
+                path: result.path.to_string(),
+                monaco: MonacoRange {
+                    start_line_number: start.line + 1,
+                    start_column: start.character + 1,
+                    end_line_number: end.line + 1,
+                    end_column: end.character + 1,
+                },
+                from: offsets.line_starts[start.line as usize] + start.character,
+                to: offsets.line_starts[end.line as usize] + end.character,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{RecordSeparator, SearchOptions, search_with_options};
+
+    #[test]
+    fn test_editor_ranges_multiline_and_line_endings() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "a\rb\r\nfoo\nbar\n\nx".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "日本foo".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+        let output = search_with_options(r"o\nba|本f", &files, &options).unwrap();
+        let ranges = editor_ranges(&output.results, &files);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(
+            ranges[0].monaco,
+            MonacoRange {
+                start_line_number: 3,
+                start_column: 3,
+                end_line_number: 4,
+                end_column: 3,
+            }
+        );
+        assert_eq!((ranges[0].from, ranges[0].to), (6, 10));
+        assert_eq!(ranges[1].path, "b.txt");
+        assert_eq!((ranges[1].from, ranges[1].to), (1, 3));
+    }
+}
@@ -0,0 +1,195 @@
+#[cfg(not(feature = "regex"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "regex")]
+pub(crate) use regex::{Captures, Error, Regex, RegexBuilder, RegexSet, RegexSetBuilder, escape};
+#[cfg(all(feature = "regex-lite", not(feature = "regex")))]
+pub(crate) use regex_lite::{Captures, Error, Regex, RegexBuilder, escape};
+
+#[cfg(any(feature = "prefilter", feature = "validate"))]
+use regex_syntax::ParserBuilder;
+
+#[cfg(any(feature = "prefilter", feature = "validate"))]
+use crate::SearchOptions;
+
+#[cfg(not(any(feature = "regex", feature = "regex-lite")))]
+compile_error!("either the `regex` or the `regex-lite` feature must be enabled");
+
+/// パターンをコンパイルする正規表現エンジン
+///
+/// デフォルトの `Standard` はマッチにかかる時間がパターンとテキストの長さに比例することを
+/// 保証する代わりに、先読み・後読みや後方参照を使えない。PCRE などの書き方のパターンを
+/// そのまま使いたい場合は `fancy-regex` フィーチャーを有効にして `Fancy` を選ぶ。
+/// 各エンジンで使える構文は `RegexEngine::features` で確かめられる。
+/// フィーチャーによって増えるバリアントがあるため、`match` ではワイルドカードのアームが必要。
+///
+/// ```
+/// use simple_find_core::{RegexEngine, SearchOptions};
+///
+/// let options = SearchOptions::new();
+/// assert_eq!(options.engine_features().lookaround, false);
+/// assert!(RegexEngine::available().contains(&RegexEngine::Standard));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RegexEngine {
+    /// 組み込みのエンジン（`regex`、無効な場合は `regex-lite`）
+    #[default]
+    Standard,
+    /// fancy-regex（先読み・後読みと後方参照を使える）
+    ///
+    /// これらの構文を含まない部分は組み込みのエンジンと同じ仕組みで探すが、複数のパターンを
+    /// まとめて絞り込む最適化は働かない。バックトラックが上限に達した行はマッチしなかったものとして扱う。
+    #[cfg(feature = "fancy-regex")]
+    Fancy,
+}
+
+impl RegexEngine {
+    /// このビルドで使えるエンジンを返す
+    pub fn available() -> &'static [RegexEngine] {
+        &[
+            Self::Standard,
+            #[cfg(feature = "fancy-regex")]
+            Self::Fancy,
+        ]
+    }
+
+    /// エンジンの実装に使うクレートの名前を返す
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "regex")]
+            Self::Standard => "regex",
+            #[cfg(not(feature = "regex"))]
+            Self::Standard => "regex-lite",
+            #[cfg(feature = "fancy-regex")]
+            Self::Fancy => "fancy-regex",
+        }
+    }
+
+    /// エンジンが対応している構文と性質を返す
+    pub fn features(self) -> EngineFeatures {
+        match self {
+            Self::Standard => EngineFeatures {
+                lookaround: false,
+                backreferences: false,
+                unicode_classes: cfg!(feature = "regex"),
+                linear_time: true,
+            },
+            #[cfg(feature = "fancy-regex")]
+            Self::Fancy => EngineFeatures {
+                lookaround: true,
+                backreferences: true,
+                unicode_classes: true,
+                linear_time: false,
+            },
+        }
+    }
+}
+
+/// 正規表現エンジンが対応している構文と性質
+///
+/// UI でエンジンの選択肢を示したり、入力されたパターンを使えるエンジンを選んだりするためのもの。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineFeatures {
+    /// 先読み・後読み（`(?=...)`、`(?<!...)` など）を使えるかどうか
+    pub lookaround: bool,
+    /// 後方参照（`\1`、`\k<name>` など）を使えるかどうか
+    pub backreferences: bool,
+    /// Unicode の文字クラス（`\p{Greek}` や Unicode の範囲の `\w`）を使えるかどうか
+    pub unicode_classes: bool,
+    /// マッチにかかる時間がパターンとテキストの長さに比例することを保証するかどうか
+    pub linear_time: bool,
+}
+
+/// オプションの正規表現のフラグを反映したパーサーのビルダーを作る
+///
+/// `build_regex` と同じく、`literal` モードでは `ignore_whitespace` を無視する。
+#[cfg(any(feature = "prefilter", feature = "validate"))]
+pub(crate) fn parser_builder(options: &SearchOptions) -> ParserBuilder {
+    let flags = &options.regex_flags;
+    let mut builder = ParserBuilder::new();
+    builder
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_all)
+        .unicode(flags.unicode)
+        .swap_greed(flags.swap_greed)
+        .ignore_whitespace(flags.ignore_whitespace && !options.literal);
+    // regex-lite と同じく、文字クラスや大文字・小文字の区別を ASCII の範囲で解釈する
+    #[cfg(not(feature = "regex"))]
+    builder.unicode(false).utf8(false);
+    builder
+}
+
+/// regex-lite には正規表現セットがないため、作られることのない型で代わりにする
+///
+/// 複数のパターンは常に1つずつ試す。
+#[cfg(not(feature = "regex"))]
+#[derive(Debug, Clone)]
+pub(crate) enum RegexSet {}
+
+#[cfg(not(feature = "regex"))]
+impl RegexSet {
+    pub(crate) fn is_match(&self, _haystack: &str) -> bool {
+        match *self {}
+    }
+
+    pub(crate) fn matches(&self, _haystack: &str) -> Vec<usize> {
+        match *self {}
+    }
+}
+
+/// regex-lite のビルダーにない設定を、何もしないメソッドで補う
+///
+/// regex-lite は Unicode に対応せず、遅延 DFA も持たないため、どちらの設定も意味を持たない。
+#[cfg(not(feature = "regex"))]
+pub(crate) trait RegexBuilderExt {
+    fn unicode(&mut self, yes: bool) -> &mut Self;
+
+    fn dfa_size_limit(&mut self, bytes: usize) -> &mut Self;
+}
+
+#[cfg(not(feature = "regex"))]
+impl RegexBuilderExt for RegexBuilder {
+    fn unicode(&mut self, _yes: bool) -> &mut Self {
+        self
+    }
+
+    fn dfa_size_limit(&mut self, _bytes: usize) -> &mut Self {
+        self
+    }
+}
+
+#[cfg(all(test, not(feature = "regex")))]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::{FileInput, SearchOptions, search_multi};
+
+    #[test]
+    fn test_regex_lite_backend() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "café\nfoo bar".to_string(),
+        }];
+        // 正規表現セットがなくても複数のパターンを探せる
+        let results = search_multi(&["bar", "foo"], &files, &SearchOptions::new())
+            .unwrap()
+            .results;
+        let mut columns: Vec<u32> = results.iter().map(|r| r.column).collect();
+        columns.sort_unstable();
+        assert_eq!(columns, [1, 5]);
+
+        // 文字クラスは ASCII だけにマッチし、Unicode の文字クラスは使えない
+        let options = SearchOptions::new();
+        assert!(
+            search_multi(&[r"caf\w"], &files, &options)
+                .unwrap()
+                .results
+                .is_empty()
+        );
+        assert!(search_multi(&[r"\p{Greek}"], &files, &options).is_err());
+    }
+}
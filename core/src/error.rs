@@ -0,0 +1,162 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::error::Error;
+use core::fmt;
+
+use crate::engine;
+
+/// 検索・置換の失敗を表すエラー
+///
+/// 正規表現のコンパイルエラーは、`regex-lite` フィーチャーだけを有効にした場合は
+/// `regex_lite::Error` になる。フィーチャーによって増えるバリアントがあるため、
+/// `match` ではワイルドカードのアームが必要。
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum SearchError {
+    /// 検索パターンが正規表現として無効
+    InvalidPattern {
+        /// 指定したパターン
+        pattern: String,
+        /// 正規表現のコンパイルエラー
+        source: engine::Error,
+    },
+    /// `exclude_pattern` が正規表現として無効
+    InvalidExcludePattern {
+        /// 指定したパターン
+        pattern: String,
+        /// 正規表現のコンパイルエラー
+        source: engine::Error,
+    },
+    /// 検索パターンが `RegexEngine::Fancy` の正規表現として無効
+    #[cfg(feature = "fancy-regex")]
+    InvalidFancyPattern {
+        /// 指定したパターン
+        pattern: String,
+        /// fancy-regex のコンパイルエラー
+        source: fancy_regex::Error,
+    },
+    /// `Pcre2Matcher` のパターンが PCRE2 の正規表現として無効
+    #[cfg(feature = "pcre2")]
+    InvalidPcre2Pattern {
+        /// 指定したパターン
+        pattern: String,
+        /// PCRE2 のコンパイルエラー
+        source: pcre2::Error,
+    },
+    /// 複数のパターンを1つにまとめられなかった（`size_limit` を超えた場合など）
+    InvalidPatternSet(engine::Error),
+    /// `search_literals` の文字列から Aho-Corasick オートマトンを作れなかった
+    LiteralMatcher(aho_corasick::BuildError),
+    /// `VectorscanMatcher` のパターンをコンパイルできなかった
+    #[cfg(feature = "vectorscan")]
+    VectorscanMatcher(Arc<vectorscan_rs::Error>),
+    /// `max_edit_distance` がパターンの長さ以上
+    EditDistanceTooLarge {
+        /// 短すぎるパターン（正規化後）
+        pattern: String,
+        /// 指定した編集距離
+        max_edits: usize,
+    },
+    /// `LineTerminator::Byte` に ASCII 以外のバイトを指定した
+    InvalidLineTerminator(u8),
+    /// 一緒に使えないオプションを指定した
+    InvalidOptions(&'static str),
+    /// 編集する範囲が内容の外にあるか、文字の途中を指している
+    InvalidEditRange {
+        /// 範囲の開始位置（バイトオフセット）
+        start: usize,
+        /// 範囲の終了位置（バイトオフセット）
+        end: usize,
+        /// 内容の長さ（バイト数）
+        len: usize,
+    },
+    /// `timeout_ms` の時間を過ぎたため処理を中断した
+    Timeout {
+        /// 処理していたファイルのパス
+        path: String,
+    },
+    /// `cancel_token` で打ち切りを求められたため処理を中断した
+    Cancelled {
+        /// 処理していたファイルのパス
+        path: String,
+    },
+    /// `ContentProvider` からファイルの内容を取得できなかった
+    ContentUnavailable {
+        /// 取得しようとしたファイルのパス
+        path: String,
+        /// `ContentProvider` が返したエラー
+        source: Arc<dyn Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPattern { pattern, source } => {
+                write!(f, "Invalid regex pattern '{}': {}", pattern, source)
+            }
+            Self::InvalidExcludePattern { pattern, source } => {
+                write!(f, "Invalid exclude pattern '{}': {}", pattern, source)
+            }
+            #[cfg(feature = "fancy-regex")]
+            Self::InvalidFancyPattern { pattern, source } => {
+                write!(f, "Invalid regex pattern '{}': {}", pattern, source)
+            }
+            #[cfg(feature = "pcre2")]
+            Self::InvalidPcre2Pattern { pattern, source } => {
+                write!(f, "Invalid regex pattern '{}': {}", pattern, source)
+            }
+            Self::InvalidPatternSet(source) => write!(f, "Invalid regex pattern set: {}", source),
+            Self::LiteralMatcher(source) => {
+                write!(f, "Failed to build literal matcher: {}", source)
+            }
+            #[cfg(feature = "vectorscan")]
+            Self::VectorscanMatcher(source) => {
+                write!(f, "Failed to build vectorscan matcher: {}", source)
+            }
+            Self::EditDistanceTooLarge { pattern, max_edits } => write!(
+                f,
+                "Edit distance {} must be smaller than the pattern length of '{}'",
+                max_edits, pattern
+            ),
+            Self::InvalidLineTerminator(byte) => write!(
+                f,
+                "Invalid line terminator: byte 0x{:02x} is not ASCII",
+                byte
+            ),
+            Self::InvalidOptions(reason) => f.write_str(reason),
+            Self::InvalidEditRange { start, end, len } => write!(
+                f,
+                "Invalid edit range {}..{} for content of {} bytes",
+                start, end, len
+            ),
+            Self::Timeout { path } => write!(f, "Timed out in '{}'", path),
+            Self::Cancelled { path } => write!(f, "Cancelled in '{}'", path),
+            Self::ContentUnavailable { path, source } => {
+                write!(f, "Failed to load content of '{}': {}", path, source)
+            }
+        }
+    }
+}
+
+impl Error for SearchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            // regex と aho-corasick のエラーは `std` フィーチャーでのみ `Error` を実装する
+            #[cfg(feature = "std")]
+            Self::InvalidPattern { source, .. }
+            | Self::InvalidExcludePattern { source, .. }
+            | Self::InvalidPatternSet(source) => Some(source),
+            #[cfg(feature = "std")]
+            Self::LiteralMatcher(source) => Some(source),
+            #[cfg(feature = "fancy-regex")]
+            Self::InvalidFancyPattern { source, .. } => Some(source),
+            #[cfg(feature = "pcre2")]
+            Self::InvalidPcre2Pattern { source, .. } => Some(source),
+            #[cfg(feature = "vectorscan")]
+            Self::VectorscanMatcher(source) => Some(&**source),
+            Self::ContentUnavailable { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,521 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use regex_syntax::ast::{
+    self, AssertionKind, Ast, ClassAsciiKind, ClassPerlKind, ClassSet, ClassSetBinaryOpKind,
+    ClassSetItem, ClassUnicodeKind, GroupKind, RepetitionKind, RepetitionRange,
+};
+
+use crate::{PatternError, SearchOptions, validate_pattern};
+
+/// 説明の木の要素の種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternNodeKind {
+    /// 空（空文字列にマッチする）
+    Empty,
+    /// 文字列そのもの（連続する文字はまとめる）
+    Literal {
+        /// マッチする文字列（エスケープを解いたもの）
+        text: String,
+    },
+    /// 任意の1文字（`.`）
+    AnyChar,
+    /// 文字クラス（`\d`、`\p{Greek}`、`[a-z]` など）
+    Class {
+        /// 否定（含まれない文字にマッチする）かどうか
+        negated: bool,
+    },
+    /// 位置だけにマッチするもの（`^`、`$`、`\b` など）
+    Assertion,
+    /// 繰り返し（子要素は繰り返される要素）
+    Repetition {
+        /// 最小の回数
+        min: u32,
+        /// 最大の回数（上限がない場合は `None`）
+        max: Option<u32>,
+        /// できるだけ多く繰り返すかどうか
+        greedy: bool,
+    },
+    /// グループ（子要素はグループの中身）
+    Group {
+        /// キャプチャグループの番号（キャプチャしないグループは `None`）
+        capture_index: Option<u32>,
+        /// キャプチャグループの名前
+        name: Option<String>,
+    },
+    /// 選択（子要素はそれぞれの候補）
+    Alternation,
+    /// 連接（子要素を順にマッチする）
+    Concat,
+    /// フラグの設定（`(?i)` など）
+    Flags,
+}
+
+/// パターンの説明の木の要素
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternNode {
+    /// 要素の種類
+    pub kind: PatternNodeKind,
+    /// 人が読むための英語の説明（例: `"a digit"`、`"repeated one or more times"`）
+    pub description: String,
+    /// パターン内の範囲（0ベースのバイト位置）
+    pub span: Range<usize>,
+    /// 開始列番号（1ベース、`SearchOptions::column_unit` の単位）
+    pub column: u32,
+    /// 終了列番号（1ベース、この位置の文字は含まない）
+    pub end_column: u32,
+    /// 子要素
+    pub children: Vec<PatternNode>,
+}
+
+/// パターンを解析し、正規表現に詳しくない人向けの説明の木を返す
+///
+/// 文字列・文字クラス・繰り返し・グループ・選択などの要素ごとに、英語の説明とパターン内の
+/// 範囲を返すため、「このパターンの意味」を要素ごとにハイライトしながら表示できる。
+/// 連続する文字は1つの `Literal` にまとめる。`literal` と `max_edit_distance` では
+/// パターン全体を1つの `Literal` として返す。`explain` フィーチャーで使える。
+///
+/// # Arguments
+///
+/// * `pattern` - 説明するパターン
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 説明の木の根、またはパターンが無効な場合は `validate_pattern` と同じエラー
+///
+/// ```
+/// use simple_find_core::{PatternNodeKind, SearchOptions, explain_pattern};
+///
+/// let root = explain_pattern(r"id-\d+", &SearchOptions::new()).unwrap();
+/// assert_eq!(root.kind, PatternNodeKind::Concat);
+/// assert_eq!(root.children[0].description, "the text \"id-\"");
+/// assert_eq!(root.children[1].description, "repeated one or more times");
+/// assert_eq!(root.children[1].children[0].description, "a digit");
+/// ```
+pub fn explain_pattern(
+    pattern: &str,
+    options: &SearchOptions,
+) -> Result<PatternNode, PatternError> {
+    validate_pattern(pattern, options)?;
+
+    let explainer = Explainer { pattern, options };
+    if options.literal || options.max_edit_distance.is_some() {
+        let mut description = format!("the text {:?}", pattern);
+        if let Some(max_edits) = options.max_edit_distance {
+            description.push_str(&format!(", allowing up to {} edits", max_edits));
+        }
+        let kind = PatternNodeKind::Literal {
+            text: pattern.to_string(),
+        };
+        return Ok(explainer.node(kind, description, 0..pattern.len(), Vec::new()));
+    }
+
+    let ast = ast::parse::ParserBuilder::new()
+        .ignore_whitespace(options.regex_flags.ignore_whitespace)
+        .build()
+        .parse(pattern)
+        .map_err(|e| PatternError::new(e.kind().to_string()))?;
+    Ok(explainer.explain(&ast))
+}
+
+/// AST を説明の木に変換する
+struct Explainer<'a> {
+    /// 説明するパターン
+    pattern: &'a str,
+    /// 検索オプション
+    options: &'a SearchOptions,
+}
+
+impl Explainer<'_> {
+    /// 範囲から列番号を求めて要素を作る
+    fn node(
+        &self,
+        kind: PatternNodeKind,
+        description: String,
+        span: Range<usize>,
+        children: Vec<PatternNode>,
+    ) -> PatternNode {
+        PatternNode {
+            kind,
+            description,
+            column: self.options.column_unit.column(self.pattern, span.start),
+            end_column: self.options.column_unit.column(self.pattern, span.end),
+            span,
+            children,
+        }
+    }
+
+    /// AST の要素を説明の木の要素に変換する
+    fn explain(&self, ast: &Ast) -> PatternNode {
+        let span = offsets(ast.span());
+        let flags = &self.options.regex_flags;
+        match ast {
+            Ast::Empty(_) => self.node(
+                PatternNodeKind::Empty,
+                "the empty string".to_string(),
+                span,
+                Vec::new(),
+            ),
+            Ast::Flags(set) => self.node(
+                PatternNodeKind::Flags,
+                format!(
+                    "set the flags {:?}",
+                    &self.pattern[offsets(&set.flags.span)]
+                ),
+                span,
+                Vec::new(),
+            ),
+            Ast::Literal(literal) => self.literal(literal.c.to_string(), span),
+            Ast::Dot(_) => {
+                let description = if flags.dot_all {
+                    "any character"
+                } else {
+                    "any character except a line break"
+                };
+                self.node(
+                    PatternNodeKind::AnyChar,
+                    description.to_string(),
+                    span,
+                    Vec::new(),
+                )
+            }
+            Ast::Assertion(assertion) => self.node(
+                PatternNodeKind::Assertion,
+                self.assertion(&assertion.kind).to_string(),
+                span,
+                Vec::new(),
+            ),
+            Ast::ClassUnicode(class) => self.class(class.is_negated(), unicode(class), span),
+            Ast::ClassPerl(class) => self.class(
+                class.negated,
+                perl(&class.kind, class.negated).to_string(),
+                span,
+            ),
+            Ast::ClassBracketed(class) => self.class(class.negated, bracketed(class), span),
+            Ast::Repetition(repetition) => {
+                let (min, max) = match repetition.op.kind {
+                    RepetitionKind::ZeroOrOne => (0, Some(1)),
+                    RepetitionKind::ZeroOrMore => (0, None),
+                    RepetitionKind::OneOrMore => (1, None),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => (n, Some(n)),
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (n, None),
+                    RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => (m, Some(n)),
+                };
+                let greedy = repetition.greedy != flags.swap_greed;
+                let mut description = match (min, max) {
+                    (0, Some(1)) => "optional".to_string(),
+                    (0, None) => "repeated zero or more times".to_string(),
+                    (1, None) => "repeated one or more times".to_string(),
+                    (m, Some(n)) if m == n => format!("repeated exactly {} times", n),
+                    (m, None) => format!("repeated at least {} times", m),
+                    (m, Some(n)) => format!("repeated between {} and {} times", m, n),
+                };
+                if !greedy {
+                    description.push_str(", as few times as possible");
+                }
+                let kind = PatternNodeKind::Repetition { min, max, greedy };
+                self.node(kind, description, span, vec![self.explain(&repetition.ast)])
+            }
+            Ast::Group(group) => {
+                let (capture_index, name, description) = match &group.kind {
+                    GroupKind::CaptureIndex(index) => {
+                        (Some(*index), None, format!("capture group {}", index))
+                    }
+                    GroupKind::CaptureName { name, .. } => (
+                        Some(name.index),
+                        Some(name.name.clone()),
+                        format!("capture group {} named {:?}", name.index, name.name),
+                    ),
+                    GroupKind::NonCapturing(group_flags) if group_flags.items.is_empty() => {
+                        (None, None, "group".to_string())
+                    }
+                    GroupKind::NonCapturing(group_flags) => (
+                        None,
+                        None,
+                        format!(
+                            "group with the flags {:?}",
+                            &self.pattern[offsets(&group_flags.span)]
+                        ),
+                    ),
+                };
+                let kind = PatternNodeKind::Group {
+                    capture_index,
+                    name,
+                };
+                self.node(kind, description, span, vec![self.explain(&group.ast)])
+            }
+            Ast::Alternation(alternation) => self.node(
+                PatternNodeKind::Alternation,
+                format!("any one of {} alternatives", alternation.asts.len()),
+                span,
+                alternation
+                    .asts
+                    .iter()
+                    .map(|ast| self.explain(ast))
+                    .collect(),
+            ),
+            Ast::Concat(concat) => {
+                let children = self.concat(&concat.asts);
+                if children.len() == 1 {
+                    return children.into_iter().next().unwrap();
+                }
+                self.node(
+                    PatternNodeKind::Concat,
+                    format!("a sequence of {} parts", children.len()),
+                    span,
+                    children,
+                )
+            }
+        }
+    }
+
+    /// 連接の要素を変換し、連続する文字を1つの `Literal` にまとめる
+    fn concat(&self, asts: &[Ast]) -> Vec<PatternNode> {
+        let mut children = Vec::new();
+        let mut pending: Option<(String, Range<usize>)> = None;
+        for ast in asts {
+            if let Ast::Literal(literal) = ast {
+                let span = offsets(&literal.span);
+                match &mut pending {
+                    Some((text, range)) => {
+                        text.push(literal.c);
+                        range.end = span.end;
+                    }
+                    None => pending = Some((literal.c.to_string(), span)),
+                }
+                continue;
+            }
+            if let Some((text, span)) = pending.take() {
+                children.push(self.literal(text, span));
+            }
+            children.push(self.explain(ast));
+        }
+        if let Some((text, span)) = pending {
+            children.push(self.literal(text, span));
+        }
+        children
+    }
+
+    /// 文字列の要素を作る
+    fn literal(&self, text: String, span: Range<usize>) -> PatternNode {
+        let description = if text.chars().count() == 1 {
+            format!("the character {:?}", text)
+        } else {
+            format!("the text {:?}", text)
+        };
+        self.node(
+            PatternNodeKind::Literal { text },
+            description,
+            span,
+            Vec::new(),
+        )
+    }
+
+    /// 文字クラスの要素を作る
+    fn class(&self, negated: bool, description: String, span: Range<usize>) -> PatternNode {
+        self.node(
+            PatternNodeKind::Class { negated },
+            description,
+            span,
+            Vec::new(),
+        )
+    }
+
+    /// 位置の説明を返す
+    fn assertion(&self, kind: &AssertionKind) -> &'static str {
+        let multi_line = self.options.regex_flags.multi_line;
+        match kind {
+            AssertionKind::StartLine if multi_line => "the start of a line",
+            AssertionKind::EndLine if multi_line => "the end of a line",
+            AssertionKind::StartLine | AssertionKind::StartText => "the start of the text",
+            AssertionKind::EndLine | AssertionKind::EndText => "the end of the text",
+            AssertionKind::WordBoundary => "a word boundary",
+            AssertionKind::NotWordBoundary => "a position that is not a word boundary",
+            AssertionKind::WordBoundaryStart | AssertionKind::WordBoundaryStartAngle => {
+                "the start of a word"
+            }
+            AssertionKind::WordBoundaryEnd | AssertionKind::WordBoundaryEndAngle => {
+                "the end of a word"
+            }
+            AssertionKind::WordBoundaryStartHalf => "a position not preceded by a word character",
+            AssertionKind::WordBoundaryEndHalf => "a position not followed by a word character",
+        }
+    }
+}
+
+/// AST の範囲をバイト位置の範囲に変換する
+fn offsets(span: &ast::Span) -> Range<usize> {
+    span.start.offset..span.end.offset
+}
+
+/// `\d`、`\s`、`\w` とその否定の説明を返す
+fn perl(kind: &ClassPerlKind, negated: bool) -> &'static str {
+    match (kind, negated) {
+        (ClassPerlKind::Digit, false) => "a digit",
+        (ClassPerlKind::Digit, true) => "a non-digit",
+        (ClassPerlKind::Space, false) => "a whitespace character",
+        (ClassPerlKind::Space, true) => "a non-whitespace character",
+        (ClassPerlKind::Word, false) => "a word character",
+        (ClassPerlKind::Word, true) => "a non-word character",
+    }
+}
+
+/// Unicode の文字クラス（`\p{...}`）の説明を返す
+fn unicode(class: &ast::ClassUnicode) -> String {
+    let name = match &class.kind {
+        ClassUnicodeKind::OneLetter(c) => c.to_string(),
+        ClassUnicodeKind::Named(name) => name.clone(),
+        ClassUnicodeKind::NamedValue { name, value, .. } => format!("{}={}", name, value),
+    };
+    if class.is_negated() {
+        format!("a character not in the Unicode class {}", name)
+    } else {
+        format!("a character in the Unicode class {}", name)
+    }
+}
+
+/// 角括弧の文字クラスの説明を返す
+fn bracketed(class: &ast::ClassBracketed) -> String {
+    if class.negated {
+        format!("any character except {}", class_set(&class.kind))
+    } else {
+        format!("one of {}", class_set(&class.kind))
+    }
+}
+
+/// 角括弧の中身の説明を返す
+fn class_set(set: &ClassSet) -> String {
+    match set {
+        ClassSet::Item(item) => class_set_item(item),
+        ClassSet::BinaryOp(op) => {
+            let relation = match op.kind {
+                ClassSetBinaryOpKind::Intersection => "that is also",
+                ClassSetBinaryOpKind::Difference => "that is not",
+                ClassSetBinaryOpKind::SymmetricDifference => "or else",
+            };
+            format!("{} {} {}", class_set(&op.lhs), relation, class_set(&op.rhs))
+        }
+    }
+}
+
+/// 角括弧の中の要素の説明を返す
+fn class_set_item(item: &ClassSetItem) -> String {
+    match item {
+        ClassSetItem::Empty(_) => "nothing".to_string(),
+        ClassSetItem::Literal(literal) => format!("{:?}", literal.c),
+        ClassSetItem::Range(range) => format!("{:?} to {:?}", range.start.c, range.end.c),
+        ClassSetItem::Ascii(class) => {
+            let name = match class.kind {
+                ClassAsciiKind::Alnum => "letter or digit",
+                ClassAsciiKind::Alpha => "letter",
+                ClassAsciiKind::Ascii => "character",
+                ClassAsciiKind::Blank => "space or tab",
+                ClassAsciiKind::Cntrl => "control character",
+                ClassAsciiKind::Digit => "digit",
+                ClassAsciiKind::Graph => "visible character",
+                ClassAsciiKind::Lower => "lowercase letter",
+                ClassAsciiKind::Print => "printable character",
+                ClassAsciiKind::Punct => "punctuation character",
+                ClassAsciiKind::Space => "whitespace character",
+                ClassAsciiKind::Upper => "uppercase letter",
+                ClassAsciiKind::Word => "word character",
+                ClassAsciiKind::Xdigit => "hex digit",
+            };
+            if class.negated {
+                format!("a character that is not an ASCII {}", name)
+            } else {
+                format!("an ASCII {}", name)
+            }
+        }
+        ClassSetItem::Unicode(class) => unicode(class),
+        ClassSetItem::Perl(class) => perl(&class.kind, class.negated).to_string(),
+        ClassSetItem::Bracketed(class) => format!("({})", bracketed(class)),
+        ClassSetItem::Union(union) => {
+            let items: Vec<String> = union.items.iter().map(class_set_item).collect();
+            match items.len() {
+                0 => "nothing".to_string(),
+                1 => items.into_iter().next().unwrap(),
+                _ => items.join(", "),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnUnit, RegexFlags};
+
+    #[test]
+    fn test_explain_pattern_tree() {
+        let options = SearchOptions::new().column_unit(ColumnUnit::Utf16);
+        let root = explain_pattern(r"(?<year>\d{4})|[^a-z_]+?|😀\b", &options).unwrap();
+        assert_eq!(root.kind, PatternNodeKind::Alternation);
+        assert_eq!(root.description, "any one of 3 alternatives");
+
+        let group = &root.children[0];
+        assert_eq!(
+            group.kind,
+            PatternNodeKind::Group {
+                capture_index: Some(1),
+                name: Some("year".to_string())
+            }
+        );
+        let repetition = &group.children[0];
+        assert_eq!(
+            repetition.kind,
+            PatternNodeKind::Repetition {
+                min: 4,
+                max: Some(4),
+                greedy: true
+            }
+        );
+        assert_eq!(repetition.description, "repeated exactly 4 times");
+
+        let lazy = &root.children[1];
+        assert_eq!(
+            lazy.description,
+            "repeated one or more times, as few times as possible"
+        );
+        assert_eq!(
+            lazy.children[0].description,
+            "any character except 'a' to 'z', '_'"
+        );
+        assert_eq!(
+            lazy.children[0].kind,
+            PatternNodeKind::Class { negated: true }
+        );
+
+        // 位置は列番号の単位に従う
+        let concat = &root.children[2];
+        assert_eq!(concat.children[0].description, "the character \"😀\"");
+        assert_eq!(concat.children[1].span, 29..31);
+        assert_eq!(
+            (concat.children[1].column, concat.children[1].end_column),
+            (28, 30)
+        );
+    }
+
+    #[test]
+    fn test_explain_pattern_modes_and_errors() {
+        let options = SearchOptions::new().literal(true);
+        let root = explain_pattern("a.b", &options).unwrap();
+        assert_eq!(
+            root.kind,
+            PatternNodeKind::Literal {
+                text: "a.b".to_string()
+            }
+        );
+
+        let options = SearchOptions::new().regex_flags(RegexFlags::new().multi_line(true));
+        let root = explain_pattern("^$", &options).unwrap();
+        assert_eq!(root.children[0].description, "the start of a line");
+
+        let error = explain_pattern("a(", &SearchOptions::new()).unwrap_err();
+        assert_eq!(error.span, Some(1..2));
+    }
+}
@@ -0,0 +1,233 @@
+#[cfg(feature = "validate")]
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use fancy_regex::{Captures, Regex, RegexBuilder};
+
+#[cfg(feature = "validate")]
+use crate::engine::parser_builder;
+use crate::normalize::normalize_pattern;
+#[cfg(feature = "validate")]
+use crate::validate::PatternInfo;
+use crate::{CaptureGroup, Matcher, SearchError, SearchOptions, SpanMatch, build_pattern};
+
+/// fancy-regex でコンパイルしたパターンで行内のマッチを探すマッチャー
+///
+/// `RegexEngine::Fancy` を選んだ場合に `PatternSet::Custom` として使う。
+pub(crate) struct FancyMatcher {
+    /// パターンごとの正規表現
+    regexes: Vec<Regex>,
+}
+
+impl FancyMatcher {
+    /// オプションに従ってパターンをコンパイルする
+    pub(crate) fn new(patterns: &[&str], options: &SearchOptions) -> Result<Self, SearchError> {
+        let regexes = patterns
+            .iter()
+            .map(|pattern| build_fancy_regex(pattern, options))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { regexes })
+    }
+}
+
+impl Matcher for FancyMatcher {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found = Vec::new();
+        for (pattern_index, re) in self.regexes.iter().enumerate() {
+            // バックトラックの上限に達するなどの実行時エラーでは、それ以降を探さない
+            if re.captures_len() > 1 {
+                found.extend(
+                    re.captures_iter(line)
+                        .map_while(Result::ok)
+                        .map(|caps| SpanMatch {
+                            pattern_index,
+                            span: caps.get(0).unwrap().range(),
+                            captures: capture_groups(&caps),
+                        }),
+                );
+            } else {
+                found.extend(
+                    re.find_iter(line)
+                        .map_while(Result::ok)
+                        .map(|m| SpanMatch::new(pattern_index, m.range())),
+                );
+            }
+        }
+        if self.regexes.len() > 1 {
+            found.sort_by_key(|m| (m.span.start, m.pattern_index));
+        }
+        found
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        self.regexes
+            .iter()
+            .any(|re| re.is_match(line).unwrap_or(false))
+    }
+
+    fn capture_name(&self, pattern_index: usize, group_index: usize) -> Option<&str> {
+        self.regexes[pattern_index]
+            .capture_names()
+            .nth(group_index)
+            .flatten()
+    }
+}
+
+/// マッチ全体を除くキャプチャグループを取り出す
+fn capture_groups(caps: &Captures) -> Vec<CaptureGroup> {
+    caps.iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(index, group)| {
+            group.map(|m| CaptureGroup {
+                index,
+                text: m.as_str().to_string(),
+                column: (m.start() + 1) as u32,
+                end_column: (m.end() + 1) as u32,
+            })
+        })
+        .collect()
+}
+
+/// オプションに従って fancy-regex の正規表現をコンパイルする
+///
+/// `build_regex` と同じく、`literal` モードでは `ignore_whitespace` を無視する。
+fn build_fancy_regex(pattern: &str, options: &SearchOptions) -> Result<Regex, SearchError> {
+    let flags = &options.regex_flags;
+    let mut built = build_pattern(&normalize_pattern(pattern, options), options);
+    // fancy-regex のビルダーには `swap_greed` がないため、インラインフラグで指定する
+    if flags.swap_greed {
+        built.insert_str(0, "(?U)");
+    }
+    RegexBuilder::new(&built)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_all)
+        .unicode_mode(flags.unicode)
+        .ignore_whitespace(flags.ignore_whitespace && !options.literal)
+        .delegate_size_limit(options.size_limit)
+        .delegate_dfa_size_limit(options.dfa_size_limit)
+        .build()
+        .map_err(|source| SearchError::InvalidFancyPattern {
+            pattern: pattern.to_string(),
+            source,
+        })
+}
+
+/// `validate_pattern` で fancy-regex のパターンを確かめる
+///
+/// fancy-regex のエラーは位置を持たないため、無効な場合の `span` は常に `None` になる。
+#[cfg(feature = "validate")]
+pub(crate) fn check(pattern: &str, options: &SearchOptions) -> Result<PatternInfo, SearchError> {
+    let regex = build_fancy_regex(pattern, options)?;
+    let (is_literal, matches_empty) = if options.literal {
+        (true, pattern.is_empty())
+    } else if let Ok(hir) = parser_builder(options).build().parse(pattern) {
+        let properties = hir.properties();
+        (properties.is_literal(), properties.minimum_len() == Some(0))
+    } else {
+        // 先読み・後読みや後方参照を含むパターンは構文から最短の長さを求められないため、
+        // 空文字列にマッチするかどうかで判定する
+        (false, regex.is_match("").unwrap_or(false))
+    };
+    Ok(PatternInfo {
+        capture_count: regex.captures_len() - 1,
+        capture_names: regex.capture_names().flatten().map(String::from).collect(),
+        is_literal,
+        matches_empty,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[cfg(feature = "validate")]
+    use crate::validate_pattern;
+    use crate::{
+        FileInput, RegexEngine, SearchOptions, replace, search_multi, search_with_options,
+    };
+
+    fn fancy() -> SearchOptions {
+        SearchOptions::new().regex_engine(RegexEngine::Fancy)
+    }
+
+    #[test]
+    fn test_fancy_lookaround_and_backreference() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "price: 100 USD\nthe the cat\ncost: 5 EUR".to_string(),
+        }];
+
+        // 組み込みのエンジンでは先読みは使えない
+        assert!(search_with_options(r"\d+(?= USD)", &files, &SearchOptions::new()).is_err());
+
+        let results = search_with_options(r"\d+(?= USD)", &files, &fancy())
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "100");
+
+        let results = search_with_options(r"(?<!cost: )\b\d+", &files, &fancy())
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 1);
+
+        // 後方参照とキャプチャグループ
+        let results = search_with_options(r"\b(\w+) \1\b", &files, &fancy())
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "the the");
+        assert_eq!(results[0].captures[0].text, "the");
+
+        // 複数のパターンは列番号順に返す
+        let results = search_multi(&["USD", r"\d+(?= )"], &files, &fancy())
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].pattern_index, 1);
+        assert_eq!(results[1].pattern_index, 0);
+
+        // オプションは組み込みのエンジンと同じように効く
+        let options = fancy().case_sensitive(false).whole_word(true);
+        let results = search_with_options("THE", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_fancy_replace() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "the the cat".to_string(),
+        }];
+        let replaced = replace(r"\b(?<word>\w+) \k<word>\b", &files, "${word}", &fancy()).unwrap();
+        assert_eq!(replaced[0].content, "the cat");
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn test_fancy_validate() {
+        let info = validate_pattern(r"(?<word>\w+) \k<word>", &fancy()).unwrap();
+        assert_eq!(info.capture_count, 1);
+        assert_eq!(info.capture_names, vec!["word"]);
+        assert!(!info.matches_empty);
+        assert!(validate_pattern(r"(?!x)", &fancy()).unwrap().matches_empty);
+        assert!(validate_pattern(r"(?<=a", &fancy()).is_err());
+    }
+
+    #[test]
+    fn test_engine_features() {
+        assert!(RegexEngine::available().contains(&RegexEngine::Fancy));
+        let features = fancy().engine_features();
+        assert!(features.lookaround && features.backreferences);
+        assert!(!features.linear_time);
+        assert!(SearchOptions::new().engine_features().linear_time);
+        assert_eq!(RegexEngine::Fancy.name(), "fancy-regex");
+    }
+}
@@ -0,0 +1,237 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::records::split_records;
+use crate::{FileInput, SearchError, SearchOptions};
+
+/// 1文字マッチするごとの基本スコア
+const SCORE_MATCH: i64 = 16;
+/// マッチ間の隙間の開始に対する減点
+const PENALTY_GAP_START: i64 = 3;
+/// 隙間が1文字伸びるごとの減点
+const PENALTY_GAP_EXTENSION: i64 = 1;
+/// 単語の先頭（行頭や空白・記号の直後）でマッチした場合の加点
+const BONUS_BOUNDARY: i64 = 8;
+/// camelCase の大文字や数字の始まりでマッチした場合の加点
+const BONUS_CAMEL: i64 = 7;
+/// 直前の文字に続けてマッチした場合の最低限の加点
+///
+/// 連続したマッチには、その連続の最初の文字が受けた加点も引き継がれる。
+const BONUS_CONSECUTIVE: i64 = 4;
+/// パターンの最初の文字に対する加点の倍率
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// あいまい検索の結果を表す構造体
+pub struct FuzzyMatch {
+    /// マッチしたファイルのパス
+    pub path: String,
+    /// マッチした行番号（1ベース）
+    pub line: u32,
+    /// マッチの良さ（大きいほど良い）
+    pub score: i64,
+    /// パターンの各文字がマッチした列番号（1ベース、単位は `SearchOptions::column_unit` に従う）
+    pub positions: Vec<u32>,
+    /// マッチした行のテキスト
+    pub line_text: String,
+}
+
+/// fzf のようにパターンの文字を順番どおりに含む行を探す
+///
+/// パターンの文字が間に別の文字を挟んでも順番どおりに現れればマッチし、
+/// 連続したマッチや単語の先頭でのマッチほど高いスコアになる。
+/// 結果はスコアの高い順（同点ならファイル・行の順）に並ぶ。
+///
+/// 使われるオプションは `case_sensitive`、`max_results`、`line_range`、
+/// `record_separator`、`line_terminator`、`column_unit`、`max_line_length`、`search_binary` で、`case_sensitive(false)` の場合は
+/// 大文字小文字を無視する。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する文字の並び（正規表現ではない）
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// スコア順の検索結果のリスト、またはオプションが無効な場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, fuzzy_search};
+///
+/// let files = vec![FileInput {
+///     path: "main.rs".to_string(),
+///     content: "fn search_with_options() {}".to_string(),
+/// }];
+/// let matches = fuzzy_search("swo", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(matches[0].positions, vec![4, 11, 16]);
+/// ```
+pub fn fuzzy_search(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<Vec<FuzzyMatch>, SearchError> {
+    options.line_terminator.validate()?;
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    let mut results = Vec::new();
+    for f in files.iter().filter(|f| !options.skips_file(f)) {
+        for record in split_records(&f.content, options) {
+            if !options.includes_record(&record) {
+                continue;
+            }
+            if let Some((score, offsets)) =
+                fuzzy_match(&pattern, record.text, options.case_sensitive)
+            {
+                results.push(FuzzyMatch {
+                    path: f.path.clone(),
+                    line: record.line as u32,
+                    score,
+                    positions: offsets
+                        .iter()
+                        .map(|&offset| options.column_unit.column(record.text, offset))
+                        .collect(),
+                    line_text: record.text.to_string(),
+                });
+            }
+        }
+    }
+
+    // 安定ソートなので同点の結果は元の順序のまま
+    results.sort_by_key(|m| core::cmp::Reverse(m.score));
+    if let Some(max_results) = options.max_results {
+        results.truncate(max_results);
+    }
+    Ok(results)
+}
+
+/// 行の中でパターンの文字を順番どおりに探し、スコアと各文字のバイト位置を返す
+///
+/// 前から走査して最初にパターンを含み終える位置を求め、そこから後ろ向きに走査して
+/// できるだけ短い範囲に絞り込む（fzf の v1 アルゴリズムと同じ考え方）。
+fn fuzzy_match(pattern: &[char], line: &str, case_sensitive: bool) -> Option<(i64, Vec<usize>)> {
+    if pattern.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let eq = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a == b || a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+
+    let mut pattern_idx = 0;
+    let mut end = None;
+    for (idx, &(_, c)) in chars.iter().enumerate() {
+        if eq(c, pattern[pattern_idx]) {
+            pattern_idx += 1;
+            if pattern_idx == pattern.len() {
+                end = Some(idx);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    for idx in (0..=end).rev() {
+        if eq(chars[idx].1, pattern[pattern.len() - 1 - positions.len()]) {
+            positions.push(idx);
+            if positions.len() == pattern.len() {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    // 連続したマッチの最初の文字が受けた加点
+    let mut chunk_bonus = 0;
+    for (n, &idx) in positions.iter().enumerate() {
+        let mut bonus = char_bonus(&chars, idx);
+        match prev {
+            Some(p) if p + 1 == idx => bonus = bonus.max(chunk_bonus).max(BONUS_CONSECUTIVE),
+            Some(p) => {
+                score -= PENALTY_GAP_START + PENALTY_GAP_EXTENSION * (idx - p - 2) as i64;
+                chunk_bonus = bonus;
+            }
+            None => chunk_bonus = bonus,
+        }
+        if n == 0 {
+            score += SCORE_MATCH + bonus * BONUS_FIRST_CHAR_MULTIPLIER;
+        } else {
+            score += SCORE_MATCH + bonus;
+        }
+        prev = Some(idx);
+    }
+
+    Some((score, positions.iter().map(|&idx| chars[idx].0).collect()))
+}
+
+/// 文字の位置に応じた加点を返す
+fn char_bonus(chars: &[(usize, char)], idx: usize) -> i64 {
+    let current = chars[idx].1;
+    let Some(&(_, before)) = idx.checked_sub(1).and_then(|i| chars.get(i)) else {
+        return BONUS_BOUNDARY;
+    };
+
+    if !before.is_alphanumeric() && current.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if (before.is_lowercase() && current.is_uppercase())
+        || (!before.is_numeric() && current.is_numeric())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn files(content: &str) -> Vec<FileInput> {
+        vec![FileInput {
+            path: "test.txt".to_string(),
+            content: content.to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_fuzzy_subsequence() {
+        let results =
+            fuzzy_search("abc", &files("a_b_c\nacb\nabcxx"), &SearchOptions::new()).unwrap();
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        // 連続したマッチが最も高く、順序が違う行はマッチしない
+        assert_eq!(lines, vec![3, 1]);
+        assert_eq!(results[0].positions, vec![1, 2, 3]);
+        assert_eq!(results[1].positions, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn test_fuzzy_prefers_word_boundaries() {
+        let results = fuzzy_search("fb", &files("xfxxxb\nfoo_bar"), &SearchOptions::new()).unwrap();
+        assert_eq!(results[0].line, 2);
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_fuzzy_case_and_limits() {
+        let content = "FooBar\nfoobar\nnothing";
+        assert_eq!(
+            fuzzy_search("fb", &files(content), &SearchOptions::new())
+                .unwrap()
+                .len(),
+            1
+        );
+        let options = SearchOptions::new().case_sensitive(false).max_results(1);
+        let results = fuzzy_search("fb", &files(content), &options).unwrap();
+        assert_eq!(results.len(), 1);
+        // camelCase の境界でマッチした行が上位になる
+        assert_eq!(results[0].line, 1);
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::engine;
+
+/// 文字列をそのまま検索する正規表現パターンに変換する
+///
+/// 正規表現の記号をすべてエスケープするため、`price ($)` のような入力も書いたとおりに検索できる。
+/// `SearchOptions::literal` と同じ変換で、パターンの一部だけをリテラルにしたい場合に使う。
+///
+/// # Arguments
+///
+/// * `text` - 検索する文字列
+///
+/// # Returns
+///
+/// エスケープしたパターン
+///
+/// ```
+/// use simple_find_core::escape_literal;
+///
+/// assert_eq!(escape_literal("price ($)"), r"price \(\$\)");
+/// ```
+pub fn escape_literal(text: &str) -> String {
+    engine::escape(text)
+}
+
+/// グロブ（`*.log` など）を同じ意味の正規表現パターンに変換する
+///
+/// 次の記法に対応する。それ以外の文字は書いたとおりにマッチする。
+///
+/// * `*` - `/` 以外の0文字以上
+/// * `**` - `/` を含む0文字以上
+/// * `?` - `/` 以外の1文字
+/// * `[abc]`、`[a-z]`、`[!abc]`（`[^abc]` も可） - 文字クラスとその否定
+/// * `{a,b}` - いずれかの候補（入れ子にできる）
+/// * `\` - 次の文字をそのままマッチさせる
+///
+/// 閉じていない `[` と `{` は文字として扱う。行の途中にもマッチするパターンになるため、
+/// 行全体と比べる場合は `SearchOptions::anchor` に `Anchor::WholeLine` を指定する。
+///
+/// # Arguments
+///
+/// * `glob` - 変換するグロブ
+///
+/// # Returns
+///
+/// 正規表現パターン
+///
+/// ```
+/// use simple_find_core::glob_to_regex;
+///
+/// assert_eq!(glob_to_regex("*.log"), r"[^/]*\.log");
+/// assert_eq!(glob_to_regex("{src,test}/**/?.rs"), r"(?:src|test)/.*/[^/]\.rs");
+/// ```
+pub fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut pattern = String::new();
+    // 対応する `}` がある `{` の数
+    let mut depth = 0;
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '*' if chars.get(idx + 1) == Some(&'*') => {
+                pattern.push_str(".*");
+                idx += 1;
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '\\' if idx + 1 < chars.len() => {
+                idx += 1;
+                push_escaped(&mut pattern, chars[idx]);
+            }
+            '[' => match class_end(&chars, idx) {
+                Some(end) => {
+                    push_class(&mut pattern, &chars[idx + 1..end]);
+                    idx = end;
+                }
+                None => pattern.push_str(r"\["),
+            },
+            '{' if has_closing_brace(&chars[idx + 1..]) => {
+                pattern.push_str("(?:");
+                depth += 1;
+            }
+            ',' if depth > 0 => pattern.push('|'),
+            '}' if depth > 0 => {
+                pattern.push(')');
+                depth -= 1;
+            }
+            c => push_escaped(&mut pattern, c),
+        }
+        idx += 1;
+    }
+    pattern
+}
+
+/// 文字をエスケープして追加する
+fn push_escaped(pattern: &mut String, c: char) {
+    let mut buf = [0; 4];
+    pattern.push_str(&engine::escape(c.encode_utf8(&mut buf)));
+}
+
+/// `[` に対応する `]` の位置を返す（先頭の `]` は文字として扱う）
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut idx = start + 1;
+    if matches!(chars.get(idx), Some('!' | '^')) {
+        idx += 1;
+    }
+    if chars.get(idx) == Some(&']') {
+        idx += 1;
+    }
+    (idx..chars.len()).find(|&i| chars[i] == ']')
+}
+
+/// グロブの文字クラスの中身を正規表現の文字クラスとして追加する
+fn push_class(pattern: &mut String, body: &[char]) {
+    pattern.push('[');
+    let body = match body.first() {
+        Some('!' | '^') => {
+            pattern.push('^');
+            &body[1..]
+        }
+        _ => body,
+    };
+    for (idx, &c) in body.iter().enumerate() {
+        // `-` は範囲として残すが、`--` は正規表現では差集合になるため続く方をエスケープする
+        let escape = match c {
+            '\\' | '[' | ']' | '^' | '&' | '~' => true,
+            '-' => idx > 0 && body[idx - 1] == '-',
+            _ => false,
+        };
+        if escape {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push(']');
+}
+
+/// `{` の後に対応する `}` があるかどうかを返す
+fn has_closing_brace(rest: &[char]) -> bool {
+    let mut depth = 0;
+    let mut escaped = false;
+    for &c in rest {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => depth += 1,
+            '}' if depth == 0 => return true,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::engine::Regex;
+
+    #[test]
+    fn test_glob_to_regex_matches_like_a_glob() {
+        let is_match = |glob: &str, text: &str| {
+            let regex = Regex::new(&format!("^(?:{})$", glob_to_regex(glob))).unwrap();
+            regex.is_match(text)
+        };
+        assert!(is_match("*.log", "error.log"));
+        assert!(!is_match("*.log", "logs/error.log"));
+        assert!(is_match("**/*.log", "logs/error.log"));
+        assert!(is_match("file?.[ct]xt", "file1.txt"));
+        assert!(!is_match("[!a-c]x", "bx"));
+        assert!(is_match("[]]", "]"));
+        assert!(is_match("{a,b{c,d}}.rs", "bd.rs"));
+        assert!(is_match(r"\*", "*"));
+
+        // 閉じていない括弧と、括弧の外のカンマは文字として扱う
+        assert!(is_match("[a{b,c", "[a{b,c"));
+        assert!(is_match("price ($)", "price ($)"));
+        assert!(is_match("[a&&b]", "&"));
+    }
+}
@@ -0,0 +1,99 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::MatchResult;
+
+/// 1行分の強調表示する範囲を表す構造体
+pub struct LineHighlights {
+    /// ファイルのパス
+    pub path: String,
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// 強調表示する範囲（`(column, end_column)` の組、1ベースで終端を含まない）
+    ///
+    /// 単位は検索時の `SearchOptions::column_unit` に従う。範囲は列番号順に並び、
+    /// 重なったり隣り合ったりする範囲は1つにまとめてある。
+    pub spans: Vec<(u32, u32)>,
+}
+
+/// 検索結果を行ごとにまとめ、強調表示する範囲を返す
+///
+/// `search_multi` などで同じ行の複数のマッチが重なっていても、範囲を列番号順に並べて
+/// 1つにまとめるため、そのまま行のテキストに適用できる。
+/// 行は結果に最初に現れた順に並ぶ。空マッチ（`invert_match` の結果を含む）は範囲に含めないが、
+/// その行自体は空の `spans` で返す。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+///
+/// # Returns
+///
+/// 行ごとの強調表示する範囲のリスト
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, highlight_spans, search_multi};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "foobar baz".to_string(),
+/// }];
+/// let output = search_multi(&["foo", "oba", "baz"], &files, &SearchOptions::new()).unwrap();
+/// let lines = highlight_spans(&output.results);
+/// assert_eq!(lines[0].spans, vec![(1, 6), (8, 11)]);
+/// ```
+pub fn highlight_spans(results: &[MatchResult]) -> Vec<LineHighlights> {
+    let mut lines: Vec<LineHighlights> = Vec::new();
+    let mut index: BTreeMap<(&str, u32), usize> = BTreeMap::new();
+    for result in results {
+        let idx = *index
+            .entry((&*result.path, result.line))
+            .or_insert_with(|| {
+                lines.push(LineHighlights {
+                    path: result.path.to_string(),
+                    line: result.line,
+                    spans: Vec::new(),
+                });
+                lines.len() - 1
+            });
+        if result.column < result.end_column {
+            lines[idx].spans.push((result.column, result.end_column));
+        }
+    }
+
+    for line in &mut lines {
+        line.spans = merge_spans(core::mem::take(&mut line.spans));
+    }
+    lines
+}
+
+/// 範囲を開始位置の順に並べ、重なったり隣り合ったりする範囲をまとめる
+fn merge_spans(mut spans: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    spans.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(spans.len());
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_spans() {
+        assert_eq!(
+            merge_spans(vec![(8, 10), (1, 4), (3, 6), (6, 7), (12, 13)]),
+            vec![(1, 7), (8, 10), (12, 13)]
+        );
+        assert_eq!(merge_spans(vec![(2, 9), (3, 4)]), vec![(2, 9)]);
+        assert!(merge_spans(Vec::new()).is_empty());
+    }
+}
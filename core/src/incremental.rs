@@ -0,0 +1,336 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{ControlFlow, Range};
+
+use crate::{
+    CompiledSearch, FileInput, MatchResult, RecordSeparator, ScanProgress, SearchError,
+    SearchOptions, compile_patterns, scan_text, search_compiled,
+};
+
+/// 編集のたびに、変わった部分だけを検索し直す1ファイル分の検索結果
+///
+/// エディタで入力するたびにファイル全体を検索し直す代わりに、`edit` で編集を適用すると
+/// 編集した行（前後のコンテキストがあればその分の行も含む）だけを検索し直し、それより後ろの
+/// 結果は行番号とバイトオフセットをずらして使い回す。結果は常に、編集後の内容を
+/// `search_with_options` で検索した場合と同じになる。
+///
+/// 行単位ではないレコード、`max_count_per_file`・`max_results`・`max_result_bytes`・`offset`・
+/// `line_range`、関連度のスコアなど、行の位置やファイル全体の結果に依存するオプションを
+/// 使う場合は、編集のたびに全体を検索し直す。
+///
+/// ```
+/// use simple_find_core::{FileInput, IncrementalSearch, SearchOptions};
+///
+/// let file = FileInput {
+///     path: "main.rs".to_string(),
+///     content: "todo one\nok\ntodo two".to_string(),
+/// };
+/// let mut search = IncrementalSearch::new("todo", file, &SearchOptions::new()).unwrap();
+/// assert_eq!(search.results().len(), 2);
+///
+/// // 2行目の前に1行挿入すると、後ろの結果は1行ずれる
+/// search.edit(9..9, "todo new\n").unwrap();
+/// let lines: Vec<u32> = search.results().iter().map(|r| r.line).collect();
+/// assert_eq!(lines, vec![1, 2, 4]);
+/// assert_eq!(search.content(), "todo one\ntodo new\nok\ntodo two");
+/// ```
+pub struct IncrementalSearch {
+    /// コンパイル済みのパターン
+    compiled: CompiledSearch,
+    /// 検索オプション
+    options: SearchOptions,
+    /// 編集を適用したファイル
+    file: FileInput,
+    /// 結果に入れるファイルのパス
+    path: Arc<str>,
+    /// 現在の内容の検索結果
+    results: Vec<MatchResult>,
+}
+
+impl IncrementalSearch {
+    /// パターンをコンパイルしてファイル全体を検索する
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - 検索する正規表現パターン
+    /// * `file` - 検索するファイル
+    /// * `options` - 検索オプション
+    ///
+    /// # Returns
+    ///
+    /// 検索結果を持つ状態、または正規表現パターンが無効な場合のエラー
+    pub fn new(
+        pattern: &str,
+        file: FileInput,
+        options: &SearchOptions,
+    ) -> Result<Self, SearchError> {
+        let mut search = Self {
+            compiled: compile_patterns(&[pattern], options)?,
+            options: options.clone(),
+            path: Arc::from(file.path.as_str()),
+            file,
+            results: Vec::new(),
+        };
+        search.search_all();
+        Ok(search)
+    }
+
+    /// 現在の内容の検索結果を返す
+    pub fn results(&self) -> &[MatchResult] {
+        &self.results
+    }
+
+    /// 編集を適用した現在の内容を返す
+    pub fn content(&self) -> &str {
+        &self.file.content
+    }
+
+    /// 内容の `range` の部分を `new_text` に置き換え、検索結果を更新する
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - 置き換える範囲（編集前の内容の先頭からのバイトオフセット）
+    /// * `new_text` - 置き換える文字列
+    ///
+    /// # Returns
+    ///
+    /// 範囲が内容の外にあるか、文字の途中を指している場合のエラー
+    pub fn edit(&mut self, range: Range<usize>, new_text: &str) -> Result<(), SearchError> {
+        let content = &self.file.content;
+        if range.start > range.end
+            || range.end > content.len()
+            || !content.is_char_boundary(range.start)
+            || !content.is_char_boundary(range.end)
+        {
+            return Err(SearchError::InvalidEditRange {
+                start: range.start,
+                end: range.end,
+                len: content.len(),
+            });
+        }
+
+        if !self.searches_by_line() {
+            self.file.content.replace_range(range, new_text);
+            self.search_all();
+            return Ok(());
+        }
+
+        // 結果が変わり得る行と、その結果のコンテキストを求めるために読む行の範囲
+        let margin = self.options.before_context.max(self.options.after_context);
+        let terminator = self.options.line_terminator;
+        let pattern = terminator.pattern();
+        let replaced_start = line_start(content, &pattern, range.start, margin);
+        let replaced_end = skip_lines(content, &pattern, range.end, margin + 1);
+        let scan_start = line_start(content, &pattern, replaced_start, margin);
+        let scan_end = skip_lines(content, &pattern, replaced_end, margin);
+        let scan_line = 1 + terminator.count_in(&content[..scan_start]);
+        let old_lines = terminator.count_in(&content[replaced_start..replaced_end]);
+        let byte_delta = new_text.len() as isize - range.len() as isize;
+
+        self.file.content.replace_range(range, new_text);
+        if self.options.skips_file(&self.file) {
+            self.search_all();
+            return Ok(());
+        }
+        let content = &self.file.content;
+        let new_replaced_end = replaced_end.saturating_add_signed(byte_delta);
+        let new_scan_end = scan_end.saturating_add_signed(byte_delta);
+        let new_lines = terminator.count_in(&content[replaced_start..new_replaced_end]);
+        let line_delta = new_lines as i64 - old_lines as i64;
+
+        // 編集した行とその前後だけを検索し直し、結果が変わり得る行の分だけを残す
+        let compiled = self.compiled.restarted(&self.options);
+        let mut progress = ScanProgress::default();
+        let mut found = Vec::new();
+        scan_text(
+            &content[scan_start..new_scan_end],
+            scan_line,
+            &compiled,
+            &self.options,
+            &mut progress,
+            |scan, line_idx, line_match| {
+                let record_start = scan_start + scan.lines[line_idx].offset;
+                if (replaced_start..new_replaced_end).contains(&record_start) {
+                    let mut result = scan
+                        .build_line_match(line_idx, line_match)
+                        .into_result(Arc::clone(&self.path));
+                    result.byte_offset += scan_start;
+                    found.push(result);
+                }
+                ControlFlow::Continue(())
+            },
+        );
+
+        // 編集より後ろの結果は位置をずらして使い回す
+        let old_results = core::mem::take(&mut self.results);
+        let mut after = Vec::new();
+        for mut result in old_results {
+            let record_start = result.byte_offset - result.line_byte_offset;
+            if record_start < replaced_start {
+                self.results.push(result);
+            } else if record_start >= replaced_end {
+                result.byte_offset = result.byte_offset.saturating_add_signed(byte_delta);
+                result.line = shift_line(result.line, line_delta);
+                for context in result
+                    .context_before
+                    .iter_mut()
+                    .chain(result.context_after.iter_mut())
+                {
+                    context.line = shift_line(context.line, line_delta);
+                }
+                after.push(result);
+            }
+        }
+        self.results.extend(found);
+        self.results.extend(after);
+        Ok(())
+    }
+
+    /// 行ごとに独立して検索できるオプションかどうかを返す
+    fn searches_by_line(&self) -> bool {
+        let options = &self.options;
+        options.record_separator == RecordSeparator::Line
+            && options.max_count_per_file.is_none()
+            && options.max_results.is_none()
+            && options.max_result_bytes.is_none()
+            && options.offset == 0
+            && options.line_range.is_none()
+            && !options.scores_relevance()
+            && !options.skips_file(&self.file)
+    }
+
+    /// ファイル全体を検索し直す
+    fn search_all(&mut self) {
+        let compiled = self.compiled.restarted(&self.options);
+        self.results =
+            search_compiled(&compiled, core::slice::from_ref(&self.file), &self.options).results;
+    }
+}
+
+/// `pos` を含む行の先頭から、さらに `back` 行さかのぼった行の先頭の位置を返す
+fn line_start(content: &str, terminator: &str, pos: usize, back: usize) -> usize {
+    let mut start = content[..pos]
+        .rfind(terminator)
+        .map_or(0, |p| p + terminator.len());
+    for _ in 0..back {
+        if start == 0 {
+            break;
+        }
+        start = content[..start - terminator.len()]
+            .rfind(terminator)
+            .map_or(0, |p| p + terminator.len());
+    }
+    start
+}
+
+/// `pos` から `count` 個の終端文字を越えた位置（足りなければ内容の末尾）を返す
+fn skip_lines(content: &str, terminator: &str, pos: usize, count: usize) -> usize {
+    let mut end = pos;
+    for _ in 0..count {
+        match content[end..].find(terminator) {
+            Some(p) => end += p + terminator.len(),
+            None => return content.len(),
+        }
+    }
+    end
+}
+
+/// 行番号を `delta` 行ずらす
+fn shift_line(line: u32, delta: i64) -> u32 {
+    (line as i64 + delta) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::{LineTerminator, search_with_options};
+
+    /// 編集を順に適用し、毎回ファイル全体を検索した結果と比べる
+    fn assert_edits_match_full_search(options: &SearchOptions) {
+        let file = FileInput {
+            path: "a.txt".to_string(),
+            content: "foo\r\nbar\nfoo bar\n\nbaz foo\nend".to_string(),
+        };
+        let mut search = IncrementalSearch::new("fo+|^$", file, options).unwrap();
+        let edits: [(Range<usize>, &str); 8] = [
+            (0..0, "foo\n"),
+            (5..8, "xx"),
+            (10..10, "\nnew foo\n\n"),
+            (3..4, ""),
+            (12..20, "f"),
+            (0..3, "日本foo"),
+            (20..21, "o\r\n"),
+            (10..12, ""),
+        ];
+        // 決まった編集の後に、疑似乱数で選んだ編集も適用する
+        let texts = ["", "foo", "\n", "o\r\n", "x\n\nfo", "日"];
+        let mut seed = 7_u64;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n.max(1)
+        };
+        let random_edits: Vec<(Range<usize>, &str)> = (0..60)
+            .map(|_| (next(64)..next(64) + next(4), texts[next(texts.len())]))
+            .collect();
+        for (range, new_text) in edits.into_iter().chain(random_edits) {
+            let content = search.content();
+            let mut start = range.start.min(content.len());
+            while !content.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = range.end.clamp(start, content.len());
+            while !content.is_char_boundary(end) {
+                end += 1;
+            }
+            search.edit(start..end, new_text).unwrap();
+            let files = vec![FileInput {
+                path: "a.txt".to_string(),
+                content: search.content().to_string(),
+            }];
+            let expected = search_with_options("fo+|^$", &files, options).unwrap();
+            assert_eq!(
+                search.results(),
+                expected.results.as_slice(),
+                "after editing to {:?} with {:?}",
+                search.content(),
+                options
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_search_matches_full_search() {
+        for options in [
+            SearchOptions::new(),
+            SearchOptions::new().before_context(1).after_context(2),
+            SearchOptions::new().invert_match(true),
+            SearchOptions::new().line_terminator(LineTerminator::Crlf),
+            SearchOptions::new().line_range(2..=4),
+            SearchOptions::new().max_results(2),
+            SearchOptions::new().record_separator(RecordSeparator::Paragraph),
+        ] {
+            assert_edits_match_full_search(&options);
+        }
+    }
+
+    #[test]
+    fn test_incremental_search_rejects_invalid_range() {
+        let file = FileInput {
+            path: "a.txt".to_string(),
+            content: "日本".to_string(),
+        };
+        let mut search = IncrementalSearch::new("本", file, &SearchOptions::new()).unwrap();
+        assert!(matches!(
+            search.edit(1..2, "x"),
+            Err(SearchError::InvalidEditRange { .. })
+        ));
+        assert!(search.edit(3..7, "x").is_err());
+        assert_eq!(search.content(), "日本");
+        assert_eq!(search.results().len(), 1);
+    }
+}
@@ -0,0 +1,23 @@
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+
+/// 同じテキストの行を1つの文字列にまとめる
+///
+/// ログのように同じ行が何度も現れるファイルで、結果ごとに行のテキストを複製しないために使う。
+#[derive(Default)]
+pub(crate) struct LineInterner {
+    /// これまでに結果に入れた行のテキスト
+    lines: BTreeSet<Arc<str>>,
+}
+
+impl LineInterner {
+    /// `text` と同じテキストの文字列があればそれを、なければ新しく作って返す
+    pub(crate) fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(line) = self.lines.get(text) {
+            return Arc::clone(line);
+        }
+        let line: Arc<str> = Arc::from(text);
+        self.lines.insert(Arc::clone(&line));
+        line
+    }
+}
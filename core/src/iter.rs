@@ -0,0 +1,234 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
+
+use crate::score::score_matches;
+use crate::{
+    CompiledSearch, FileInput, LineMatch, MatchResult, ScanProgress, SearchError, SearchOptions,
+    Stop, compile_patterns, search_file,
+};
+
+/// 検索結果を見つかった順に返すイテレータ
+///
+/// `search_iter` で作る。ファイルは結果を取り出すのに必要になった時点で1つずつ検索するため、
+/// 途中で止めれば残りのファイルは走査しない。
+pub struct Matches<'a> {
+    /// まだ検索していないファイル
+    files: slice::Iter<'a, FileInput>,
+    /// 検索の状態
+    cursor: MatchCursor<'a>,
+}
+
+impl Matches<'_> {
+    /// `max_results` の上限に達して検索を打ち切ったかどうかを返す
+    pub fn truncated(&self) -> bool {
+        self.cursor.truncated
+    }
+
+    /// `timeout_ms` の時間を過ぎて検索を打ち切ったかどうかを返す
+    pub fn timed_out(&self) -> bool {
+        self.cursor.timed_out
+    }
+
+    /// `cancel_token` で打ち切りを求められて検索を打ち切ったかどうかを返す
+    pub fn cancelled(&self) -> bool {
+        self.cursor.cancelled
+    }
+}
+
+impl Iterator for Matches<'_> {
+    type Item = MatchResult;
+
+    fn next(&mut self) -> Option<MatchResult> {
+        loop {
+            if let Some(result) = self.cursor.next_pending() {
+                return Some(result);
+            }
+            if self.cursor.is_stopped() {
+                return None;
+            }
+            let f = self.files.next()?;
+            self.cursor.search(f);
+        }
+    }
+}
+
+/// ファイルを1つずつ検索し、結果を見つかった順に取り出すための状態
+///
+/// `Matches` と、非同期版の `AsyncMatches` で共有する。
+pub(crate) struct MatchCursor<'a> {
+    /// コンパイル済みのパターン
+    compiled: CompiledSearch,
+    /// 検索オプション
+    options: &'a SearchOptions,
+    /// 検索の進み具合
+    progress: ScanProgress,
+    /// 検索したファイルのパス（結果で共有する）
+    path: Arc<str>,
+    /// 検索したファイルのまだ返していない結果
+    pending: vec::IntoIter<LineMatch>,
+    /// `max_results` の上限に達したかどうか
+    pub(crate) truncated: bool,
+    /// `timeout_ms` の時間を過ぎたかどうか
+    pub(crate) timed_out: bool,
+    /// `cancel_token` で打ち切りを求められたかどうか
+    pub(crate) cancelled: bool,
+}
+
+impl<'a> MatchCursor<'a> {
+    pub(crate) fn new(compiled: CompiledSearch, options: &'a SearchOptions) -> Self {
+        MatchCursor {
+            compiled,
+            options,
+            progress: ScanProgress {
+                skip: options.offset,
+                ..Default::default()
+            },
+            path: Arc::from(""),
+            pending: Vec::new().into_iter(),
+            truncated: false,
+            timed_out: false,
+            cancelled: false,
+        }
+    }
+
+    /// 検索したファイルのまだ返していない結果を1つ取り出す
+    pub(crate) fn next_pending(&mut self) -> Option<MatchResult> {
+        self.pending
+            .next()
+            .map(|line_match| line_match.into_result(Arc::clone(&self.path)))
+    }
+
+    /// 検索を打ち切っていて、これ以上ファイルを検索しないかどうかを返す
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.truncated || self.timed_out || self.cancelled
+    }
+
+    /// ファイルを1つ検索し、結果を取り出せるようにする
+    pub(crate) fn search(&mut self, f: &FileInput) {
+        if self.options.skips_file(f) {
+            return;
+        }
+        let mut matches = Vec::new();
+        let stop = search_file(
+            f,
+            &self.compiled,
+            self.options,
+            &mut self.progress,
+            &mut matches,
+        );
+        self.progress.found += matches.len();
+        if self.options.scores_relevance() {
+            score_matches(f, &mut matches, &self.compiled);
+        }
+        match stop {
+            Some(Stop::TotalLimit) => self.truncated = true,
+            Some(Stop::Timeout) => self.timed_out = true,
+            Some(Stop::Cancelled) => self.cancelled = true,
+            Some(Stop::FileLimit | Stop::Interrupted) | None => {}
+        }
+        if !matches.is_empty() {
+            self.path = Arc::from(f.path.as_str());
+        }
+        self.pending = matches.into_iter();
+    }
+}
+
+/// パターンでファイルを検索し、結果を見つかった順に返すイテレータを作る
+///
+/// 結果をまとめた `Vec` を作らないため、先頭の数件だけが必要な場合（`take(n)` など）や、
+/// 結果を見つかったそばから処理する場合に `search_with_options` より軽い。ファイルは
+/// 1つずつ必要になった時点で検索し、1ファイル分の結果をまとめて組み立てる。
+///
+/// `sort` は結果をすべて集めないと適用できないため無視し、常にファイルの入力順・
+/// ファイル内の出現順で返す。それ以外のオプション（`offset`、`max_results`、`timeout_ms` など）は
+/// `search_with_options` と同じように働き、打ち切られたかどうかは `Matches::truncated` /
+/// `Matches::timed_out` / `Matches::cancelled` で確かめられる。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果のイテレータ、または正規表現パターンが無効な場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_iter};
+///
+/// let files = vec![
+///     FileInput {
+///         path: "a.txt".to_string(),
+///         content: "TODO one\nTODO two".to_string(),
+///     },
+///     FileInput {
+///         path: "b.txt".to_string(),
+///         content: "TODO three".to_string(),
+///     },
+/// ];
+/// let options = SearchOptions::new();
+/// let first: Vec<_> = search_iter("TODO", &files, &options).unwrap().take(1).collect();
+/// assert_eq!(&*first[0].line_text, "TODO one");
+/// ```
+pub fn search_iter<'a>(
+    pattern: &str,
+    files: &'a [FileInput],
+    options: &'a SearchOptions,
+) -> Result<Matches<'a>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(Matches {
+        files: files.iter(),
+        cursor: MatchCursor::new(compiled, options),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+    use crate::search_with_options;
+
+    fn files() -> Vec<FileInput> {
+        vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x1\ny\nx2".to_string(),
+            },
+            FileInput {
+                path: "bin".to_string(),
+                content: "x\0".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "x3 x4".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_search_iter_matches_search_with_options() {
+        let files = files();
+        let options = SearchOptions::new().offset(1).max_count_per_file(1);
+        let lazy: Vec<MatchResult> = search_iter("x", &files, &options).unwrap().collect();
+        let output = search_with_options("x", &files, &options).unwrap();
+        assert_eq!(lazy, output.results);
+        assert_eq!(lazy.len(), 1);
+        assert_eq!(&*lazy[0].path, "b.txt");
+    }
+
+    #[test]
+    fn test_search_iter_stops_at_max_results() {
+        let files = files();
+        let options = SearchOptions::new().max_results(3);
+        let mut matches = search_iter("x", &files, &options).unwrap();
+        let lines: Vec<String> = matches.by_ref().map(|m| m.match_text).collect();
+        assert_eq!(lines, vec!["x", "x", "x"]);
+        assert!(matches.truncated());
+        assert!(!matches.timed_out());
+    }
+}
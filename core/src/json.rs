@@ -0,0 +1,181 @@
+use alloc::borrow::Borrow;
+use alloc::string::String;
+use core::fmt::Write as _;
+use std::io;
+
+use crate::{CaptureGroup, ContextLine, MatchResult};
+
+/// 検索結果を1件1行の JSON（JSON Lines / NDJSON）として `writer` に書き出す
+///
+/// 結果ごとに1行ずつ書き出すため、巨大な検索結果を1つの JSON 配列に
+/// まとめることなくパイプやストリームに流せる。各行のキーは `MatchResult` の
+/// フィールド名と同じで（WebAssembly の出力とも同じ）、`named_captures` はオブジェクトになる。
+///
+/// # Arguments
+///
+/// * `writer` - 書き出し先
+/// * `results` - 検索結果（`&MatchResult` でも `MatchResult` でもよい）
+///
+/// # Returns
+///
+/// 書き出しに失敗した場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_with_options, write_ndjson};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "say \"hi\"".to_string(),
+/// }];
+/// let output = search_with_options("hi", &files, &SearchOptions::new()).unwrap();
+/// let mut out = Vec::new();
+/// write_ndjson(&mut out, &output.results).unwrap();
+/// let line = String::from_utf8(out).unwrap();
+/// assert!(line.starts_with("{\"path\":\"a.txt\",\"line\":1,"));
+/// assert!(line.contains("\"line_text\":\"say \\\"hi\\\"\""));
+/// assert!(line.ends_with("}\n"));
+/// ```
+pub fn write_ndjson<W, I>(mut writer: W, results: I) -> io::Result<()>
+where
+    W: io::Write,
+    I: IntoIterator,
+    I::Item: Borrow<MatchResult>,
+{
+    let mut line = String::new();
+    for result in results {
+        line.clear();
+        write_match(&mut line, result.borrow());
+        line.push('\n');
+        writer.write_all(line.as_bytes())?;
+    }
+    writer.flush()
+}
+
+/// 検索結果を1つの JSON オブジェクトとして `out` に追加する
+fn write_match(out: &mut String, result: &MatchResult) {
+    out.push_str("{\"path\":");
+    write_string(out, &result.path);
+    let _ = write!(
+        out,
+        ",\"line\":{},\"pattern_index\":{},\"column\":{},\"end_column\":{},\"match_text\":",
+        result.line, result.pattern_index, result.column, result.end_column
+    );
+    write_string(out, &result.match_text);
+    let _ = write!(
+        out,
+        ",\"byte_offset\":{},\"line_byte_offset\":{},\"line_text\":",
+        result.byte_offset, result.line_byte_offset
+    );
+    write_string(out, &result.line_text);
+    let _ = write!(
+        out,
+        ",\"line_text_offset\":{},\"line_truncated\":{},\"line_text_column\":{},\"captures\":",
+        result.line_text_offset, result.line_truncated, result.line_text_column
+    );
+    write_array(out, &result.captures, write_capture);
+    out.push_str(",\"named_captures\":{");
+    for (idx, (name, text)) in result.named_captures.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        write_string(out, name);
+        out.push(':');
+        write_string(out, text);
+    }
+    out.push_str("},\"context_before\":");
+    write_array(out, &result.context_before, write_context_line);
+    out.push_str(",\"context_after\":");
+    write_array(out, &result.context_after, write_context_line);
+    let _ = write!(out, ",\"score\":{}}}", result.score);
+}
+
+/// キャプチャグループを JSON オブジェクトとして `out` に追加する
+fn write_capture(out: &mut String, group: &CaptureGroup) {
+    let _ = write!(out, "{{\"index\":{},\"text\":", group.index);
+    write_string(out, &group.text);
+    let _ = write!(
+        out,
+        ",\"column\":{},\"end_column\":{}}}",
+        group.column, group.end_column
+    );
+}
+
+/// コンテキスト行を JSON オブジェクトとして `out` に追加する
+fn write_context_line(out: &mut String, line: &ContextLine) {
+    let _ = write!(out, "{{\"line\":{},\"text\":", line.line);
+    write_string(out, &line.text);
+    out.push('}');
+}
+
+/// 要素を `write_item` で書き出した JSON 配列を `out` に追加する
+fn write_array<T>(out: &mut String, items: &[T], write_item: fn(&mut String, &T)) {
+    out.push('[');
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        write_item(out, item);
+    }
+    out.push(']');
+}
+
+/// 文字列をエスケープした JSON の文字列リテラルとして `out` に追加する
+pub(crate) fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{FileInput, SearchOptions, search_with_options};
+
+    #[test]
+    fn test_write_string_escapes_control_characters() {
+        let mut out = String::new();
+        write_string(&mut out, "a\"b\\c\nd\te\u{1}f日本");
+        assert_eq!(out, "\"a\\\"b\\\\c\\nd\\te\\u0001f日本\"");
+    }
+
+    #[test]
+    fn test_write_ndjson() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "key=1\nkey=22".to_string(),
+        }];
+        let options = SearchOptions::new().before_context(1);
+        let output = search_with_options(r"key=(?<value>\d+)", &files, &options).unwrap();
+        let mut out = Vec::new();
+        write_ndjson(&mut out, output.results).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            "{\"path\":\"a.txt\",\"line\":2,\"pattern_index\":0,\"column\":1,\"end_column\":7,\
+             \"match_text\":\"key=22\",\"byte_offset\":6,\"line_byte_offset\":0,\
+             \"line_text\":\"key=22\",\"line_text_offset\":0,\"line_truncated\":false,\
+             \"line_text_column\":1,\
+             \"captures\":[{\"index\":1,\"text\":\"22\",\"column\":5,\"end_column\":7}],\
+             \"named_captures\":{\"value\":\"22\"},\
+             \"context_before\":[{\"line\":1,\"text\":\"key=1\"}],\"context_after\":[],\"score\":0}"
+        );
+    }
+}
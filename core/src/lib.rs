@@ -1,6 +1,158 @@
-use regex::{Regex, RegexBuilder};
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::convert::Infallible;
+use core::ops::{ControlFlow, Range};
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use approx::ApproxMatcher;
+use blocks::context_blocks;
+use cache::compile_patterns;
+use clock::Instant;
+use content::{ContentMatches, content_regex};
+#[cfg(not(feature = "regex"))]
+use engine::RegexBuilderExt as _;
+#[cfg(feature = "regex")]
+use engine::RegexSetBuilder;
+use engine::{Captures, Regex, RegexBuilder, RegexSet};
+use intern::LineInterner;
+use normalize::{Normalized, match_targets, normalize_pattern, normalize_records};
+use parallel::presearch;
+use prefilter::Prefilter;
+use preview::{context_preview, line_preview};
+use query::CompiledQuery;
+use records::{Record, split_records};
+use score::score_matches;
+use sort::sort_file_matches;
+
+mod approx;
+#[cfg(feature = "async")]
+mod async_search;
+mod binary;
+mod blocks;
+mod borrowed;
+mod cache;
+mod cancel;
+mod clock;
+mod columns;
+mod compare;
+mod content;
+#[cfg(feature = "std")]
+mod csv;
+mod density;
+mod diff;
+mod editor;
+mod engine;
+mod error;
+#[cfg(feature = "explain")]
+mod explain;
+#[cfg(feature = "fancy-regex")]
+mod fancy;
+mod fuzzy;
+mod glob;
+mod highlight;
+mod incremental;
+mod intern;
+mod iter;
+#[cfg(feature = "std")]
+mod json;
+mod lsp;
+mod matcher;
+mod normalize;
+mod options;
+mod parallel;
+#[cfg(feature = "pcre2")]
+mod pcre;
+mod prefilter;
+mod preview;
+mod provider;
+mod query;
+mod records;
+mod render;
+mod replace;
+mod report;
+mod sarif;
+mod score;
+mod searcher;
+mod sink;
+mod sort;
+mod stream;
+#[cfg(feature = "validate")]
+mod validate;
+#[cfg(feature = "vectorscan")]
+mod vectorscan;
+#[cfg(feature = "std")]
+mod xml;
+
+#[cfg(feature = "async")]
+pub use async_search::{AsyncContentProvider, AsyncMatches, search_async};
+pub use binary::is_binary;
+pub use blocks::{BlockLine, ContextBlock};
+pub use borrowed::{MatchRef, search_borrowed};
+pub use cache::{clear_regex_cache, set_regex_cache_capacity};
+pub use cancel::CancelToken;
+pub use columns::ColumnUnit;
+pub use compare::{ResultDiff, diff_results};
+#[cfg(feature = "std")]
+pub use csv::{write_csv, write_tsv};
+pub use density::{DensityBuckets, FileDensity, match_density};
+pub use editor::{EditorRange, MonacoRange, editor_ranges};
+pub use engine::{EngineFeatures, RegexEngine};
+pub use error::SearchError;
+#[cfg(feature = "explain")]
+pub use explain::{PatternNode, PatternNodeKind, explain_pattern};
+pub use fuzzy::{FuzzyMatch, fuzzy_search};
+pub use glob::{escape_literal, glob_to_regex};
+pub use highlight::{LineHighlights, highlight_spans};
+pub use incremental::IncrementalSearch;
+pub use iter::{Matches, search_iter};
+#[cfg(feature = "std")]
+pub use json::write_ndjson;
+pub use lsp::{LspLocation, LspPosition, LspRange, lsp_locations, lsp_range};
+pub use matcher::{Matcher, search_with_matcher};
+pub use normalize::Normalization;
+pub use options::{
+    Anchor, DEFAULT_DFA_SIZE_LIMIT, DEFAULT_SIZE_LIMIT, Dedupe, LongLineMode, RegexFlags,
+    SearchOptions, SortOrder,
+};
+#[cfg(feature = "pcre2")]
+pub use pcre::Pcre2Matcher;
+pub use provider::{ContentProvider, search_provider};
+pub use query::{Query, QueryScope, search_query};
+pub use records::{LineTerminator, RecordSeparator};
+pub use render::{render_ansi, render_grep, render_html, render_markdown, render_vimgrep};
+pub use replace::{
+    ReplacePreview, ReplacedFile, TextEdit, replace, replace_edits, replace_preview, replace_with,
+};
+pub use report::{FileProfile, SearchReport};
+#[cfg(feature = "std")]
+pub use sarif::write_sarif;
+pub use searcher::Searcher;
+pub use sink::{Match, Sink, SinkOutcome, search_sink};
+pub use sort::sort_results;
+pub use stream::ChunkedSearch;
+#[cfg(feature = "validate")]
+pub use validate::{PatternError, PatternInfo, validate_pattern};
+#[cfg(feature = "vectorscan")]
+pub use vectorscan::VectorscanMatcher;
+#[cfg(feature = "std")]
+pub use xml::write_checkstyle;
 
 /// ファイルのパスとコンテンツを表す構造体
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FileInput {
     /// ファイルのパス
     pub path: String,
@@ -8,212 +160,3539 @@ pub struct FileInput {
     pub content: String,
 }
 
-/// 検索結果を表す構造体
-pub struct MatchResult {
-    /// マッチしたファイルのパス
-    pub path: String,
-    /// マッチした行番号（1ベース）
-    pub line: u32,
-    /// マッチした列番号（1ベース）
-    pub column: u32,
-    /// マッチした行のテキスト
-    pub line_text: String,
-}
+/// 検索結果を表す構造体
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchResult {
+    /// マッチしたファイルのパス
+    ///
+    /// 同じファイルの結果は1つの文字列を共有するため、マッチの数だけパスを複製しない。
+    pub path: Arc<str>,
+    /// マッチした行番号（1ベース）
+    pub line: u32,
+    /// マッチしたパターンの位置（`search_multi` 以外では常に0）
+    pub pattern_index: usize,
+    /// マッチした列番号（1ベース、単位は `SearchOptions::column_unit` に従う）
+    pub column: u32,
+    /// マッチ範囲の直後の列番号（1ベース、`column` と等しければ空マッチ）
+    pub end_column: u32,
+    /// マッチした文字列（`invert_match` の場合は空文字列）
+    pub match_text: String,
+    /// ファイル先頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub byte_offset: usize,
+    /// 行頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub line_byte_offset: usize,
+    /// マッチした行のテキスト（`max_line_length` を超える行では切り詰めたもの）
+    ///
+    /// `intern_lines` が有効な場合は、同じテキストの行の結果どうしで1つの文字列を共有する。
+    pub line_text: Arc<str>,
+    /// `line_text` の元になった部分の行内での開始位置（0ベースのバイトオフセット、行頭からなら0）
+    pub line_text_offset: usize,
+    /// `line_text` が `max_line_length` や `preview_width` によって切り詰められたかどうか
+    pub line_truncated: bool,
+    /// `line_text` の中でのマッチの列番号（1ベース、単位は `SearchOptions::column_unit` に従う）
+    pub line_text_column: u32,
+    /// パターン内のキャプチャグループにマッチした部分（マッチしなかったグループは含まない）
+    pub captures: Vec<CaptureGroup>,
+    /// 名前付きキャプチャグループの名前とマッチした文字列
+    pub named_captures: BTreeMap<String, String>,
+    /// マッチした行の直前の行（`before_context` で指定した行数まで）
+    pub context_before: Vec<ContextLine>,
+    /// マッチした行の直後の行（`after_context` で指定した行数まで）
+    pub context_after: Vec<ContextLine>,
+    /// 関連度（大きいほど関連が高い、`relevance` が無効な場合は0）
+    pub score: i64,
+}
+
+/// ファイルごとにまとめた検索結果の1件を表す構造体
+///
+/// `MatchResult` からパスを除いたもので、パスは `FileMatches` に1つだけ持つ。
+pub struct LineMatch {
+    /// マッチした行番号（1ベース）
+    pub line: u32,
+    /// マッチしたパターンの位置（`search_multi` 以外では常に0）
+    pub pattern_index: usize,
+    /// マッチした列番号（1ベース、単位は `SearchOptions::column_unit` に従う）
+    pub column: u32,
+    /// マッチ範囲の直後の列番号（1ベース、`column` と等しければ空マッチ）
+    pub end_column: u32,
+    /// マッチした文字列（`invert_match` の場合は空文字列）
+    pub match_text: String,
+    /// ファイル先頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub byte_offset: usize,
+    /// 行頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub line_byte_offset: usize,
+    /// マッチした行のテキスト（`max_line_length` を超える行では切り詰めたもの）
+    pub line_text: Arc<str>,
+    /// `line_text` の元になった部分の行内での開始位置（0ベースのバイトオフセット、行頭からなら0）
+    pub line_text_offset: usize,
+    /// `line_text` が `max_line_length` や `preview_width` によって切り詰められたかどうか
+    pub line_truncated: bool,
+    /// `line_text` の中でのマッチの列番号（1ベース、単位は `SearchOptions::column_unit` に従う）
+    pub line_text_column: u32,
+    /// パターン内のキャプチャグループにマッチした部分（マッチしなかったグループは含まない）
+    pub captures: Vec<CaptureGroup>,
+    /// 名前付きキャプチャグループの名前とマッチした文字列
+    pub named_captures: BTreeMap<String, String>,
+    /// マッチした行の直前の行（`before_context` で指定した行数まで）
+    pub context_before: Vec<ContextLine>,
+    /// マッチした行の直後の行（`after_context` で指定した行数まで）
+    pub context_after: Vec<ContextLine>,
+    /// 関連度（大きいほど関連が高い、`relevance` が無効な場合は0）
+    pub score: i64,
+}
+
+impl LineMatch {
+    /// 結果が使うメモリのおおよその大きさ（バイト）を返す
+    fn approx_size(&self) -> usize {
+        let captures: usize = self
+            .captures
+            .iter()
+            .map(|group| size_of::<CaptureGroup>() + group.text.len())
+            .sum();
+        let named_captures: usize = self
+            .named_captures
+            .iter()
+            .map(|(name, text)| 2 * size_of::<String>() + name.len() + text.len())
+            .sum();
+        let context: usize = self
+            .context_before
+            .iter()
+            .chain(&self.context_after)
+            .map(|context| size_of::<ContextLine>() + context.text.len())
+            .sum();
+        size_of::<MatchResult>()
+            + self.match_text.len()
+            + self.line_text.len()
+            + captures
+            + named_captures
+            + context
+    }
+
+    /// パスを付けて `MatchResult` に変換する
+    fn into_result(self, path: Arc<str>) -> MatchResult {
+        MatchResult {
+            path,
+            line: self.line,
+            pattern_index: self.pattern_index,
+            column: self.column,
+            end_column: self.end_column,
+            match_text: self.match_text,
+            byte_offset: self.byte_offset,
+            line_byte_offset: self.line_byte_offset,
+            line_text: self.line_text,
+            line_text_offset: self.line_text_offset,
+            line_truncated: self.line_truncated,
+            line_text_column: self.line_text_column,
+            captures: self.captures,
+            named_captures: self.named_captures,
+            context_before: self.context_before,
+            context_after: self.context_after,
+            score: self.score,
+        }
+    }
+}
+
+/// 1ファイル分の検索結果をまとめた構造体
+pub struct FileMatches {
+    /// マッチしたファイルのパス
+    pub path: String,
+    /// ファイル内の検索結果（出現順）
+    pub matches: Vec<LineMatch>,
+    /// マッチした行と前後のコンテキストを連続する行ごとにまとめたブロック
+    ///
+    /// `before_context` か `after_context` を指定した場合のみ作り、それ以外は空になる。
+    pub blocks: Vec<ContextBlock>,
+}
+
+/// キャプチャグループにマッチした部分を表す構造体
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CaptureGroup {
+    /// グループ番号（1ベース、マッチ全体を表す0番は含まない）
+    pub index: usize,
+    /// グループにマッチした文字列
+    pub text: String,
+    /// グループの開始列番号（1ベース）
+    pub column: u32,
+    /// グループ範囲の直後の列番号（1ベース）
+    pub end_column: u32,
+}
+
+/// マッチした行の前後にある行を表す構造体
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContextLine {
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// 行のテキスト
+    pub text: String,
+}
+
+/// オプション付き検索の出力を表す構造体
+///
+/// `search_grouped` では `results` がファイルごとにまとめた `FileMatches` のリストになる。
+pub struct SearchOutput<T = MatchResult> {
+    /// 検索結果のリスト
+    pub results: Vec<T>,
+    /// `max_count_per_file` の上限に達して結果が打ち切られたファイルのパス
+    pub truncated_files: Vec<String>,
+    /// `max_results` か `max_result_bytes` の上限に達して検索全体が打ち切られたかどうか
+    pub truncated: bool,
+    /// `timeout_ms` の時間を過ぎて検索が打ち切られたかどうか
+    pub timed_out: bool,
+    /// `cancel_token` で検索が打ち切られたかどうか
+    pub cancelled: bool,
+    /// バイナリとみなして読み飛ばしたファイルのパス
+    pub binary_files: Vec<String>,
+    /// 続きの結果を取得するときに `offset` に渡す値（打ち切られずに検索を終えた場合は `None`）
+    pub next_offset: Option<usize>,
+    /// 検索の統計（`report` が無効な場合は `None`）
+    pub report: Option<SearchReport>,
+}
+
+/// 単語リスト検索で見つかった語とその検索結果
+pub struct TermMatch {
+    /// 見つかった語のリスト内の位置（0ベース）
+    pub term_index: usize,
+    /// 見つかった語（リストに渡した文字列そのもの）
+    pub term: String,
+    /// 検索結果
+    pub result: MatchResult,
+}
+
+/// パターンでファイルを検索する
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `case_sensitive` - 大文字小文字を区別するかどうか
+///
+/// # Returns
+///
+/// 検索結果のリスト、または正規表現パターンが無効な場合のエラー
+pub fn search(
+    pattern: &str,
+    files: &[FileInput],
+    case_sensitive: bool,
+) -> Result<Vec<MatchResult>, SearchError> {
+    search_with_options(
+        pattern,
+        files,
+        &SearchOptions::new().case_sensitive(case_sensitive),
+    )
+    .map(|output| output.results)
+}
+
+/// オプションを指定してパターンでファイルを検索する
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、または正規表現パターンが無効な場合のエラー
+pub fn search_with_options(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(search_compiled(&compiled, files, options))
+}
+
+/// 複数のパターンでファイルを一度に検索する
+///
+/// パターンは `RegexSet` にまとめてコンパイルされ、どのパターンにもマッチしない行は
+/// 個別の正規表現を実行せずに読み飛ばす。各結果の `pattern_index` には
+/// マッチしたパターンの位置が入り、同じ行の結果は列番号順に並ぶ。
+///
+/// # Arguments
+///
+/// * `patterns` - 検索する正規表現パターンのリスト
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション（すべてのパターンに共通で適用される）
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、またはいずれかの正規表現パターンが無効な場合のエラー
+pub fn search_multi(
+    patterns: &[&str],
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let compiled = compile_patterns(patterns, options)?;
+
+    Ok(search_compiled(&compiled, files, options))
+}
+
+/// オプションを指定してパターンでファイルを検索し、結果をファイルごとにまとめて返す
+///
+/// 結果ごとにパスを複製しないため、1ファイルに大量のマッチがある場合でも
+/// `search_with_options` よりメモリを使わない。マッチがなかったファイルは含まない。
+/// 前後のコンテキストを指定した場合は、重なるコンテキストを連続する行のブロックにまとめた
+/// `FileMatches::blocks` も返すため、同じ行を重複して表示せずに済む。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// ファイルごとの検索結果と打ち切り情報、または正規表現パターンが無効な場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_grouped};
+///
+/// let files = vec![FileInput {
+///     path: "log.txt".to_string(),
+///     content: "ERROR a\nok\nERROR b".to_string(),
+/// }];
+/// let output = search_grouped("ERROR", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(output.results[0].path, "log.txt");
+/// assert_eq!(output.results[0].matches.len(), 2);
+/// ```
+pub fn search_grouped(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput<FileMatches>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    let mut output = scan_files(&compiled, files, options, |f, matches, results| {
+        if !matches.is_empty() {
+            results.push(FileMatches {
+                path: f.path.clone(),
+                matches,
+                blocks: Vec::new(),
+            });
+        }
+    });
+    sort_file_matches(&mut output.results, options.sort);
+    if options.before_context > 0 || options.after_context > 0 {
+        for file_matches in &mut output.results {
+            file_matches.blocks = context_blocks(&file_matches.matches);
+        }
+    }
+
+    Ok(output)
+}
+
+/// 大量のリテラル文字列でファイルを一度に検索する
+///
+/// 文字列は Aho-Corasick オートマトンにまとめられ、ファイルを1回走査するだけで
+/// すべての文字列を探す。数千語規模の禁止語リストなどを想定している。
+/// 同じ位置から複数の文字列がマッチする場合は最も長いものを採用し、
+/// 各結果の `pattern_index` にはマッチした文字列の位置が入る。
+///
+/// `case_sensitive(false)` は ASCII の範囲でのみ大文字小文字を無視する。
+/// `literal` は常に有効なものとして扱われる。
+///
+/// # Arguments
+///
+/// * `literals` - 検索する文字列のリスト
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、またはオートマトンを構築できなかった場合のエラー
+pub fn search_literals(
+    literals: &[&str],
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let patterns = PatternSet::literals(literals, options)?;
+
+    run_search(patterns, files, options)
+}
+
+/// 単語リストでファイルを検索し、各マッチがどの語によるものかを返す
+///
+/// 用語集のチェックなどで、結果を元の語に対応付けるために使う。
+/// 検索は `search_literals` と同じく Aho-Corasick で1回の走査にまとめて行う。
+///
+/// # Arguments
+///
+/// * `terms` - 検索する語のリスト
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 語の位置と語を付けた検索結果のリスト、またはオートマトンを構築できなかった場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_terms};
+///
+/// let files = vec![FileInput {
+///     path: "doc.md".to_string(),
+///     content: "Use the API via HTTP".to_string(),
+/// }];
+/// let matches = search_terms(&["HTTP", "API"], &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(matches[0].term, "API");
+/// assert_eq!(matches[0].term_index, 1);
+/// ```
+pub fn search_terms(
+    terms: &[&str],
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<Vec<TermMatch>, SearchError> {
+    let output = search_literals(terms, files, options)?;
+
+    Ok(output
+        .results
+        .into_iter()
+        .map(|result| TermMatch {
+            term_index: result.pattern_index,
+            term: terms[result.pattern_index].to_string(),
+            result,
+        })
+        .collect())
+}
+
+/// パターンをコンパイルして全ファイルを検索する
+fn run_search(
+    patterns: PatternSet,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let compiled = CompiledSearch::new(patterns, options)?;
+
+    Ok(search_compiled(&compiled, files, options))
+}
+
+/// コンパイル済みのパターンで全ファイルを検索する
+fn search_compiled(
+    compiled: &CompiledSearch,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> SearchOutput {
+    let mut output = scan_files(compiled, files, options, push_results);
+    sort_results(&mut output.results, options.sort);
+
+    output
+}
+
+/// 1ファイル分の結果に、ファイルで共有するパスを付けて `results` に追加する
+fn push_results(f: &FileInput, matches: Vec<LineMatch>, results: &mut Vec<MatchResult>) {
+    if matches.is_empty() {
+        return;
+    }
+    let path: Arc<str> = Arc::from(f.path.as_str());
+    results.extend(
+        matches
+            .into_iter()
+            .map(|line_match| line_match.into_result(Arc::clone(&path))),
+    );
+}
+
+/// コンパイル済みのパターンで全ファイルを検索し、ファイルごとの結果を `collect` で積み上げる
+fn scan_files<T>(
+    compiled: &CompiledSearch,
+    files: &[FileInput],
+    options: &SearchOptions,
+    collect: impl FnMut(&FileInput, Vec<LineMatch>, &mut Vec<T>),
+) -> SearchOutput<T> {
+    let presearched = presearch(files, compiled, options);
+    let files = files.iter().map(|f| Ok::<_, Infallible>(Cow::Borrowed(f)));
+    let Ok(output) = scan_inputs(compiled, files, presearched, options, collect);
+    output
+}
+
+/// ファイルを1つずつ受け取りながら検索し、ファイルごとの結果を `collect` で積み上げる
+///
+/// `presearched` があれば、検索する代わりにその結果を順に使う。ファイルを受け取れなかった
+/// 場合はそのエラーを返す。
+fn scan_inputs<'a, T, E>(
+    compiled: &CompiledSearch,
+    files: impl Iterator<Item = Result<Cow<'a, FileInput>, E>>,
+    presearched: Option<Vec<parallel::FileOutcome>>,
+    options: &SearchOptions,
+    mut collect: impl FnMut(&FileInput, Vec<LineMatch>, &mut Vec<T>),
+) -> Result<SearchOutput<T>, E> {
+    let start = Instant::now();
+    let mut progress = ScanProgress {
+        skip: options.offset,
+        ..Default::default()
+    };
+    let mut output = SearchOutput {
+        results: Vec::new(),
+        truncated_files: Vec::new(),
+        truncated: false,
+        timed_out: false,
+        cancelled: false,
+        binary_files: Vec::new(),
+        next_offset: None,
+        report: None,
+    };
+
+    let mut presearched = presearched.map(Vec::into_iter);
+    for f in files {
+        let f = &*f?;
+        if options.skips_file(f) {
+            output.binary_files.push(f.path.clone());
+            continue;
+        }
+        let (matches, stop) = match presearched.as_mut().and_then(Iterator::next) {
+            Some((matches, stop, file_progress)) => {
+                progress.merge(file_progress);
+                (matches, stop)
+            }
+            None => scan_one(f, compiled, options, &mut progress),
+        };
+        collect(f, matches, &mut output.results);
+        match stop {
+            Some(Stop::FileLimit) => output.truncated_files.push(f.path.clone()),
+            Some(Stop::TotalLimit) => {
+                output.truncated = true;
+                break;
+            }
+            Some(Stop::Timeout) => {
+                output.timed_out = true;
+                break;
+            }
+            Some(Stop::Cancelled) => {
+                output.cancelled = true;
+                break;
+            }
+            Some(Stop::Interrupted) | None => {}
+        }
+    }
+    if output.truncated || output.timed_out || output.cancelled {
+        output.next_offset = Some(options.offset + progress.found);
+    }
+    if options.report || options.profile {
+        output.report = Some(SearchReport {
+            files_scanned: progress.files,
+            bytes_scanned: progress.bytes,
+            lines_scanned: progress.lines,
+            matches_found: progress.found + options.offset - progress.skip,
+            files_skipped: output.binary_files.len(),
+            elapsed: start.elapsed(),
+            files: progress.profiles,
+        });
+    }
+
+    Ok(output)
+}
+
+/// パターンにマッチするファイルのパスを返す（`grep -l` 相当）
+///
+/// 各ファイルは最初のマッチが見つかった時点で走査を打ち切るため、
+/// `search_with_options` よりも軽量に動作する。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// マッチしたファイルのパスのリスト、または正規表現パターンが無効な場合のエラー
+pub fn files_with_matches(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<Vec<String>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(files
+        .iter()
+        .filter(|f| file_has_match(f, &compiled, options))
+        .map(|f| f.path.clone())
+        .collect())
+}
+
+/// パターンに1件もマッチしないファイルのパスを返す（`grep -L` 相当）
+///
+/// ライセンスヘッダーや必須の import が欠けているファイルを探す用途を想定している。
+/// 読み飛ばしたバイナリファイルは結果に含めない。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// マッチしなかったファイルのパスのリスト、または正規表現パターンが無効な場合のエラー
+pub fn files_without_match(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<Vec<String>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(files
+        .iter()
+        .filter(|f| !options.skips_file(f) && !file_has_match(f, &compiled, options))
+        .map(|f| f.path.clone())
+        .collect())
+}
+
+/// ファイルごとのマッチ数を返す
+///
+/// `MatchResult` を組み立てず行テキストも複製しないため、件数だけが必要な
+/// 統計表示などでは `search_with_options` よりも大幅に軽量に動作する。
+/// `invert_match` が有効な場合はマッチしない行の数を数える。`max_count_per_file` を
+/// 指定した場合は、`search_with_options`（や `grep -c -m`）と同じく各ファイルの件数を
+/// その数で打ち切る。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 入力順のファイルパスとマッチ数の組のリスト、または正規表現パターンが無効な場合のエラー
+pub fn count(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<Vec<(String, u64)>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(files
+        .iter()
+        .map(|f| (f.path.clone(), count_matches(f, &compiled, options)))
+        .collect())
+}
+
+/// マッチ数の多いファイルを最大 `n` 件返す
+///
+/// `count` と同じく `MatchResult` を組み立てずに数えるため、大量のファイルから
+/// マッチが集中している箇所を一覧する場合に使う。マッチのないファイルは含まない。
+/// 各ファイルの件数は `count` と同じく `max_count_per_file` で打ち切る。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+/// * `n` - 返すファイルの最大数
+///
+/// # Returns
+///
+/// マッチ数の多い順（同数の場合は入力順）のファイルパスとマッチ数の組のリスト、
+/// または正規表現パターンが無効な場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, top_files};
+///
+/// let files = vec![
+///     FileInput { path: "a.txt".to_string(), content: "x".to_string() },
+///     FileInput { path: "b.txt".to_string(), content: "x x x".to_string() },
+///     FileInput { path: "c.txt".to_string(), content: "x x".to_string() },
+/// ];
+/// let top = top_files("x", &files, &SearchOptions::new(), 2).unwrap();
+/// assert_eq!(top, vec![("b.txt".to_string(), 3), ("c.txt".to_string(), 2)]);
+/// ```
+pub fn top_files(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+    n: usize,
+) -> Result<Vec<(String, u64)>, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    let mut counts: Vec<(&FileInput, u64)> = files
+        .iter()
+        .map(|f| (f, count_matches(f, &compiled, options)))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    counts.sort_by_key(|&(_, count)| Reverse(count));
+
+    Ok(counts
+        .into_iter()
+        .take(n)
+        .map(|(f, count)| (f.path.clone(), count))
+        .collect())
+}
+
+/// ファイル内のマッチ数を数える（`max_count_per_file` を上限とする）
+fn count_matches(f: &FileInput, compiled: &CompiledSearch, options: &SearchOptions) -> u64 {
+    if options.skips_file(f) {
+        return 0;
+    }
+    let limit = options.max_count_per_file.map_or(u64::MAX, |n| n as u64);
+    let records = split_records(&f.content, options);
+    let normalized = normalize_records(&records, options);
+    match_targets(&records, &normalized)
+        .into_iter()
+        .filter(|record| options.includes_record(record) && !compiled.is_excluded(record.text))
+        .map(|record| {
+            if options.invert_match {
+                u64::from(!compiled.patterns.is_match(record.text))
+            } else {
+                compiled.find_in_line(record.text, options).len() as u64
+            }
+        })
+        .sum::<u64>()
+        .min(limit)
+}
+
+/// いずれかのファイルにマッチが存在するかどうかを返す
+///
+/// 最初のマッチが見つかった時点で走査を打ち切るため、バッジ表示の判定など
+/// 有無だけが必要な場合に使う。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// マッチが存在するかどうか、または正規表現パターンが無効な場合のエラー
+pub fn exists(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<bool, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    Ok(files.iter().any(|f| file_has_match(f, &compiled, options)))
+}
+
+/// ファイルに1件でもマッチする行があるかどうかを返す
+fn file_has_match(f: &FileInput, compiled: &CompiledSearch, options: &SearchOptions) -> bool {
+    if options.skips_file(f) {
+        return false;
+    }
+    let records = split_records(&f.content, options);
+    let normalized = normalize_records(&records, options);
+    match_targets(&records, &normalized)
+        .into_iter()
+        .any(|record| {
+            options.includes_record(&record)
+                && !compiled.is_excluded(record.text)
+                && compiled.patterns.is_match(record.text) != options.invert_match
+        })
+}
+
+/// ファイルの検索を途中で打ち切った理由
+enum Stop {
+    /// `max_count_per_file` の上限に達した
+    FileLimit,
+    /// `max_results` か `max_result_bytes` の上限に達した
+    TotalLimit,
+    /// `timeout_ms` の時間を過ぎた
+    Timeout,
+    /// `cancel_token` で打ち切りを求められた
+    Cancelled,
+    /// `Sink` が検索の中断を求めた
+    Interrupted,
+}
+
+/// 経過時間と打ち切りの求めを確認する間隔（レコード数）
+///
+/// wasm では現在時刻の取得が JavaScript の呼び出しになるため、毎行は確認しない。
+const DEADLINE_CHECK_INTERVAL: usize = 256;
+
+/// 複数のファイルにまたがる検索の進み具合
+#[derive(Default)]
+struct ScanProgress {
+    /// これまでのファイルで見つかった結果の数（`max_results` の判定に使う）
+    found: usize,
+    /// これまでに集めた結果のおおよその大きさ（`max_result_bytes` の判定に使う）
+    result_bytes: usize,
+    /// 結果に入れた行のテキスト（`intern_lines` が有効な場合のみ使う）
+    interner: LineInterner,
+    /// `offset` のうち、まだ読み飛ばしていない結果の数
+    skip: usize,
+    /// 走査したファイルの数
+    files: usize,
+    /// 走査したファイルの合計バイト数
+    bytes: u64,
+    /// 走査したレコードの数
+    lines: u64,
+    /// ファイルごとの計測結果（`profile` が有効な場合のみ）
+    profiles: Vec<FileProfile>,
+}
+
+impl ScanProgress {
+    /// 別に検索したファイルの進み具合を足し合わせる
+    fn merge(&mut self, other: ScanProgress) {
+        self.found += other.found;
+        self.result_bytes += other.result_bytes;
+        self.files += other.files;
+        self.bytes += other.bytes;
+        self.lines += other.lines;
+        self.profiles.extend(other.profiles);
+    }
+}
+
+/// 1ファイルを検索し、進み具合を更新して結果と打ち切った理由を返す
+///
+/// `profile` の計測と `relevance` のスコア付けもここで行う。
+fn scan_one(
+    f: &FileInput,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+) -> (Vec<LineMatch>, Option<Stop>) {
+    scan_one_with(f, compiled, options, progress, |progress, matches| {
+        search_file(f, compiled, options, progress, matches)
+    })
+}
+
+/// `search` で1ファイルを検索し、進み具合を更新して結果と打ち切った理由を返す
+///
+/// `search` は結果を追加して打ち切った理由を返す。それ以外は `scan_one` と同じ。
+fn scan_one_with(
+    f: &FileInput,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+    search: impl FnOnce(&mut ScanProgress, &mut Vec<LineMatch>) -> Option<Stop>,
+) -> (Vec<LineMatch>, Option<Stop>) {
+    let file_start = options.profile.then(Instant::now);
+    let (lines_before, skip_before) = (progress.lines, progress.skip);
+    let mut matches = Vec::new();
+    let stop = search(progress, &mut matches);
+    if let Some(file_start) = file_start {
+        progress.profiles.push(FileProfile {
+            path: f.path.clone(),
+            elapsed: file_start.elapsed(),
+            lines: progress.lines - lines_before,
+            matches: matches.len() + skip_before - progress.skip,
+        });
+    }
+    progress.found += matches.len();
+    progress.files += 1;
+    progress.bytes += f.content.len() as u64;
+    if options.scores_relevance() {
+        score_matches(f, &mut matches, compiled);
+    }
+    (matches, stop)
+}
+
+/// 1ファイル分の検索結果を `matches` に追加する
+///
+/// 上限に達してまだマッチが残っている場合は打ち切った理由を返す。`max_result_bytes` を
+/// 超える結果は追加せず、`max_results` の上限に達した場合と同じく `Stop::TotalLimit` を返す。
+/// ただし、`next_offset` でページを送る呼び出し側が先に進めるよう、1回の検索の最初の結果は
+/// 上限を超えていても追加する。
+fn search_file(
+    f: &FileInput,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+    matches: &mut Vec<LineMatch>,
+) -> Option<Stop> {
+    search_text(&f.content, 1, compiled, options, progress, matches)
+}
+
+/// `first_line` 行目から始まるテキストの検索結果を `matches` に追加する
+///
+/// 結果の位置は `content` の先頭からのままにする。それ以外は `search_file` と同じ。
+fn search_text(
+    content: &str,
+    first_line: usize,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+    matches: &mut Vec<LineMatch>,
+) -> Option<Stop> {
+    let byte_limit = options.max_result_bytes.unwrap_or(usize::MAX);
+    let mut result_bytes = progress.result_bytes;
+    let mut over_budget = false;
+    let mut interner = core::mem::take(&mut progress.interner);
+    let stop = scan_text(
+        content,
+        first_line,
+        compiled,
+        options,
+        progress,
+        |scan, line_idx, line_match| {
+            let line_match = if options.intern_lines {
+                scan.build_line_match_with(line_idx, line_match, |text| interner.intern(text))
+            } else {
+                scan.build_line_match(line_idx, line_match)
+            };
+            let size = line_match.approx_size();
+            if result_bytes > 0 && size > byte_limit.saturating_sub(result_bytes) {
+                over_budget = true;
+                return ControlFlow::Break(());
+            }
+            result_bytes += size;
+            matches.push(line_match);
+            ControlFlow::Continue(())
+        },
+    );
+    progress.result_bytes = result_bytes;
+    progress.interner = interner;
+    if over_budget {
+        Some(Stop::TotalLimit)
+    } else {
+        stop
+    }
+}
+
+/// 1ファイルを走査し、オプションに従って残したマッチごとに `emit` を呼ぶ
+///
+/// 上限に達してまだマッチが残っている場合や `emit` が中断を求めた場合は打ち切った理由を返す
+fn scan_file(
+    f: &FileInput,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+    emit: impl FnMut(&FileScan, usize, SpanMatch) -> ControlFlow<()>,
+) -> Option<Stop> {
+    scan_text(&f.content, 1, compiled, options, progress, emit)
+}
+
+/// `first_line` 行目から始まるテキストを走査し、残したマッチごとに `emit` を呼ぶ
+///
+/// レコードの行番号は `first_line` から数え、位置は `content` の先頭からのままにする。
+fn scan_text(
+    content: &str,
+    first_line: usize,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+    mut emit: impl FnMut(&FileScan, usize, SpanMatch) -> ControlFlow<()>,
+) -> Option<Stop> {
+    let file_limit = options.max_count_per_file.unwrap_or(usize::MAX);
+    let total_limit = options.max_results.unwrap_or(usize::MAX);
+    let mut count = 0;
+    let mut emitted = 0;
+
+    // 次のマッチを追加する前に上限を確認する
+    let check_limits = |count: usize, total: usize| {
+        if total == total_limit {
+            Some(Stop::TotalLimit)
+        } else if count == file_limit {
+            Some(Stop::FileLimit)
+        } else {
+            None
+        }
+    };
+
+    let patterns = &compiled.patterns;
+    let mut lines = split_records(content, options);
+    for record in &mut lines {
+        record.line += first_line - 1;
+    }
+    let scan = FileScan {
+        normalized: normalize_records(&lines, options),
+        lines,
+        patterns,
+        options,
+    };
+    let targets = match_targets(&scan.lines, &scan.normalized);
+
+    let mask = patterns.line_mask(&targets);
+    let mut content_matches = compiled
+        .content_regex
+        .as_ref()
+        .and_then(|re| ContentMatches::new(re, content, options));
+    // 正規化していなければレコードはファイルの内容の一部なので、まずファイル全体で確かめる
+    let prefilter = compiled.prefilter.as_ref();
+    let file_may_match = prefilter
+        .is_none_or(|prefilter| !scan.normalized.is_empty() || prefilter.may_match(content));
+
+    for (line_idx, record) in targets.iter().enumerate() {
+        let line = record.text;
+        if line_idx % DEADLINE_CHECK_INTERVAL == 0
+            && let Some(stop) = compiled.check_stop(options)
+        {
+            return Some(stop);
+        }
+        progress.lines += 1;
+        if !options.includes_record(record) || compiled.is_excluded(line) {
+            continue;
+        }
+        let masked_out = mask.as_ref().is_some_and(|mask| !mask[line_idx]);
+        let rejected =
+            !file_may_match || prefilter.is_some_and(|prefilter| !prefilter.may_match(line));
+
+        if options.invert_match {
+            if masked_out || (mask.is_none() && (rejected || !patterns.is_match(line))) {
+                if let Some(stop) = check_limits(count, progress.found + emitted) {
+                    return Some(stop);
+                }
+                count += 1;
+                if progress.skip > 0 {
+                    progress.skip -= 1;
+                    continue;
+                }
+                let line_match = SpanMatch {
+                    pattern_index: 0,
+                    span: 0..0,
+                    captures: Vec::new(),
+                };
+                emitted += 1;
+                if emit(&scan, line_idx, line_match).is_break() {
+                    return Some(Stop::Interrupted);
+                }
+            }
+            continue;
+        }
+
+        if masked_out || rejected {
+            continue;
+        }
+
+        let found = match &mut content_matches {
+            Some(matches) => {
+                let mut found = matches.take_line(record);
+                options.dedupe.apply(&mut found);
+                found
+            }
+            None => compiled.find_in_line(line, options),
+        };
+        for line_match in found {
+            if let Some(stop) = check_limits(count, progress.found + emitted) {
+                return Some(stop);
+            }
+            count += 1;
+            if progress.skip > 0 {
+                progress.skip -= 1;
+                continue;
+            }
+            emitted += 1;
+            if emit(&scan, line_idx, line_match).is_break() {
+                return Some(Stop::Interrupted);
+            }
+        }
+    }
+
+    None
+}
+
+/// `timeout_ms` から検索を打ち切る時刻を求める
+///
+/// `std` フィーチャーが無効な場合は時計を読めないため、打ち切らない。
+fn deadline(options: &SearchOptions) -> Option<Instant> {
+    #[cfg(feature = "std")]
+    {
+        options
+            .timeout_ms
+            .map(|ms| Instant::now() + Duration::from_millis(ms))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = options;
+        None
+    }
+}
+
+/// 検索に使うコンパイル済みのパターンと除外条件
+#[derive(Clone)]
+struct CompiledSearch {
+    /// 検索するパターン
+    patterns: PatternSet,
+    /// `exclude_pattern` をコンパイルした正規表現
+    exclude: Option<Regex>,
+    /// `timeout_ms` から求めた検索を打ち切る時刻
+    deadline: Option<Instant>,
+    /// ファイル全体に1回だけ適用する正規表現（使える場合のみ）
+    content_regex: Option<Regex>,
+    /// マッチに必ず含まれるリテラルによる絞り込み（使える場合のみ）
+    prefilter: Option<Prefilter>,
+}
+
+impl CompiledSearch {
+    /// オプションの除外パターンをコンパイルしてパターンと組にする
+    ///
+    /// 行の終端文字の指定もここで検証する。
+    fn new(patterns: PatternSet, options: &SearchOptions) -> Result<Self, SearchError> {
+        options.line_terminator.validate()?;
+        let exclude = options
+            .exclude_pattern
+            .as_deref()
+            .map(|pattern| {
+                options
+                    .regex_flags
+                    .apply(&mut RegexBuilder::new(&normalize_pattern(pattern, options)))
+                    .case_insensitive(!options.case_sensitive)
+                    .size_limit(options.size_limit)
+                    .dfa_size_limit(options.dfa_size_limit)
+                    .build()
+                    .map_err(|source| SearchError::InvalidExcludePattern {
+                        pattern: pattern.to_string(),
+                        source,
+                    })
+            })
+            .transpose()?;
+
+        let deadline = deadline(options);
+
+        let content_regex = content_regex(&patterns, options);
+        let prefilter = Prefilter::new(&patterns, options);
+
+        Ok(Self {
+            patterns,
+            exclude,
+            deadline,
+            content_regex,
+            prefilter,
+        })
+    }
+
+    /// 行内のマッチを列番号順に、`dedupe` で重複を取り除いて返す
+    fn find_in_line(&self, line: &str, options: &SearchOptions) -> Vec<SpanMatch> {
+        let mut found = self.patterns.find_in_line(line);
+        options.dedupe.apply(&mut found);
+        found
+    }
+
+    /// 検索を打ち切る時刻を今から数え直したものを返す
+    ///
+    /// コンパイル済みのパターンを別の検索で使い回すために使う。
+    fn restarted(&self, options: &SearchOptions) -> Self {
+        Self {
+            deadline: deadline(options),
+            ..self.clone()
+        }
+    }
+
+    /// 検索を打ち切る時刻を過ぎたかどうかを返す
+    fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// 打ち切りを求められているか、打ち切る時刻を過ぎていればその理由を返す
+    fn check_stop(&self, options: &SearchOptions) -> Option<Stop> {
+        if options
+            .cancel_token
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+        {
+            Some(Stop::Cancelled)
+        } else if self.is_past_deadline() {
+            Some(Stop::Timeout)
+        } else {
+            None
+        }
+    }
+
+    /// 行が除外パターンにマッチするかどうかを返す
+    fn is_excluded(&self, line: &str) -> bool {
+        self.exclude.as_ref().is_some_and(|re| re.is_match(line))
+    }
+}
+
+/// 検索に使うコンパイル済みのパターン
+#[derive(Clone)]
+enum PatternSet {
+    /// 正規表現によるパターン
+    Regex {
+        /// パターンごとの正規表現
+        regexes: Vec<Regex>,
+        /// 複数パターンの場合に行を事前に絞り込むための集合
+        set: Option<RegexSet>,
+    },
+    /// Aho-Corasick による複数のリテラル文字列
+    Literals {
+        /// すべてのリテラルをまとめたオートマトン
+        automaton: AhoCorasick,
+        /// 単語単位でのみマッチさせるかどうか
+        whole_word: bool,
+        /// マッチの位置の固定方法
+        anchor: Anchor,
+    },
+    /// 編集距離を許して探すパターン
+    Approximate(ApproxMatcher),
+    /// AND / OR / NOT で組み合わせた検索条件
+    Query(Box<CompiledQuery>),
+    /// 呼び出し側が用意したマッチャー
+    Custom(Arc<dyn Matcher>),
+}
+
+/// 1行の中で見つかったマッチ
+///
+/// `Matcher::find_in_line` が返す。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanMatch {
+    /// マッチしたパターンの位置（0ベース）
+    pub pattern_index: usize,
+    /// 行内のマッチ範囲（0ベースのバイト位置）
+    pub span: Range<usize>,
+    /// マッチ全体を除くキャプチャグループ（列番号は行頭からの1ベースのバイト位置）
+    pub captures: Vec<CaptureGroup>,
+}
+
+impl SpanMatch {
+    /// キャプチャグループのないマッチを作る
+    pub fn new(pattern_index: usize, span: Range<usize>) -> Self {
+        Self {
+            pattern_index,
+            span,
+            captures: Vec::new(),
+        }
+    }
+}
+
+impl PatternSet {
+    /// オプションに従ってパターンをコンパイルする
+    fn new(patterns: &[&str], options: &SearchOptions) -> Result<Self, SearchError> {
+        if let Some(max_edits) = options.max_edit_distance {
+            return Ok(Self::Approximate(ApproxMatcher::new(
+                patterns, max_edits, options,
+            )?));
+        }
+        #[cfg(feature = "fancy-regex")]
+        if options.regex_engine == RegexEngine::Fancy {
+            return Ok(Self::Custom(Arc::new(fancy::FancyMatcher::new(
+                patterns, options,
+            )?)));
+        }
+
+        let regexes = patterns
+            .iter()
+            .map(|pattern| build_regex(pattern, options))
+            .collect::<Result<Vec<_>, _>>()?;
+        #[cfg(feature = "regex")]
+        let set = if regexes.len() > 1 {
+            let mut builder = RegexSetBuilder::new(regexes.iter().map(Regex::as_str));
+            let set = options
+                .regex_flags
+                .apply_set(&mut builder)
+                .case_insensitive(!options.case_sensitive)
+                .size_limit(options.size_limit)
+                .dfa_size_limit(options.dfa_size_limit)
+                .ignore_whitespace(options.regex_flags.ignore_whitespace && !options.literal)
+                .build()
+                .map_err(SearchError::InvalidPatternSet)?;
+            Some(set)
+        } else {
+            None
+        };
+        #[cfg(not(feature = "regex"))]
+        let set = None;
+
+        Ok(Self::Regex { regexes, set })
+    }
+
+    /// リテラル文字列のリストを Aho-Corasick オートマトンにまとめる
+    ///
+    /// `whole_word` の場合は、単語境界で弾かれた長いリテラルの中の短いリテラルも拾えるよう、
+    /// 重なりを含めてすべてのマッチを探せるオートマトンにする。
+    fn literals(literals: &[&str], options: &SearchOptions) -> Result<Self, SearchError> {
+        let literal_options = options.clone().literal(true);
+        let literals = literals
+            .iter()
+            .map(|literal| normalize_pattern(literal, &literal_options));
+        let match_kind = if options.whole_word {
+            MatchKind::Standard
+        } else {
+            MatchKind::LeftmostLongest
+        };
+        let automaton = AhoCorasick::builder()
+            .match_kind(match_kind)
+            .ascii_case_insensitive(!options.case_sensitive)
+            .build(literals)
+            .map_err(SearchError::LiteralMatcher)?;
+
+        Ok(Self::Literals {
+            automaton,
+            whole_word: options.whole_word,
+            anchor: options.anchor,
+        })
+    }
+
+    /// 行がいずれかのパターンにマッチするかどうかを返す
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            Self::Regex { set: Some(set), .. } => set.is_match(line),
+            Self::Regex { regexes, set: None } => regexes.iter().any(|re| re.is_match(line)),
+            Self::Literals { .. } => !self.find_in_line(line).is_empty(),
+            Self::Approximate(matcher) => matcher.is_match(line),
+            Self::Query(query) => query.is_match(line),
+            Self::Custom(matcher) => matcher.is_match(line),
+        }
+    }
+
+    /// 行にマッチするパターンの位置を返す
+    fn matched_patterns(&self, line: &str) -> Vec<usize> {
+        match self {
+            Self::Regex { set: Some(set), .. } => set.matches(line).into_iter().collect(),
+            Self::Regex { regexes, set: None } => regexes
+                .iter()
+                .enumerate()
+                .filter(|(_, re)| re.is_match(line))
+                .map(|(index, _)| index)
+                .collect(),
+            Self::Literals { .. } | Self::Approximate(_) | Self::Query(_) | Self::Custom(_) => {
+                let mut indices: Vec<usize> = self
+                    .find_in_line(line)
+                    .iter()
+                    .map(|m| m.pattern_index)
+                    .collect();
+                indices.sort_unstable();
+                indices.dedup();
+                indices
+            }
+        }
+    }
+
+    /// 行単位で評価できない条件の場合に、各行が条件を満たすかどうかを返す
+    fn line_mask(&self, lines: &[Record]) -> Option<Vec<bool>> {
+        match self {
+            Self::Query(query) => Some(query.line_mask(lines)),
+            _ => None,
+        }
+    }
+
+    /// 行内のすべてのマッチを列番号順に返す
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found = Vec::new();
+
+        match self {
+            Self::Regex {
+                regexes,
+                set: Some(set),
+            } => {
+                for pattern_index in set.matches(line).into_iter() {
+                    find_with_regex(&regexes[pattern_index], pattern_index, line, &mut found);
+                }
+                found.sort_by_key(|m| (m.span.start, m.pattern_index));
+            }
+            Self::Regex { regexes, set: None } => {
+                for (pattern_index, re) in regexes.iter().enumerate() {
+                    find_with_regex(re, pattern_index, line, &mut found);
+                }
+                if regexes.len() > 1 {
+                    found.sort_by_key(|m| (m.span.start, m.pattern_index));
+                }
+            }
+            Self::Literals {
+                automaton,
+                whole_word,
+                anchor,
+            } if *whole_word => {
+                found = find_whole_words(automaton, line);
+                found.retain(|m| anchor.accepts(line, &m.span));
+            }
+            Self::Literals {
+                automaton, anchor, ..
+            } => {
+                found = automaton.find_in_line(line);
+                found.retain(|m| anchor.accepts(line, &m.span));
+            }
+            Self::Approximate(matcher) => found = matcher.find_in_line(line),
+            Self::Query(query) => found = query.find_in_line(line),
+            Self::Custom(matcher) => found = matcher.find_in_line(line),
+        }
+
+        found
+    }
+
+    /// パターン内のキャプチャグループ名を返す
+    fn capture_name(&self, pattern_index: usize, group_index: usize) -> Option<&str> {
+        match self {
+            Self::Regex { regexes, .. } => regexes[pattern_index]
+                .capture_names()
+                .nth(group_index)
+                .flatten(),
+            Self::Literals { .. } | Self::Approximate(_) => None,
+            Self::Query(query) => query.capture_name(pattern_index, group_index),
+            Self::Custom(matcher) => matcher.capture_name(pattern_index, group_index),
+        }
+    }
+}
+
+/// 単語境界にあるリテラルのマッチを、左端で最長のものから重ならないように返す
+///
+/// `automaton` は重なりを含めて探せる `MatchKind::Standard` でなければならない。
+/// 単語境界で弾かれたマッチの中にある短いマッチも候補に残すため、先にすべての
+/// マッチを集めてから選ぶ。
+fn find_whole_words(automaton: &AhoCorasick, line: &str) -> Vec<SpanMatch> {
+    let mut candidates: Vec<SpanMatch> = automaton
+        .find_overlapping_iter(line)
+        .map(|m| SpanMatch::new(m.pattern().as_usize(), m.range()))
+        .filter(|m| is_word_bounded(line, m.span.clone()))
+        .collect();
+    candidates.sort_by_key(|m| (m.span.start, Reverse(m.span.end), m.pattern_index));
+
+    let mut found: Vec<SpanMatch> = Vec::new();
+    for candidate in candidates {
+        let overlaps = found.last().is_some_and(|last| {
+            candidate.span.start < last.span.end || candidate.span.start == last.span.start
+        });
+        if !overlaps {
+            found.push(candidate);
+        }
+    }
+    found
+}
+
+/// マッチ範囲の前後が単語文字でないかどうかを返す
+fn is_word_bounded(line: &str, span: Range<usize>) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let before = line[..span.start].chars().next_back();
+    let after = line[span.end..].chars().next();
+
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+/// `index` 以下で最も近い文字の境目を返す（`index` がテキストより長い場合はテキストの長さ）
+///
+/// `str::floor_char_boundary` と同じ。
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    let mut boundary = index;
+    while !text.is_char_boundary(boundary) {
+        boundary -= 1;
+    }
+    boundary
+}
+
+/// 1つの正規表現で行内のマッチを探して `found` に追加する
+fn find_with_regex(re: &Regex, pattern_index: usize, line: &str, found: &mut Vec<SpanMatch>) {
+    // キャプチャグループがなければ軽量な find_iter で済ませる
+    if re.captures_len() > 1 {
+        found.extend(re.captures_iter(line).map(|caps| SpanMatch {
+            pattern_index,
+            span: caps.get(0).unwrap().range(),
+            captures: capture_groups(&caps),
+        }));
+    } else {
+        found.extend(re.find_iter(line).map(|m| SpanMatch {
+            pattern_index,
+            span: m.range(),
+            captures: Vec::new(),
+        }));
+    }
+}
+
+/// マッチ全体を除くキャプチャグループを取り出す
+fn capture_groups(caps: &Captures) -> Vec<CaptureGroup> {
+    caps.iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(index, group)| {
+            group.map(|m| CaptureGroup {
+                index,
+                text: m.as_str().to_string(),
+                column: (m.start() + 1) as u32,
+                end_column: (m.end() + 1) as u32,
+            })
+        })
+        .collect()
+}
+
+/// 1ファイルの走査中に共有する情報
+struct FileScan<'a> {
+    /// 区切り方に従って分割したレコード（通常は行）のリスト
+    lines: Vec<Record<'a>>,
+    /// 正規化が有効な場合の、各レコードを正規化したもの
+    normalized: Vec<Normalized>,
+    /// 検索に使うパターン
+    patterns: &'a PatternSet,
+    /// 検索オプション
+    options: &'a SearchOptions,
+}
+
+impl FileScan<'_> {
+    /// 行番号と行内のマッチからパスを除いた検索結果を組み立てる
+    fn build_line_match(&self, line_idx: usize, line_match: SpanMatch) -> LineMatch {
+        self.build_line_match_with(line_idx, line_match, |text| Arc::from(text))
+    }
+
+    /// 行のテキストを `line_text` で文字列にして、パスを除いた検索結果を組み立てる
+    fn build_line_match_with(
+        &self,
+        line_idx: usize,
+        line_match: SpanMatch,
+        line_text: impl FnOnce(&str) -> Arc<str>,
+    ) -> LineMatch {
+        let options = self.options;
+        let Record {
+            line: line_number,
+            offset: line_start,
+            text: line,
+        } = self.lines[line_idx];
+        let SpanMatch {
+            pattern_index,
+            span,
+            captures,
+        } = match self.normalized.get(line_idx) {
+            Some(normalized) => normalized.map_match(line_match, line),
+            None => line_match,
+        };
+        let captures: Vec<CaptureGroup> = captures
+            .into_iter()
+            .map(|group| CaptureGroup {
+                column: options.column_unit.column(line, group.column as usize - 1),
+                end_column: options
+                    .column_unit
+                    .column(line, group.end_column as usize - 1),
+                ..group
+            })
+            .collect();
+        let match_text = &line[span.clone()];
+        let preview = line_preview(line, &span, options);
+        let before_start = line_idx.saturating_sub(options.before_context);
+        let after_end = (line_idx + 1 + options.after_context).min(self.lines.len());
+        let named_captures = captures
+            .iter()
+            .filter_map(|group| {
+                self.patterns
+                    .capture_name(pattern_index, group.index)
+                    .map(|name| (name.to_string(), group.text.clone()))
+            })
+            .collect();
+
+        LineMatch {
+            line: line_number as u32,
+            pattern_index,
+            column: options.column_unit.column(line, span.start),
+            end_column: options.column_unit.column(line, span.end),
+            match_text: match_text.to_string(),
+            byte_offset: line_start + span.start,
+            line_byte_offset: span.start,
+            line_text_column: options
+                .column_unit
+                .column(&preview.text, preview.match_start),
+            line_text: line_text(&preview.text),
+            line_text_offset: preview.offset,
+            line_truncated: preview.truncated,
+            captures,
+            named_captures,
+            context_before: context_lines(&self.lines, before_start..line_idx, options),
+            context_after: context_lines(&self.lines, line_idx + 1..after_end, options),
+            score: 0,
+        }
+    }
+}
+
+/// 指定した範囲（0ベース）のレコードをコンテキスト行に変換する
+fn context_lines(
+    lines: &[Record],
+    range: Range<usize>,
+    options: &SearchOptions,
+) -> Vec<ContextLine> {
+    range
+        .map(|idx| ContextLine {
+            line: lines[idx].line as u32,
+            text: context_preview(lines[idx].text, options).to_string(),
+        })
+        .collect()
+}
+
+/// オプションに従ってパターン文字列を組み立てる
+fn build_pattern(pattern: &str, options: &SearchOptions) -> String {
+    let pattern = if options.literal {
+        engine::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    let pattern = if options.whole_word {
+        // `\b` で囲むと記号で始まる・終わるパターンが一切マッチしなくなるため、
+        // 前後が単語文字でないことだけを確認する半分の境界を使う
+        format!(r"\b{{start-half}}(?:{})\b{{end-half}}", pattern)
+    } else {
+        pattern
+    };
+
+    match options.anchor {
+        Anchor::Unanchored => pattern,
+        Anchor::LineStart => format!("^(?:{})", pattern),
+        Anchor::WholeLine => format!("^(?:{})$", pattern),
+    }
+}
+
+/// オプションに従って正規表現をコンパイルする
+fn build_regex(pattern: &str, options: &SearchOptions) -> Result<Regex, SearchError> {
+    let mut builder = RegexBuilder::new(&build_pattern(
+        &normalize_pattern(pattern, options),
+        options,
+    ));
+    options
+        .regex_flags
+        .apply(&mut builder)
+        .case_insensitive(!options.case_sensitive)
+        .size_limit(options.size_limit)
+        .dfa_size_limit(options.dfa_size_limit)
+        // 空白をエスケープしない `literal` モードのパターンが崩れないようにする
+        .ignore_whitespace(options.regex_flags.ignore_whitespace && !options.literal)
+        .build()
+        .map_err(|source| SearchError::InvalidPattern {
+            pattern: pattern.to_string(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_basic_search_match() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, world!".to_string(),
+        }];
+        let results = search("world", &files, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(&*results[0].path, "test.txt");
+        assert_eq!(results[0].line, 1);
+        assert_eq!(results[0].column, 8);
+        assert_eq!(&*results[0].line_text, "Hello, world!");
+    }
+
+    #[test]
+    fn test_search_no_match() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, world!".to_string(),
+        }];
+        let results = search("foo", &files, true).unwrap();
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    fn test_case_insensitive_search() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        let results = search("world", &files, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(&*results[0].line_text, "Hello, WORLD!");
+    }
+
+    #[test]
+    fn test_case_sensitive_search() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        let results = search("world", &files, true).unwrap();
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    fn test_multiline_file() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Line 1\nLine 2\nLine 3".to_string(),
+        }];
+        let results = search("Line", &files, true).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].line, 1);
+        assert_eq!(results[1].line, 2);
+        assert_eq!(results[2].line, 3);
+    }
+
+    #[test]
+    fn test_multiple_files() {
+        let files = vec![
+            FileInput {
+                path: "file1.txt".to_string(),
+                content: "Hello from file1".to_string(),
+            },
+            FileInput {
+                path: "file2.txt".to_string(),
+                content: "Hello from file2".to_string(),
+            },
+        ];
+        let results = search("Hello", &files, true).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(&*results[0].path, "file1.txt");
+        assert_eq!(&*results[1].path, "file2.txt");
+    }
+
+    #[test]
+    fn test_multiple_matches_same_line() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo bar foo baz".to_string(),
+        }];
+        let results = search("foo", &files, true).unwrap();
+        // re.find_iter() により、すべてのマッチが返される
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[1].column, 9);
+    }
+
+    #[test]
+    fn test_regex_pattern() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "abc123 def456".to_string(),
+        }];
+        let results = search(r"\d+", &files, true).unwrap();
+        // re.find_iter() により、すべてのマッチが返される
+        // "abc123 def456" では "123" と "456" の2つにマッチ
+        // "123" は位置3 (0ベース) = 列4 (1ベース)
+        // "456" は位置10 (0ベース) = 列11 (1ベース)
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 4); // "123" の開始位置
+        assert_eq!(results[1].column, 11); // "456" の開始位置
+        assert_eq!(&*results[0].line_text, "abc123 def456");
+        assert_eq!(&*results[1].line_text, "abc123 def456");
+    }
+
+    #[test]
+    fn test_invalid_regex_pattern() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, world!".to_string(),
+        }];
+        let result = search("[", &files, true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let files = vec![FileInput {
+            path: "empty.txt".to_string(),
+            content: "".to_string(),
+        }];
+        let results = search("test", &files, true).unwrap();
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    fn test_empty_pattern() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, world!".to_string(),
+        }];
+        let results = search("", &files, true).unwrap();
+        // 空のパターンはすべての位置（文字の間）にマッチする
+        // "Hello, world!" は13文字なので、14個の位置がある
+        assert_eq!(results.len(), 14);
+    }
+
+    #[test]
+    fn test_column_position() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "  Hello".to_string(),
+        }];
+        let results = search("Hello", &files, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 3);
+    }
+
+    #[test]
+    fn test_search_with_default_options() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        // デフォルトでは大文字小文字を区別する
+        let results = search_with_options("world", &files, &SearchOptions::new())
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    fn test_search_with_options_case_insensitive() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        let options = SearchOptions::new().case_sensitive(false);
+        let results = search_with_options("world", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 8);
+    }
+
+    #[test]
+    fn test_search_with_options_invalid_regex() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, world!".to_string(),
+        }];
+        let result = search_with_options("(", &files, &SearchOptions::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_whole_word_search() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "cat concatenate cat_food cat.".to_string(),
+        }];
+        let options = SearchOptions::new().whole_word(true);
+        let results = search_with_options("cat", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[1].column, 26);
+    }
+
+    #[test]
+    fn test_whole_word_with_regex_alternation() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "dog dogma cat".to_string(),
+        }];
+        let options = SearchOptions::new().whole_word(true);
+        // 選択全体が境界で囲まれるため `dog` は `dogma` にマッチしない
+        let results = search_with_options("dog|cat", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[1].column, 11);
+    }
+
+    #[test]
+    fn test_whole_word_pattern_with_symbols() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "call foo() and foo()bar".to_string(),
+        }];
+        let options = SearchOptions::new().whole_word(true);
+        let results = search_with_options(r"foo\(\)", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 6);
+    }
+
+    #[test]
+    fn test_literal_search() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "price ($) is 1.5, not 105".to_string(),
+        }];
+        let options = SearchOptions::new().literal(true);
+        let results = search_with_options("($)", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 7);
+
+        // `.` は任意の1文字ではなくピリオドとして扱われる
+        let results = search_with_options("1.5", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 14);
+    }
+
+    #[test]
+    fn test_literal_search_does_not_fail_on_invalid_regex() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "let v = a[0];".to_string(),
+        }];
+        let options = SearchOptions::new().literal(true);
+        let results = search_with_options("[", &files, &options).unwrap().results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 10);
+    }
+
+    #[test]
+    fn test_literal_whole_word_search() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "a.b a.bc xa.b a.b".to_string(),
+        }];
+        let options = SearchOptions::new().literal(true).whole_word(true);
+        let results = search_with_options("a.b", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[1].column, 15);
+    }
+
+    #[test]
+    fn test_invert_match() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nbar\nfoo bar\nbaz".to_string(),
+        }];
+        let options = SearchOptions::new().invert_match(true);
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].line, 2);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(&*results[0].line_text, "bar");
+        assert_eq!(results[1].line, 4);
+        assert_eq!(&*results[1].line_text, "baz");
+    }
+
+    #[test]
+    fn test_invert_match_reports_each_line_once() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "abc\n123".to_string(),
+        }];
+        // 1行に複数の非マッチ箇所があっても行単位で1件だけ返す
+        let options = SearchOptions::new().invert_match(true);
+        let results = search_with_options(r"\d", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(&*results[0].line_text, "abc");
+    }
+
+    #[test]
+    fn test_max_count_per_file() {
+        let files = vec![
+            FileInput {
+                path: "big.log".to_string(),
+                content: "error\nerror error\nerror".to_string(),
+            },
+            FileInput {
+                path: "small.log".to_string(),
+                content: "error".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().max_count_per_file(2);
+        let output = search_with_options("error", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 3);
+        assert_eq!(&*output.results[0].path, "big.log");
+        assert_eq!(&*output.results[1].path, "big.log");
+        assert_eq!(&*output.results[2].path, "small.log");
+        assert_eq!(output.truncated_files, vec!["big.log".to_string()]);
+    }
+
+    #[test]
+    fn test_max_count_per_file_exact_limit_is_not_truncated() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nfoo".to_string(),
+        }];
+        let options = SearchOptions::new().max_count_per_file(2);
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert!(output.truncated_files.is_empty());
+    }
+
+    #[test]
+    fn test_max_count_per_file_with_invert_match() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "a\nb\nc".to_string(),
+        }];
+        let options = SearchOptions::new()
+            .invert_match(true)
+            .max_count_per_file(1);
+        let output = search_with_options("x", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(&*output.results[0].line_text, "a");
+        assert_eq!(output.truncated_files, vec!["test.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_max_results() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo foo".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "foo".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().max_results(2);
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(&*output.results[1].path, "a.txt");
+        assert!(output.truncated);
+    }
+
+    #[test]
+    fn test_max_results_exact_limit_is_not_truncated() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nbar\nfoo".to_string(),
+        }];
+        let options = SearchOptions::new().max_results(2);
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert!(!output.truncated);
+    }
+
+    #[test]
+    fn test_max_result_bytes() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x\n".repeat(99) + "x",
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "x".to_string(),
+            },
+        ];
+        // 全行にマッチするパターン
+        let size = size_of::<MatchResult>() + 1;
+        let options = SearchOptions::new().max_result_bytes(size * 3 + 1);
+        let output = search_with_options("^", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 3);
+        assert!(output.truncated);
+        assert_eq!(output.next_offset, Some(3));
+
+        let output = search_with_options("^", &files, &options.clone().offset(99)).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert!(!output.truncated);
+
+        // 最初の結果は上限を超えていても返し、次のページへ進めるようにする
+        let options = SearchOptions::new().max_result_bytes(0);
+        let output = search_with_options("^", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert!(output.truncated);
+        assert_eq!(output.next_offset, Some(1));
+
+        let output = search_with_options("^", &files, &options.clone().offset(100)).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert!(!output.truncated);
+        assert_eq!(output.next_offset, None);
+    }
+
+    #[test]
+    fn test_intern_lines() {
+        let files = vec![
+            FileInput {
+                path: "a.log".to_string(),
+                content: "GET / 200\nGET / 404\nGET / 200".to_string(),
+            },
+            FileInput {
+                path: "b.log".to_string(),
+                content: "GET / 200".to_string(),
+            },
+        ];
+        let output = search_with_options("GET", &files, &SearchOptions::new()).unwrap();
+        assert!(!Arc::ptr_eq(
+            &output.results[0].line_text,
+            &output.results[2].line_text
+        ));
+
+        let options = SearchOptions::new().intern_lines(true);
+        let interned = search_with_options("GET", &files, &options).unwrap();
+        assert_eq!(interned.results, output.results);
+        let lines: Vec<&Arc<str>> = interned.results.iter().map(|r| &r.line_text).collect();
+        assert!(Arc::ptr_eq(lines[0], lines[2]));
+        assert!(!Arc::ptr_eq(lines[0], lines[1]));
+    }
+
+    #[test]
+    fn test_max_results_with_max_count_per_file() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nfoo\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "foo\nfoo".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().max_count_per_file(1).max_results(2);
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.truncated_files, vec!["a.txt".to_string()]);
+        assert!(output.truncated);
+    }
+
+    #[test]
+    fn test_context_lines() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "one\ntwo\nthree\nfour\nfive".to_string(),
+        }];
+        let options = SearchOptions::new().before_context(2).after_context(1);
+        let results = search_with_options("four", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        let before: Vec<(u32, &str)> = results[0]
+            .context_before
+            .iter()
+            .map(|c| (c.line, c.text.as_str()))
+            .collect();
+        assert_eq!(before, vec![(2, "two"), (3, "three")]);
+        assert_eq!(results[0].context_after.len(), 1);
+        assert_eq!(results[0].context_after[0].line, 5);
+        assert_eq!(results[0].context_after[0].text, "five");
+    }
+
+    #[test]
+    fn test_context_lines_clamped_at_file_edges() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "first\nmiddle\nlast".to_string(),
+        }];
+        let options = SearchOptions::new().before_context(3).after_context(3);
+        let results = search_with_options("first|last", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert!(results[0].context_before.is_empty());
+        assert_eq!(results[0].context_after.len(), 2);
+        assert_eq!(results[1].context_before.len(), 2);
+        assert!(results[1].context_after.is_empty());
+    }
+
+    #[test]
+    fn test_no_context_by_default() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "a\nb\nc".to_string(),
+        }];
+        let results = search("b", &files, true).unwrap();
+        assert!(results[0].context_before.is_empty());
+        assert!(results[0].context_after.is_empty());
+    }
+
+    #[test]
+    fn test_only_matching() {
+        let files = vec![FileInput {
+            path: "contacts.txt".to_string(),
+            content: "alice@example.com, bob@example.org\nno email here".to_string(),
+        }];
+        let options = SearchOptions::new().only_matching(true);
+        let results = search_with_options(r"\w+@\w+\.\w+", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(&*results[0].line_text, "alice@example.com");
+        assert_eq!(results[0].column, 1);
+        assert_eq!(&*results[1].line_text, "bob@example.org");
+        assert_eq!(results[1].column, 20);
+    }
+
+    #[test]
+    fn test_long_line_modes() {
+        let long_line = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let files = vec![FileInput {
+            path: "bundle.min.js".to_string(),
+            content: format!("short needle\n{}", long_line),
+        }];
+        let search = |options: SearchOptions| {
+            search_with_options("needle", &files, &options.max_line_length(20))
+                .unwrap()
+                .results
+        };
+
+        let results = search(SearchOptions::new());
+        assert_eq!(results.len(), 2);
+        assert!(!results[0].line_truncated);
+        assert_eq!(&*results[0].line_text, "short needle");
+        assert!(results[1].line_truncated);
+        assert_eq!(&*results[1].line_text, "a".repeat(20));
+        // 列番号は元の行に対するもの
+        assert_eq!(results[1].column, 101);
+
+        let results = search(SearchOptions::new().long_line_mode(LongLineMode::Window));
+        assert_eq!(&*results[1].line_text, "aaaaaaaneedlebbbbbbb");
+        assert_eq!(results[1].line_text_offset, 93);
+
+        let results = search(SearchOptions::new().long_line_mode(LongLineMode::Skip));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 1);
+    }
+
+    #[test]
+    fn test_preview_width() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "日本語のテキストの中の needle とその後ろの長い文章\nshort needle\nneedle at the start of a line"
+                .to_string(),
+        }];
+        let options = SearchOptions::new()
+            .preview_width(10)
+            .column_unit(ColumnUnit::Chars);
+        let results = search_with_options("needle", &files, &options)
+            .unwrap()
+            .results;
+        let previews: Vec<(&str, u32, bool)> = results
+            .iter()
+            .map(|m| (&*m.line_text, m.line_text_column, m.line_truncated))
+            .collect();
+        assert_eq!(
+            previews,
+            vec![
+                ("…の needle と…", 4, true),
+                // 行末側で余った文字数は前側に回す
+                ("…ort needle", 6, true),
+                ("needle at …", 1, true),
+            ]
+        );
+        assert_eq!(results[0].column, 13);
+    }
+
+    #[test]
+    fn test_search_grouped() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nbar foo\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "bar".to_string(),
+            },
+            FileInput {
+                path: "c.txt".to_string(),
+                content: "foo foo".to_string(),
+            },
+        ];
+        let output = search_grouped("foo", &files, &SearchOptions::new()).unwrap();
+        // マッチがなかったファイルは含まない
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[0].path, "a.txt");
+        let lines: Vec<u32> = output.results[0].matches.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+        assert_eq!(output.results[0].matches[1].column, 5);
+        assert_eq!(output.results[1].path, "c.txt");
+        assert_eq!(output.results[1].matches.len(), 2);
+
+        // 上限はファイルをまたいで数える
+        let options = SearchOptions::new().max_results(4);
+        let output = search_grouped("foo", &files, &options).unwrap();
+        assert!(output.truncated);
+        assert_eq!(output.results[1].matches.len(), 1);
+
+        let options = SearchOptions::new().max_count_per_file(1);
+        let output = search_grouped("foo", &files, &options).unwrap();
+        assert_eq!(output.truncated_files, vec!["a.txt", "c.txt"]);
+        assert!(output.results.iter().all(|f| f.matches.len() == 1));
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let files = vec![
+            FileInput {
+                path: "c.txt".to_string(),
+                content: "x\nx".to_string(),
+            },
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "x x x".to_string(),
+            },
+        ];
+        let order = |sort: SortOrder| -> Vec<(String, u32, u32)> {
+            let options = SearchOptions::new().sort(sort);
+            search_with_options("x", &files, &options)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| (r.path.to_string(), r.line, r.column))
+                .collect()
+        };
+        let key = |path: &str, line, column| (path.to_string(), line, column);
+
+        assert_eq!(order(SortOrder::Input)[0], key("c.txt", 1, 1));
+        assert_eq!(
+            order(SortOrder::Path),
+            vec![
+                key("a.txt", 1, 1),
+                key("b.txt", 1, 1),
+                key("b.txt", 1, 3),
+                key("b.txt", 1, 5),
+                key("c.txt", 1, 1),
+                key("c.txt", 2, 1),
+            ]
+        );
+        assert_eq!(
+            order(SortOrder::MatchCount),
+            vec![
+                key("b.txt", 1, 1),
+                key("b.txt", 1, 3),
+                key("b.txt", 1, 5),
+                key("c.txt", 1, 1),
+                key("c.txt", 2, 1),
+                key("a.txt", 1, 1),
+            ]
+        );
+
+        let options = SearchOptions::new().sort(SortOrder::Path);
+        let grouped = search_grouped("x", &files, &options).unwrap();
+        let paths: Vec<&str> = grouped.results.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.txt", "b.txt", "c.txt"]);
+
+        // 同じパスが複数回入力されても、パスの順では行・列の順に並ぶ
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "\nx".to_string(),
+            },
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().sort(SortOrder::Path);
+        let lines: Vec<u32> = search_with_options("x", &files, &options)
+            .unwrap()
+            .results
+            .iter()
+            .map(|r| r.line)
+            .collect();
+        assert_eq!(lines, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_offset_pages_across_files() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x x".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "x\nx\nx".to_string(),
+            },
+        ];
+        let mut pages = Vec::new();
+        let mut offset = Some(0);
+        while let Some(start) = offset {
+            let options = SearchOptions::new().offset(start).max_results(2);
+            let output = search_with_options("x", &files, &options).unwrap();
+            pages.push(
+                output
+                    .results
+                    .iter()
+                    .map(|r| (r.path.to_string(), r.line, r.column))
+                    .collect::<Vec<_>>(),
+            );
+            offset = output.next_offset;
+        }
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[1][0], ("b.txt".to_string(), 1, 1));
+        assert_eq!(pages[2], vec![("b.txt".to_string(), 3, 1)]);
+
+        // ファイルごとの上限は読み飛ばした結果も含めて数える
+        let options = SearchOptions::new().offset(1).max_count_per_file(2);
+        let output = search_with_options("x", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 3);
+        assert_eq!(output.truncated_files, vec!["b.txt"]);
+        assert_eq!(output.next_offset, None);
+    }
+
+    #[test]
+    fn test_search_report() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nbar".to_string(),
+            },
+            FileInput {
+                path: "data.bin".to_string(),
+                content: "foo\0".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "foo foo".to_string(),
+            },
+        ];
+        let output = search_with_options("foo", &files, &SearchOptions::new()).unwrap();
+        assert!(output.report.is_none());
+
+        let options = SearchOptions::new().report(true);
+        let report = search_with_options("foo", &files, &options)
+            .unwrap()
+            .report
+            .unwrap();
+        assert_eq!(report.files_scanned, 2);
+        assert_eq!(report.bytes_scanned, 14);
+        assert_eq!(report.lines_scanned, 3);
+        assert_eq!(report.matches_found, 3);
+        assert_eq!(report.files_skipped, 1);
+
+        // 打ち切った場合はそこまでを数え、読み飛ばした結果もマッチに含める
+        let options = SearchOptions::new().report(true).offset(1).max_results(1);
+        let report = search_with_options("foo", &files, &options)
+            .unwrap()
+            .report
+            .unwrap();
+        assert_eq!(report.files_scanned, 2);
+        assert_eq!(report.matches_found, 2);
+        assert!(report.files.is_empty());
+
+        let options = SearchOptions::new().profile(true);
+        let report = search_with_options("foo", &files, &options)
+            .unwrap()
+            .report
+            .unwrap();
+        let profiles: Vec<(&str, u64, usize)> = report
+            .files
+            .iter()
+            .map(|p| (p.path.as_str(), p.lines, p.matches))
+            .collect();
+        assert_eq!(profiles, vec![("a.txt", 2, 1), ("b.txt", 1, 2)]);
+    }
+
+    #[test]
+    fn test_results_can_be_compared_and_hashed() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "foo\nfoo".to_string(),
+        }];
+        let options = SearchOptions::new().after_context(1);
+        let first = search_with_options("(f)oo", &files, &options)
+            .unwrap()
+            .results;
+        let second = search_with_options("(f)oo", &files.clone(), &options)
+            .unwrap()
+            .results;
+        assert_eq!(first, second);
+        assert_ne!(first[0], first[1]);
+
+        let unique: std::collections::HashSet<MatchResult> =
+            first.iter().chain(&second).cloned().collect();
+        assert_eq!(unique.len(), 2);
+        assert!(format!("{:?}", files[0]).contains("a.txt"));
+    }
+
+    #[test]
+    fn test_results_share_path_per_file() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x x
+x"
+                .to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "x".to_string(),
+            },
+        ];
+        let results = search("x", &files, true).unwrap();
+        assert_eq!(results.len(), 4);
+        // 同じファイルの結果はパスの文字列を共有する
+        assert!(Arc::ptr_eq(&results[0].path, &results[2].path));
+        assert!(!Arc::ptr_eq(&results[2].path, &results[3].path));
+    }
+
+    #[test]
+    fn test_top_files() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "bar".to_string(),
+            },
+            FileInput {
+                path: "c.txt".to_string(),
+                content: "foo foo foo".to_string(),
+            },
+            FileInput {
+                path: "d.txt".to_string(),
+                content: "foo foo".to_string(),
+            },
+        ];
+        let top = top_files("foo", &files, &SearchOptions::new(), 10).unwrap();
+        // 同数の場合は入力順、マッチのないファイルは含まない
+        assert_eq!(
+            top,
+            vec![
+                ("c.txt".to_string(), 3),
+                ("a.txt".to_string(), 2),
+                ("d.txt".to_string(), 2),
+            ]
+        );
+        assert!(
+            top_files("foo", &files, &SearchOptions::new(), 0)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "foo foo\nfoo".to_string(),
+        }];
+        let lines = |patterns: &[&str], dedupe: Dedupe| -> Vec<(u32, u32, usize)> {
+            let options = SearchOptions::new().dedupe(dedupe);
+            search_multi(patterns, &files, &options)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| (r.line, r.column, r.pattern_index))
+                .collect()
+        };
+
+        assert_eq!(lines(&["foo"], Dedupe::None).len(), 3);
+        // 1行につき最初のマッチだけを返す
+        assert_eq!(lines(&["foo"], Dedupe::Line), vec![(1, 1, 0), (2, 1, 0)]);
+        // 同じ範囲に複数のパターンがマッチした場合は最初のパターンだけを返す
+        assert_eq!(lines(&["foo", "fo+"], Dedupe::None).len(), 6);
+        assert_eq!(
+            lines(&["foo", "fo+"], Dedupe::Span),
+            vec![(1, 1, 0), (1, 5, 0), (2, 1, 0)]
+        );
+
+        let options = SearchOptions::new().dedupe(Dedupe::Line);
+        assert_eq!(
+            count("foo", &files, &options).unwrap(),
+            vec![("a.txt".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_relevance_scoring() {
+        let files = vec![
+            FileInput {
+                path: "notes.txt".to_string(),
+                content: "bar\nfoo\nbaz\nqux\nfoo".to_string(),
+            },
+            FileInput {
+                path: "src/foo.txt".to_string(),
+                content: "foo".to_string(),
+            },
+        ];
+        // デフォルトでは関連度を付けない
+        let output = search_with_options("foo", &files, &SearchOptions::new()).unwrap();
+        assert!(output.results.iter().all(|r| r.score == 0));
+
+        // ファイル名のマッチと密度が高いファイル、ファイル内では先頭に近い行が先になる
+        let options = SearchOptions::new().sort(SortOrder::Relevance);
+        let output = search_with_options("foo", &files, &options).unwrap();
+        let order: Vec<(&str, u32)> = output.results.iter().map(|r| (&*r.path, r.line)).collect();
+        assert_eq!(
+            order,
+            vec![("src/foo.txt", 1), ("notes.txt", 2), ("notes.txt", 5)]
+        );
+        assert!(output.results[1].score > output.results[2].score);
+
+        // 並べ替えずに関連度だけを付けることもできる
+        let options = SearchOptions::new().relevance(true);
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(&*output.results[0].path, "notes.txt");
+        assert!(output.results.iter().all(|r| r.score > 0));
+
+        let options = SearchOptions::new().sort(SortOrder::Relevance);
+        let grouped = search_grouped("foo", &files, &options).unwrap();
+        assert_eq!(grouped.results[0].path, "src/foo.txt");
+        assert_eq!(grouped.results[1].matches[0].line, 2);
+    }
+
+    #[test]
+    fn test_binary_files_are_skipped() {
+        let files = vec![
+            FileInput {
+                path: "logo.png".to_string(),
+                content: "\u{FFFD}PNG\r\n\u{1a}\n\0\0\0\rIHDR".to_string(),
+            },
+            FileInput {
+                path: "readme.txt".to_string(),
+                content: "PNG images are supported".to_string(),
+            },
+        ];
+        let output = search_with_options("PNG", &files, &SearchOptions::new()).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(&*output.results[0].path, "readme.txt");
+        assert_eq!(output.binary_files, vec!["logo.png"]);
+        assert!(
+            files_without_match("missing", &files, &SearchOptions::new())
+                .unwrap()
+                .iter()
+                .all(|path| path != "logo.png")
+        );
+
+        let options = SearchOptions::new().search_binary(true);
+        let output = search_with_options("PNG", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert!(output.binary_files.is_empty());
+
+        // 置換文字が少しだけ含まれるテキストはバイナリとみなさない
+        assert!(!is_binary("caf\u{FFFD} au lait, s'il vous pla\u{FFFD}t"));
+    }
+
+    #[test]
+    fn test_only_matching_ignored_with_invert_match() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nbar".to_string(),
+        }];
+        let options = SearchOptions::new().only_matching(true).invert_match(true);
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(&*results[0].line_text, "bar");
+    }
+
+    #[test]
+    fn test_files_with_matches() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "TODO: one\nTODO: two".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "done".to_string(),
+            },
+            FileInput {
+                path: "c.txt".to_string(),
+                content: "nothing\nTODO later".to_string(),
+            },
+        ];
+        let paths = files_with_matches("TODO", &files, &SearchOptions::new()).unwrap();
+        assert_eq!(paths, vec!["a.txt".to_string(), "c.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_files_with_matches_respects_options() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "Concatenate".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "a CAT".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().case_sensitive(false).whole_word(true);
+        let paths = files_with_matches("cat", &files, &options).unwrap();
+        assert_eq!(paths, vec!["b.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_files_with_matches_invalid_regex() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello".to_string(),
+        }];
+        assert!(files_with_matches("[", &files, &SearchOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_files_without_match() {
+        let files = vec![
+            FileInput {
+                path: "a.rs".to_string(),
+                content: "// SPDX-License-Identifier: MIT\nfn main() {}".to_string(),
+            },
+            FileInput {
+                path: "b.rs".to_string(),
+                content: "fn main() {}".to_string(),
+            },
+            FileInput {
+                path: "empty.rs".to_string(),
+                content: "".to_string(),
+            },
+        ];
+        let paths =
+            files_without_match("SPDX-License-Identifier", &files, &SearchOptions::new()).unwrap();
+        assert_eq!(paths, vec!["b.rs".to_string(), "empty.rs".to_string()]);
+    }
+
+    #[test]
+    fn test_files_without_match_invalid_regex() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello".to_string(),
+        }];
+        assert!(files_without_match("(", &files, &SearchOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_count() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo foo\nbar\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "bar".to_string(),
+            },
+        ];
+        let counts = count("foo", &files, &SearchOptions::new()).unwrap();
+        assert_eq!(
+            counts,
+            vec![("a.txt".to_string(), 3), ("b.txt".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_count_with_invert_match() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "foo foo\nbar\nbaz".to_string(),
+        }];
+        let options = SearchOptions::new().invert_match(true);
+        let counts = count("foo", &files, &options).unwrap();
+        assert_eq!(counts, vec![("a.txt".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_count_with_max_count_per_file() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x x\nx".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "x".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().max_count_per_file(1);
+        let counts = count("x", &files, &options).unwrap();
+        assert_eq!(
+            counts,
+            vec![("a.txt".to_string(), 1), ("b.txt".to_string(), 1)]
+        );
+        // search_with_options と同じ件数になる
+        let output = search_with_options("x", &files[..1], &options).unwrap();
+        assert_eq!(output.results.len(), 1);
+
+        // top_files も同じ上限で数えるため、同数なら入力順になる
+        let top = top_files("x", &files, &options, 1).unwrap();
+        assert_eq!(top, vec![("a.txt".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_count_invalid_regex() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello".to_string(),
+        }];
+        assert!(count("[", &files, &SearchOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_exists() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "alpha".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "beta".to_string(),
+            },
+        ];
+        assert!(exists("beta", &files, &SearchOptions::new()).unwrap());
+        assert!(!exists("gamma", &files, &SearchOptions::new()).unwrap());
+    }
+
+    #[test]
+    fn test_exists_with_no_files() {
+        assert!(!exists("", &[], &SearchOptions::new()).unwrap());
+    }
+
+    #[test]
+    fn test_exists_invalid_regex() {
+        assert!(exists("[", &[], &SearchOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_match_text_and_end_column() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "id: 12345, other: 7".to_string(),
+        }];
+        let results = search(r"\d+", &files, true).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].match_text, "12345");
+        assert_eq!(results[0].column, 5);
+        assert_eq!(results[0].end_column, 10);
+        assert_eq!(results[1].match_text, "7");
+        assert_eq!(results[1].column, 19);
+        assert_eq!(results[1].end_column, 20);
+    }
+
+    #[test]
+    fn test_empty_match_has_equal_columns() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "ab".to_string(),
+        }];
+        let results = search("", &files, true).unwrap();
+        assert_eq!(results[0].match_text, "");
+        assert_eq!(results[0].column, results[0].end_column);
+    }
+
+    #[test]
+    fn test_invert_match_has_empty_match_text() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nbar".to_string(),
+        }];
+        let options = SearchOptions::new().invert_match(true);
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].match_text, "");
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[0].end_column, 1);
+    }
+
+    #[test]
+    fn test_byte_offsets() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "first line\r\nsecond target\nthird target".to_string(),
+        }];
+        let results = search("target", &files, true).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].line, 2);
+        assert_eq!(results[0].line_byte_offset, 7);
+        assert_eq!(results[0].byte_offset, 19);
+        assert_eq!(results[1].line, 3);
+        assert_eq!(results[1].byte_offset, 32);
+        let content = &files[0].content;
+        assert_eq!(&content[results[1].byte_offset..][..6], "target");
+    }
+
+    #[test]
+    fn test_byte_offsets_with_multibyte_text() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "日本\n語の検索".to_string(),
+        }];
+        let results = search("検索", &files, true).unwrap();
+        assert_eq!(results[0].line_byte_offset, 6);
+        assert_eq!(results[0].byte_offset, 13);
+    }
+
+    #[test]
+    fn test_capture_groups() {
+        let files = vec![FileInput {
+            path: "Cargo.toml".to_string(),
+            content: "name = \"demo\"\nversion = \"1.2.3\"".to_string(),
+        }];
+        let results = search(r#"version = "(\d+)\.(\d+\.\d+)""#, &files, true).unwrap();
+        assert_eq!(results.len(), 1);
+        let captures = &results[0].captures;
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures[0].index, 1);
+        assert_eq!(captures[0].text, "1");
+        assert_eq!(captures[0].column, 12);
+        assert_eq!(captures[0].end_column, 13);
+        assert_eq!(captures[1].index, 2);
+        assert_eq!(captures[1].text, "2.3");
+    }
+
+    #[test]
+    fn test_unmatched_optional_group_is_omitted() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "v1".to_string(),
+        }];
+        let results = search(r"v(\d)(-beta)?", &files, true).unwrap();
+        assert_eq!(results[0].captures.len(), 1);
+        assert_eq!(results[0].captures[0].index, 1);
+    }
+
+    #[test]
+    fn test_no_captures_without_groups() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "abc".to_string(),
+        }];
+        let results = search("b", &files, true).unwrap();
+        assert!(results[0].captures.is_empty());
+    }
+
+    #[test]
+    fn test_named_capture_groups() {
+        let files = vec![FileInput {
+            path: "config.ini".to_string(),
+            content: "mode=fast\nlevel=3".to_string(),
+        }];
+        let results = search(r"(?P<key>\w+)=(?P<value>\S+)", &files, true).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].named_captures["key"], "mode");
+        assert_eq!(results[0].named_captures["value"], "fast");
+        assert_eq!(results[1].named_captures["key"], "level");
+        assert_eq!(results[1].named_captures["value"], "3");
+    }
+
+    #[test]
+    fn test_named_and_unnamed_groups_mixed() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "2024-06".to_string(),
+        }];
+        let results = search(r"(?<year>\d{4})-(\d{2})", &files, true).unwrap();
+        assert_eq!(results[0].captures.len(), 2);
+        assert_eq!(results[0].named_captures.len(), 1);
+        assert_eq!(results[0].named_captures["year"], "2024");
+    }
+
+    #[test]
+    fn test_search_multi() {
+        let files = vec![FileInput {
+            path: "app.log".to_string(),
+            content: "WARN disk\nINFO ok\nERROR db WARN retry".to_string(),
+        }];
+        let output = search_multi(&["ERROR", "WARN"], &files, &SearchOptions::new()).unwrap();
+        let found: Vec<(u32, u32, usize)> = output
+            .results
+            .iter()
+            .map(|m| (m.line, m.column, m.pattern_index))
+            .collect();
+        assert_eq!(found, vec![(1, 1, 1), (3, 1, 0), (3, 10, 1)]);
+    }
 
-/// パターンでファイルを検索する
-///
-/// # Arguments
-///
-/// * `pattern` - 検索する正規表現パターン
-/// * `files` - 検索対象のファイルリスト
-/// * `case_sensitive` - 大文字小文字を区別するかどうか
-///
-/// # Returns
-///
-/// 検索結果のリスト、または正規表現パターンが無効な場合のエラー
-pub fn search(
-    pattern: &str,
-    files: &[FileInput],
-    case_sensitive: bool,
-) -> Result<Vec<MatchResult>, String> {
-    let re = if case_sensitive {
-        Regex::new(pattern).map_err(|e| format!("Invalid regex pattern '{}': {}", pattern, e))?
-    } else {
-        RegexBuilder::new(pattern)
-            .case_insensitive(true)
-            .build()
-            .map_err(|e| format!("Invalid regex pattern '{}': {}", pattern, e))?
-    };
+    #[test]
+    fn test_search_multi_orders_matches_by_column() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "ab ab".to_string(),
+        }];
+        // regex-lite のように正規表現セットがなくても、列・パターンの順に並ぶ
+        let output = search_multi(&["b", "a"], &files, &SearchOptions::new()).unwrap();
+        let found: Vec<(u32, usize)> = output
+            .results
+            .iter()
+            .map(|m| (m.column, m.pattern_index))
+            .collect();
+        assert_eq!(found, vec![(1, 1), (2, 0), (4, 1), (5, 0)]);
+    }
 
-    let mut results = Vec::new();
+    #[test]
+    fn test_search_multi_with_options() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Cat and DOG\nbird".to_string(),
+        }];
+        let options = SearchOptions::new()
+            .case_sensitive(false)
+            .invert_match(true);
+        let output = search_multi(&["cat", "dog"], &files, &options).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(&*output.results[0].line_text, "bird");
+    }
 
-    for f in files {
-        for (line_idx, line) in f.content.lines().enumerate() {
-            for m in re.find_iter(line) {
-                results.push(MatchResult {
-                    path: f.path.clone(),
-                    line: (line_idx + 1) as u32,
-                    column: (m.start() + 1) as u32,
-                    line_text: line.to_string(),
-                });
-            }
-        }
+    #[test]
+    fn test_search_multi_named_captures_per_pattern() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "a=1 b:2".to_string(),
+        }];
+        let output = search_multi(
+            &[r"(?P<key>\w)=\d", r"(?P<other>\w):\d"],
+            &files,
+            &SearchOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[0].named_captures["key"], "a");
+        assert_eq!(output.results[1].named_captures["other"], "b");
     }
 
-    Ok(results)
-}
+    #[test]
+    fn test_search_multi_invalid_pattern() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello".to_string(),
+        }];
+        assert!(search_multi(&["ok", "["], &files, &SearchOptions::new()).is_err());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_single_pattern_index_is_zero() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello".to_string(),
+        }];
+        let results = search("Hello", &files, true).unwrap();
+        assert_eq!(results[0].pattern_index, 0);
+    }
 
     #[test]
-    fn test_basic_search_match() {
+    fn test_search_literals() {
+        let files = vec![FileInput {
+            path: "post.txt".to_string(),
+            content: "buy cheap pills\nfree money (now)".to_string(),
+        }];
+        let denylist = ["free money (now)", "cheap", "pills"];
+        let output = search_literals(&denylist, &files, &SearchOptions::new()).unwrap();
+        let found: Vec<(u32, u32, usize)> = output
+            .results
+            .iter()
+            .map(|m| (m.line, m.column, m.pattern_index))
+            .collect();
+        assert_eq!(found, vec![(1, 5, 1), (1, 11, 2), (2, 1, 0)]);
+        assert_eq!(output.results[2].match_text, "free money (now)");
+    }
+
+    #[test]
+    fn test_search_literals_prefers_longest() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "Hello, world!".to_string(),
+            content: "foobar".to_string(),
         }];
-        let results = search("world", &files, true).unwrap();
+        let output = search_literals(&["foo", "foobar"], &files, &SearchOptions::new()).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.results[0].pattern_index, 1);
+    }
+
+    #[test]
+    fn test_search_literals_case_insensitive_and_whole_word() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Cat scatter CAT".to_string(),
+        }];
+        let options = SearchOptions::new().case_sensitive(false).whole_word(true);
+        let output = search_literals(&["cat"], &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[0].column, 1);
+        assert_eq!(output.results[1].column, 13);
+    }
+
+    #[test]
+    fn test_search_literals_whole_word_finds_literal_inside_rejected_one() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "afoo bar".to_string(),
+        }];
+        let options = SearchOptions::new().whole_word(true);
+        // "foo bar" は単語境界で弾かれるが、その中の "bar" は拾う
+        let output = search_literals(&["foo bar", "bar"], &files, &options).unwrap();
+        let found: Vec<(u32, usize)> = output
+            .results
+            .iter()
+            .map(|m| (m.column, m.pattern_index))
+            .collect();
+        assert_eq!(found, vec![(6, 1)]);
+
+        let expected = search_multi(&["foo bar", "bar"], &files, &options.clone().literal(true))
+            .unwrap()
+            .results;
+        assert_eq!(output.results, expected);
+    }
+
+    #[test]
+    fn test_exclude_pattern() {
+        let files = vec![FileInput {
+            path: "main.rs".to_string(),
+            content: "a.unwrap();\nb.unwrap(); // allow-unwrap\nc.unwrap();".to_string(),
+        }];
+        let options = SearchOptions::new()
+            .literal(true)
+            .exclude_pattern("// allow-unwrap");
+        let results = search_with_options("unwrap(", &files, &options)
+            .unwrap()
+            .results;
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_exclude_pattern_applies_to_other_apis() {
+        let files = vec![
+            FileInput {
+                path: "a.rs".to_string(),
+                content: "x.unwrap(); // allow-unwrap".to_string(),
+            },
+            FileInput {
+                path: "b.rs".to_string(),
+                content: "y.unwrap();\nz.unwrap(); // allow-unwrap".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().exclude_pattern("allow-unwrap");
+        assert_eq!(
+            files_with_matches(r"unwrap\(", &files, &options).unwrap(),
+            vec!["b.rs".to_string()]
+        );
+        assert_eq!(
+            count(r"unwrap\(", &files, &options).unwrap(),
+            vec![("a.rs".to_string(), 0), ("b.rs".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_exclude_pattern() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "Hello".to_string(),
+        }];
+        let options = SearchOptions::new().exclude_pattern("[");
+        let result = search_with_options("Hello", &files, &options);
+        let error = result.err().unwrap();
+        assert!(matches!(
+            &error,
+            SearchError::InvalidExcludePattern { pattern, .. } if pattern == "["
+        ));
+        assert!(error.to_string().contains("exclude"));
+        #[cfg(feature = "std")]
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn test_line_range() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nfoo\nfoo\nfoo\nfoo".to_string(),
+        }];
+        let options = SearchOptions::new().line_range(2..=4);
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        // 行番号はファイル先頭からの番号のまま
+        assert_eq!(lines, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_line_range_applies_to_count_and_invert() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nbar\nfoo\nbar".to_string(),
+        }];
+        let options = SearchOptions::new().line_range(1..=2);
+        assert_eq!(
+            count("foo", &files, &options).unwrap(),
+            vec![("test.txt".to_string(), 1)]
+        );
+        let options = options.invert_match(true);
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 2);
+    }
+
+    #[test]
+    fn test_line_range_beyond_file() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "foo".to_string(),
+        }];
+        let options = SearchOptions::new().line_range(10..=20);
+        assert!(!exists("foo", &files, &options).unwrap());
+    }
+
+    #[test]
+    fn test_paragraph_records() {
+        let files = vec![FileInput {
+            path: "CHANGELOG.md".to_string(),
+            content: "## 1.0\n- add foo\n\n\n## 1.1\n- fix bar\n- add baz\n".to_string(),
+        }];
+        let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+        let results = search_with_options("fix", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        // 行番号は段落の開始行、列番号は段落先頭からの位置
+        assert_eq!(results[0].line, 5);
+        assert_eq!(results[0].column, 10);
+        assert_eq!(&*results[0].line_text, "## 1.1\n- fix bar\n- add baz");
+        assert_eq!(results[0].byte_offset, 28);
+    }
+
+    #[test]
+    fn test_paragraph_records_match_across_lines() {
+        let files = vec![FileInput {
+            path: "app.log".to_string(),
+            content: "ERROR failed\n  at main\n\nINFO ok\n  at main".to_string(),
+        }];
+        let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+        // 1つのレコード内で行をまたいでマッチする
+        let results = search_with_options(r"ERROR[\s\S]*at main", &files, &options)
+            .unwrap()
+            .results;
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].path, "test.txt");
         assert_eq!(results[0].line, 1);
-        assert_eq!(results[0].column, 8);
-        assert_eq!(results[0].line_text, "Hello, world!");
+        assert_eq!(
+            count("at main", &files, &options).unwrap(),
+            vec![("app.log".to_string(), 2)]
+        );
     }
 
     #[test]
-    fn test_search_no_match() {
+    fn test_custom_record_separator() {
+        let files = vec![FileInput {
+            path: "records.txt".to_string(),
+            content: "id: 1\nok\n---\nid: 2\nerror\n---\n".to_string(),
+        }];
+        let options =
+            SearchOptions::new().record_separator(RecordSeparator::Custom("---\n".to_string()));
+        let results = search_with_options("error", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 4);
+        assert_eq!(&*results[0].line_text, "id: 2\nerror\n");
+        // 反転した場合は区切られたレコード単位で返る
+        let options = options.invert_match(true);
+        let results = search_with_options("error", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 1);
+    }
+
+    #[test]
+    fn test_crlf_is_stripped_from_line_text() {
+        let files = vec![FileInput {
+            path: "win.txt".to_string(),
+            content: "foo\r\nbar foo\r\n".to_string(),
+        }];
+        let results = search_with_options("foo$", &files, &SearchOptions::new())
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(&*results[1].line_text, "bar foo");
+        assert_eq!(results[1].end_column, 8);
+        assert_eq!(results[1].byte_offset, 9);
+    }
+
+    #[test]
+    fn test_line_terminators() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "Hello, world!".to_string(),
+            content: "foo\rbar\r\nfoo".to_string(),
         }];
-        let results = search("foo", &files, true).unwrap();
-        assert_eq!(results.len(), 0);
+        // CR 区切りでは `\r\n` の `\n` が次の行の先頭に残る
+        let options = SearchOptions::new().line_terminator(LineTerminator::Cr);
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(&*results[1].line_text, "\nfoo");
+
+        // LF 区切りでは `\r` が行のテキストに残る
+        let options = SearchOptions::new().line_terminator(LineTerminator::Lf);
+        let results = search_with_options("bar", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(&*results[0].line_text, "foo\rbar\r");
+
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "a;b;foo".to_string(),
+        }];
+        let options = SearchOptions::new().line_terminator(LineTerminator::Byte(b';'));
+        let results = search_with_options("foo", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].line, 3);
     }
 
     #[test]
-    fn test_case_insensitive_search() {
+    fn test_non_ascii_line_terminator() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "Hello, WORLD!".to_string(),
+            content: "foo".to_string(),
         }];
-        let results = search("world", &files, false).unwrap();
+        let options = SearchOptions::new().line_terminator(LineTerminator::Byte(0xff));
+        let result = search_with_options("foo", &files, &options);
+        let error = result.err().unwrap();
+        assert!(matches!(error, SearchError::InvalidLineTerminator(0xff)));
+        assert!(error.to_string().contains("line terminator"));
+    }
+
+    #[test]
+    fn test_regex_flags() {
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "key = value\ncafé 42".to_string(),
+        }];
+        // 空白とコメントを無視する拡張パターン
+        let flags = RegexFlags::new().ignore_whitespace(true);
+        let options = SearchOptions::new().regex_flags(flags);
+        let pattern = r"(?P<key>\w+) \s* = \s* (?P<value>\w+)  # 代入";
+        let results = search_with_options(pattern, &files, &options)
+            .unwrap()
+            .results;
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].line_text, "Hello, WORLD!");
+        assert_eq!(results[0].named_captures["value"], "value");
+
+        // Unicode を無効にすると `\w` は ASCII だけにマッチする（regex-lite では常に ASCII だけ）
+        let flags = RegexFlags::new().unicode(false);
+        let options = SearchOptions::new().regex_flags(flags);
+        #[cfg(feature = "regex")]
+        assert!(exists(r"caf\w", &files, &SearchOptions::new()).unwrap());
+        assert!(!exists(r"caf\w", &files, &options).unwrap());
     }
 
     #[test]
-    fn test_case_sensitive_search() {
+    fn test_multi_line_flag_with_paragraphs() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "Hello, WORLD!".to_string(),
+            content: "title\nbody".to_string(),
         }];
-        let results = search("world", &files, true).unwrap();
-        assert_eq!(results.len(), 0);
+        let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+        assert!(!exists("^body", &files, &options).unwrap());
+        let options = options.regex_flags(RegexFlags::new().multi_line(true));
+        assert!(exists("^body", &files, &options).unwrap());
     }
 
+    // regex-lite は `\w` が ASCII だけで、パターンの大きさの数え方も異なる
+    #[cfg(feature = "regex")]
     #[test]
-    fn test_multiline_file() {
+    fn test_size_limit() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "Line 1\nLine 2\nLine 3".to_string(),
+            content: "Hello".to_string(),
         }];
-        let results = search("Line", &files, true).unwrap();
-        assert_eq!(results.len(), 3);
-        assert_eq!(results[0].line, 1);
-        assert_eq!(results[1].line, 2);
-        assert_eq!(results[2].line, 3);
+        // デフォルトの上限で巨大なパターンを拒否する
+        let result = search_with_options(r"\w{1000}", &files, &SearchOptions::new());
+        assert!(result.err().unwrap().to_string().contains("size limit"));
+
+        // 除外パターンにも同じ上限を適用する
+        let options = SearchOptions::new().size_limit(1000);
+        assert!(exists(r"\w+", &files, &options).is_err());
+        assert!(exists("Hello", &files, &options).unwrap());
+        assert!(count("Hello", &files, &options.exclude_pattern(r"\w+")).is_err());
     }
 
+    // `std` フィーチャーが無効な場合は時計を読めず、打ち切らない
+    #[cfg(feature = "std")]
     #[test]
-    fn test_multiple_files() {
+    fn test_timeout() {
         let files = vec![
             FileInput {
-                path: "file1.txt".to_string(),
-                content: "Hello from file1".to_string(),
+                path: "a.txt".to_string(),
+                content: "foo\nfoo".to_string(),
             },
             FileInput {
-                path: "file2.txt".to_string(),
-                content: "Hello from file2".to_string(),
+                path: "b.txt".to_string(),
+                content: "foo".to_string(),
             },
         ];
-        let results = search("Hello", &files, true).unwrap();
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0].path, "file1.txt");
-        assert_eq!(results[1].path, "file2.txt");
+        let output =
+            search_with_options("foo", &files, &SearchOptions::new().timeout_ms(0)).unwrap();
+        assert!(output.timed_out);
+        assert!(output.results.is_empty());
+
+        let output =
+            search_with_options("foo", &files, &SearchOptions::new().timeout_ms(60_000)).unwrap();
+        assert!(!output.timed_out);
+        assert_eq!(output.results.len(), 3);
     }
 
     #[test]
-    fn test_multiple_matches_same_line() {
+    fn test_cancel_token() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\n".repeat(1000),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "foo".to_string(),
+            },
+        ];
+        let token = CancelToken::new();
+        let options = SearchOptions::new().cancel_token(token.clone());
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert!(!output.cancelled);
+        assert_eq!(output.results.len(), 1001);
+
+        // 検索の途中で打ち切ると、それまでに見つかった結果が返る
+        let mut found = 0;
+        let outcome = search_sink("foo", &files, &options, &mut |_: &FileInput, _: &Match| {
+            found += 1;
+            if found == 10 {
+                token.cancel();
+            }
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(outcome, SinkOutcome::Cancelled);
+        assert_eq!(found, DEADLINE_CHECK_INTERVAL);
+
+        let output = search_with_options("foo", &files, &options).unwrap();
+        assert!(output.cancelled);
+        assert!(output.results.is_empty());
+        assert_eq!(output.next_offset, Some(0));
+        assert!(matches!(
+            replace("foo", &files, "bar", &options),
+            Err(SearchError::Cancelled { .. })
+        ));
+    }
+
+    #[test]
+    fn test_anchor() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "foo bar foo baz".to_string(),
+            content: "a.b\nxa.b\na.bx".to_string(),
         }];
-        let results = search("foo", &files, true).unwrap();
-        // re.find_iter() により、すべてのマッチが返される
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0].column, 1);
-        assert_eq!(results[1].column, 9);
+        let options = SearchOptions::new().literal(true).anchor(Anchor::LineStart);
+        let results = search_with_options("a.b", &files, &options)
+            .unwrap()
+            .results;
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+
+        let options = options.anchor(Anchor::WholeLine);
+        let results = search_with_options("a.b", &files, &options)
+            .unwrap()
+            .results;
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1]);
     }
 
     #[test]
-    fn test_regex_pattern() {
+    fn test_anchor_with_alternation_and_literals() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "abc123 def456".to_string(),
+            content: "foo\nbar baz\nbaz".to_string(),
         }];
-        let results = search(r"\d+", &files, true).unwrap();
-        // re.find_iter() により、すべてのマッチが返される
-        // "abc123 def456" では "123" と "456" の2つにマッチ
-        // "123" は位置3 (0ベース) = 列4 (1ベース)
-        // "456" は位置10 (0ベース) = 列11 (1ベース)
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0].column, 4); // "123" の開始位置
-        assert_eq!(results[1].column, 11); // "456" の開始位置
-        assert_eq!(results[0].line_text, "abc123 def456");
-        assert_eq!(results[1].line_text, "abc123 def456");
+        // 選択を含むパターンでも全体が固定される
+        let options = SearchOptions::new().anchor(Anchor::WholeLine);
+        assert_eq!(
+            count("foo|baz", &files, &options).unwrap(),
+            vec![("test.txt".to_string(), 2)]
+        );
+        let results = search_literals(&["bar", "baz"], &files, &options)
+            .unwrap()
+            .results;
+        let lines: Vec<u32> = results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![3]);
     }
 
     #[test]
-    fn test_invalid_regex_pattern() {
+    fn test_search_terms() {
+        let files = vec![
+            FileInput {
+                path: "a.md".to_string(),
+                content: "color and colour".to_string(),
+            },
+            FileInput {
+                path: "b.md".to_string(),
+                content: "Colour".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().case_sensitive(false).whole_word(true);
+        let matches = search_terms(&["colour", "color"], &files, &options).unwrap();
+        let found: Vec<(usize, &str, &str)> = matches
+            .iter()
+            .map(|m| (m.term_index, m.term.as_str(), &*m.result.path))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "color", "a.md"),
+                (0, "colour", "a.md"),
+                (0, "colour", "b.md")
+            ]
+        );
+        // 語はリストに渡した表記のまま、マッチした文字列は本文の表記になる
+        assert_eq!(matches[2].result.match_text, "Colour");
+    }
+
+    #[test]
+    fn test_approximate_match() {
         let files = vec![FileInput {
-            path: "test.txt".to_string(),
-            content: "Hello, world!".to_string(),
+            path: "reviews.txt".to_string(),
+            content: "I love my new Iphnoe\nThe iPhone case\nphone".to_string(),
         }];
-        let result = search("[", &files, true);
-        assert!(result.is_err());
+        let options = SearchOptions::new()
+            .max_edit_distance(2)
+            .case_sensitive(false);
+        let results = search_with_options("iphone", &files, &options)
+            .unwrap()
+            .results;
+        let found: Vec<(u32, &str)> = results
+            .iter()
+            .map(|m| (m.line, m.match_text.as_str()))
+            .collect();
+        // 入れ替わり（置換2回）と1文字の欠落の両方が見つかる
+        assert_eq!(found, vec![(1, "Iphnoe"), (2, "iPhone"), (3, "phone")]);
+
+        // 大文字小文字を区別すると `phone` は欠落と置換で距離2になる
+        let options = SearchOptions::new().max_edit_distance(1);
+        assert_eq!(
+            count("iPhone", &files, &options).unwrap(),
+            vec![("reviews.txt".to_string(), 1)]
+        );
     }
 
     #[test]
-    fn test_empty_file() {
+    fn test_approximate_match_whole_word_and_errors() {
         let files = vec![FileInput {
-            path: "empty.txt".to_string(),
-            content: "".to_string(),
+            path: "test.txt".to_string(),
+            content: "colr colours".to_string(),
         }];
-        let results = search("test", &files, true).unwrap();
-        assert_eq!(results.len(), 0);
+        let options = SearchOptions::new().max_edit_distance(1).whole_word(true);
+        let results = search_with_options("color", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "colr");
+
+        let options = SearchOptions::new().max_edit_distance(3);
+        assert!(search_with_options("abc", &files, &options).is_err());
     }
 
     #[test]
-    fn test_empty_pattern() {
+    fn test_column_units() {
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "Hello, world!".to_string(),
+            content: "日本🇯🇵e\u{301} target".to_string(),
         }];
-        let results = search("", &files, true).unwrap();
-        // 空のパターンはすべての位置（文字の間）にマッチする
-        // "Hello, world!" は13文字なので、14個の位置がある
-        assert_eq!(results.len(), 14);
+        let columns = |unit: ColumnUnit| {
+            let options = SearchOptions::new().column_unit(unit);
+            let results = search_with_options("(?P<x>target)", &files, &options)
+                .unwrap()
+                .results;
+            (
+                results[0].column,
+                results[0].end_column,
+                results[0].captures[0].column,
+            )
+        };
+        assert_eq!(columns(ColumnUnit::Bytes), (19, 25, 19));
+        assert_eq!(columns(ColumnUnit::Chars), (8, 14, 8));
+        // 国旗と結合文字付きの `é` はそれぞれ1文字として数える
+        #[cfg(feature = "graphemes")]
+        assert_eq!(columns(ColumnUnit::Graphemes), (6, 12, 6));
+        // 国旗の各文字はサロゲートペアで2つずつ数える
+        assert_eq!(columns(ColumnUnit::Utf16), (10, 16, 10));
     }
 
+    #[cfg(feature = "display-width")]
     #[test]
-    fn test_column_position() {
+    fn test_display_width_columns() {
+        // 全角文字と国旗は幅2、結合文字は幅0
         let files = vec![FileInput {
             path: "test.txt".to_string(),
-            content: "  Hello".to_string(),
+            content: "日本🇯🇵e\u{301} target".to_string(),
         }];
-        let results = search("Hello", &files, true).unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].column, 3);
+        let options = SearchOptions::new().column_unit(ColumnUnit::DisplayWidth { tab_width: 4 });
+        let results = search_with_options("target", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!((results[0].column, results[0].end_column), (9, 15));
+
+        // タブは次のタブ位置まで進める
+        let files = vec![FileInput {
+            path: "test.txt".to_string(),
+            content: "\tx\t日\ttarget".to_string(),
+        }];
+        let options = SearchOptions::new().column_unit(ColumnUnit::DisplayWidth { tab_width: 4 });
+        let results = search_with_options("target", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!((results[0].column, results[0].end_column), (13, 19));
     }
 }
@@ -0,0 +1,286 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::sarif::path_to_uri;
+use crate::{FileInput, MatchResult, floor_char_boundary};
+
+/// LSP の `Position`（0ベースの行番号と、行頭からの UTF-16 のコードユニット数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LspPosition {
+    /// 行番号（0ベース）
+    pub line: u32,
+    /// 行頭からの位置（0ベース、UTF-16 のコードユニット単位）
+    pub character: u32,
+}
+
+/// LSP の `Range`（終了位置を含まない）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LspRange {
+    /// 開始位置
+    pub start: LspPosition,
+    /// 終了位置（この位置の文字は含まない）
+    pub end: LspPosition,
+}
+
+/// LSP の `Location`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspLocation {
+    /// ファイルの URI
+    pub uri: String,
+    /// ファイル内の範囲
+    pub range: LspRange,
+}
+
+/// ファイルの内容から LSP の位置を求めるための行の開始位置
+pub(crate) struct LineIndex<'a> {
+    /// ファイルの内容
+    content: &'a str,
+    /// 各行の開始位置（0ベースのバイトオフセット）
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// LSP の規則（`\n`、`\r\n`、`\r` のいずれも改行）で行の開始位置を求める
+    pub(crate) fn new(content: &'a str) -> Self {
+        let bytes = content.as_bytes();
+        let mut line_starts = vec![0];
+        for (idx, &byte) in bytes.iter().enumerate() {
+            let ends_line = byte == b'\n' || (byte == b'\r' && bytes.get(idx + 1) != Some(&b'\n'));
+            if ends_line {
+                line_starts.push(idx + 1);
+            }
+        }
+        Self {
+            content,
+            line_starts,
+        }
+    }
+
+    /// 行の数を返す
+    pub(crate) fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// 行のテキスト（改行を除く）を返す
+    pub(crate) fn line_text(&self, line: usize) -> &'a str {
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.content.len());
+        self.content[self.line_starts[line]..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// バイトオフセットを LSP の位置に変換する
+    pub(crate) fn position(&self, byte_offset: usize) -> LspPosition {
+        let byte_offset = floor_char_boundary(self.content, byte_offset);
+        let line = self
+            .line_starts
+            .partition_point(|&start| start <= byte_offset)
+            - 1;
+        let character = self.content[self.line_starts[line]..byte_offset]
+            .encode_utf16()
+            .count();
+        LspPosition {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+}
+
+/// 検索結果のマッチ範囲を LSP の `Range` に変換する
+///
+/// 位置は `content`（検索したファイルの内容）と `byte_offset` から求めるため、検索時の
+/// `column_unit` や `max_line_length`・`preview_width` による切り詰めに関わらず、
+/// LSP の規則どおりの 0ベースの行番号と UTF-16 の位置になる。
+/// 複数行にまたがるマッチ（`record_separator` を使った検索など）では終了位置が後の行になる。
+///
+/// # Arguments
+///
+/// * `result` - 検索結果
+/// * `content` - 検索結果のファイルの内容
+///
+/// # Returns
+///
+/// マッチ範囲
+///
+/// ```
+/// use simple_find_core::{FileInput, LspPosition, SearchOptions, lsp_range, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "first\n😀 target".to_string(),
+/// }];
+/// let output = search_with_options("target", &files, &SearchOptions::new()).unwrap();
+/// let range = lsp_range(&output.results[0], &files[0].content);
+/// assert_eq!(range.start, LspPosition { line: 1, character: 3 });
+/// assert_eq!(range.end, LspPosition { line: 1, character: 9 });
+/// ```
+pub fn lsp_range(result: &MatchResult, content: &str) -> LspRange {
+    range_in(&LineIndex::new(content), result)
+}
+
+/// 行の開始位置を使ってマッチ範囲を求める
+fn range_in(index: &LineIndex, result: &MatchResult) -> LspRange {
+    LspRange {
+        start: index.position(result.byte_offset),
+        end: index.position(result.byte_offset + result.match_text.len()),
+    }
+}
+
+/// 検索結果を LSP の `Location` のリストに変換する
+///
+/// 各結果のファイルを `files` からパスで探し、`base_uri`（ワークスペースのルートの URI、
+/// 例: `file:///home/user/project`）にパーセントエンコードしたパスをつなげて URI とする。
+/// 間に `/` がなければ補うため、絶対パスの場合は `base_uri` に `file://` を渡す。
+/// ファイルごとの行の開始位置は1回だけ求める。`files` に見つからないファイルの結果は含めない。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+/// * `files` - 検索したファイルのリスト
+/// * `base_uri` - パスの前に付ける URI
+///
+/// # Returns
+///
+/// 検索結果の順の `Location` のリスト
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, lsp_locations, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "src/my lib.rs".to_string(),
+///     content: "fn main() {}".to_string(),
+/// }];
+/// let output = search_with_options("main", &files, &SearchOptions::new()).unwrap();
+/// let locations = lsp_locations(&output.results, &files, "file:///work/");
+/// assert_eq!(locations[0].uri, "file:///work/src/my%20lib.rs");
+/// assert_eq!(locations[0].range.start.character, 3);
+/// ```
+pub fn lsp_locations(
+    results: &[MatchResult],
+    files: &[FileInput],
+    base_uri: &str,
+) -> Vec<LspLocation> {
+    let contents: BTreeMap<&str, &str> = files
+        .iter()
+        .map(|f| (f.path.as_str(), f.content.as_str()))
+        .collect();
+    let mut indexes: BTreeMap<&str, LineIndex> = BTreeMap::new();
+
+    results
+        .iter()
+        .filter_map(|result| {
+            let content = contents.get(&*result.path)?;
+            let index = indexes
+                .entry(&*result.path)
+                .or_insert_with(|| LineIndex::new(content));
+            let path = path_to_uri(&result.path);
+            let separator = if base_uri.ends_with('/') || path.starts_with('/') {
+                ""
+            } else {
+                "/"
+            };
+            Some(LspLocation {
+                uri: format!("{}{}{}", base_uri, separator, path),
+                range: range_in(index, result),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{RecordSeparator, SearchOptions, search_with_options};
+
+    #[test]
+    fn test_line_index_line_endings() {
+        let index = LineIndex::new("a\r\nb\rc\nd");
+        assert_eq!(index.line_starts, vec![0, 3, 5, 7]);
+        assert_eq!(
+            index.position(6),
+            LspPosition {
+                line: 2,
+                character: 1
+            }
+        );
+        // 末尾の位置も変換できる
+        assert_eq!(
+            index.position(8),
+            LspPosition {
+                line: 3,
+                character: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_lsp_range_ignores_column_unit_and_truncation() {
+        let content = "x\n日本語😀 needle";
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: content.to_string(),
+        }];
+        let options = SearchOptions::new().preview_width(6);
+        let output = search_with_options("needle", &files, &options).unwrap();
+        let range = lsp_range(&output.results[0], content);
+        assert_eq!(
+            range.start,
+            LspPosition {
+                line: 1,
+                character: 6
+            }
+        );
+        assert_eq!(
+            range.end,
+            LspPosition {
+                line: 1,
+                character: 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_lsp_range_multiline_match() {
+        let content = "one\ntwo\n\nthree";
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: content.to_string(),
+        }];
+        let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+        let output = search_with_options(r"ne\ntw", &files, &options).unwrap();
+        let range = lsp_range(&output.results[0], content);
+        assert_eq!(
+            range.start,
+            LspPosition {
+                line: 0,
+                character: 1
+            }
+        );
+        assert_eq!(
+            range.end,
+            LspPosition {
+                line: 1,
+                character: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_lsp_locations_skips_unknown_files() {
+        let files = vec![FileInput {
+            path: "/abs/a.txt".to_string(),
+            content: "hit".to_string(),
+        }];
+        let output = search_with_options("hit", &files, &SearchOptions::new()).unwrap();
+        let locations = lsp_locations(&output.results, &files, "file://");
+        assert_eq!(locations[0].uri, "file:///abs/a.txt");
+        assert!(lsp_locations(&output.results, &[], "file://").is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use aho_corasick::AhoCorasick;
+
+use crate::engine::Regex;
+use crate::{
+    CompiledSearch, FileInput, PatternSet, SearchError, SearchOptions, SearchOutput, SpanMatch,
+    find_with_regex, search_compiled,
+};
+
+/// 1行の中でマッチを探すバックエンド
+///
+/// 検索のループは行（レコード）ごとにこのトレイトのメソッドを呼ぶだけなので、実装を
+/// 差し替えれば行の分割・上限・コンテキスト・出力形式などをそのまま使って別のマッチングを
+/// 組み込める。`regex::Regex` と `aho_corasick::AhoCorasick` には実装済みで、
+/// `search_with_matcher` や `Searcher::with_matcher` に渡して使う。
+///
+/// ```
+/// use simple_find_core::{
+///     FileInput, Matcher, SearchOptions, SpanMatch, search_with_matcher,
+/// };
+///
+/// /// 数字の並びを探すマッチャー
+/// struct Digits;
+///
+/// impl Matcher for Digits {
+///     fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+///         let mut found = Vec::new();
+///         let mut start = None;
+///         for (idx, c) in line.char_indices().chain([(line.len(), ' ')]) {
+///             match (c.is_ascii_digit(), start) {
+///                 (true, None) => start = Some(idx),
+///                 (false, Some(s)) => {
+///                     found.push(SpanMatch::new(0, s..idx));
+///                     start = None;
+///                 }
+///                 _ => {}
+///             }
+///         }
+///         found
+///     }
+/// }
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "port 8080\nhost localhost".to_string(),
+/// }];
+/// let output = search_with_matcher(Digits, &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(output.results[0].match_text, "8080");
+/// assert_eq!(output.results[0].column, 6);
+/// ```
+pub trait Matcher: Send + Sync {
+    /// 行内のすべてのマッチを開始位置の順に返す
+    ///
+    /// 範囲は行頭からのバイト位置で、文字の境目になければならない。
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch>;
+
+    /// 行にマッチがあるかどうかを返す
+    ///
+    /// `invert_match` などマッチの位置が要らない場合に使う。デフォルトでは
+    /// `find_in_line` の結果が空かどうかで判定する。
+    fn is_match(&self, line: &str) -> bool {
+        !self.find_in_line(line).is_empty()
+    }
+
+    /// パターン内のキャプチャグループの名前を返す（名前がなければ `None`）
+    fn capture_name(&self, _pattern_index: usize, _group_index: usize) -> Option<&str> {
+        None
+    }
+}
+
+impl Matcher for Regex {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found = Vec::new();
+        find_with_regex(self, 0, line, &mut found);
+        found
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        Regex::is_match(self, line)
+    }
+
+    fn capture_name(&self, _pattern_index: usize, group_index: usize) -> Option<&str> {
+        self.capture_names().nth(group_index).flatten()
+    }
+}
+
+/// 見つかったリテラルの位置をパターンの位置として返す
+impl Matcher for AhoCorasick {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        self.find_iter(line)
+            .map(|m| SpanMatch::new(m.pattern().as_usize(), m.range()))
+            .collect()
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        AhoCorasick::is_match(self, line)
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for Arc<M> {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        (**self).find_in_line(line)
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        (**self).is_match(line)
+    }
+
+    fn capture_name(&self, pattern_index: usize, group_index: usize) -> Option<&str> {
+        (**self).capture_name(pattern_index, group_index)
+    }
+}
+
+/// 指定したマッチャーでファイルを検索する
+///
+/// パターンをコンパイルする代わりに `matcher` で行内のマッチを探す。`case_sensitive`・
+/// `whole_word`・`anchor` などパターンのコンパイルに使うオプションは無視し、それ以外の
+/// オプション（上限・コンテキスト・除外パターン・`invert_match` など）は
+/// `search_with_options` と同じように働く。正規化を指定した場合は、正規化した行を
+/// `matcher` に渡す。
+///
+/// # Arguments
+///
+/// * `matcher` - 行内のマッチを探すマッチャー
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、または除外パターンが無効な場合のエラー
+pub fn search_with_matcher(
+    matcher: impl Matcher + 'static,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let compiled = CompiledSearch::new(PatternSet::Custom(Arc::new(matcher)), options)?;
+
+    Ok(search_compiled(&compiled, files, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::search_with_options;
+    use aho_corasick::{AhoCorasick, MatchKind};
+
+    #[test]
+    fn test_builtin_matchers_match_pattern_search() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "key=1 id=22\nnothing\nid=3".to_string(),
+        }];
+        let options = SearchOptions::new().after_context(1);
+
+        let regex = Regex::new(r"(?<name>\w+)=(\d+)").unwrap();
+        let expected = search_with_options(regex.as_str(), &files, &options).unwrap();
+        let output = search_with_matcher(regex, &files, &options).unwrap();
+        assert_eq!(output.results, expected.results);
+        assert_eq!(output.results[0].named_captures["name"], "key");
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(["id", "key"])
+            .unwrap();
+        let output = search_with_matcher(automaton, &files, &options).unwrap();
+        let found: Vec<(u32, usize)> = output
+            .results
+            .iter()
+            .map(|r| (r.line, r.pattern_index))
+            .collect();
+        assert_eq!(found, vec![(1, 1), (1, 0), (3, 0)]);
+
+        let options = SearchOptions::new().invert_match(true);
+        let automaton = AhoCorasick::new(["id"]).unwrap();
+        let output = search_with_matcher(Arc::new(automaton), &files, &options).unwrap();
+        assert_eq!(&*output.results[0].line_text, "nothing");
+    }
+}
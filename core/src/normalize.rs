@@ -0,0 +1,401 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "normalization")]
+use unicode_normalization::UnicodeNormalization;
+#[cfg(feature = "normalization")]
+use unicode_normalization::char::is_combining_mark;
+#[cfg(feature = "normalization")]
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::engine;
+use crate::records::Record;
+use crate::{CaptureGroup, SearchOptions, SpanMatch};
+
+/// マッチングの前にパターンと内容に適用する Unicode 正規化の形式
+///
+/// `Nfc` と `Nfkc` は `normalization` フィーチャーで使える。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Normalization {
+    /// 正規化しない
+    #[default]
+    None,
+    /// 正規化形式 C（合成済みの文字と結合文字の並びを同じものとして扱う）
+    #[cfg(feature = "normalization")]
+    Nfc,
+    /// 正規化形式 KC（NFC に加えて互換文字も同じものとして扱う）
+    #[cfg(feature = "normalization")]
+    Nfkc,
+}
+
+/// 正規化したテキストと、元のテキストでの位置の対応
+pub(crate) struct Normalized {
+    /// 正規化したテキスト
+    pub(crate) text: String,
+    /// 正規化後の各バイトの元になった文字列の、元のテキストでの開始位置
+    starts: Vec<usize>,
+    /// 正規化後の各バイトの元になった文字列の、元のテキストでの終了位置
+    ends: Vec<usize>,
+    /// 元のテキストの長さ
+    original_len: usize,
+}
+
+impl Normalized {
+    /// 正規化後のバイト範囲を元のテキストでのバイト範囲に変換する
+    ///
+    /// 1文字が複数の文字に展開された場合でも、元の文字の途中で切れることはない。
+    fn original_span(&self, span: Range<usize>) -> Range<usize> {
+        if span.is_empty() {
+            let start = self
+                .starts
+                .get(span.start)
+                .copied()
+                .unwrap_or(self.original_len);
+            start..start
+        } else {
+            self.starts[span.start]..self.ends[span.end - 1]
+        }
+    }
+
+    /// 正規化後のテキストで見つかったマッチを元のテキストでのマッチに変換する
+    pub(crate) fn map_match(&self, line_match: SpanMatch, original: &str) -> SpanMatch {
+        let captures = line_match
+            .captures
+            .into_iter()
+            .map(|group| {
+                let span =
+                    self.original_span(group.column as usize - 1..group.end_column as usize - 1);
+                CaptureGroup {
+                    index: group.index,
+                    text: original[span.clone()].to_string(),
+                    column: (span.start + 1) as u32,
+                    end_column: (span.end + 1) as u32,
+                }
+            })
+            .collect();
+
+        SpanMatch {
+            pattern_index: line_match.pattern_index,
+            span: self.original_span(line_match.span),
+            captures,
+        }
+    }
+}
+
+/// 正規化の設定が1つでも有効かどうかを返す
+pub(crate) fn is_enabled(options: &SearchOptions) -> bool {
+    #[cfg(feature = "normalization")]
+    if options.fold_diacritics || options.fold_width {
+        return true;
+    }
+    options.normalization != Normalization::None || options.kana_insensitive
+}
+
+/// テキストを正規化し、元のテキストとの位置の対応を記録する
+///
+/// 正規化は書記素クラスタ単位で行うため、結合文字の並びは1つのまとまりとして扱われる。
+pub(crate) fn normalize_text(text: &str, options: &SearchOptions) -> Normalized {
+    let mut normalized = String::with_capacity(text.len());
+    let mut starts = Vec::with_capacity(text.len());
+    let mut ends = Vec::with_capacity(text.len());
+
+    for (offset, grapheme) in units(text) {
+        let before = normalized.len();
+        normalize_unit(grapheme, options, &mut normalized);
+        let added = normalized.len() - before;
+        starts.extend(core::iter::repeat_n(offset, added));
+        ends.extend(core::iter::repeat_n(offset + grapheme.len(), added));
+    }
+
+    Normalized {
+        text: normalized,
+        starts,
+        ends,
+        original_len: text.len(),
+    }
+}
+
+/// テキストを正規化の単位（書記素クラスタ）に分ける
+#[cfg(feature = "normalization")]
+fn units(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.grapheme_indices(true)
+}
+
+/// テキストを正規化の単位に分ける
+///
+/// 書記素クラスタの表がないため1文字ずつ分ける。ひらがな・カタカナの同一視は
+/// 1文字ずつ変換するため、結果は変わらない。
+#[cfg(not(feature = "normalization"))]
+fn units(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.char_indices()
+        .map(|(offset, c)| (offset, &text[offset..offset + c.len_utf8()]))
+}
+
+/// パターンを内容と同じ規則で正規化する
+///
+/// 正規表現として扱う場合は、記号とエスケープシーケンスをそのまま残して
+/// それ以外の部分だけを正規化する。正規化によって記号になった文字
+/// （全角の `（` など）はエスケープし、文字そのものとしてマッチさせる。
+pub(crate) fn normalize_pattern(pattern: &str, options: &SearchOptions) -> String {
+    if !is_enabled(options) {
+        return pattern.to_string();
+    }
+    if options.literal {
+        return normalize_text(pattern, options).text;
+    }
+
+    let mut normalized = String::with_capacity(pattern.len());
+    let mut segment = String::new();
+    let mut chars = pattern.chars();
+    let flush = |segment: &mut String, normalized: &mut String| {
+        if !segment.is_empty() {
+            normalized.push_str(&engine::escape(&normalize_text(segment, options).text));
+            segment.clear();
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            flush(&mut segment, &mut normalized);
+            normalized.push(c);
+            normalized.extend(chars.next());
+        } else if c.is_ascii_punctuation() {
+            flush(&mut segment, &mut normalized);
+            normalized.push(c);
+        } else {
+            segment.push(c);
+        }
+    }
+    flush(&mut segment, &mut normalized);
+    normalized
+}
+
+/// 正規化が有効な場合、各レコードを正規化する（無効な場合は空のリストを返す）
+pub(crate) fn normalize_records(records: &[Record], options: &SearchOptions) -> Vec<Normalized> {
+    if !is_enabled(options) {
+        return Vec::new();
+    }
+    records
+        .iter()
+        .map(|record| normalize_text(record.text, options))
+        .collect()
+}
+
+/// マッチングに使うレコードのリストを返す
+///
+/// `normalized` が空でなければ、テキストを正規化したものに置き換える。
+pub(crate) fn match_targets<'a>(
+    records: &[Record<'a>],
+    normalized: &'a [Normalized],
+) -> Vec<Record<'a>> {
+    if normalized.is_empty() {
+        return records.to_vec();
+    }
+    records
+        .iter()
+        .zip(normalized)
+        .map(|(record, normalized)| Record {
+            text: &normalized.text,
+            ..*record
+        })
+        .collect()
+}
+
+/// 1つの書記素クラスタを正規化して `out` に追加する
+fn normalize_unit(unit: &str, options: &SearchOptions, out: &mut String) {
+    let mut unit: String = match options.normalization {
+        Normalization::None => unit.to_string(),
+        #[cfg(feature = "normalization")]
+        Normalization::Nfc => unit.nfc().collect(),
+        #[cfg(feature = "normalization")]
+        Normalization::Nfkc => unit.nfkc().collect(),
+    };
+    #[cfg(feature = "normalization")]
+    if options.fold_width {
+        unit = fold_width(&unit);
+    }
+    #[cfg(feature = "normalization")]
+    if options.fold_diacritics {
+        // 分解してから結合文字を取り除き、残った文字を合成し直す。
+        // 濁点・半濁点は別の文字を表すため残す（`が` を `か` にしない）
+        unit = unit
+            .nfd()
+            .filter(|&c| !is_combining_mark(c) || matches!(c, '\u{3099}' | '\u{309A}'))
+            .nfc()
+            .collect();
+    }
+    if options.kana_insensitive {
+        unit = unit.chars().map(katakana_to_hiragana).collect();
+    }
+    out.push_str(&unit);
+}
+
+/// 全角英数字・記号と半角カタカナを、それぞれ半角と全角の文字に変換する
+#[cfg(feature = "normalization")]
+fn fold_width(unit: &str) -> String {
+    let mut folded = String::with_capacity(unit.len());
+    for c in unit.chars() {
+        // 全角スペースと「半角・全角形」ブロックの文字だけを NFKC で変換する
+        if matches!(c, '\u{3000}' | '\u{FF01}'..='\u{FFEF}') {
+            folded.extend(c.to_string().nfkc());
+        } else {
+            folded.push(c);
+        }
+    }
+    if folded == unit {
+        return folded;
+    }
+    // 半角の濁点・半濁点は結合文字になるため、直前のカナと合成する（`ｶﾞ` → `ガ`）
+    folded.nfc().collect()
+}
+
+/// 全角カタカナを対応するひらがなに変換する（対応するひらがながない文字はそのまま）
+fn katakana_to_hiragana(c: char) -> char {
+    match c {
+        // ァ〜ヶ と ヽヾ はひらがなと同じ並びで 0x60 離れている
+        '\u{30A1}'..='\u{30F6}' | '\u{30FD}' | '\u{30FE}' => {
+            char::from_u32(c as u32 - 0x60).unwrap_or(c)
+        }
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{FileInput, search_with_options};
+
+    fn single_file(content: &str) -> Vec<FileInput> {
+        vec![FileInput {
+            path: "test.txt".to_string(),
+            content: content.to_string(),
+        }]
+    }
+
+    #[cfg(feature = "normalization")]
+    #[test]
+    fn test_nfc_matches_decomposed_content() {
+        // 内容は `e` + 結合アクセント、パターンは合成済みの `é`
+        let files = single_file("un cafe\u{301} noir");
+        let options = SearchOptions::new().normalization(Normalization::Nfc);
+        let results = search_with_options("café", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        // 列番号とマッチした文字列は元の内容に対するもの
+        assert_eq!(results[0].column, 4);
+        assert_eq!(results[0].end_column, 10);
+        assert_eq!(results[0].match_text, "cafe\u{301}");
+
+        assert!(
+            search_with_options("café", &files, &SearchOptions::new())
+                .unwrap()
+                .results
+                .is_empty()
+        );
+    }
+
+    #[cfg(feature = "normalization")]
+    #[test]
+    fn test_nfkc_keeps_regex_syntax() {
+        let files = single_file("ＡＢＣ(1) ABC");
+        let options = SearchOptions::new().normalization(Normalization::Nfkc);
+        // 全角の括弧は記号ではなく文字としてマッチする
+        let results = search_with_options(r"ABC（\d）", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "ＡＢＣ(1)");
+
+        let results = search_with_options("(?P<name>ABC)$", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].captures[0].column, 14);
+        assert_eq!(results[0].named_captures["name"], "ABC");
+    }
+
+    #[cfg(feature = "normalization")]
+    #[test]
+    fn test_fold_diacritics() {
+        let files = single_file("Crème brûlée at the Café\nnaïve cafe");
+        let options = SearchOptions::new()
+            .fold_diacritics(true)
+            .case_sensitive(false);
+        let results = search_with_options("cafe", &files, &options)
+            .unwrap()
+            .results;
+        let found: Vec<(u32, u32, &str)> = results
+            .iter()
+            .map(|m| (m.line, m.column, m.match_text.as_str()))
+            .collect();
+        assert_eq!(found, vec![(1, 24, "Café"), (2, 8, "cafe")]);
+
+        // パターン側のアクセントも取り除かれる
+        let results = search_with_options("brulée", &files, &options)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].match_text, "brûlée");
+        assert_eq!(results[0].end_column, 16);
+
+        // 濁点は発音区別符号として扱わない
+        let results = search_with_options("か", &single_file("かがみ"), &options)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_kana_insensitive() {
+        let files = single_file("リンゴとりんごとリンゴジュース");
+        let options = SearchOptions::new().kana_insensitive(true);
+        let results = search_with_options("りんご", &files, &options)
+            .unwrap()
+            .results;
+        let found: Vec<(u32, &str)> = results
+            .iter()
+            .map(|m| (m.column, m.match_text.as_str()))
+            .collect();
+        assert_eq!(found, vec![(1, "リンゴ"), (13, "りんご"), (25, "リンゴ")]);
+
+        // カタカナのパターンでもひらがなにマッチする
+        let results = search_with_options("リンゴ$", &single_file("ゆうがたのりんご"), &options)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].column, 16);
+    }
+
+    #[cfg(feature = "normalization")]
+    #[test]
+    fn test_fold_width() {
+        let options = SearchOptions::new().fold_width(true);
+        let results = search_with_options("ABC", &single_file("ＡＢＣ株式会社とABC"), &options)
+            .unwrap()
+            .results;
+        let found: Vec<(u32, &str)> = results
+            .iter()
+            .map(|m| (m.column, m.match_text.as_str()))
+            .collect();
+        assert_eq!(found, vec![(1, "ＡＢＣ"), (25, "ABC")]);
+
+        // 半角カタカナは濁点を含めて全角カタカナとして扱う
+        let results = search_with_options("ガス", &single_file("ｶﾞｽ代"), &options)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].match_text, "ｶﾞｽ");
+        assert_eq!(results[0].end_column, 10);
+
+        // ひらがな・カタカナの同一視と組み合わせられる
+        let options = options.kana_insensitive(true);
+        assert_eq!(
+            search_with_options("がす", &single_file("ｶﾞｽ代"), &options)
+                .unwrap()
+                .results
+                .len(),
+            1
+        );
+    }
+}
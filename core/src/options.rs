@@ -0,0 +1,861 @@
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+#[cfg(feature = "parallel")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Range, RangeInclusive};
+
+#[cfg(not(feature = "regex"))]
+use crate::engine::RegexBuilderExt as _;
+#[cfg(feature = "regex")]
+use crate::engine::RegexSetBuilder;
+use crate::engine::{EngineFeatures, RegexBuilder, RegexEngine};
+use crate::records::Record;
+use crate::{
+    CancelToken, ColumnUnit, FileInput, LineTerminator, Normalization, RecordSeparator, SpanMatch,
+    is_binary,
+};
+
+/// コンパイル済み正規表現の大きさの上限のデフォルト値（バイト）
+///
+/// 信頼できない入力から巨大なオートマトンが作られてメモリを使い切らないよう、
+/// `regex` クレートのデフォルト値より小さくしている。
+pub const DEFAULT_SIZE_LIMIT: usize = 2 * (1 << 20);
+
+/// 遅延 DFA のキャッシュの大きさの上限のデフォルト値（バイト）
+pub const DEFAULT_DFA_SIZE_LIMIT: usize = 2 * (1 << 20);
+
+/// 検索オプションを表す構造体
+///
+/// ビルダー形式で組み立て、`search_with_options` に渡す。
+/// オプションを追加しても既存の呼び出し側には影響しない。
+///
+/// ```
+/// use simple_find_core::SearchOptions;
+///
+/// let options = SearchOptions::new().case_sensitive(false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub(crate) case_sensitive: bool,
+    pub(crate) whole_word: bool,
+    pub(crate) literal: bool,
+    pub(crate) invert_match: bool,
+    pub(crate) max_count_per_file: Option<usize>,
+    pub(crate) max_results: Option<usize>,
+    pub(crate) max_result_bytes: Option<usize>,
+    pub(crate) intern_lines: bool,
+    pub(crate) offset: usize,
+    pub(crate) before_context: usize,
+    pub(crate) after_context: usize,
+    pub(crate) only_matching: bool,
+    pub(crate) exclude_pattern: Option<String>,
+    pub(crate) line_range: Option<RangeInclusive<u32>>,
+    pub(crate) record_separator: RecordSeparator,
+    pub(crate) line_terminator: LineTerminator,
+    pub(crate) regex_flags: RegexFlags,
+    pub(crate) regex_engine: RegexEngine,
+    pub(crate) size_limit: usize,
+    pub(crate) dfa_size_limit: usize,
+    pub(crate) timeout_ms: Option<u64>,
+    pub(crate) cancel_token: Option<CancelToken>,
+    pub(crate) threads: Option<usize>,
+    #[cfg(feature = "parallel")]
+    pub(crate) thread_pool: Option<Arc<rayon::ThreadPool>>,
+    pub(crate) anchor: Anchor,
+    pub(crate) max_edit_distance: Option<usize>,
+    pub(crate) normalization: Normalization,
+    #[cfg(feature = "normalization")]
+    pub(crate) fold_diacritics: bool,
+    pub(crate) kana_insensitive: bool,
+    #[cfg(feature = "normalization")]
+    pub(crate) fold_width: bool,
+    pub(crate) column_unit: ColumnUnit,
+    pub(crate) max_line_length: Option<usize>,
+    pub(crate) long_line_mode: LongLineMode,
+    pub(crate) preview_width: Option<usize>,
+    pub(crate) search_binary: bool,
+    pub(crate) sort: SortOrder,
+    pub(crate) relevance: bool,
+    pub(crate) dedupe: Dedupe,
+    pub(crate) report: bool,
+    pub(crate) profile: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: true,
+            whole_word: false,
+            literal: false,
+            invert_match: false,
+            max_count_per_file: None,
+            max_results: None,
+            max_result_bytes: None,
+            intern_lines: false,
+            offset: 0,
+            before_context: 0,
+            after_context: 0,
+            only_matching: false,
+            exclude_pattern: None,
+            line_range: None,
+            record_separator: RecordSeparator::Line,
+            line_terminator: LineTerminator::Auto,
+            regex_flags: RegexFlags::default(),
+            regex_engine: RegexEngine::default(),
+            size_limit: DEFAULT_SIZE_LIMIT,
+            dfa_size_limit: DEFAULT_DFA_SIZE_LIMIT,
+            timeout_ms: None,
+            cancel_token: None,
+            threads: None,
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+            anchor: Anchor::Unanchored,
+            max_edit_distance: None,
+            normalization: Normalization::None,
+            #[cfg(feature = "normalization")]
+            fold_diacritics: false,
+            kana_insensitive: false,
+            #[cfg(feature = "normalization")]
+            fold_width: false,
+            column_unit: ColumnUnit::Bytes,
+            max_line_length: None,
+            long_line_mode: LongLineMode::Truncate,
+            preview_width: None,
+            search_binary: false,
+            sort: SortOrder::Input,
+            relevance: false,
+            dedupe: Dedupe::None,
+            report: false,
+            profile: false,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// デフォルト値（大文字小文字を区別する）でオプションを作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 大文字小文字を区別するかどうかを設定する
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// 単語単位でのみマッチさせるかどうかを設定する
+    ///
+    /// 有効にすると `cat` は `concatenate` にはマッチしなくなる。
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// パターンを正規表現ではなく文字列そのものとして検索するかどうかを設定する
+    ///
+    /// 有効にすると `(` や `.` などの記号もそのままの文字としてマッチする。
+    pub fn literal(mut self, literal: bool) -> Self {
+        self.literal = literal;
+        self
+    }
+
+    /// パターンにマッチしない行を返すかどうかを設定する（`grep -v` 相当）
+    ///
+    /// 有効にした場合、結果の列番号は常に1になる。
+    pub fn invert_match(mut self, invert_match: bool) -> Self {
+        self.invert_match = invert_match;
+        self
+    }
+
+    /// 1ファイルあたりの最大マッチ数を設定する
+    ///
+    /// 上限を超えたファイルのパスは `SearchOutput::truncated_files` に記録される。
+    pub fn max_count_per_file(mut self, max_count: usize) -> Self {
+        self.max_count_per_file = Some(max_count);
+        self
+    }
+
+    /// 検索全体での最大結果数を設定する
+    ///
+    /// 上限に達すると検索を打ち切り、`SearchOutput::truncated` が `true` になる。
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// 集めた検索結果の大きさの上限（バイト、おおよその値）を設定する
+    ///
+    /// 結果ごとにマッチした文字列・行のテキスト・キャプチャグループ・前後の行の長さと構造体の
+    /// 大きさを足し合わせ、次の結果で上限を超える場合はその手前で検索を打ち切って
+    /// `SearchOutput::truncated` を `true` にする。空のパターンのように全行にマッチする
+    /// クエリで wasm のヒープが際限なく大きくならないようにするためのもの。
+    /// `offset` でページを送る場合に先へ進めなくならないよう、最初の結果だけは上限を
+    /// 超えていても返す。`MatchResult` を集める関数でのみ使われる。
+    pub fn max_result_bytes(mut self, bytes: usize) -> Self {
+        self.max_result_bytes = Some(bytes);
+        self
+    }
+
+    /// 同じテキストの行の結果どうしで `MatchResult::line_text` を共有するかどうかを設定する
+    ///
+    /// ログのように同じ行が何度も現れるファイルでは、結果ごとに行のテキストを複製せずに
+    /// 1つの文字列を共有するため、結果が使うメモリが減る。行ごとにテキストを探す手間が
+    /// かかるため、デフォルトは `false`。`parallel` フィーチャーで並列に検索する場合は、
+    /// 並列に検索する単位（ファイルや、大きなファイルを区切った部分）ごとに共有する。
+    pub fn intern_lines(mut self, intern_lines: bool) -> Self {
+        self.intern_lines = intern_lines;
+        self
+    }
+
+    /// 先頭から読み飛ばす結果の数を設定する
+    ///
+    /// `max_results` と組み合わせてページごとに結果を取得する。検索は毎回先頭からやり直すが、
+    /// 読み飛ばした結果は `MatchResult` を組み立てないため余分なメモリを使わない。
+    /// 次のページの `offset` は `SearchOutput::next_offset` で得られる。
+    /// ページは検索順（ファイルの入力順）に区切り、`sort` は各ページの中だけで並べ替える。
+    ///
+    /// ```
+    /// use simple_find_core::{FileInput, SearchOptions, search_with_options};
+    ///
+    /// let files = vec![FileInput { path: "a.txt".to_string(), content: "x\nx\nx".to_string() }];
+    /// let page = |offset| {
+    ///     let options = SearchOptions::new().offset(offset).max_results(2);
+    ///     search_with_options("x", &files, &options).unwrap()
+    /// };
+    /// let first = page(0);
+    /// assert_eq!(first.results.len(), 2);
+    /// assert_eq!(first.next_offset, Some(2));
+    /// let second = page(first.next_offset.unwrap());
+    /// assert_eq!(second.results[0].line, 3);
+    /// assert_eq!(second.next_offset, None);
+    /// ```
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// マッチした行の直前に含めるコンテキスト行数を設定する（`grep -B` 相当）
+    pub fn before_context(mut self, lines: usize) -> Self {
+        self.before_context = lines;
+        self
+    }
+
+    /// マッチした行の直後に含めるコンテキスト行数を設定する（`grep -A` 相当）
+    pub fn after_context(mut self, lines: usize) -> Self {
+        self.after_context = lines;
+        self
+    }
+
+    /// 行全体ではなくマッチした部分だけを返すかどうかを設定する（`grep -o` 相当）
+    ///
+    /// 有効にすると `MatchResult::line_text` にはマッチした文字列だけが入る。
+    /// `invert_match` と併用した場合は無視される。
+    pub fn only_matching(mut self, only_matching: bool) -> Self {
+        self.only_matching = only_matching;
+        self
+    }
+
+    /// 除外パターンを設定する
+    ///
+    /// 除外パターン（正規表現）にマッチする行は、検索パターンにマッチしても結果に含めない。
+    /// 大文字小文字の区別は `case_sensitive` に従う。
+    pub fn exclude_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude_pattern = Some(pattern.into());
+        self
+    }
+
+    /// 検索する行の範囲（1ベース、両端を含む）を設定する
+    ///
+    /// 範囲外の行は検索されないが、結果の行番号はファイル先頭からの番号のまま報告される。
+    ///
+    /// ```
+    /// use simple_find_core::SearchOptions;
+    ///
+    /// let options = SearchOptions::new().line_range(100..=250);
+    /// ```
+    pub fn line_range(mut self, range: RangeInclusive<u32>) -> Self {
+        self.line_range = Some(range);
+        self
+    }
+
+    /// マッチングの単位となるレコードの区切り方を設定する
+    ///
+    /// 段落や任意の区切り文字列を単位にすると、複数行にまたがるログや変更履歴の項目を
+    /// まとめて検索できる。結果の行番号はレコードの開始行、列番号はレコード先頭からの位置になる。
+    ///
+    /// ```
+    /// use simple_find_core::{RecordSeparator, SearchOptions};
+    ///
+    /// let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+    /// ```
+    pub fn record_separator(mut self, separator: RecordSeparator) -> Self {
+        self.record_separator = separator;
+        self
+    }
+
+    /// 行の終端文字を設定する
+    ///
+    /// 終端文字は `MatchResult::line_text` や列番号に含まれない。
+    /// デフォルトの `LineTerminator::Auto` は LF と CRLF のどちらも扱う。
+    /// `LineTerminator::Byte` に ASCII 以外のバイトを指定すると検索時にエラーになる。
+    pub fn line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.line_terminator = terminator;
+        self
+    }
+
+    /// 正規表現の詳細なフラグを設定する
+    ///
+    /// 検索パターンと除外パターンの両方に適用される。
+    ///
+    /// ```
+    /// use simple_find_core::{RegexFlags, SearchOptions};
+    ///
+    /// let flags = RegexFlags::new().ignore_whitespace(true).unicode(false);
+    /// let options = SearchOptions::new().regex_flags(flags);
+    /// ```
+    pub fn regex_flags(mut self, flags: RegexFlags) -> Self {
+        self.regex_flags = flags;
+        self
+    }
+
+    /// パターンをコンパイルする正規表現エンジンを設定する
+    ///
+    /// `RegexEngine::Fancy`（`fancy-regex` フィーチャー）を選ぶと、先読み・後読みや後方参照を
+    /// 含むパターンを検索・置換・`validate_pattern` で使える。`max_edit_distance` を指定した
+    /// 場合と `exclude_pattern` には常に組み込みのエンジンを使う。デフォルトは `RegexEngine::Standard`。
+    pub fn regex_engine(mut self, engine: RegexEngine) -> Self {
+        self.regex_engine = engine;
+        self
+    }
+
+    /// 設定した正規表現エンジンが対応している構文と性質を返す
+    ///
+    /// 入力されたパターンが先読みなどを使っている場合に、エンジンを切り替えるよう UI で
+    /// 案内するために使う。
+    pub fn engine_features(&self) -> EngineFeatures {
+        self.regex_engine.features()
+    }
+
+    /// コンパイル済み正規表現の大きさの上限（バイト）を設定する
+    ///
+    /// 上限を超えるパターンはエラーになる。デフォルトは [`DEFAULT_SIZE_LIMIT`]。
+    pub fn size_limit(mut self, bytes: usize) -> Self {
+        self.size_limit = bytes;
+        self
+    }
+
+    /// 遅延 DFA のキャッシュの大きさの上限（バイト）を設定する
+    ///
+    /// 上限に達しても検索は遅くなるだけでエラーにはならない。
+    /// デフォルトは [`DEFAULT_DFA_SIZE_LIMIT`]。
+    pub fn dfa_size_limit(mut self, bytes: usize) -> Self {
+        self.dfa_size_limit = bytes;
+        self
+    }
+
+    /// 検索にかけられる時間の上限（ミリ秒）を設定する
+    ///
+    /// 上限を過ぎると検索を打ち切り、それまでの結果とともに `SearchOutput::timed_out` が
+    /// `true` になる。ブラウザのメインスレッドで遅いパターンを実行しても固まらないようにするためのもの。
+    /// `SearchOutput` を返す関数でのみ使われる。`std` フィーチャーが無効な場合は時計を読めないため無視する。
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// 検索を外から打ち切るためのハンドルを設定する
+    ///
+    /// `CancelToken::cancel` が呼ばれると、検索は次に打ち切りを確かめたところで止まり、
+    /// それまでの結果とともに `SearchOutput::cancelled` が `true` になる。ユーザーが次の
+    /// 検索語を入力したときに、実行中の古い検索を止めるためのもの。
+    /// `timeout_ms` と同じく `SearchOutput` を返す関数でのみ使われる。
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// 並列に検索するときに使うスレッドの数を設定する
+    ///
+    /// `parallel` フィーチャーで複数のファイルや大きなファイルを検索するとき、デフォルトでは
+    /// rayon のグローバルなスレッドプール（CPU のコア数分）を使う。このオプションを指定すると、
+    /// 指定した数のスレッドを持つ専用のプールで検索するため、サーバーに組み込んだ場合に
+    /// 1つの検索が使う CPU を抑えられる。`1` なら並列にせず呼び出し元のスレッドで検索する。
+    /// `0` はデフォルトと同じ。専用のプールはスレッド数ごとに作って使い回す。
+    /// `parallel` フィーチャーが無効な場合は無視する。
+    ///
+    /// ```
+    /// use simple_find_core::SearchOptions;
+    ///
+    /// let options = SearchOptions::new().threads(2);
+    /// ```
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads).filter(|&threads| threads > 0);
+        self
+    }
+
+    /// 並列に検索するときに使うスレッドプールを設定する
+    ///
+    /// アプリケーションで用意した rayon のスレッドプールを検索に使う。複数のリクエストで
+    /// 1つのプールを共有し、検索全体が使うスレッドの数を抑えたい場合に使う。`threads` より
+    /// 優先する。`parallel` フィーチャーでのみ使える。
+    #[cfg(feature = "parallel")]
+    pub fn thread_pool(mut self, pool: Arc<rayon::ThreadPool>) -> Self {
+        self.thread_pool = Some(pool);
+        self
+    }
+
+    /// マッチの位置を行頭や行全体に固定する
+    ///
+    /// パターンを `^...$` で囲むのと同じだが、`literal` モードや複数パターンでもそのまま使える。
+    ///
+    /// ```
+    /// use simple_find_core::{Anchor, SearchOptions};
+    ///
+    /// // `grep -x` 相当
+    /// let options = SearchOptions::new().anchor(Anchor::WholeLine);
+    /// ```
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// 編集距離を許してパターンを探すあいまい一致モードを設定する
+    ///
+    /// 設定するとパターンは正規表現ではなく文字列として扱われ、置換・挿入・削除を合わせて
+    /// `max_edits` 回以内の違いで一致する部分が見つかる。誤字を含む製品名の検索などに使う。
+    /// `max_edits` はパターンの文字数より小さくなければならない。
+    ///
+    /// ```
+    /// use simple_find_core::SearchOptions;
+    ///
+    /// // `color` で `colour` や `colr` も見つける
+    /// let options = SearchOptions::new().max_edit_distance(1);
+    /// ```
+    pub fn max_edit_distance(mut self, max_edits: usize) -> Self {
+        self.max_edit_distance = Some(max_edits);
+        self
+    }
+
+    /// マッチングの前にパターンと内容に適用する Unicode 正規化を設定する
+    ///
+    /// 合成済みの `é` と `e` + 結合アクセントのように表現の異なる文字を同じものとして扱う。
+    /// 結果の列番号やマッチした文字列は正規化前の内容に対するものになる。
+    /// `Normalization::Nfc` と `Normalization::Nfkc` は `normalization` フィーチャーで使える。
+    ///
+    #[cfg_attr(feature = "normalization", doc = "```")]
+    #[cfg_attr(not(feature = "normalization"), doc = "```ignore")]
+    /// use simple_find_core::{Normalization, SearchOptions};
+    ///
+    /// let options = SearchOptions::new().normalization(Normalization::Nfc);
+    /// ```
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// アクセントなどの発音区別符号を無視するかどうかを設定する
+    ///
+    /// 有効にすると `café` と `cafe` が互いにマッチする。結果の列番号やマッチした文字列は
+    /// 符号を取り除く前の内容に対するものになる。`normalization` フィーチャーで使える。
+    #[cfg(feature = "normalization")]
+    pub fn fold_diacritics(mut self, fold_diacritics: bool) -> Self {
+        self.fold_diacritics = fold_diacritics;
+        self
+    }
+
+    /// ひらがなとカタカナを区別しないかどうかを設定する
+    ///
+    /// 有効にすると `りんご` と `リンゴ` が互いにマッチする。
+    /// 半角カタカナも対象にする場合は全角・半角の正規化と組み合わせる。
+    pub fn kana_insensitive(mut self, kana_insensitive: bool) -> Self {
+        self.kana_insensitive = kana_insensitive;
+        self
+    }
+
+    /// 全角・半角を区別しないかどうかを設定する
+    ///
+    /// 有効にすると全角英数字・記号は半角として、半角カタカナは全角として扱い、
+    /// `ＡＢＣ` と `ABC`、`ｶﾞｽ` と `ガス` が互いにマッチする。`normalization` フィーチャーで使える。
+    #[cfg(feature = "normalization")]
+    pub fn fold_width(mut self, fold_width: bool) -> Self {
+        self.fold_width = fold_width;
+        self
+    }
+
+    /// 結果の列番号を数える単位を設定する
+    ///
+    /// デフォルトはバイト単位。絵文字や CJK の文字を含む行で見た目どおりの列番号が必要な場合は
+    /// `ColumnUnit::Graphemes` を、JavaScript の `String.prototype.slice` などに渡す場合は
+    /// `ColumnUnit::Utf16` を、端末でマッチの下にキャレットを揃える場合は
+    /// `ColumnUnit::DisplayWidth` を使う。`byte_offset` と `line_byte_offset` は常にバイト単位。
+    /// `Graphemes` と `DisplayWidth` はそれぞれ `graphemes` と `display-width` フィーチャーで使える。
+    ///
+    /// ```
+    /// use simple_find_core::{ColumnUnit, SearchOptions};
+    ///
+    /// let options = SearchOptions::new().column_unit(ColumnUnit::Utf16);
+    /// ```
+    pub fn column_unit(mut self, unit: ColumnUnit) -> Self {
+        self.column_unit = unit;
+        self
+    }
+
+    /// 長すぎる行とみなす長さ（バイト）を設定する
+    ///
+    /// 圧縮された JavaScript のような数 MB の行がマッチするたびに `line_text` へ
+    /// 複製されないよう、この長さを超える行は `long_line_mode` に従って扱う。
+    /// 切り詰めた結果は `MatchResult::line_truncated` が `true` になる。
+    /// コンテキスト行も同じ長さまでに切り詰める。
+    ///
+    /// ```
+    /// use simple_find_core::{LongLineMode, SearchOptions};
+    ///
+    /// let options = SearchOptions::new()
+    ///     .max_line_length(1000)
+    ///     .long_line_mode(LongLineMode::Window);
+    /// ```
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// `max_line_length` を超える行の扱い方を設定する（デフォルトは `LongLineMode::Truncate`）
+    pub fn long_line_mode(mut self, mode: LongLineMode) -> Self {
+        self.long_line_mode = mode;
+        self
+    }
+
+    /// 結果の `line_text` をマッチを中心とした指定した文字数までに切り詰める
+    ///
+    /// これより長い行は、切り詰めた側に `…` を付けた抜粋になる。抜粋の中でのマッチの
+    /// 列番号は `MatchResult::line_text_column` に入る（`column` は元の行に対するまま）。
+    /// `max_line_length` の切り詰めよりも優先する。
+    ///
+    /// ```
+    /// use simple_find_core::{FileInput, SearchOptions, search_with_options};
+    ///
+    /// let files = vec![FileInput {
+    ///     path: "log.txt".to_string(),
+    ///     content: "0123456789 ERROR 0123456789".to_string(),
+    /// }];
+    /// let options = SearchOptions::new().preview_width(11);
+    /// let results = search_with_options("ERROR", &files, &options).unwrap().results;
+    /// assert_eq!(&*results[0].line_text, "…89 ERROR 01…");
+    /// // `…` は UTF-8 で3バイト
+    /// assert_eq!(results[0].line_text_column, 7);
+    /// ```
+    pub fn preview_width(mut self, width: usize) -> Self {
+        self.preview_width = Some(width);
+        self
+    }
+
+    /// バイナリとみなしたファイルも検索するかどうかを設定する
+    ///
+    /// デフォルトでは `is_binary` がバイナリと判定したファイルを読み飛ばし、
+    /// `SearchOutput::binary_files` にパスを記録する。置換でも内容を変更しない。
+    pub fn search_binary(mut self, search_binary: bool) -> Self {
+        self.search_binary = search_binary;
+        self
+    }
+
+    /// 検索結果の並び順を設定する
+    ///
+    /// デフォルトは入力したファイルの順（`SortOrder::Input`）。並べ替えは `max_results` などの
+    /// 上限で打ち切った後の結果に対して行う。`SortOrder::Relevance` 以外では、
+    /// 同じファイルの結果は常に行・列の順に並ぶ。
+    ///
+    /// ```
+    /// use simple_find_core::{FileInput, SearchOptions, SortOrder, search_with_options};
+    ///
+    /// let files = vec![
+    ///     FileInput { path: "a.txt".to_string(), content: "x".to_string() },
+    ///     FileInput { path: "b.txt".to_string(), content: "x x".to_string() },
+    /// ];
+    /// let options = SearchOptions::new().sort(SortOrder::MatchCount);
+    /// let results = search_with_options("x", &files, &options).unwrap().results;
+    /// assert_eq!(&*results[0].path, "b.txt");
+    /// ```
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// 検索結果に関連度（`MatchResult::score`）を付けるかどうかを設定する
+    ///
+    /// 関連度はファイル内のマッチ数、行数に対するマッチの密度、ファイル名がパターンに
+    /// マッチするかどうか、ファイル内での位置から求める。`sort(SortOrder::Relevance)` を
+    /// 指定した場合は常に付ける。
+    ///
+    /// ```
+    /// use simple_find_core::{FileInput, SearchOptions, SortOrder, search_with_options};
+    ///
+    /// let files = vec![
+    ///     FileInput { path: "notes.txt".to_string(), content: "a\nb\nc\ntodo".to_string() },
+    ///     FileInput { path: "todo.md".to_string(), content: "todo".to_string() },
+    /// ];
+    /// let options = SearchOptions::new().sort(SortOrder::Relevance);
+    /// let results = search_with_options("todo", &files, &options).unwrap().results;
+    /// assert_eq!(&*results[0].path, "todo.md");
+    /// assert!(results[0].score > results[1].score);
+    /// ```
+    pub fn relevance(mut self, relevance: bool) -> Self {
+        self.relevance = relevance;
+        self
+    }
+
+    /// 同じ行の重複した結果のまとめ方を設定する
+    ///
+    /// デフォルトは行内のすべてのマッチを結果にする（`Dedupe::None`）。`Dedupe::Line` では
+    /// grep と同じく1行につき最初のマッチだけを、`Dedupe::Span` では複数のパターンが同じ範囲に
+    /// マッチした場合に最初のパターンの結果だけを返す。`max_count_per_file` などの上限や
+    /// `count` もまとめた後の件数で数える。
+    ///
+    /// ```
+    /// use simple_find_core::{Dedupe, FileInput, SearchOptions, search_with_options};
+    ///
+    /// let files = vec![FileInput { path: "a.txt".to_string(), content: "x x\nx".to_string() }];
+    /// let options = SearchOptions::new().dedupe(Dedupe::Line);
+    /// let results = search_with_options("x", &files, &options).unwrap().results;
+    /// assert_eq!(results.len(), 2);
+    /// ```
+    pub fn dedupe(mut self, dedupe: Dedupe) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// 検索の統計（`SearchOutput::report`）を返すかどうかを設定する
+    ///
+    /// 走査したファイル数・バイト数・行数、見つかったマッチの数、読み飛ばしたファイルの数、
+    /// かかった時間を `SearchReport` にまとめる。
+    ///
+    /// ```
+    /// use simple_find_core::{FileInput, SearchOptions, search_with_options};
+    ///
+    /// let files = vec![FileInput { path: "a.txt".to_string(), content: "x\ny".to_string() }];
+    /// let options = SearchOptions::new().report(true);
+    /// let report = search_with_options("x", &files, &options).unwrap().report.unwrap();
+    /// assert_eq!((report.files_scanned, report.lines_scanned, report.matches_found), (1, 2, 1));
+    /// ```
+    pub fn report(mut self, report: bool) -> Self {
+        self.report = report;
+        self
+    }
+
+    /// ファイルごとの検索時間とマッチ数を計測するかどうかを設定する
+    ///
+    /// 有効にすると `report` も有効になり、`SearchReport::files` に走査したファイルごとの
+    /// `FileProfile` が入る。対話的な検索を遅くしているファイルを探すために使う。
+    ///
+    /// ```
+    /// use simple_find_core::{FileInput, SearchOptions, search_with_options};
+    ///
+    /// let files = vec![FileInput { path: "a.txt".to_string(), content: "x x".to_string() }];
+    /// let options = SearchOptions::new().profile(true);
+    /// let report = search_with_options("x", &files, &options).unwrap().report.unwrap();
+    /// assert_eq!(report.files[0].path, "a.txt");
+    /// assert_eq!(report.files[0].matches, 2);
+    /// ```
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// 検索結果に関連度を付けるかどうかを返す
+    pub(crate) fn scores_relevance(&self) -> bool {
+        self.relevance || self.sort == SortOrder::Relevance
+    }
+
+    /// ファイルをバイナリとして読み飛ばすかどうかを返す
+    pub(crate) fn skips_file(&self, file: &FileInput) -> bool {
+        !self.search_binary && is_binary(&file.content)
+    }
+
+    /// レコードが検索対象に含まれるかどうかを返す
+    ///
+    /// `line_range` はレコードの開始行で判定し、`LongLineMode::Skip` の場合は長すぎる
+    /// レコードを除く。
+    pub(crate) fn includes_record(&self, record: &Record) -> bool {
+        let in_range = self
+            .line_range
+            .as_ref()
+            .is_none_or(|range| range.contains(&(record.line as u32)));
+        let too_long = self.long_line_mode == LongLineMode::Skip
+            && self
+                .max_line_length
+                .is_some_and(|max_len| record.text.len() > max_len);
+        in_range && !too_long
+    }
+}
+
+/// マッチの位置をどこに固定するか
+///
+/// レコード単位で検索する場合はレコードの先頭・末尾が基準になる
+/// （`RegexFlags::multi_line` を有効にした正規表現では各行が基準になる）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Anchor {
+    /// 行内のどこでもマッチする
+    #[default]
+    Unanchored,
+    /// 行頭から始まるマッチだけを返す
+    LineStart,
+    /// 行全体がパターンにマッチする場合だけ返す（`grep -x` 相当）
+    WholeLine,
+}
+
+impl Anchor {
+    /// 行内のマッチ範囲が固定位置の条件を満たすかどうかを返す
+    pub(crate) fn accepts(self, line: &str, span: &Range<usize>) -> bool {
+        match self {
+            Self::Unanchored => true,
+            Self::LineStart => span.start == 0,
+            Self::WholeLine => span.start == 0 && span.end == line.len(),
+        }
+    }
+}
+
+/// `max_line_length` を超える行の扱い方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LongLineMode {
+    /// 長すぎる行は検索しない
+    Skip,
+    /// 検索はするが、結果の `line_text` を行頭から上限の長さまでに切り詰める
+    #[default]
+    Truncate,
+    /// 検索はするが、結果の `line_text` をマッチを中心とした上限の長さの範囲に切り詰める
+    Window,
+}
+
+/// 検索結果の並び順
+///
+/// 並び順は検索の進め方（走査するファイルの順や、将来の並列化）によらず、各値の説明にある
+/// キーで決まる。キーが等しい結果は入力したファイルの順、同じファイル内では行・列の順に並ぶ。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// 入力したファイルの順（同じファイル内では行・列・パターンの順）
+    #[default]
+    Input,
+    /// パス（バイト列としての辞書順）、行、列、パターンの順
+    Path,
+    /// マッチ数の多いファイルから順に並べる（同数の場合は入力順）
+    MatchCount,
+    /// 関連度（`MatchResult::score`）の高い順に並べる（同点の場合は入力順）
+    Relevance,
+}
+
+/// 同じ行の重複した結果のまとめ方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dedupe {
+    /// まとめずに行内のすべてのマッチを返す
+    #[default]
+    None,
+    /// 1行につき最初のマッチだけを返す（grep と同じ）
+    Line,
+    /// 同じ行の同じ範囲へのマッチは最初のパターンのものだけを返す
+    Span,
+}
+
+impl Dedupe {
+    /// 1行分のマッチ（列番号順）から重複を取り除く
+    pub(crate) fn apply(self, matches: &mut Vec<SpanMatch>) {
+        match self {
+            Self::None => {}
+            Self::Line => matches.truncate(1),
+            Self::Span => {
+                let mut seen = BTreeSet::new();
+                matches.retain(|m| seen.insert((m.span.start, m.span.end)));
+            }
+        }
+    }
+}
+
+/// `regex::RegexBuilder` のフラグにそのまま対応する正規表現のフラグ
+///
+/// いずれもパターン中のインラインフラグ（`(?m)` など）と同じ意味を持つ。
+/// `literal` モードでは `ignore_whitespace` は無視される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegexFlags {
+    pub(crate) multi_line: bool,
+    pub(crate) dot_all: bool,
+    pub(crate) unicode: bool,
+    pub(crate) swap_greed: bool,
+    pub(crate) ignore_whitespace: bool,
+}
+
+impl Default for RegexFlags {
+    fn default() -> Self {
+        Self {
+            multi_line: false,
+            dot_all: false,
+            unicode: true,
+            swap_greed: false,
+            ignore_whitespace: false,
+        }
+    }
+}
+
+impl RegexFlags {
+    /// デフォルト値（Unicode モードのみ有効）でフラグを作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `^` と `$` を行頭・行末にもマッチさせるかどうかを設定する（`(?m)` 相当）
+    ///
+    /// 段落単位などで複数行のレコードを検索するときに使う。
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    /// `.` を改行にもマッチさせるかどうかを設定する（`(?s)` 相当）
+    pub fn dot_all(mut self, dot_all: bool) -> Self {
+        self.dot_all = dot_all;
+        self
+    }
+
+    /// Unicode モードを有効にするかどうかを設定する（`(?u)` 相当）
+    ///
+    /// 無効にすると `\w` や `\d` は ASCII の文字だけにマッチする。
+    /// UTF-8 として不正なバイト列にマッチしうるパターン（`.` など）はエラーになる。
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// 量指定子の貪欲・非貪欲を入れ替えるかどうかを設定する（`(?U)` 相当）
+    pub fn swap_greed(mut self, swap_greed: bool) -> Self {
+        self.swap_greed = swap_greed;
+        self
+    }
+
+    /// パターン中の空白と `#` から行末までのコメントを無視するかどうかを設定する（`(?x)` 相当）
+    pub fn ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// 正規表現のビルダーにフラグを反映する
+    pub(crate) fn apply<'a>(&self, builder: &'a mut RegexBuilder) -> &'a mut RegexBuilder {
+        builder
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_all)
+            .unicode(self.unicode)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+    }
+
+    /// 正規表現セットのビルダーにフラグを反映する
+    #[cfg(feature = "regex")]
+    pub(crate) fn apply_set<'a>(
+        &self,
+        builder: &'a mut RegexSetBuilder,
+    ) -> &'a mut RegexSetBuilder {
+        builder
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_all)
+            .unicode(self.unicode)
+            .swap_greed(self.swap_greed)
+            .ignore_whitespace(self.ignore_whitespace)
+    }
+}
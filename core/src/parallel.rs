@@ -0,0 +1,336 @@
+#[cfg(feature = "parallel")]
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::{CompiledSearch, FileInput, LineMatch, ScanProgress, SearchOptions, Stop};
+
+/// 1ファイル分の検索結果と打ち切った理由、そのファイルだけの進み具合
+pub(crate) type FileOutcome = (Vec<LineMatch>, Option<Stop>, ScanProgress);
+
+/// これより大きいファイルは、行の境目で区切った部分ごとに並列に検索する（バイト）
+#[cfg(feature = "parallel")]
+const LARGE_FILE_LEN: usize = 16 << 20;
+
+/// 大きなファイルを区切るときの1つ分のおおよその長さ（バイト）
+#[cfg(feature = "parallel")]
+const CHUNK_LEN: usize = 4 << 20;
+
+/// バイナリとして読み飛ばすもの以外のファイルを、スレッドプールで並列に検索しておく
+///
+/// 結果は `files` の順に並ぶため、順に取り出せば1つずつ検索した場合と同じ結果になる。
+/// `offset`・`max_results`・`max_result_bytes` はそれまでのファイルの結果に依存するため、指定された場合は
+/// 並列にせず `None` を返す（呼び出し側で1つずつ検索する）。`threads(1)` の場合も同じ。
+#[cfg(feature = "parallel")]
+pub(crate) fn presearch(
+    files: &[FileInput],
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+) -> Option<Vec<FileOutcome>> {
+    use rayon::prelude::*;
+
+    if options.offset > 0
+        || options.max_results.is_some()
+        || options.max_result_bytes.is_some()
+        || options.threads == Some(1)
+    {
+        return None;
+    }
+    Some(in_thread_pool(options, || {
+        files
+            .par_iter()
+            .filter(|f| !options.skips_file(f))
+            .map(|f| {
+                let mut progress = ScanProgress::default();
+                let (matches, stop) =
+                    if f.content.len() > LARGE_FILE_LEN && splits_into_chunks(compiled, options) {
+                        crate::scan_one_with(
+                            f,
+                            compiled,
+                            options,
+                            &mut progress,
+                            |progress, matches| {
+                                search_chunks(
+                                    &f.content, CHUNK_LEN, compiled, options, progress, matches,
+                                )
+                            },
+                        )
+                    } else {
+                        crate::scan_one(f, compiled, options, &mut progress)
+                    };
+                (matches, stop, progress)
+            })
+            .collect()
+    }))
+}
+
+/// `op` を `thread_pool` / `threads` で指定されたスレッドプールで実行する
+///
+/// どちらも指定されていなければ、rayon のグローバルなスレッドプールで実行する。
+#[cfg(feature = "parallel")]
+fn in_thread_pool<R: Send>(options: &SearchOptions, op: impl FnOnce() -> R + Send) -> R {
+    if let Some(pool) = &options.thread_pool {
+        return pool.install(op);
+    }
+    match options.threads.and_then(sized_thread_pool) {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// `threads` 個のスレッドを持つ専用のスレッドプールを返す
+///
+/// プールはスレッド数ごとに1つ作って使い回す。スレッドを起動できなかった場合は `None` を返す
+/// （グローバルなスレッドプールで検索する）。
+#[cfg(feature = "parallel")]
+fn sized_thread_pool(threads: usize) -> Option<std::sync::Arc<rayon::ThreadPool>> {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> = OnceLock::new();
+
+    let mut pools = POOLS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(pool) = pools.get(&threads) {
+        return Some(Arc::clone(pool));
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|idx| format!("simple-find-{}", idx))
+        .build()
+        .ok()?;
+    Some(Arc::clone(pools.entry(threads).or_insert(Arc::new(pool))))
+}
+
+/// ファイルを行の境目で区切り、部分ごとに別々に検索しても結果が変わらないかどうかを返す
+///
+/// 前後のコンテキストは隣の部分の行を必要とし、複数行のレコードや検索クエリの条件は
+/// ファイル全体を見ないと決まらないため、区切らない。
+#[cfg(feature = "parallel")]
+fn splits_into_chunks(compiled: &CompiledSearch, options: &SearchOptions) -> bool {
+    options.record_separator == crate::RecordSeparator::Line
+        && options.before_context == 0
+        && options.after_context == 0
+        && !matches!(compiled.patterns, crate::PatternSet::Query(_))
+}
+
+/// 内容を `chunk_len` バイトほどの行の境目で終わる部分に区切り、範囲と先頭の行番号を返す
+#[cfg(feature = "parallel")]
+fn line_chunks(
+    content: &str,
+    chunk_len: usize,
+    options: &SearchOptions,
+) -> Vec<(core::ops::Range<usize>, usize)> {
+    let terminator = options.line_terminator;
+    let pattern = terminator.pattern();
+    let mut chunks = Vec::new();
+    let (mut start, mut line) = (0, 1);
+    while start < content.len() {
+        let end = match content.as_bytes().get(start + chunk_len..) {
+            Some(rest) => memchr::memmem::find(rest, pattern.as_bytes())
+                .map_or(content.len(), |pos| start + chunk_len + pos + pattern.len()),
+            None => content.len(),
+        };
+        chunks.push((start..end, line));
+        line += terminator.count_in(&content[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+/// 行の境目で区切った部分ごとに並列に検索し、ファイル全体の結果として `matches` に追加する
+///
+/// 結果の位置はファイルの先頭からに直す。`max_count_per_file` の上限は部分ごとに適用した
+/// うえで、つなげた結果に改めて適用する。制限時間を過ぎた部分があれば、その部分までの
+/// 結果を残して打ち切る。
+#[cfg(feature = "parallel")]
+fn search_chunks(
+    content: &str,
+    chunk_len: usize,
+    compiled: &CompiledSearch,
+    options: &SearchOptions,
+    progress: &mut ScanProgress,
+    matches: &mut Vec<LineMatch>,
+) -> Option<Stop> {
+    use rayon::prelude::*;
+
+    let outcomes: Vec<FileOutcome> = line_chunks(content, chunk_len, options)
+        .into_par_iter()
+        .map(|(range, first_line)| {
+            let mut progress = ScanProgress::default();
+            let mut found = Vec::new();
+            let stop = crate::search_text(
+                &content[range.clone()],
+                first_line,
+                compiled,
+                options,
+                &mut progress,
+                &mut found,
+            );
+            for line_match in &mut found {
+                line_match.byte_offset += range.start;
+            }
+            (found, stop, progress)
+        })
+        .collect();
+
+    let file_limit = options.max_count_per_file.unwrap_or(usize::MAX);
+    for (found, stop, chunk_progress) in outcomes {
+        progress.merge(chunk_progress);
+        let room = file_limit - matches.len();
+        let limited = found.len() > room || matches!(stop, Some(Stop::FileLimit));
+        matches.extend(found.into_iter().take(room));
+        if limited {
+            return Some(Stop::FileLimit);
+        }
+        if stop.is_some() {
+            return stop;
+        }
+    }
+    None
+}
+
+/// `parallel` フィーチャーが無効な場合は常に1つずつ検索する
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn presearch(
+    _files: &[FileInput],
+    _compiled: &CompiledSearch,
+    _options: &SearchOptions,
+) -> Option<Vec<FileOutcome>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    use crate::{FileInput, SearchOptions, SortOrder, search_with_options};
+
+    #[test]
+    fn test_results_keep_input_order_across_files() {
+        let files: Vec<FileInput> = (0..64)
+            .map(|idx| FileInput {
+                path: format!("{:02}.txt", 63 - idx),
+                content: "hit\n".repeat(idx % 5),
+            })
+            .collect();
+        let options = SearchOptions::new().sort(SortOrder::Input).report(true);
+        let output = search_with_options("hit", &files, &options).unwrap();
+        let expected: Vec<(String, u32)> = files
+            .iter()
+            .flat_map(|f| (1..=f.content.lines().count() as u32).map(|line| (f.path.clone(), line)))
+            .collect();
+        let actual: Vec<(String, u32)> = output
+            .results
+            .iter()
+            .map(|r| (r.path.to_string(), r.line))
+            .collect();
+        assert_eq!(actual, expected);
+        let report = output.report.unwrap();
+        assert_eq!(report.files_scanned, 64);
+        assert_eq!(report.matches_found, expected.len());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_thread_pool_options() {
+        use std::sync::Arc;
+
+        use super::{in_thread_pool, sized_thread_pool};
+
+        let options = SearchOptions::new().threads(3);
+        assert_eq!(in_thread_pool(&options, rayon::current_num_threads), 3);
+        // 同じスレッド数のプールは使い回す
+        assert!(Arc::ptr_eq(
+            &sized_thread_pool(3).unwrap(),
+            &sized_thread_pool(3).unwrap()
+        ));
+
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(2)
+                .build()
+                .unwrap(),
+        );
+        let options = SearchOptions::new()
+            .threads(3)
+            .thread_pool(Arc::clone(&pool));
+        assert_eq!(in_thread_pool(&options, rayon::current_num_threads), 2);
+
+        let files: Vec<FileInput> = (0..8)
+            .map(|idx| FileInput {
+                path: format!("{}.txt", idx),
+                content: "hit\nmiss\nhit".to_string(),
+            })
+            .collect();
+        let expected = search_with_options("hit", &files, &SearchOptions::new()).unwrap();
+        for options in [
+            SearchOptions::new().threads(1),
+            SearchOptions::new().threads(2),
+            SearchOptions::new().thread_pool(pool),
+        ] {
+            let output = search_with_options("hit", &files, &options).unwrap();
+            assert_eq!(output.results, expected.results);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_search_chunks_matches_whole_file_search() {
+        use super::search_chunks;
+        use crate::{LineMatch, LineTerminator, ScanProgress, Stop, compile_patterns, search_file};
+
+        let results = |matches: Vec<LineMatch>| {
+            matches
+                .into_iter()
+                .map(|m| m.into_result("big.log".into()))
+                .collect::<Vec<_>>()
+        };
+
+        let file = FileInput {
+            path: "big.log".to_string(),
+            content: "foo\r\nbar foo\n\n日本foo\r\nfoo foo\nlast".repeat(5),
+        };
+        for options in [
+            SearchOptions::new(),
+            SearchOptions::new().line_terminator(LineTerminator::Crlf),
+            SearchOptions::new().line_range(4..=12),
+            SearchOptions::new().invert_match(true),
+            SearchOptions::new().max_count_per_file(7),
+            SearchOptions::new().max_count_per_file(15),
+        ] {
+            let compiled = compile_patterns(&["fo+|^$"], &options).unwrap();
+            let mut expected = Vec::new();
+            let expected_stop = search_file(
+                &file,
+                &compiled,
+                &options,
+                &mut ScanProgress::default(),
+                &mut expected,
+            );
+            let expected = results(expected);
+            for chunk_len in [1, 5, 17, 1000] {
+                let mut found = Vec::new();
+                let stop = search_chunks(
+                    &file.content,
+                    chunk_len,
+                    &compiled,
+                    &options,
+                    &mut ScanProgress::default(),
+                    &mut found,
+                );
+                let message = format!("chunk_len {} with {:?}", chunk_len, options);
+                assert_eq!(results(found), expected, "{}", message);
+                assert_eq!(
+                    matches!(stop, Some(Stop::FileLimit)),
+                    matches!(expected_stop, Some(Stop::FileLimit)),
+                    "{}",
+                    message
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use pcre2::bytes::{Captures, Regex, RegexBuilder};
+
+use crate::normalize::normalize_pattern;
+use crate::{Anchor, CaptureGroup, Matcher, SearchError, SearchOptions, SpanMatch, engine};
+
+/// PCRE2 でコンパイルしたパターンで行内のマッチを探すマッチャー
+///
+/// PCRE2 の構文（先読み・後読み、後方参照、アトミックグループ、所有量指定子、再帰など）を
+/// そのまま使い、JIT が使える環境では JIT コンパイルして探す。`Matcher` を実装しているため、
+/// `search_with_matcher` や `Searcher::with_matcher` に渡して使う。`pcre2` フィーチャーで使える
+/// （C ライブラリをビルドするため、ネイティブ向けのビルドのみ）。
+///
+/// パターンは `case_sensitive`・`whole_word`・`literal`・`anchor`・`regex_flags` と
+/// 正規化のオプションに従ってコンパイルする。マッチの上限に達するなど実行時にエラーになった行は、
+/// それ以降のマッチがないものとして扱う。
+///
+/// ```
+/// use simple_find_core::{FileInput, Pcre2Matcher, SearchOptions, search_with_matcher};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "price: 100 USD\ncost: 5 EUR".to_string(),
+/// }];
+/// let options = SearchOptions::new();
+/// let matcher = Pcre2Matcher::new(&[r"\d++(?= USD)"], &options).unwrap();
+/// let output = search_with_matcher(matcher, &files, &options).unwrap();
+/// assert_eq!(output.results.len(), 1);
+/// assert_eq!(output.results[0].match_text, "100");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pcre2Matcher {
+    /// パターンごとの正規表現
+    regexes: Vec<Regex>,
+}
+
+impl Pcre2Matcher {
+    /// オプションに従ってパターンを PCRE2 でコンパイルする
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - PCRE2 の構文のパターンのリスト（`SpanMatch::pattern_index` はこの順の位置）
+    /// * `options` - 検索オプション
+    ///
+    /// # Returns
+    ///
+    /// マッチャー、またはいずれかのパターンが無効な場合のエラー
+    pub fn new(patterns: &[&str], options: &SearchOptions) -> Result<Self, SearchError> {
+        let regexes = patterns
+            .iter()
+            .map(|pattern| build_pcre2_regex(pattern, options))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { regexes })
+    }
+}
+
+impl Matcher for Pcre2Matcher {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found = Vec::new();
+        for (pattern_index, re) in self.regexes.iter().enumerate() {
+            find_with_pcre2(re, pattern_index, line, &mut found);
+        }
+        if self.regexes.len() > 1 {
+            found.sort_by_key(|m| (m.span.start, m.pattern_index));
+        }
+        found
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        self.regexes
+            .iter()
+            .any(|re| re.is_match(line.as_bytes()).unwrap_or(false))
+    }
+
+    fn capture_name(&self, pattern_index: usize, group_index: usize) -> Option<&str> {
+        self.regexes[pattern_index]
+            .capture_names()
+            .get(group_index)?
+            .as_deref()
+    }
+}
+
+/// 検索オプションを使わず、コンパイル済みの PCRE2 の正規表現でそのまま探す
+///
+/// UTF モードでコンパイルした正規表現でなければならない。
+impl Matcher for Regex {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found = Vec::new();
+        find_with_pcre2(self, 0, line, &mut found);
+        found
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        Regex::is_match(self, line.as_bytes()).unwrap_or(false)
+    }
+
+    fn capture_name(&self, _pattern_index: usize, group_index: usize) -> Option<&str> {
+        self.capture_names().get(group_index)?.as_deref()
+    }
+}
+
+/// 行内のマッチを `found` に追加する
+///
+/// マッチの上限に達するなどの実行時エラーでは、それ以降を探さない。
+fn find_with_pcre2(re: &Regex, pattern_index: usize, line: &str, found: &mut Vec<SpanMatch>) {
+    let bytes = line.as_bytes();
+    // キャプチャグループがなければ軽量な find_iter で済ませる
+    if re.captures_len() > 1 {
+        found.extend(
+            re.captures_iter(bytes)
+                .map_while(Result::ok)
+                .map(|caps| SpanMatch {
+                    pattern_index,
+                    span: caps.get(0).map(|m| m.start()..m.end()).unwrap(),
+                    captures: capture_groups(line, &caps),
+                }),
+        );
+    } else {
+        found.extend(
+            re.find_iter(bytes)
+                .map_while(Result::ok)
+                .map(|m| SpanMatch::new(pattern_index, m.start()..m.end())),
+        );
+    }
+}
+
+/// マッチ全体を除くキャプチャグループを取り出す
+fn capture_groups(line: &str, caps: &Captures) -> Vec<CaptureGroup> {
+    (1..caps.len())
+        .filter_map(|index| {
+            caps.get(index).map(|m| CaptureGroup {
+                index,
+                text: line[m.start()..m.end()].to_string(),
+                column: (m.start() + 1) as u32,
+                end_column: (m.end() + 1) as u32,
+            })
+        })
+        .collect()
+}
+
+/// オプションに従って PCRE2 のパターン文字列を組み立てる
+///
+/// `build_pattern` と同じだが、PCRE2 には `\b{start-half}` がないため、単語単位の検索は
+/// 前後が単語文字でないことを後読み・先読みで確かめる。
+fn build_pcre2_pattern(pattern: &str, options: &SearchOptions) -> String {
+    let pattern = if options.literal {
+        engine::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    let pattern = if options.whole_word {
+        format!(r"(?<!\w)(?:{})(?!\w)", pattern)
+    } else {
+        pattern
+    };
+
+    let pattern = match options.anchor {
+        Anchor::Unanchored => pattern,
+        Anchor::LineStart => format!(r"^(?:{})", pattern),
+        Anchor::WholeLine => format!(r"^(?:{})\z", pattern),
+    };
+
+    // PCRE2 のビルダーには `swap_greed` がないため、インラインフラグで指定する
+    if options.regex_flags.swap_greed {
+        format!("(?U){}", pattern)
+    } else {
+        pattern
+    }
+}
+
+/// オプションに従って PCRE2 の正規表現をコンパイルする
+///
+/// `build_regex` と同じく、`literal` モードでは `ignore_whitespace` を無視する。
+fn build_pcre2_regex(pattern: &str, options: &SearchOptions) -> Result<Regex, SearchError> {
+    let flags = &options.regex_flags;
+    RegexBuilder::new()
+        .utf(true)
+        .ucp(flags.unicode)
+        .caseless(!options.case_sensitive)
+        .multi_line(flags.multi_line)
+        .dotall(flags.dot_all)
+        .extended(flags.ignore_whitespace && !options.literal)
+        .jit_if_available(true)
+        .build(&build_pcre2_pattern(
+            &normalize_pattern(pattern, options),
+            options,
+        ))
+        .map_err(|source| SearchError::InvalidPcre2Pattern {
+            pattern: pattern.to_string(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{FileInput, search_with_matcher};
+
+    #[test]
+    fn test_pcre2_matcher() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "the the Cat\ncat concatenate\n(a(b)c)".to_string(),
+        }];
+        let search = |patterns: &[&str], options: &SearchOptions| {
+            let matcher = Pcre2Matcher::new(patterns, options).unwrap();
+            search_with_matcher(matcher, &files, options)
+                .unwrap()
+                .results
+        };
+
+        // 後方参照と名前付きキャプチャグループ
+        let results = search(&[r"\b(?<word>\w+) \k<word>\b"], &SearchOptions::new());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "the the");
+        assert_eq!(results[0].named_captures["word"], "the");
+
+        // 再帰で入れ子の括弧にマッチする
+        let results = search(&[r"\((?:[^()]|(?R))*\)"], &SearchOptions::new());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "(a(b)c)");
+
+        // 単語単位・大文字小文字の区別・リテラルのオプションが効く
+        let options = SearchOptions::new().case_sensitive(false).whole_word(true);
+        let lines: Vec<u32> = search(&["cat"], &options).iter().map(|r| r.line).collect();
+        assert_eq!(lines, [1, 2]);
+        let options = SearchOptions::new().literal(true);
+        assert_eq!(search(&["(b)"], &options)[0].column, 3);
+        let options = SearchOptions::new().anchor(Anchor::WholeLine);
+        assert_eq!(search(&[r"\(.*\)"], &options).len(), 1);
+
+        // 複数のパターンは列番号順に返す
+        let results = search(&["concat", "cat"], &SearchOptions::new());
+        let found: Vec<(u32, u32, usize)> = results
+            .iter()
+            .map(|r| (r.line, r.column, r.pattern_index))
+            .collect();
+        assert_eq!(found, [(2, 1, 1), (2, 5, 0), (2, 8, 1)]);
+
+        assert!(matches!(
+            Pcre2Matcher::new(&["a(b"], &SearchOptions::new()),
+            Err(SearchError::InvalidPcre2Pattern { .. })
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+#[cfg(feature = "prefilter")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "prefilter")]
+use memchr::memmem::Finder;
+#[cfg(feature = "prefilter")]
+use regex_syntax::hir::{Hir, HirKind};
+
+#[cfg(feature = "prefilter")]
+use crate::engine::parser_builder;
+use crate::{PatternSet, SearchOptions};
+
+/// 正規表現を呼ぶ前に、マッチし得ないテキストを読み飛ばすためのリテラル
+///
+/// パターンのどのマッチにも必ず含まれるリテラルを memchr で探し、見つからない行や
+/// ファイルには正規表現エンジンを使わない。マッチがまれな大きいファイルの検索で効く。
+#[cfg(feature = "prefilter")]
+#[derive(Clone)]
+pub(crate) struct Prefilter {
+    /// 必ず含まれるリテラルを探す検索器
+    finder: Finder<'static>,
+}
+
+#[cfg(feature = "prefilter")]
+impl Prefilter {
+    /// パターンが1つの正規表現で、必ず含まれるリテラルがある場合だけ作る
+    pub(crate) fn new(patterns: &PatternSet, options: &SearchOptions) -> Option<Self> {
+        let PatternSet::Regex { regexes, set: None } = patterns else {
+            return None;
+        };
+        let [regex] = regexes.as_slice() else {
+            return None;
+        };
+        let hir = parser_builder(options).build().parse(regex.as_str()).ok()?;
+        let literal = required_literal(&hir)?;
+        Some(Self {
+            finder: Finder::new(&literal).into_owned(),
+        })
+    }
+
+    /// テキストがマッチを含み得るかどうかを返す
+    pub(crate) fn may_match(&self, text: &str) -> bool {
+        self.finder.find(text.as_bytes()).is_some()
+    }
+}
+
+/// `prefilter` フィーチャーが無効な場合はパターンを解析できないため、作られることのない型で代わりにする
+#[cfg(not(feature = "prefilter"))]
+#[derive(Clone)]
+pub(crate) enum Prefilter {}
+
+#[cfg(not(feature = "prefilter"))]
+impl Prefilter {
+    pub(crate) fn new(_patterns: &PatternSet, _options: &SearchOptions) -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn may_match(&self, _text: &str) -> bool {
+        match *self {}
+    }
+}
+
+/// どのマッチにも必ず含まれるリテラルのうち、最も長いものを返す
+///
+/// 大文字と小文字を区別しない文字は文字クラスになるため、リテラルとしては扱わない。
+#[cfg(feature = "prefilter")]
+fn required_literal(hir: &Hir) -> Option<Vec<u8>> {
+    match hir.kind() {
+        HirKind::Literal(literal) => Some(literal.0.to_vec()),
+        HirKind::Capture(capture) => required_literal(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min > 0 => required_literal(&repetition.sub),
+        HirKind::Concat(subs) => {
+            subs.iter()
+                .filter_map(required_literal)
+                .reduce(|best, literal| {
+                    if literal.len() > best.len() {
+                        literal
+                    } else {
+                        best
+                    }
+                })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "prefilter"))]
+mod tests {
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+
+    use super::*;
+    use crate::{FileInput, search_with_options};
+
+    /// パターンから取り出した必ず含まれるリテラルを返す
+    fn literal_of(pattern: &str, options: &SearchOptions) -> Option<String> {
+        let hir = parser_builder(options).build().parse(pattern).unwrap();
+        required_literal(&hir).map(|literal| String::from_utf8(literal).unwrap())
+    }
+
+    #[test]
+    fn test_required_literal() {
+        let options = SearchOptions::new();
+        assert_eq!(
+            literal_of(r"fn \w+_test", &options).as_deref(),
+            Some("_test")
+        );
+        assert_eq!(
+            literal_of(r"(ab|cd)+=(\d+)", &options).as_deref(),
+            Some("=")
+        );
+        assert_eq!(literal_of(r"(?:error)?x", &options).as_deref(), Some("x"));
+        assert_eq!(literal_of(r"foo|bar", &options), None);
+        assert_eq!(literal_of(r"a*", &options), None);
+        let options = SearchOptions::new().case_sensitive(false);
+        assert_eq!(literal_of("ab-12", &options).as_deref(), Some("-12"));
+    }
+
+    #[test]
+    fn test_prefilter_keeps_results_and_stats() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "id=1\nnone\r\nid=22".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "nothing here\nat all".to_string(),
+            },
+        ];
+        let options = SearchOptions::new();
+        let output = search_with_options(r"id=(\d+)", &files, &options).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[1].captures[0].text, "22");
+
+        let options = SearchOptions::new().invert_match(true).report(true);
+        let output = search_with_options(r"id=\d", &files, &options).unwrap();
+        let lines: Vec<&str> = output.results.iter().map(|r| &*r.line_text).collect();
+        assert_eq!(lines, vec!["none", "nothing here", "at all"]);
+        assert_eq!(output.report.unwrap().lines_scanned, 5);
+    }
+}
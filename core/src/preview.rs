@@ -0,0 +1,151 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::options::LongLineMode;
+use crate::{MatchResult, SearchOptions, floor_char_boundary};
+
+/// `preview_width` で切り詰めた側に付ける省略記号
+const ELLIPSIS: &str = "…";
+
+/// 結果に含める行のテキスト
+pub(crate) struct LinePreview<'a> {
+    /// 行のテキスト（切り詰めた場合はその一部）
+    pub(crate) text: Cow<'a, str>,
+    /// `text` の元になった部分の行内での開始位置（0ベースのバイトオフセット）
+    pub(crate) offset: usize,
+    /// 行を切り詰めたかどうか
+    pub(crate) truncated: bool,
+    /// `text` 内でのマッチの開始位置（0ベースのバイトオフセット）
+    pub(crate) match_start: usize,
+}
+
+/// マッチした行から結果に含めるテキストを取り出す
+///
+/// `preview_width` が指定されていればマッチを中心に文字数で切り詰め、
+/// そうでなければ `max_line_length` を超える行を `long_line_mode` に従って切り詰める。
+pub(crate) fn line_preview<'a>(
+    line: &'a str,
+    span: &Range<usize>,
+    options: &SearchOptions,
+) -> LinePreview<'a> {
+    if options.only_matching && !options.invert_match {
+        return LinePreview {
+            text: Cow::Borrowed(&line[span.clone()]),
+            offset: span.start,
+            truncated: false,
+            match_start: 0,
+        };
+    }
+    if let Some(width) = options.preview_width {
+        return centered_preview(line, span, width);
+    }
+    let whole = LinePreview {
+        text: Cow::Borrowed(line),
+        offset: 0,
+        truncated: false,
+        match_start: span.start,
+    };
+    let Some(max_len) = options.max_line_length else {
+        return whole;
+    };
+    if line.len() <= max_len {
+        return whole;
+    }
+
+    let start = match options.long_line_mode {
+        LongLineMode::Window => {
+            // マッチの中央が範囲の中央に来るようにし、行末からはみ出さないようにする
+            let center = span.start + (span.end - span.start) / 2;
+            floor_char_boundary(
+                line,
+                center.saturating_sub(max_len / 2).min(line.len() - max_len),
+            )
+        }
+        LongLineMode::Skip | LongLineMode::Truncate => 0,
+    };
+    let end = floor_char_boundary(line, start + max_len);
+    LinePreview {
+        text: Cow::Borrowed(&line[start..end]),
+        offset: start,
+        truncated: true,
+        match_start: span.start.saturating_sub(start).min(end - start),
+    }
+}
+
+/// マッチを中心に `width` 文字を取り出し、切り詰めた側に省略記号を付ける
+///
+/// マッチが `width` 文字より長い場合はマッチの先頭から取り出す。
+fn centered_preview<'a>(line: &'a str, span: &Range<usize>, width: usize) -> LinePreview<'a> {
+    let starts: Vec<usize> = line.char_indices().map(|(offset, _)| offset).collect();
+    if starts.len() <= width {
+        return LinePreview {
+            text: Cow::Borrowed(line),
+            offset: 0,
+            truncated: false,
+            match_start: span.start,
+        };
+    }
+
+    let char_at = |byte: usize| starts.partition_point(|&start| start < byte);
+    let match_start = char_at(span.start);
+    let match_len = char_at(span.end) - match_start;
+    // マッチの前後に残りの文字数を半分ずつ割り振り、行の端で余った分は反対側に回す
+    let before = width.saturating_sub(match_len) / 2;
+    let first = match_start.saturating_sub(before).min(starts.len() - width);
+    let last = first + width;
+
+    let byte_at = |idx: usize| starts.get(idx).copied().unwrap_or(line.len());
+    let (start, end) = (byte_at(first), byte_at(last));
+    let mut text = String::with_capacity(end - start + ELLIPSIS.len() * 2);
+    if first > 0 {
+        text.push_str(ELLIPSIS);
+    }
+    let match_offset = text.len() + span.start - start;
+    text.push_str(&line[start..end]);
+    if last < starts.len() {
+        text.push_str(ELLIPSIS);
+    }
+
+    LinePreview {
+        text: Cow::Owned(text),
+        offset: start,
+        truncated: true,
+        match_start: match_offset,
+    }
+}
+
+/// コンテキスト行のテキストを `max_line_length` までに切り詰める
+pub(crate) fn context_preview<'a>(line: &'a str, options: &SearchOptions) -> &'a str {
+    match options.max_line_length {
+        Some(max_len) => &line[..floor_char_boundary(line, max_len)],
+        None => line,
+    }
+}
+
+/// 検索結果の `line_text` の中でマッチが占める範囲（バイト位置）を返す
+///
+/// `line_text` が切り詰められてマッチの一部しか含まない場合はその部分を、
+/// まったく含まない場合は空の範囲を返す。
+pub(crate) fn match_in_line_text(result: &MatchResult) -> Range<usize> {
+    let text: &str = &result.line_text;
+    let match_len = result.match_text.len();
+    // `preview_width` で先頭を切り詰めた場合は省略記号の分だけ後ろにずれる
+    let prefix = [0, ELLIPSIS.len()]
+        .into_iter()
+        .find(|&prefix| {
+            result.line_byte_offset >= result.line_text_offset
+                && text
+                    .get(prefix + result.line_byte_offset - result.line_text_offset..)
+                    .is_some_and(|rest| rest.starts_with(&result.match_text))
+        })
+        .unwrap_or(0);
+
+    let start = (prefix + result.line_byte_offset).saturating_sub(result.line_text_offset);
+    let end =
+        (prefix + result.line_byte_offset + match_len).saturating_sub(result.line_text_offset);
+    let start = floor_char_boundary(text, start.min(text.len()));
+    let end = floor_char_boundary(text, end.min(text.len()));
+    start..end.max(start)
+}
@@ -0,0 +1,175 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::format;
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use core::error::Error;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::{
+    FileInput, SearchError, SearchOptions, SearchOutput, compile_patterns, push_results,
+    scan_inputs, sort_results,
+};
+
+/// ファイルの内容を必要になった時点で取得するための提供元
+///
+/// `search_with_options` はすべてのファイルの内容を `Vec<FileInput>` に読み込んでから
+/// 検索するが、このトレイトを実装すれば、ディスクやネットワーク上のファイルを検索する
+/// 直前に1つずつ読み込み、検索が終わったら手放せる。`search_provider` に渡して使う。
+///
+/// `HashMap<String, String>`（パスから内容への対応）には実装済み。
+pub trait ContentProvider {
+    /// `path` のファイルの内容を返す
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - `search_provider` に渡したパス
+    ///
+    /// # Returns
+    ///
+    /// ファイルの内容、または取得できなかった場合のエラー
+    fn content(&self, path: &str) -> Result<Cow<'_, str>, Box<dyn Error + Send + Sync>>;
+}
+
+#[cfg(feature = "std")]
+impl ContentProvider for HashMap<String, String> {
+    fn content(&self, path: &str) -> Result<Cow<'_, str>, Box<dyn Error + Send + Sync>> {
+        self.get(path)
+            .map(|content| Cow::Borrowed(content.as_str()))
+            .ok_or_else(|| format!("No such file: {}", path).into())
+    }
+}
+
+/// ファイルの内容を `provider` から1つずつ取得しながら検索する
+///
+/// `paths` の順に内容を取得して検索し、次のファイルに進む前に手放すため、同時にメモリに
+/// 載るのは1ファイル分だけになる。上限や制限時間で打ち切った場合は、残りのファイルの
+/// 内容は取得しない。結果とオプションの扱いは `search_with_options` と同じ
+/// （`parallel` フィーチャーでも並列には検索しない）。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `paths` - 検索するファイルのパスのリスト
+/// * `provider` - ファイルの内容の提供元
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、または正規表現パターンが無効な場合や内容を取得できなかった
+/// 場合のエラー
+///
+// `HashMap` の `ContentProvider` の実装は `std` フィーチャーでのみ使える
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use std::collections::HashMap;
+///
+/// use simple_find_core::{SearchOptions, search_provider};
+///
+/// let mut files = HashMap::new();
+/// files.insert("a.txt".to_string(), "foo\nbar".to_string());
+/// files.insert("b.txt".to_string(), "bar".to_string());
+/// let output = search_provider("bar", &["b.txt", "a.txt"], &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(&*output.results[0].path, "b.txt");
+/// assert_eq!(output.results[1].line, 2);
+/// assert!(search_provider("bar", &["c.txt"], &files, &SearchOptions::new()).is_err());
+/// ```
+pub fn search_provider<P: ContentProvider + ?Sized>(
+    pattern: &str,
+    paths: &[&str],
+    provider: &P,
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+
+    let files = paths.iter().map(|&path| {
+        let content = provider
+            .content(path)
+            .map_err(|source| SearchError::ContentUnavailable {
+                path: path.to_string(),
+                source: Arc::from(source),
+            })?;
+        Ok(Cow::Owned(FileInput {
+            path: path.to_string(),
+            content: content.into_owned(),
+        }))
+    });
+    let mut output = scan_inputs(&compiled, files, None, options, push_results)?;
+    sort_results(&mut output.results, options.sort);
+
+    Ok(output)
+}
+
+// テストは `HashMap` の実装を使う
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::search_with_options;
+
+    /// 内容を取得したパスを記録する提供元
+    struct Recording {
+        files: HashMap<String, String>,
+        requested: RefCell<Vec<String>>,
+    }
+
+    impl ContentProvider for Recording {
+        fn content(&self, path: &str) -> Result<Cow<'_, str>, Box<dyn Error + Send + Sync>> {
+            self.requested.borrow_mut().push(path.to_string());
+            self.files.content(path)
+        }
+    }
+
+    #[test]
+    fn test_search_provider_loads_files_lazily() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.bin".to_string(),
+                content: "foo\0".to_string(),
+            },
+            FileInput {
+                path: "c.txt".to_string(),
+                content: "foo bar".to_string(),
+            },
+        ];
+        let provider = Recording {
+            files: files
+                .iter()
+                .map(|f| (f.path.clone(), f.content.clone()))
+                .collect(),
+            requested: RefCell::new(Vec::new()),
+        };
+        let paths = ["a.txt", "b.bin", "c.txt"];
+        let options = SearchOptions::new().report(true);
+        let output = search_provider("foo", &paths, &provider, &options).unwrap();
+        let expected = search_with_options("foo", &files, &options).unwrap();
+        assert_eq!(output.results, expected.results);
+        assert_eq!(output.binary_files, vec!["b.bin".to_string()]);
+        assert_eq!(output.report.unwrap().files_scanned, 2);
+
+        // 上限に達したら残りのファイルは取得しない
+        provider.requested.borrow_mut().clear();
+        let options = SearchOptions::new().max_results(1);
+        let output = search_provider("foo", &paths, &provider, &options).unwrap();
+        assert!(output.truncated);
+        assert_eq!(*provider.requested.borrow(), vec!["a.txt".to_string()]);
+
+        let options = SearchOptions::new();
+        let error = search_provider("foo", &["a.txt", "missing.txt"], &provider, &options);
+        assert!(matches!(
+            error,
+            Err(SearchError::ContentUnavailable { ref path, .. }) if path == "missing.txt"
+        ));
+    }
+}
@@ -0,0 +1,411 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::records::Record;
+use crate::{
+    FileInput, PatternSet, SearchError, SearchOptions, SearchOutput, SpanMatch, run_search,
+};
+
+/// 複数のパターンを AND / OR / NOT で組み合わせた検索条件
+///
+/// ```
+/// use simple_find_core::Query;
+///
+/// // `error` と `timeout` を含み、`retry` を含まない
+/// let query = Query::all(vec![
+///     Query::pattern("error"),
+///     Query::pattern("timeout"),
+///     Query::negate(Query::pattern("retry")),
+/// ]);
+///
+/// // `error` から3行以内に `timeout` がある
+/// let query = Query::near(Query::pattern("error"), Query::pattern("timeout"), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// 正規表現パターン（`SearchOptions` の設定が適用される）
+    Pattern(String),
+    /// すべての条件を満たす（空の場合は常に真）
+    And(Vec<Query>),
+    /// いずれかの条件を満たす（空の場合は常に偽）
+    Or(Vec<Query>),
+    /// 条件を満たさない
+    Not(Box<Query>),
+    /// 2つの条件を満たす行が指定した行数以内にある（行単位で評価される）
+    Near {
+        /// 一方の条件
+        first: Box<Query>,
+        /// もう一方の条件
+        second: Box<Query>,
+        /// 許容する行の距離（0 なら同じ行）
+        distance: usize,
+    },
+}
+
+impl Query {
+    /// 1つのパターンからなる条件を作成する
+    pub fn pattern(pattern: impl Into<String>) -> Self {
+        Self::Pattern(pattern.into())
+    }
+
+    /// すべての条件を満たす条件を作成する
+    pub fn all(queries: Vec<Query>) -> Self {
+        Self::And(queries)
+    }
+
+    /// いずれかの条件を満たす条件を作成する
+    pub fn any(queries: Vec<Query>) -> Self {
+        Self::Or(queries)
+    }
+
+    /// 条件を否定した条件を作成する
+    pub fn negate(query: Query) -> Self {
+        Self::Not(Box::new(query))
+    }
+
+    /// 2つの条件を満たす行が `distance` 行以内にあるという条件を作成する
+    ///
+    /// 条件を満たすのは、一方の条件を満たし、かつ `distance` 行以内に
+    /// もう一方の条件を満たす行がある行となる（両端の行がどちらも該当する）。
+    pub fn near(first: Query, second: Query, distance: usize) -> Self {
+        Self::Near {
+            first: Box::new(first),
+            second: Box::new(second),
+            distance,
+        }
+    }
+}
+
+/// 検索条件を評価する単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryScope {
+    /// 行ごとに評価する
+    Line,
+    /// ファイル全体で評価する（パターンごとに「ファイル内のどこかにマッチするか」を見る）
+    File,
+}
+
+/// 検索条件でファイルを検索する
+///
+/// パターンは条件式の中で現れる順に番号付けされ、各結果の `pattern_index` には
+/// マッチしたパターンの番号が入る。結果として報告されるのは否定されていない
+/// パターンのマッチだけで、否定のみで条件を満たした行は空のマッチ（列番号1）として報告する。
+///
+/// `QueryScope::File` の場合は条件を満たしたファイルについて、否定されていない
+/// パターンにマッチした箇所を報告する。
+///
+/// # Arguments
+///
+/// * `query` - 検索条件
+/// * `scope` - 条件を評価する単位
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション（すべてのパターンに共通で適用される）
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報、またはいずれかの正規表現パターンが無効な場合のエラー
+pub fn search_query(
+    query: &Query,
+    scope: QueryScope,
+    files: &[FileInput],
+    options: &SearchOptions,
+) -> Result<SearchOutput, SearchError> {
+    let patterns = PatternSet::Query(Box::new(CompiledQuery::new(query, scope, options)?));
+
+    run_search(patterns, files, options)
+}
+
+/// パターンを番号に置き換えた条件式
+#[derive(Clone)]
+enum Expr {
+    Leaf(usize),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Near(Box<Expr>, Box<Expr>, usize),
+}
+
+impl Expr {
+    /// 行ごとのパターンのマッチ有無から、指定した行で条件式を評価する
+    fn eval_line(&self, line_idx: usize, table: &[Vec<bool>]) -> bool {
+        match self {
+            Expr::Leaf(index) => table[line_idx][*index],
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval_line(line_idx, table)),
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval_line(line_idx, table)),
+            Expr::Not(expr) => !expr.eval_line(line_idx, table),
+            Expr::Near(first, second, distance) => {
+                let window =
+                    line_idx.saturating_sub(*distance)..(line_idx + distance + 1).min(table.len());
+                let near = |here: &Expr, there: &Expr| {
+                    here.eval_line(line_idx, table)
+                        && window.clone().any(|other| there.eval_line(other, table))
+                };
+                near(first, second) || near(second, first)
+            }
+        }
+    }
+
+    /// 行ごとのパターンのマッチ有無から、ファイル全体で条件式を評価する
+    fn eval_file(&self, table: &[Vec<bool>]) -> bool {
+        match self {
+            Expr::Leaf(index) => table.iter().any(|matched| matched[*index]),
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval_file(table)),
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval_file(table)),
+            Expr::Not(expr) => !expr.eval_file(table),
+            Expr::Near(..) => (0..table.len()).any(|line_idx| self.eval_line(line_idx, table)),
+        }
+    }
+}
+
+/// コンパイル済みの検索条件
+#[derive(Clone)]
+pub(crate) struct CompiledQuery {
+    expr: Expr,
+    /// 条件式に現れるすべてのパターン
+    leaves: PatternSet,
+    /// パターンごとに否定されていない（結果として報告する）かどうか
+    positive: Vec<bool>,
+    scope: QueryScope,
+}
+
+impl CompiledQuery {
+    fn new(query: &Query, scope: QueryScope, options: &SearchOptions) -> Result<Self, SearchError> {
+        let mut patterns = Vec::new();
+        let mut positive = Vec::new();
+        let expr = flatten(query, true, &mut patterns, &mut positive);
+        let leaves = PatternSet::new(&patterns, options)?;
+
+        Ok(Self {
+            expr,
+            leaves,
+            positive,
+            scope,
+        })
+    }
+
+    /// 各行が条件を満たすかどうかを返す
+    ///
+    /// `QueryScope::File` の場合、条件を満たしたファイルでは否定されていない
+    /// パターンにマッチする行が、満たさなかったファイルではどの行も該当しない。
+    pub(crate) fn line_mask(&self, lines: &[Record]) -> Vec<bool> {
+        let table: Vec<Vec<bool>> = lines
+            .iter()
+            .map(|record| {
+                let mut matched = vec![false; self.positive.len()];
+                for index in self.leaves.matched_patterns(record.text) {
+                    matched[index] = true;
+                }
+                matched
+            })
+            .collect();
+
+        match self.scope {
+            QueryScope::Line => (0..lines.len())
+                .map(|line_idx| self.expr.eval_line(line_idx, &table))
+                .collect(),
+            QueryScope::File => {
+                let accepted = self.expr.eval_file(&table);
+                table
+                    .iter()
+                    .map(|matched| {
+                        accepted && matched.iter().zip(&self.positive).any(|(&m, &p)| m && p)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// 行に否定されていないパターンのマッチがあるかどうかを返す
+    pub(crate) fn is_match(&self, line: &str) -> bool {
+        !self.positive_matches(line).is_empty()
+    }
+
+    /// 条件を満たした行の中で報告すべきマッチを返す
+    pub(crate) fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let found = self.positive_matches(line);
+        if found.is_empty() {
+            // 否定のみで条件を満たした行は行全体を表す空のマッチとして報告する
+            return vec![SpanMatch {
+                pattern_index: 0,
+                span: 0..0,
+                captures: Vec::new(),
+            }];
+        }
+        found
+    }
+
+    /// パターン内のキャプチャグループ名を返す
+    pub(crate) fn capture_name(&self, pattern_index: usize, group_index: usize) -> Option<&str> {
+        self.leaves.capture_name(pattern_index, group_index)
+    }
+
+    fn positive_matches(&self, line: &str) -> Vec<SpanMatch> {
+        let mut found = self.leaves.find_in_line(line);
+        found.retain(|m| self.positive[m.pattern_index]);
+        found
+    }
+}
+
+/// 条件式のパターンを出現順に取り出し、番号で置き換えた式を返す
+fn flatten<'q>(
+    query: &'q Query,
+    is_positive: bool,
+    patterns: &mut Vec<&'q str>,
+    positive: &mut Vec<bool>,
+) -> Expr {
+    match query {
+        Query::Pattern(pattern) => {
+            patterns.push(pattern);
+            positive.push(is_positive);
+            Expr::Leaf(patterns.len() - 1)
+        }
+        Query::And(queries) => Expr::And(
+            queries
+                .iter()
+                .map(|q| flatten(q, is_positive, patterns, positive))
+                .collect(),
+        ),
+        Query::Or(queries) => Expr::Or(
+            queries
+                .iter()
+                .map(|q| flatten(q, is_positive, patterns, positive))
+                .collect(),
+        ),
+        Query::Not(query) => Expr::Not(Box::new(flatten(query, !is_positive, patterns, positive))),
+        Query::Near {
+            first,
+            second,
+            distance,
+        } => Expr::Near(
+            Box::new(flatten(first, is_positive, patterns, positive)),
+            Box::new(flatten(second, is_positive, patterns, positive)),
+            *distance,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn log_file() -> Vec<FileInput> {
+        vec![FileInput {
+            path: "app.log".to_string(),
+            content: "error: timeout\nerror: timeout, retry\ninfo: timeout\nerror: disk"
+                .to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_and_not_query_per_line() {
+        let query = Query::all(vec![
+            Query::pattern("error"),
+            Query::pattern("timeout"),
+            Query::negate(Query::pattern("retry")),
+        ]);
+        let output =
+            search_query(&query, QueryScope::Line, &log_file(), &SearchOptions::new()).unwrap();
+        let found: Vec<(u32, u32, usize)> = output
+            .results
+            .iter()
+            .map(|m| (m.line, m.column, m.pattern_index))
+            .collect();
+        assert_eq!(found, vec![(1, 1, 0), (1, 8, 1)]);
+    }
+
+    #[test]
+    fn test_or_query() {
+        let query = Query::any(vec![Query::pattern("disk"), Query::pattern("info")]);
+        let output =
+            search_query(&query, QueryScope::Line, &log_file(), &SearchOptions::new()).unwrap();
+        let lines: Vec<u32> = output.results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_not_only_query_reports_whole_line() {
+        let query = Query::negate(Query::pattern("timeout"));
+        let output =
+            search_query(&query, QueryScope::Line, &log_file(), &SearchOptions::new()).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.results[0].line, 4);
+        assert_eq!(output.results[0].column, 1);
+        assert_eq!(output.results[0].match_text, "");
+    }
+
+    #[test]
+    fn test_file_scope_query() {
+        let files = vec![
+            FileInput {
+                path: "a.rs".to_string(),
+                content: "use std::io;\nfn main() {}".to_string(),
+            },
+            FileInput {
+                path: "b.rs".to_string(),
+                content: "use std::io;\n#[test]\nfn t() {}".to_string(),
+            },
+        ];
+        // `use std::io` を含み、テストを含まないファイル
+        let query = Query::all(vec![
+            Query::pattern("use std::io"),
+            Query::negate(Query::pattern(r"#\[test\]")),
+        ]);
+        let output = search_query(&query, QueryScope::File, &files, &SearchOptions::new()).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(&*output.results[0].path, "a.rs");
+        assert_eq!(output.results[0].line, 1);
+    }
+
+    #[test]
+    fn test_query_invalid_pattern() {
+        let query = Query::all(vec![Query::pattern("ok"), Query::pattern("(")]);
+        let result = search_query(&query, QueryScope::Line, &log_file(), &SearchOptions::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_near_query() {
+        let files = vec![FileInput {
+            path: "app.log".to_string(),
+            content: "error: db\nok\nok\nwaiting\ntimeout\nerror: disk\nok\nok\nok\nok\ntimeout"
+                .to_string(),
+        }];
+        // 1行目の error は4行離れた timeout とは3行以内にないが、
+        // 6行目の error は5行目の timeout と隣り合っている
+        let query = Query::near(Query::pattern("error"), Query::pattern("timeout"), 3);
+        let output = search_query(&query, QueryScope::Line, &files, &SearchOptions::new()).unwrap();
+        let lines: Vec<u32> = output.results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![5, 6]);
+    }
+
+    #[test]
+    fn test_near_query_same_line() {
+        let query = Query::near(Query::pattern("error"), Query::pattern("retry"), 0);
+        let output =
+            search_query(&query, QueryScope::Line, &log_file(), &SearchOptions::new()).unwrap();
+        let lines: Vec<u32> = output.results.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![2, 2]);
+    }
+
+    #[test]
+    fn test_near_query_file_scope() {
+        let files = vec![
+            FileInput {
+                path: "near.log".to_string(),
+                content: "error\ntimeout".to_string(),
+            },
+            FileInput {
+                path: "far.log".to_string(),
+                content: "error\nok\nok\ntimeout".to_string(),
+            },
+        ];
+        let query = Query::near(Query::pattern("error"), Query::pattern("timeout"), 1);
+        let output = search_query(&query, QueryScope::File, &files, &SearchOptions::new()).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert!(output.results.iter().all(|m| &*m.path == "near.log"));
+    }
+}
@@ -0,0 +1,194 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{SearchError, SearchOptions};
+
+/// 行の終端として扱う文字
+///
+/// 終端文字は行のテキストや列番号の計算には含めない。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineTerminator {
+    /// `\n` で区切り、直前の `\r` も取り除く（LF と CRLF が混在していてもよい）
+    #[default]
+    Auto,
+    /// `\n` だけで区切る（`\r` は行のテキストに残る）
+    Lf,
+    /// `\r\n` だけで区切る
+    Crlf,
+    /// `\r` だけで区切る（古い Mac OS 形式）
+    Cr,
+    /// 任意の1バイトで区切る（ASCII 文字のみ指定できる）
+    Byte(u8),
+}
+
+impl LineTerminator {
+    /// 行を区切る文字列を返す
+    pub(crate) fn pattern(self) -> String {
+        match self {
+            Self::Auto | Self::Lf => "\n".to_string(),
+            Self::Crlf => "\r\n".to_string(),
+            Self::Cr => "\r".to_string(),
+            Self::Byte(byte) => char::from(byte).to_string(),
+        }
+    }
+
+    /// 指定できない終端文字であればエラーを返す
+    pub(crate) fn validate(self) -> Result<(), SearchError> {
+        match self {
+            Self::Byte(byte) if !byte.is_ascii() => Err(SearchError::InvalidLineTerminator(byte)),
+            _ => Ok(()),
+        }
+    }
+
+    /// テキストに含まれる終端文字の数を返す
+    pub(crate) fn count_in(self, text: &str) -> usize {
+        text.matches(self.pattern().as_str()).count()
+    }
+}
+
+/// マッチングの単位となるレコードの区切り方
+///
+/// デフォルトは1行を1レコードとして扱う。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RecordSeparator {
+    /// 1行を1レコードとする
+    #[default]
+    Line,
+    /// 空行（空白文字だけの行を含む）で区切られた段落を1レコードとする
+    Paragraph,
+    /// 指定した文字列で区切る（区切り文字列はレコードに含めない）
+    ///
+    /// 空文字列を指定した場合はファイル全体を1レコードとして扱う。
+    Custom(String),
+}
+
+/// ファイルを区切った1レコード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Record<'a> {
+    /// レコードの開始行番号（1ベース）
+    pub(crate) line: usize,
+    /// ファイル先頭からのレコードの開始位置（バイトオフセット）
+    pub(crate) offset: usize,
+    /// レコードのテキスト
+    pub(crate) text: &'a str,
+}
+
+impl Record<'_> {
+    /// レコード内のバイト位置を含む行の行番号（1ベース）と、その行のレコード内での開始位置を返す
+    ///
+    /// 段落などの複数行にまたがるレコードで、マッチの終了位置の行を求めるために使う。
+    pub(crate) fn locate(&self, byte_offset: usize, terminator: LineTerminator) -> (usize, usize) {
+        let pattern = terminator.pattern();
+        let prefix = &self.text[..byte_offset];
+        let line_start = prefix
+            .rfind(pattern.as_str())
+            .map_or(0, |pos| pos + pattern.len());
+        (self.line + terminator.count_in(prefix), line_start)
+    }
+}
+
+/// 内容をオプションの区切り方と行の終端文字に従ってレコードに分割する
+pub(crate) fn split_records<'a>(content: &'a str, options: &SearchOptions) -> Vec<Record<'a>> {
+    let terminator = options.line_terminator;
+    match &options.record_separator {
+        RecordSeparator::Line => split_lines(content, terminator)
+            .into_iter()
+            .enumerate()
+            .map(|(line_idx, (offset, text))| Record {
+                line: line_idx + 1,
+                offset,
+                text,
+            })
+            .collect(),
+        RecordSeparator::Paragraph => split_paragraphs(content, terminator),
+        RecordSeparator::Custom(separator) if separator.is_empty() => vec![Record {
+            line: 1,
+            offset: 0,
+            text: content,
+        }],
+        RecordSeparator::Custom(separator) => {
+            let mut line = 1;
+            let mut offset = 0;
+            let mut records: Vec<Record> = content
+                .split(separator.as_str())
+                .map(|text| {
+                    let record = Record { line, offset, text };
+                    line += terminator.count_in(text) + terminator.count_in(separator);
+                    offset += text.len() + separator.len();
+                    record
+                })
+                .collect();
+            // 末尾の区切り文字列の後ろにできる空のレコードは数えない
+            if records.last().is_some_and(|record| record.text.is_empty()) {
+                records.pop();
+            }
+            records
+        }
+    }
+}
+
+/// 内容を空行で区切られた段落に分割する
+///
+/// 段落のテキストは最初の行頭から最後の行末までで、最後の改行は含めない。
+fn split_paragraphs(content: &str, terminator: LineTerminator) -> Vec<Record<'_>> {
+    let mut records = Vec::new();
+    // 読み込み中の段落の（開始行番号, 開始位置, 終了位置）
+    let mut current: Option<(usize, usize, usize)> = None;
+
+    for (line_idx, (offset, line)) in split_lines(content, terminator).into_iter().enumerate() {
+        if line.trim().is_empty() {
+            if let Some((line, start, end)) = current.take() {
+                records.push(Record {
+                    line,
+                    offset: start,
+                    text: &content[start..end],
+                });
+            }
+            continue;
+        }
+        let end = offset + line.len();
+        match &mut current {
+            Some((_, _, current_end)) => *current_end = end,
+            None => current = Some((line_idx + 1, offset, end)),
+        }
+    }
+
+    if let Some((line, start, end)) = current {
+        records.push(Record {
+            line,
+            offset: start,
+            text: &content[start..end],
+        });
+    }
+    records
+}
+
+/// 内容を行に分割し、各行の開始バイト位置と組にして返す
+///
+/// 終端文字は行に含めない。`LineTerminator::Auto` の場合は `str::lines` と同じく
+/// `\n` と `\r\n` のどちらでも区切る。
+fn split_lines(content: &str, terminator: LineTerminator) -> Vec<(usize, &str)> {
+    let pattern = terminator.pattern();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    // 終端文字の位置は memchr でまとめて探す
+    for end in memchr::memmem::find_iter(content.as_bytes(), pattern.as_bytes()) {
+        ranges.push(start..end);
+        start = end + pattern.len();
+    }
+    if start < content.len() {
+        ranges.push(start..content.len());
+    }
+    ranges
+        .into_iter()
+        .map(|range| {
+            let line = &content[range.clone()];
+            if terminator == LineTerminator::Auto {
+                (range.start, line.strip_suffix('\r').unwrap_or(line))
+            } else {
+                (range.start, line)
+            }
+        })
+        .collect()
+}
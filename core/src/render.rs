@@ -0,0 +1,410 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::MatchResult;
+use crate::preview::match_in_line_text;
+
+/// パスの色（マゼンタ）
+const PATH_COLOR: &str = "\x1b[35m";
+/// 行番号・列番号の色（緑）
+const NUMBER_COLOR: &str = "\x1b[32m";
+/// マッチ部分の色（太字の赤）
+const MATCH_COLOR: &str = "\x1b[1;31m";
+/// 色の指定を元に戻すエスケープシーケンス
+const RESET: &str = "\x1b[0m";
+
+/// 検索結果を grep 形式（`path:line:col: text`）の文字列に整形する
+///
+/// 1件の結果を1行として出力し、`color` が `true` の場合はパスと行番号・列番号に色を付け、
+/// `line_text` の中のマッチ部分を太字の赤で強調する。列番号は検索時の
+/// `SearchOptions::column_unit` に従う。マッチ部分は `line_text` の文字境界で切り出すため、
+/// マルチバイト文字を含む行や `preview_width` で切り詰めた行もそのまま扱える。
+/// コンテキスト行は出力しない。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+/// * `color` - ANSI エスケープシーケンスで色を付けるかどうか
+///
+/// # Returns
+///
+/// 各行が改行で終わる整形済みの文字列
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, render_ansi, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "日本語 ERROR です".to_string(),
+/// }];
+/// let output = search_with_options("ERROR", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(render_ansi(&output.results, false), "a.txt:1:11: 日本語 ERROR です\n");
+/// assert_eq!(
+///     render_ansi(&output.results, true),
+///     "\x1b[35ma.txt\x1b[0m:\x1b[32m1\x1b[0m:\x1b[32m11\x1b[0m: 日本語 \x1b[1;31mERROR\x1b[0m です\n"
+/// );
+/// ```
+pub fn render_ansi(results: &[MatchResult], color: bool) -> String {
+    let mut out = String::new();
+    for result in results {
+        if !color {
+            let _ = writeln!(
+                out,
+                "{}:{}:{}: {}",
+                result.path, result.line, result.column, result.line_text
+            );
+            continue;
+        }
+
+        let text: &str = &result.line_text;
+        let span = match_in_line_text(result);
+        let _ = write!(
+            out,
+            "{PATH_COLOR}{}{RESET}:{NUMBER_COLOR}{}{RESET}:{NUMBER_COLOR}{}{RESET}: {}",
+            result.path,
+            result.line,
+            result.column,
+            &text[..span.start]
+        );
+        if !span.is_empty() {
+            let _ = write!(out, "{MATCH_COLOR}{}{RESET}", &text[span.clone()]);
+        }
+        let _ = writeln!(out, "{}", &text[span.end..]);
+    }
+    out
+}
+
+/// 検索結果を vimgrep 形式（`path:line:column:text`）の文字列に整形する
+///
+/// Vim の quickfix リスト（`:cgetexpr` や `-q`）やエディタの grep 連携が読み込める形式で、
+/// 1件の結果を1行として出力する。Vim の列番号はバイト単位のため、`column_unit` に関わらず
+/// 行頭からのバイト位置（1ベース）を出力する。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+///
+/// # Returns
+///
+/// 各行が改行で終わる整形済みの文字列
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, render_vimgrep, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "src/main.rs".to_string(),
+///     content: "fn main() {\n    todo!();\n}".to_string(),
+/// }];
+/// let output = search_with_options("todo", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(render_vimgrep(&output.results), "src/main.rs:2:5:    todo!();\n");
+/// ```
+pub fn render_vimgrep(results: &[MatchResult]) -> String {
+    let mut out = String::new();
+    for result in results {
+        let _ = writeln!(
+            out,
+            "{}:{}:{}:{}",
+            result.path,
+            result.line,
+            result.line_byte_offset + 1,
+            result.line_text
+        );
+    }
+    out
+}
+
+/// 検索結果を grep 形式（`path:line:text`）の文字列に整形する
+///
+/// `grep -n` と同じく、同じ行に複数のマッチがあっても1行だけ出力する。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト
+///
+/// # Returns
+///
+/// 各行が改行で終わる整形済みの文字列
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, render_grep, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "foo foo\nbar\nfoo".to_string(),
+/// }];
+/// let output = search_with_options("foo", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(render_grep(&output.results), "a.txt:1:foo foo\na.txt:3:foo\n");
+/// ```
+pub fn render_grep(results: &[MatchResult]) -> String {
+    let mut out = String::new();
+    let mut previous: Option<(&str, u32)> = None;
+    for result in results {
+        let key = (&*result.path, result.line);
+        if previous == Some(key) {
+            continue;
+        }
+        previous = Some(key);
+        let _ = writeln!(out, "{}:{}:{}", result.path, result.line, result.line_text);
+    }
+    out
+}
+
+/// 検索結果をファイルごとにまとめた Markdown のレポートに整形する
+///
+/// 先頭に件数の要約を置き、ファイルごとに見出しと、行番号付きの行をまとめた
+/// コードブロックを出力する。PR の説明や issue にそのまま貼り付ける用途を想定している。
+/// 同じ行に複数のマッチがある場合はその行を1回だけ載せる。コードブロックの囲みは
+/// 行の中のバッククォートより長くするため、行の内容で Markdown が崩れることはない。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト（同じファイルの結果は連続しているものとする）
+///
+/// # Returns
+///
+/// Markdown の文字列
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, render_markdown, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "src/lib.rs".to_string(),
+///     content: "// TODO: a\nfn f() {}\n// TODO: b".to_string(),
+/// }];
+/// let output = search_with_options("TODO", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(
+///     render_markdown(&output.results),
+///     "**2 matches in 1 file**\n\n### `src/lib.rs`\n\n```\n1: // TODO: a\n3: // TODO: b\n```\n"
+/// );
+/// ```
+pub fn render_markdown(results: &[MatchResult]) -> String {
+    let mut groups: Vec<(&str, Vec<&MatchResult>)> = Vec::new();
+    for result in results {
+        match groups.last_mut() {
+            Some((path, group)) if *path == &*result.path => group.push(result),
+            _ => groups.push((&result.path, vec![result])),
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "**{} {} in {} {}**",
+        results.len(),
+        if results.len() == 1 {
+            "match"
+        } else {
+            "matches"
+        },
+        groups.len(),
+        if groups.len() == 1 { "file" } else { "files" }
+    );
+    for (path, mut group) in groups {
+        group.dedup_by_key(|result| result.line);
+        let width = group
+            .last()
+            .map_or(1, |result| result.line.to_string().len());
+        let inline = "`".repeat(longest_backtick_run(path) + 1);
+        // パスがバッククォートで始まる・終わる場合は空白を挟む（CommonMark の規則）
+        let padding = if path.starts_with('`') || path.ends_with('`') {
+            " "
+        } else {
+            ""
+        };
+        let _ = writeln!(out, "\n### {inline}{padding}{path}{padding}{inline}\n");
+
+        let longest = group
+            .iter()
+            .map(|result| longest_backtick_run(&result.line_text))
+            .max()
+            .unwrap_or(0);
+        let fence = "`".repeat((longest + 1).max(3));
+        let _ = writeln!(out, "{fence}");
+        for result in group {
+            let _ = writeln!(out, "{:>width$}: {}", result.line, result.line_text);
+        }
+        let _ = writeln!(out, "{fence}");
+    }
+    out
+}
+
+/// 文字列の中で最も長く連続するバッククォートの数を返す
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// 検索結果の `line_text` を、マッチ部分を `<mark>` で囲んだ HTML の断片に変換する
+///
+/// 行のテキストは `&`、`<`、`>`、`"`、`'` をエスケープするため、結果をそのまま
+/// ページに埋め込める。`mark_class` を指定すると `<mark class="...">` として出力し、
+/// クラス名もエスケープする。マッチ部分の切り出しは `render_ansi` と同じく
+/// `line_text` の文字境界で行う。
+///
+/// # Arguments
+///
+/// * `result` - 検索結果
+/// * `mark_class` - `<mark>` に付けるクラス名（`None` の場合は付けない）
+///
+/// # Returns
+///
+/// エスケープ済みの HTML の断片
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, render_html, search_with_options};
+///
+/// let files = vec![FileInput {
+///     path: "a.html".to_string(),
+///     content: "<p>Tom & Jerry</p>".to_string(),
+/// }];
+/// let output = search_with_options("Jerry", &files, &SearchOptions::new()).unwrap();
+/// assert_eq!(
+///     render_html(&output.results[0], Some("hit")),
+///     "&lt;p&gt;Tom &amp; <mark class=\"hit\">Jerry</mark>&lt;/p&gt;"
+/// );
+/// ```
+pub fn render_html(result: &MatchResult, mark_class: Option<&str>) -> String {
+    let text: &str = &result.line_text;
+    let span = match_in_line_text(result);
+    let mut out = String::with_capacity(text.len() + 32);
+    escape_html(&text[..span.start], &mut out);
+    if !span.is_empty() {
+        match mark_class {
+            Some(class) => {
+                out.push_str("<mark class=\"");
+                escape_html(class, &mut out);
+                out.push_str("\">");
+            }
+            None => out.push_str("<mark>"),
+        }
+        escape_html(&text[span.clone()], &mut out);
+        out.push_str("</mark>");
+    }
+    escape_html(&text[span.end..], &mut out);
+    out
+}
+
+/// HTML の特殊文字をエスケープして `out` に追加する
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::{ColumnUnit, FileInput, LongLineMode, SearchOptions, search_with_options};
+
+    fn render(content: &str, pattern: &str, options: &SearchOptions) -> String {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: content.to_string(),
+        }];
+        let output = search_with_options(pattern, &files, options).unwrap();
+        render_ansi(&output.results, true)
+    }
+
+    #[test]
+    fn test_render_highlights_each_result() {
+        assert_eq!(
+            render("foo foo", "foo", &SearchOptions::new()),
+            "\x1b[35ma.txt\x1b[0m:\x1b[32m1\x1b[0m:\x1b[32m1\x1b[0m: \x1b[1;31mfoo\x1b[0m foo\n\
+             \x1b[35ma.txt\x1b[0m:\x1b[32m1\x1b[0m:\x1b[32m5\x1b[0m: foo \x1b[1;31mfoo\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_truncated_lines() {
+        // 省略記号の後ろにあるマッチを強調する
+        let options = SearchOptions::new().preview_width(7);
+        assert!(
+            render("αβγδεζ ERROR ηθικλ", "ERROR", &options)
+                .ends_with(": … \x1b[1;31mERROR\x1b[0m …\n")
+        );
+
+        // 行頭から切り詰めてマッチの途中で切れた場合は残った部分だけを強調する
+        let options = SearchOptions::new()
+            .max_line_length(10)
+            .long_line_mode(LongLineMode::Truncate);
+        assert!(
+            render("12345678 ERROR", "ERROR", &options)
+                .ends_with(": 12345678 \x1b[1;31mE\x1b[0m\n")
+        );
+
+        // 反転検索の結果は強調しない
+        let options = SearchOptions::new().invert_match(true);
+        assert!(render("abc", "x", &options).ends_with(": abc\n"));
+    }
+
+    #[test]
+    fn test_render_vimgrep_uses_byte_columns() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "日本 ok ok".to_string(),
+        }];
+        let options = SearchOptions::new().column_unit(ColumnUnit::Chars);
+        let output = search_with_options("ok", &files, &options).unwrap();
+        assert_eq!(
+            render_vimgrep(&output.results),
+            "a.txt:1:8:日本 ok ok\na.txt:1:11:日本 ok ok\n"
+        );
+        assert_eq!(render_grep(&output.results), "a.txt:1:日本 ok ok\n");
+    }
+
+    #[test]
+    fn test_render_markdown_groups_by_file() {
+        let files = vec![
+            FileInput {
+                path: "a.md".to_string(),
+                content: format!("x{}x ```code``` x", "\n".repeat(10)),
+            },
+            FileInput {
+                path: "b`q`.txt".to_string(),
+                content: "x".to_string(),
+            },
+        ];
+        let output = search_with_options("x", &files, &SearchOptions::new()).unwrap();
+        assert_eq!(
+            render_markdown(&output.results),
+            "**4 matches in 2 files**\n\
+             \n### `a.md`\n\n\
+             ````\n 1: x\n11: x ```code``` x\n````\n\
+             \n### ``b`q`.txt``\n\n\
+             ```\n1: x\n```\n"
+        );
+        assert_eq!(render_markdown(&[]), "**0 matches in 0 files**\n");
+    }
+
+    #[test]
+    fn test_render_html_escapes_text_and_class() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "if a<b && c>'d' { \"<x>\" }".to_string(),
+        }];
+        let output = search_with_options("<x>", &files, &SearchOptions::new()).unwrap();
+        assert_eq!(
+            render_html(&output.results[0], None),
+            "if a&lt;b &amp;&amp; c&gt;&#39;d&#39; { &quot;<mark>&lt;x&gt;</mark>&quot; }"
+        );
+        assert!(
+            render_html(&output.results[0], Some("a\"><script>"))
+                .contains("<mark class=\"a&quot;&gt;&lt;script&gt;\">")
+        );
+
+        // 反転検索の結果は `<mark>` を付けない
+        let options = SearchOptions::new().invert_match(true);
+        let output = search_with_options("zzz", &files, &options).unwrap();
+        assert!(!render_html(&output.results[0], None).contains("<mark>"));
+    }
+}
@@ -0,0 +1,760 @@
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::diff::unified_diff;
+use crate::normalize::{match_targets, normalize_records};
+use crate::records::{Record, split_records};
+use crate::{
+    CompiledSearch, DEADLINE_CHECK_INTERVAL, FileInput, FileScan, MatchResult, PatternSet,
+    SearchError, SearchOptions, SpanMatch, Stop, compile_patterns,
+};
+
+/// 置換後のファイルを表す構造体
+pub struct ReplacedFile {
+    /// ファイルのパス
+    pub path: String,
+    /// 置換後のファイルの内容
+    pub content: String,
+    /// 置換した箇所の数（0 なら内容は元のまま）
+    pub replacements: usize,
+}
+
+/// エディタに適用する1か所の置換
+///
+/// 行番号と列番号は1ベースで、列番号の単位は `SearchOptions::column_unit` に従う。
+/// 範囲は終了位置を含まず、LSP の `TextEdit` や Monaco の `executeEdits` に渡す
+/// 範囲として使える（LSP の場合はそれぞれ1を引く）。
+pub struct TextEdit {
+    /// 置換するファイルのパス
+    pub path: String,
+    /// 置換範囲の開始行番号
+    pub start_line: u32,
+    /// 置換範囲の開始列番号
+    pub start_col: u32,
+    /// 置換範囲の終了行番号
+    pub end_line: u32,
+    /// 置換範囲の直後の列番号
+    pub end_col: u32,
+    /// 置換範囲と置き換える文字列
+    pub new_text: String,
+}
+
+/// 置換を適用する前に確認するための、1ファイル分の差分
+pub struct ReplacePreview {
+    /// ファイルのパス
+    pub path: String,
+    /// 置換前後の unified diff（`--- a/パス` と `+++ b/パス` の見出しを含む）
+    pub diff: String,
+    /// 置換する箇所の数
+    pub replacements: usize,
+}
+
+/// パターンにマッチした部分を置換したファイルの内容を返す
+///
+/// マッチの探し方は `search_with_options` と同じで、`whole_word`、`line_range`、
+/// `exclude_pattern`、正規化などのオプションがそのまま適用される。
+/// `max_count_per_file` を指定した場合は各ファイルの先頭からその数だけ置換する。
+/// バイナリとみなしたファイルは（`search_binary` を指定しない限り）変更しない。
+/// 結果には入力と同じ順序ですべてのファイルが含まれる。
+///
+/// 置換文字列では `regex` クレートの `Captures::expand` と同じ規則でキャプチャグループを
+/// 参照できる。`$1` や `$name` はグループにマッチした文字列に、`$0` はマッチ全体に置き換わり、
+/// 名前の直後に文字が続く場合は `${1}` や `${name}` のように囲む。`$` そのものは `$$` と書く。
+/// 存在しないグループやマッチしなかったグループは空文字列になる。
+///
+/// 大文字小文字の変換も指定できる。`\U` と `\L` はそれ以降を `\E` まで大文字・小文字にし、
+/// `\u` と `\l` は直後の1文字だけを大文字・小文字にする（`\u\L$1` で先頭だけ大文字）。
+/// `\` そのものは `\\` と書き、それ以外の `\` は文字として扱う。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト
+/// * `replacement` - マッチした部分と置き換える文字列（キャプチャグループを参照できる）
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 置換後のファイルのリスト、または正規表現パターンが無効な場合や
+/// `invert_match` が指定された場合、`timeout_ms` の時間を過ぎた場合や `cancel_token` で
+/// 打ち切られた場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, replace};
+///
+/// let files = vec![FileInput {
+///     path: "main.rs".to_string(),
+///     content: "let colour = colour_of(x);".to_string(),
+/// }];
+/// let replaced = replace("colour", &files, "color", &SearchOptions::new()).unwrap();
+/// assert_eq!(replaced[0].content, "let color = color_of(x);");
+/// assert_eq!(replaced[0].replacements, 2);
+///
+/// let replaced = replace(r"(?P<name>\w+) = (\w+)", &files, "$2 = ${name}", &SearchOptions::new());
+/// assert_eq!(replaced.unwrap()[0].content, "let colour_of = colour(x);");
+///
+/// // snake_case を CamelCase にする
+/// let replaced = replace(r"(?:\b|_)(\w)(\w*?)", &files, r"\u$1$2", &SearchOptions::new());
+/// assert_eq!(replaced.unwrap()[0].content, "Let Colour = ColourOf(X);");
+/// ```
+pub fn replace(
+    pattern: &str,
+    files: &[FileInput],
+    replacement: &str,
+    options: &SearchOptions,
+) -> Result<Vec<ReplacedFile>, SearchError> {
+    let compiled = compile(pattern, options)?;
+    let replacer = Replacer::Template(Template::parse(replacement));
+
+    files
+        .iter()
+        .map(|f| {
+            let edits = file_edits(f, &compiled, &replacer, options)?;
+            Ok(apply_edits(f, edits))
+        })
+        .collect()
+}
+
+/// マッチごとに関数を呼び出し、その戻り値で置換したファイルの内容を返す
+///
+/// 置換後の文字列がマッチした内容に依存する場合（連番を振り直す、秘密の値をハッシュに
+/// 置き換えるなど）に使う。関数には `search_with_options` と同じ検索結果が渡され、
+/// 戻り値はテンプレートとして解釈せずそのまま使う。それ以外は `replace` と同じ。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト
+/// * `replacer` - マッチから置換後の文字列を返す関数（マッチの順に呼び出される）
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 置換後のファイルのリスト、または `replace` と同じ条件でのエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, replace_with};
+///
+/// let files = vec![FileInput {
+///     path: "list.md".to_string(),
+///     content: "1. a\n1. b\n1. c".to_string(),
+/// }];
+/// let replaced = replace_with(
+///     r"^\d+",
+///     &files,
+///     |m| m.line.to_string(),
+///     &SearchOptions::new(),
+/// )
+/// .unwrap();
+/// assert_eq!(replaced[0].content, "1. a\n2. b\n3. c");
+/// ```
+pub fn replace_with<F>(
+    pattern: &str,
+    files: &[FileInput],
+    replacer: F,
+    options: &SearchOptions,
+) -> Result<Vec<ReplacedFile>, SearchError>
+where
+    F: Fn(&MatchResult) -> String,
+{
+    let compiled = compile(pattern, options)?;
+    let replacer = Replacer::Callback(&replacer);
+
+    files
+        .iter()
+        .map(|f| {
+            let edits = file_edits(f, &compiled, &replacer, options)?;
+            Ok(apply_edits(f, edits))
+        })
+        .collect()
+}
+
+/// `replace` と同じ置換を、書き換えた内容ではなく置換箇所のリストとして返す
+///
+/// エディタで最小限の差分だけを適用するために使う。置換箇所はファイルの入力順、
+/// ファイル内では位置の順に並び、互いに重ならない。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト
+/// * `replacement` - マッチした部分と置き換える文字列（`replace` と同じ書式）
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 置換箇所のリスト、または `replace` と同じ条件でのエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, replace_edits};
+///
+/// let files = vec![FileInput {
+///     path: "main.rs".to_string(),
+///     content: "fn main() {\n    old();\n}".to_string(),
+/// }];
+/// let edits = replace_edits("old", &files, "new", &SearchOptions::new()).unwrap();
+/// assert_eq!((edits[0].start_line, edits[0].start_col), (2, 5));
+/// assert_eq!((edits[0].end_line, edits[0].end_col), (2, 8));
+/// assert_eq!(edits[0].new_text, "new");
+/// ```
+pub fn replace_edits(
+    pattern: &str,
+    files: &[FileInput],
+    replacement: &str,
+    options: &SearchOptions,
+) -> Result<Vec<TextEdit>, SearchError> {
+    let compiled = compile(pattern, options)?;
+    let replacer = Replacer::Template(Template::parse(replacement));
+
+    let mut text_edits = Vec::new();
+    for f in files {
+        for edit in file_edits(f, &compiled, &replacer, options)? {
+            let (start_line, start_col) = edit.position(edit.span.start, options);
+            let (end_line, end_col) = edit.position(edit.span.end, options);
+            text_edits.push(TextEdit {
+                path: f.path.clone(),
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+                new_text: edit.new_text,
+            });
+        }
+    }
+    Ok(text_edits)
+}
+
+/// 置換を適用せずに、各ファイルで何が変わるかを unified diff 形式で返す
+///
+/// 一括置換の前に確認画面を表示するために使う。置換の内容は `replace` と同じで、
+/// 置換箇所のないファイルは結果に含めない。差分は行単位で、変更箇所の前後3行を含む。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト
+/// * `replacement` - マッチした部分と置き換える文字列（`replace` と同じ書式）
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// 置換箇所のあるファイルの差分のリスト、または `replace` と同じ条件でのエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, replace_preview};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "one\ntwo\n".to_string(),
+/// }];
+/// let previews = replace_preview("two", &files, "2", &SearchOptions::new()).unwrap();
+/// assert_eq!(previews[0].diff, "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n");
+/// ```
+pub fn replace_preview(
+    pattern: &str,
+    files: &[FileInput],
+    replacement: &str,
+    options: &SearchOptions,
+) -> Result<Vec<ReplacePreview>, SearchError> {
+    let compiled = compile(pattern, options)?;
+    let replacer = Replacer::Template(Template::parse(replacement));
+
+    let mut previews = Vec::new();
+    for f in files {
+        let edits = file_edits(f, &compiled, &replacer, options)?;
+        if edits.is_empty() {
+            continue;
+        }
+        let changes: Vec<(Range<usize>, &str)> = edits
+            .iter()
+            .map(|edit| {
+                let start = edit.record.offset;
+                (
+                    start + edit.span.start..start + edit.span.end,
+                    edit.new_text.as_str(),
+                )
+            })
+            .collect();
+        previews.push(ReplacePreview {
+            path: f.path.clone(),
+            diff: unified_diff(&f.path, &f.content, &changes),
+            replacements: edits.len(),
+        });
+    }
+    Ok(previews)
+}
+
+/// 置換に使うパターンをコンパイルする
+fn compile(pattern: &str, options: &SearchOptions) -> Result<CompiledSearch, SearchError> {
+    if options.invert_match {
+        return Err(SearchError::InvalidOptions(
+            "invert_match cannot be used with replace",
+        ));
+    }
+    compile_patterns(&[pattern], options)
+}
+
+/// 置換後の文字列の作り方
+enum Replacer<'r> {
+    /// 置換文字列のテンプレート
+    Template(Template),
+    /// マッチごとに呼び出す関数
+    Callback(&'r dyn Fn(&MatchResult) -> String),
+}
+
+/// ファイル内の1か所の置換
+struct Edit<'a> {
+    /// 置換範囲を含むレコード
+    record: Record<'a>,
+    /// レコード内の置換範囲（0ベースのバイト位置）
+    span: Range<usize>,
+    /// 置換範囲と置き換える文字列
+    new_text: String,
+}
+
+impl Edit<'_> {
+    /// レコード内のバイト位置を1ベースの行番号と列番号に変換する
+    fn position(&self, byte_offset: usize, options: &SearchOptions) -> (u32, u32) {
+        let (line, line_start) = self.record.locate(byte_offset, options.line_terminator);
+        let column = options
+            .column_unit
+            .column(&self.record.text[line_start..], byte_offset - line_start);
+        (line as u32, column)
+    }
+}
+
+/// 1ファイル分の置換箇所を位置の順に返す
+fn file_edits<'a>(
+    f: &'a FileInput,
+    compiled: &'a CompiledSearch,
+    replacer: &Replacer,
+    options: &'a SearchOptions,
+) -> Result<Vec<Edit<'a>>, SearchError> {
+    if options.skips_file(f) {
+        return Ok(Vec::new());
+    }
+    let limit = options.max_count_per_file.unwrap_or(usize::MAX);
+    let lines = split_records(&f.content, options);
+    let scan = FileScan {
+        normalized: normalize_records(&lines, options),
+        lines,
+        patterns: &compiled.patterns,
+        options,
+    };
+    let targets = match_targets(&scan.lines, &scan.normalized);
+    let mask = compiled.patterns.line_mask(&targets);
+    // コールバックに渡す検索結果で共有するパス
+    let path: Arc<str> = Arc::from(f.path.as_str());
+
+    let mut edits = Vec::new();
+
+    for (line_idx, record) in targets.iter().enumerate() {
+        if edits.len() == limit {
+            break;
+        }
+        if line_idx % DEADLINE_CHECK_INTERVAL == 0
+            && let Some(stop) = compiled.check_stop(options)
+        {
+            let path = f.path.clone();
+            return Err(match stop {
+                Stop::Cancelled => SearchError::Cancelled { path },
+                _ => SearchError::Timeout { path },
+            });
+        }
+        if !options.includes_record(record)
+            || compiled.is_excluded(record.text)
+            || mask.as_ref().is_some_and(|mask| !mask[line_idx])
+        {
+            continue;
+        }
+
+        for line_match in compiled.patterns.find_in_line(record.text) {
+            if edits.len() == limit {
+                break;
+            }
+            let record = scan.lines[line_idx];
+            let (span, new_text) = match replacer {
+                Replacer::Template(template) => {
+                    let line_match = match scan.normalized.get(line_idx) {
+                        Some(normalized) => normalized.map_match(line_match, record.text),
+                        None => line_match,
+                    };
+                    let mut new_text = String::new();
+                    template.expand(&line_match, record.text, &compiled.patterns, &mut new_text);
+                    (line_match.span, new_text)
+                }
+                Replacer::Callback(callback) => {
+                    let result = scan
+                        .build_line_match(line_idx, line_match)
+                        .into_result(Arc::clone(&path));
+                    let start = result.line_byte_offset;
+                    (start..start + result.match_text.len(), callback(&result))
+                }
+            };
+            edits.push(Edit {
+                record,
+                span,
+                new_text,
+            });
+        }
+    }
+    Ok(edits)
+}
+
+/// 置換箇所をファイルの内容に適用する
+fn apply_edits(f: &FileInput, edits: Vec<Edit>) -> ReplacedFile {
+    let mut content = String::with_capacity(f.content.len());
+    // 置換後の内容にまだ書き写していない元の内容の位置
+    let mut copied = 0;
+    let replacements = edits.len();
+
+    for edit in edits {
+        content.push_str(&f.content[copied..edit.record.offset + edit.span.start]);
+        content.push_str(&edit.new_text);
+        copied = edit.record.offset + edit.span.end;
+    }
+    content.push_str(&f.content[copied..]);
+
+    ReplacedFile {
+        path: f.path.clone(),
+        content,
+        replacements,
+    }
+}
+
+/// 置換文字列を解析したもの
+struct Template {
+    pieces: Vec<Piece>,
+}
+
+/// 大文字小文字の変換方法
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Case {
+    /// 変換しない
+    Keep,
+    /// 大文字にする
+    Upper,
+    /// 小文字にする
+    Lower,
+}
+
+impl Case {
+    /// 文字を変換して `out` に追加する
+    fn push(self, c: char, out: &mut String) {
+        match self {
+            Self::Keep => out.push(c),
+            Self::Upper => out.extend(c.to_uppercase()),
+            Self::Lower => out.extend(c.to_lowercase()),
+        }
+    }
+}
+
+/// 置換文字列の構成要素
+enum Piece {
+    /// そのまま出力する文字列
+    Literal(String),
+    /// 番号で参照するキャプチャグループ（0 はマッチ全体）
+    Index(usize),
+    /// 名前で参照するキャプチャグループ
+    Name(String),
+    /// 以降の文字の変換方法の切り替え（`\U`、`\L`、`\E`）
+    SetCase(Case),
+    /// 直後の1文字だけの変換（`\u`、`\l`）
+    NextCase(Case),
+}
+
+impl Template {
+    /// 置換文字列を解析する
+    ///
+    /// `$` の後ろに参照として解釈できるものがなければ `$` を文字として扱う。
+    fn parse(replacement: &str) -> Self {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut rest = replacement;
+
+        while let Some(pos) = rest.find(['$', '\\']) {
+            literal.push_str(&rest[..pos]);
+            let marker = &rest[pos..pos + 1];
+            rest = &rest[pos + 1..];
+            if let Some(after) = rest.strip_prefix(marker) {
+                literal.push_str(marker);
+                rest = after;
+                continue;
+            }
+            let parsed = if marker == "$" {
+                parse_reference(rest).map(|(name, after)| {
+                    let piece = match name.parse() {
+                        Ok(index) => Piece::Index(index),
+                        Err(_) => Piece::Name(name.to_string()),
+                    };
+                    (piece, after)
+                })
+            } else {
+                parse_case(rest)
+            };
+            let Some((piece, after)) = parsed else {
+                literal.push_str(marker);
+                continue;
+            };
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(core::mem::take(&mut literal)));
+            }
+            pieces.push(piece);
+            rest = after;
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+
+        Self { pieces }
+    }
+
+    /// マッチに対する置換後の文字列を `out` に追加する
+    fn expand(&self, line_match: &SpanMatch, line: &str, patterns: &PatternSet, out: &mut String) {
+        let mut case = Case::Keep;
+        let mut next_case = None;
+
+        for piece in &self.pieces {
+            let text = match piece {
+                Piece::Literal(text) => Some(text.as_str()),
+                Piece::Index(0) => Some(&line[line_match.span.clone()]),
+                Piece::Index(index) => line_match
+                    .captures
+                    .iter()
+                    .find(|g| g.index == *index)
+                    .map(|g| g.text.as_str()),
+                Piece::Name(name) => line_match
+                    .captures
+                    .iter()
+                    .find(|g| {
+                        patterns.capture_name(line_match.pattern_index, g.index) == Some(name)
+                    })
+                    .map(|g| g.text.as_str()),
+                Piece::SetCase(new_case) => {
+                    case = *new_case;
+                    None
+                }
+                Piece::NextCase(new_case) => {
+                    next_case = Some(*new_case);
+                    None
+                }
+            };
+            for c in text.unwrap_or_default().chars() {
+                next_case.take().unwrap_or(case).push(c, out);
+            }
+        }
+    }
+}
+
+/// `$` の直後からグループの参照を読み取り、名前と残りの文字列を返す
+///
+/// `${name}` の形式か、英数字と `_` が続く限りを名前とする（`regex` クレートと同じ規則）。
+fn parse_reference(rest: &str) -> Option<(&str, &str)> {
+    if let Some(braced) = rest.strip_prefix('{') {
+        let end = braced.find('}')?;
+        let name = &braced[..end];
+        return (!name.is_empty()).then(|| (name, &braced[end + 1..]));
+    }
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| rest.split_at(end))
+}
+
+/// `\\` の直後から大文字小文字の変換の指定を読み取り、残りの文字列と組にして返す
+fn parse_case(rest: &str) -> Option<(Piece, &str)> {
+    let piece = match rest.chars().next()? {
+        'U' => Piece::SetCase(Case::Upper),
+        'L' => Piece::SetCase(Case::Lower),
+        'E' => Piece::SetCase(Case::Keep),
+        'u' => Piece::NextCase(Case::Upper),
+        'l' => Piece::NextCase(Case::Lower),
+        _ => return None,
+    };
+    Some((piece, &rest[1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::vec;
+
+    use super::*;
+    use crate::{ColumnUnit, RecordSeparator};
+
+    fn single_file(content: &str) -> Vec<FileInput> {
+        vec![FileInput {
+            path: "test.txt".to_string(),
+            content: content.to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_replace_all_matches() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo bar\r\nbar foo\n".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "nothing".to_string(),
+            },
+        ];
+        let replaced = replace("foo", &files, "baz", &SearchOptions::new()).unwrap();
+        assert_eq!(replaced[0].content, "baz bar\r\nbar baz\n");
+        assert_eq!(replaced[0].replacements, 2);
+        // マッチしなかったファイルも元の内容のまま返す
+        assert_eq!(replaced[1].path, "b.txt");
+        assert_eq!(replaced[1].content, "nothing");
+        assert_eq!(replaced[1].replacements, 0);
+    }
+
+    #[test]
+    fn test_replace_respects_options() {
+        let content = "cat category\ncat\ncat";
+        let options = SearchOptions::new()
+            .whole_word(true)
+            .line_range(1..=2)
+            .max_count_per_file(2);
+        let replaced = replace("cat", &single_file(content), "dog", &options).unwrap();
+        assert_eq!(replaced[0].content, "dog category\ndog\ncat");
+
+        // 正規化した場合も元の内容の範囲を置換する
+        #[cfg(feature = "normalization")]
+        {
+            let options = SearchOptions::new().fold_width(true);
+            let replaced = replace("ABC", &single_file("ＡＢＣとABC"), "xyz", &options).unwrap();
+            assert_eq!(replaced[0].content, "xyzとxyz");
+        }
+
+        assert!(
+            replace(
+                "cat",
+                &single_file(content),
+                "dog",
+                &SearchOptions::new().invert_match(true)
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_replace_capture_references() {
+        let files = single_file("2024-01-15 and 2023-12-31");
+        let pattern = r"(?P<y>\d{4})-(\d{2})-(\d{2})";
+        let expand = |replacement: &str| {
+            replace(pattern, &files, replacement, &SearchOptions::new()).unwrap()[0]
+                .content
+                .clone()
+        };
+        assert_eq!(expand("$3/$2/${y}"), "15/01/2024 and 31/12/2023");
+        assert_eq!(expand("[$0]"), "[2024-01-15] and [2023-12-31]");
+        // `$y_` は `y_` という名前のグループとして扱われ、存在しないので空になる
+        assert_eq!(expand("$y_${y}_"), "2024_ and 2023_");
+        assert_eq!(expand("$$1 $9 $ ${"), "$1  $ ${ and $1  $ ${");
+
+        // マッチしなかったグループは空文字列になる
+        let replaced = replace(
+            r"a(x)?b",
+            &single_file("ab axb"),
+            "<$1>",
+            &SearchOptions::new(),
+        );
+        assert_eq!(replaced.unwrap()[0].content, "<> <x>");
+    }
+
+    #[test]
+    fn test_replace_case_conversion() {
+        let files = single_file("user_name = get_user_id()");
+        let expand = |pattern: &str, replacement: &str| {
+            replace(pattern, &files, replacement, &SearchOptions::new()).unwrap()[0]
+                .content
+                .clone()
+        };
+        // snake_case を camelCase と CONSTANT_CASE にする
+        assert_eq!(expand(r"_(\w)", r"\u$1"), "userName = getUserId()");
+        assert_eq!(expand(r"\w+ =", r"\U$0\E!"), "USER_NAME =! get_user_id()");
+        // `\u` は `\L` よりも優先される
+        assert_eq!(expand(r"get_(\w+)", r"\u\L${1}X"), "user_name = User_idx()");
+        // 変換の指定でない `\` はそのまま残り、`\\` は `\` になる
+        assert_eq!(expand("user_name", r"a\nb\\c"), r"a\nb\c = get_user_id()");
+    }
+
+    #[test]
+    fn test_replace_edits_positions() {
+        let files = single_file("intro\n\nfirst 日本 line\nsecond line\n");
+        let options = SearchOptions::new().column_unit(ColumnUnit::Chars);
+        let edits = replace_edits("line", &files, "row", &options).unwrap();
+        let ranges: Vec<(u32, u32, u32, u32)> = edits
+            .iter()
+            .map(|e| (e.start_line, e.start_col, e.end_line, e.end_col))
+            .collect();
+        assert_eq!(ranges, vec![(3, 10, 3, 14), (4, 8, 4, 12)]);
+
+        // 段落をまたぐマッチは終了位置の行を別に数える
+        let options = SearchOptions::new().record_separator(RecordSeparator::Paragraph);
+        let edits = replace_edits(r"日本 line\nsecond", &files, "x", &options).unwrap();
+        let edit = &edits[0];
+        assert_eq!(
+            (edit.start_line, edit.start_col, edit.end_line, edit.end_col),
+            (3, 7, 4, 7)
+        );
+    }
+
+    #[test]
+    fn test_replace_preview_hunks() {
+        let content: String = (1..=12).map(|n| format!("line {}\n", n)).collect();
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content,
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "unchanged".to_string(),
+            },
+        ];
+        let previews =
+            replace_preview(r"^line (1|3|12)$", &files, "L$1", &SearchOptions::new()).unwrap();
+        // 置換箇所のないファイルは含めない
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].replacements, 3);
+        assert_eq!(
+            previews[0].diff,
+            "--- a/a.txt\n+++ b/a.txt\n\
+             @@ -1,6 +1,6 @@\n-line 1\n+L1\n line 2\n-line 3\n+L3\n line 4\n line 5\n line 6\n\
+             @@ -9,4 +9,4 @@\n line 9\n line 10\n line 11\n-line 12\n+L12\n"
+        );
+
+        // 行全体の削除と、末尾に改行がない行
+        let files = single_file("keep\ndrop\nlast");
+        let options = SearchOptions::new().record_separator(RecordSeparator::Custom(String::new()));
+        let previews = replace_preview(r"drop\n|last", &files, "", &options);
+        assert_eq!(
+            previews.unwrap()[0].diff,
+            "--- a/test.txt\n+++ b/test.txt\n@@ -1,3 +1 @@\n keep\n-drop\n-last\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_with_callback() {
+        let files = single_file("id=7 id=41\nid=x");
+        let replaced = replace_with(
+            r"id=(?P<n>\d+)",
+            &files,
+            |m| format!("id={}", m.named_captures["n"].parse::<u32>().unwrap() + 1),
+            &SearchOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(replaced[0].content, "id=8 id=42\nid=x");
+        assert_eq!(replaced[0].replacements, 2);
+
+        // 戻り値はテンプレートとして解釈しない
+        let replaced = replace_with("x", &files, |_| "$0".to_string(), &SearchOptions::new());
+        assert_eq!(replaced.unwrap()[0].content, "id=7 id=41\nid=$0");
+    }
+}
@@ -0,0 +1,38 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// 検索1回分の統計
+///
+/// `SearchOptions::report` か `SearchOptions::profile` を有効にした場合に `SearchOutput::report` に入る。
+/// 上限や時間切れで検索を打ち切った場合は、それまでに走査した分だけを数える。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchReport {
+    /// 走査したファイルの数（読み飛ばしたファイルを除く）
+    pub files_scanned: usize,
+    /// 走査したファイルの合計バイト数
+    pub bytes_scanned: u64,
+    /// 走査したレコード（行）の数
+    pub lines_scanned: u64,
+    /// 見つかったマッチの数（`offset` で読み飛ばしたものを含む）
+    pub matches_found: usize,
+    /// バイナリとみなして読み飛ばしたファイルの数
+    pub files_skipped: usize,
+    /// パターンのコンパイルを含む検索にかかった時間
+    pub elapsed: Duration,
+    /// 走査したファイルごとの計測結果（`profile` が無効な場合は空）
+    pub files: Vec<FileProfile>,
+}
+
+/// 1ファイル分の計測結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileProfile {
+    /// ファイルのパス
+    pub path: String,
+    /// ファイルの検索にかかった時間
+    pub elapsed: Duration,
+    /// 走査したレコード（行）の数
+    pub lines: u64,
+    /// 見つかったマッチの数（`offset` で読み飛ばしたものを含む）
+    pub matches: usize,
+}
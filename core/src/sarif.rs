@@ -0,0 +1,188 @@
+#[cfg(feature = "std")]
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use core::fmt::Write as _;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use crate::MatchResult;
+#[cfg(feature = "std")]
+use crate::json::write_string;
+
+/// SARIF のスキーマの URI
+#[cfg(feature = "std")]
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// 出力に記録するツール名
+#[cfg(feature = "std")]
+const TOOL_NAME: &str = "simple_find";
+
+#[cfg(feature = "std")]
+/// 検索結果を SARIF 2.1.0 形式のログとして `writer` に書き出す
+///
+/// GitHub code scanning などにアップロードできる形式で、各結果を `warning` レベルの
+/// `result` として出力する。`rule_ids` は `pattern_index` の順に並べたルール ID で、
+/// `search_multi` で複数のルールをまとめて検索した結果をルールごとに分類できる。
+/// ID が足りないパターンには `pattern{index}` を使う。
+///
+/// SARIF の列番号はデフォルトで UTF-16 のコードユニット単位のため、検索時に
+/// `column_unit(ColumnUnit::Utf16)` を指定しておくこと。パスは `/` 区切りの相対 URI として出力する。
+///
+/// # Arguments
+///
+/// * `writer` - 書き出し先
+/// * `results` - 検索結果のリスト
+/// * `rule_ids` - `pattern_index` に対応するルール ID のリスト
+///
+/// # Returns
+///
+/// 書き出しに失敗した場合のエラー
+///
+/// ```
+/// use simple_find_core::{ColumnUnit, FileInput, SearchOptions, search_multi, write_sarif};
+///
+/// let files = vec![FileInput {
+///     path: "src/app.js".to_string(),
+///     content: "eval(input);".to_string(),
+/// }];
+/// let options = SearchOptions::new().column_unit(ColumnUnit::Utf16);
+/// let output = search_multi(&[r"\beval\("], &files, &options).unwrap();
+/// let mut out = Vec::new();
+/// write_sarif(&mut out, &output.results, &["no-eval"]).unwrap();
+/// let log = String::from_utf8(out).unwrap();
+/// assert!(log.contains("\"ruleId\":\"no-eval\""));
+/// assert!(log.contains("\"uri\":\"src/app.js\""));
+/// ```
+pub fn write_sarif<W: io::Write>(
+    mut writer: W,
+    results: &[MatchResult],
+    rule_ids: &[&str],
+) -> io::Result<()> {
+    let rule_count = results
+        .iter()
+        .map(|result| result.pattern_index + 1)
+        .max()
+        .unwrap_or(0)
+        .max(rule_ids.len());
+    let rule_id = |index: usize| match rule_ids.get(index) {
+        Some(id) => id.to_string(),
+        None => format!("pattern{}", index),
+    };
+
+    let mut out = String::new();
+    out.push_str("{\"version\":\"2.1.0\",\"$schema\":");
+    write_string(&mut out, SARIF_SCHEMA);
+    out.push_str(",\"runs\":[{\"tool\":{\"driver\":{\"name\":");
+    write_string(&mut out, TOOL_NAME);
+    out.push_str(",\"version\":");
+    write_string(&mut out, env!("CARGO_PKG_VERSION"));
+    out.push_str(",\"rules\":[");
+    for index in 0..rule_count {
+        if index > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"id\":");
+        write_string(&mut out, &rule_id(index));
+        out.push('}');
+    }
+    out.push_str("]}},\"columnKind\":\"utf16CodeUnits\",\"results\":[");
+    writer.write_all(out.as_bytes())?;
+
+    for (idx, result) in results.iter().enumerate() {
+        out.clear();
+        if idx > 0 {
+            out.push(',');
+        }
+        let id = rule_id(result.pattern_index);
+        out.push_str("{\"ruleId\":");
+        write_string(&mut out, &id);
+        let _ = write!(
+            out,
+            ",\"ruleIndex\":{},\"level\":\"warning\",\"message\":{{\"text\":",
+            result.pattern_index
+        );
+        write_string(&mut out, &format!("{}: {}", id, result.match_text));
+        out.push_str("},\"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":");
+        write_string(&mut out, &path_to_uri(&result.path));
+        let _ = write!(
+            out,
+            "}},\"region\":{{\"startLine\":{line},\"startColumn\":{},\"endLine\":{line},\"endColumn\":{},\"snippet\":{{\"text\":",
+            result.column,
+            result.end_column,
+            line = result.line
+        );
+        write_string(&mut out, &result.line_text);
+        out.push_str("}}}}]}");
+        writer.write_all(out.as_bytes())?;
+    }
+
+    writer.write_all(b"]}]}\n")?;
+    writer.flush()
+}
+
+/// パスを SARIF の `artifactLocation.uri` に使える相対 URI に変換する
+///
+/// `\` は `/` に置き換え、URI で使えない文字はパーセントエンコードする。
+pub(crate) fn path_to_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'\\' => uri.push('/'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => {
+                let _ = write!(uri, "%{:02X}", byte);
+            }
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use alloc::vec;
+    #[cfg(feature = "std")]
+    use alloc::vec::Vec;
+
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::{FileInput, SearchOptions, search_multi};
+
+    #[test]
+    fn test_path_to_uri() {
+        assert_eq!(path_to_uri("src/main.rs"), "src/main.rs");
+        assert_eq!(path_to_uri(r"src\my file#1.rs"), "src/my%20file%231.rs");
+        assert_eq!(path_to_uri("docs/日本.md"), "docs/%E6%97%A5%E6%9C%AC.md");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_write_sarif() {
+        let files = vec![FileInput {
+            path: "a.js".to_string(),
+            content: "eval(x);\nvar y;".to_string(),
+        }];
+        let output = search_multi(&["eval", r"\bvar\b"], &files, &SearchOptions::new()).unwrap();
+        let mut out = Vec::new();
+        write_sarif(&mut out, &output.results, &["no-eval"]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"version\":\"2.1.0\",\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\
+             \"runs\":[{\"tool\":{\"driver\":{\"name\":\"simple_find\",\"version\":\"0.1.0\",\
+             \"rules\":[{\"id\":\"no-eval\"},{\"id\":\"pattern1\"}]}},\
+             \"columnKind\":\"utf16CodeUnits\",\"results\":[\
+             {\"ruleId\":\"no-eval\",\"ruleIndex\":0,\"level\":\"warning\",\
+             \"message\":{\"text\":\"no-eval: eval\"},\"locations\":[{\"physicalLocation\":{\
+             \"artifactLocation\":{\"uri\":\"a.js\"},\"region\":{\"startLine\":1,\"startColumn\":1,\
+             \"endLine\":1,\"endColumn\":5,\"snippet\":{\"text\":\"eval(x);\"}}}}]},\
+             {\"ruleId\":\"pattern1\",\"ruleIndex\":1,\"level\":\"warning\",\
+             \"message\":{\"text\":\"pattern1: var\"},\"locations\":[{\"physicalLocation\":{\
+             \"artifactLocation\":{\"uri\":\"a.js\"},\"region\":{\"startLine\":2,\"startColumn\":1,\
+             \"endLine\":2,\"endColumn\":4,\"snippet\":{\"text\":\"var y;\"}}}}]}]}]}\n"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{CompiledSearch, FileInput, LineMatch};
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("the `libm` feature must be enabled when the `std` feature is disabled");
+
+/// ファイル内のマッチ数に対する重み（マッチ数の対数に掛ける）
+const WEIGHT_TERM_FREQUENCY: f64 = 1000.0;
+/// ファイルの行数に対するマッチ数の割合に対する重み
+const WEIGHT_DENSITY: f64 = 2000.0;
+/// ファイル内の位置に対する重み（ファイルの先頭にあるほど大きい）
+const WEIGHT_POSITION: f64 = 500.0;
+/// ファイル名がパターンにマッチする場合の加点
+const BONUS_FILE_NAME: f64 = 1500.0;
+
+/// 1ファイル分の検索結果に関連度を付ける
+///
+/// 関連度はファイル内のマッチ数（対数）、行数に対するマッチの密度、ファイル名がパターンに
+/// マッチするかどうか、ファイル内での位置（先頭に近いほど高い）を重み付けして足したもの。
+pub(crate) fn score_matches(f: &FileInput, matches: &mut [LineMatch], compiled: &CompiledSearch) {
+    if matches.is_empty() {
+        return;
+    }
+    let lines = f.content.bytes().filter(|&b| b == b'\n').count() + 1;
+    let count = matches.len() as f64;
+    let file_name = f.path.rsplit(['/', '\\']).next().unwrap_or(&f.path);
+
+    let mut file_score =
+        WEIGHT_TERM_FREQUENCY * log1p(count) + WEIGHT_DENSITY * (count / lines as f64).min(1.0);
+    if compiled.patterns.is_match(file_name) {
+        file_score += BONUS_FILE_NAME;
+    }
+    for line_match in matches {
+        let position = 1.0 - (line_match.line as f64 - 1.0) / lines as f64;
+        line_match.score = round(file_score + WEIGHT_POSITION * position) as i64;
+    }
+}
+
+/// `ln(1 + x)` を返す
+#[cfg(feature = "std")]
+fn log1p(x: f64) -> f64 {
+    x.ln_1p()
+}
+
+/// `ln(1 + x)` を返す（標準ライブラリがないため libm で計算する）
+#[cfg(not(feature = "std"))]
+fn log1p(x: f64) -> f64 {
+    libm::log1p(x)
+}
+
+/// 最も近い整数に丸める（ちょうど中間の場合は0から遠いほう）
+#[cfg(feature = "std")]
+fn round(x: f64) -> f64 {
+    x.round()
+}
+
+/// 最も近い整数に丸める（標準ライブラリがないため libm で計算する）
+#[cfg(not(feature = "std"))]
+fn round(x: f64) -> f64 {
+    libm::round(x)
+}
@@ -0,0 +1,153 @@
+use alloc::sync::Arc;
+
+use crate::{
+    CompiledSearch, FileInput, Matcher, PatternSet, SearchError, SearchOptions, SearchOutput,
+    search_compiled,
+};
+
+/// パターンを1回だけコンパイルし、何度でも検索に使える検索器
+///
+/// `search_with_options` は呼ぶたびにパターンをコンパイルし直すため、インクリメンタル検索の
+/// ように同じパターンで何度も検索する場合は `Searcher` を作って使い回す。
+/// `timeout_ms` の制限時間は検索ごとに数える。
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, Searcher};
+///
+/// let searcher = Searcher::new(r"fn \w+", &SearchOptions::new()).unwrap();
+/// let first = vec![FileInput {
+///     path: "a.rs".to_string(),
+///     content: "fn main() {}".to_string(),
+/// }];
+/// let second = vec![FileInput {
+///     path: "b.rs".to_string(),
+///     content: "fn run() {}\nfn stop() {}".to_string(),
+/// }];
+/// assert_eq!(searcher.search(&first).results[0].match_text, "fn main");
+/// assert_eq!(searcher.search(&second).results.len(), 2);
+/// ```
+pub struct Searcher {
+    /// コンパイル済みのパターン
+    compiled: CompiledSearch,
+    /// 検索オプション
+    options: SearchOptions,
+}
+
+impl Searcher {
+    /// パターンをコンパイルして検索器を作る
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - 検索する正規表現パターン
+    /// * `options` - 検索オプション
+    ///
+    /// # Returns
+    ///
+    /// 検索器、または正規表現パターンが無効な場合のエラー
+    pub fn new(pattern: &str, options: &SearchOptions) -> Result<Self, SearchError> {
+        Self::multi(&[pattern], options)
+    }
+
+    /// 複数のパターンを `search_multi` と同じようにまとめてコンパイルして検索器を作る
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - 検索する正規表現パターンのリスト
+    /// * `options` - 検索オプション（すべてのパターンに共通で適用される）
+    ///
+    /// # Returns
+    ///
+    /// 検索器、またはいずれかの正規表現パターンが無効な場合のエラー
+    pub fn multi(patterns: &[&str], options: &SearchOptions) -> Result<Self, SearchError> {
+        let patterns = PatternSet::new(patterns, options)?;
+        Ok(Self {
+            compiled: CompiledSearch::new(patterns, options)?,
+            options: options.clone(),
+        })
+    }
+
+    /// パターンの代わりに `matcher` で行内のマッチを探す検索器を作る
+    ///
+    /// オプションの扱いは `search_with_matcher` と同じ。
+    ///
+    /// # Arguments
+    ///
+    /// * `matcher` - 行内のマッチを探すマッチャー
+    /// * `options` - 検索オプション
+    ///
+    /// # Returns
+    ///
+    /// 検索器、または除外パターンが無効な場合のエラー
+    pub fn with_matcher(
+        matcher: impl Matcher + 'static,
+        options: &SearchOptions,
+    ) -> Result<Self, SearchError> {
+        Ok(Self {
+            compiled: CompiledSearch::new(PatternSet::Custom(Arc::new(matcher)), options)?,
+            options: options.clone(),
+        })
+    }
+
+    /// 検索器を作ったときのオプションを返す
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// コンパイル済みのパターンでファイルを検索する
+    ///
+    /// 結果は同じパターンとオプションで `search_with_options` を呼んだ場合と同じになる。
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - 検索対象のファイルリスト
+    ///
+    /// # Returns
+    ///
+    /// 検索結果と打ち切り情報
+    pub fn search(&self, files: &[FileInput]) -> SearchOutput {
+        let compiled = self.compiled.restarted(&self.options);
+        search_compiled(&compiled, files, &self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::search_with_options;
+
+    #[test]
+    fn test_searcher_matches_stateless_search() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "key=1\nKEY=2\nnone".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "key=3".to_string(),
+            },
+        ];
+        let options = SearchOptions::new().case_sensitive(false).max_results(2);
+        let searcher = Searcher::new(r"key=(\d)", &options).unwrap();
+        let expected = search_with_options(r"key=(\d)", &files, &options).unwrap();
+        for _ in 0..2 {
+            let output = searcher.search(&files);
+            assert_eq!(output.results, expected.results);
+            assert!(output.truncated);
+        }
+
+        let searcher = Searcher::multi(&["key", "none"], &SearchOptions::new()).unwrap();
+        let indices: Vec<usize> = searcher
+            .search(&files)
+            .results
+            .iter()
+            .map(|r| r.pattern_index)
+            .collect();
+        assert_eq!(indices, vec![0, 1, 0]);
+        assert!(Searcher::new("(", &SearchOptions::new()).is_err());
+    }
+}
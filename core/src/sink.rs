@@ -0,0 +1,271 @@
+use core::ops::ControlFlow;
+
+use crate::records::Record;
+use crate::{
+    FileInput, FileScan, ScanProgress, SearchError, SearchOptions, SpanMatch, Stop,
+    compile_patterns, scan_file,
+};
+
+/// 走査中に見つかったマッチ（検索したファイルの内容を借用する）
+///
+/// `MatchResult` と違い文字列を複製しないため、受け取るたびに確保は発生しない。
+/// 位置の意味は `MatchResult` の同名のフィールドと同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'a> {
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// マッチしたパターンの位置（0ベース）
+    pub pattern_index: usize,
+    /// マッチ開始位置の列番号（1ベース、`SearchOptions::column_unit` の単位）
+    pub column: u32,
+    /// マッチ終了位置の列番号（1ベース、この位置の文字は含まない）
+    pub end_column: u32,
+    /// マッチした文字列（`invert_match` の場合は空）
+    pub match_text: &'a str,
+    /// ファイル先頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub byte_offset: usize,
+    /// 行頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub line_byte_offset: usize,
+    /// 行のテキスト（`max_line_length` や `preview_width` で切り詰めない）
+    pub line_text: &'a str,
+}
+
+/// `search_sink` が走査中にマッチを渡す先
+///
+/// `ControlFlow::Break(())` を返すと、その時点で検索全体を打ち切る。
+/// `FnMut(&FileInput, &Match) -> ControlFlow<()>` のクロージャもそのまま渡せる。
+pub trait Sink {
+    /// マッチが見つかるたびに呼ばれる
+    fn matched(&mut self, file: &FileInput, m: &Match) -> ControlFlow<()>;
+}
+
+impl<F> Sink for F
+where
+    F: FnMut(&FileInput, &Match) -> ControlFlow<()>,
+{
+    fn matched(&mut self, file: &FileInput, m: &Match) -> ControlFlow<()> {
+        self(file, m)
+    }
+}
+
+/// `search_sink` の検索の終わり方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkOutcome {
+    /// すべてのファイルを検索した
+    Finished,
+    /// `Sink` が中断を求めた
+    Interrupted,
+    /// `max_results` の上限に達した
+    Truncated,
+    /// `timeout_ms` の時間を過ぎた
+    TimedOut,
+    /// `cancel_token` で打ち切りを求められた
+    Cancelled,
+}
+
+impl FileScan<'_> {
+    /// 行番号と行内のマッチから、ファイルの内容を借用したマッチを作る
+    pub(crate) fn borrowed_match(&self, line_idx: usize, line_match: SpanMatch) -> Match<'_> {
+        let Record { line, offset, text } = self.lines[line_idx];
+        let pattern_index = line_match.pattern_index;
+        let span = match self.normalized.get(line_idx) {
+            Some(normalized) => normalized.map_match(line_match, text).span,
+            None => line_match.span,
+        };
+        let column_unit = self.options.column_unit;
+        Match {
+            line: line as u32,
+            pattern_index,
+            column: column_unit.column(text, span.start),
+            end_column: column_unit.column(text, span.end),
+            match_text: &text[span.clone()],
+            byte_offset: offset + span.start,
+            line_byte_offset: span.start,
+            line_text: text,
+        }
+    }
+}
+
+/// パターンでファイルを検索し、見つかったマッチを順に `sink` に渡す
+///
+/// 結果ごとに `MatchResult` を組み立てず、ファイルの内容を借用した `Match` を渡すため、
+/// マッチの数を数える・ストリームに書き出すといった大量の結果を扱う処理でも
+/// マッチごとの確保が発生しない。前後のコンテキストやキャプチャグループは渡さず、
+/// 結果を集めないため `sort`・`relevance`・`report` は無視する。`offset`・上限・
+/// `timeout_ms`・`cancel_token`・`invert_match` などは `search_with_options` と同じように働く。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト
+/// * `options` - 検索オプション
+/// * `sink` - マッチを渡す先
+///
+/// # Returns
+///
+/// 検索の終わり方、または正規表現パターンが無効な場合のエラー
+///
+/// ```
+/// use std::ops::ControlFlow;
+///
+/// use simple_find_core::{FileInput, Match, SearchOptions, SinkOutcome, search_sink};
+///
+/// let files = vec![FileInput {
+///     path: "a.txt".to_string(),
+///     content: "foo bar\nfoo".to_string(),
+/// }];
+/// let mut bytes = 0;
+/// let mut add_bytes = |_: &FileInput, m: &Match| {
+///     bytes += m.match_text.len();
+///     ControlFlow::Continue(())
+/// };
+/// let outcome = search_sink("foo", &files, &SearchOptions::new(), &mut add_bytes).unwrap();
+/// assert_eq!(outcome, SinkOutcome::Finished);
+/// assert_eq!(bytes, 6);
+/// ```
+pub fn search_sink<S: Sink + ?Sized>(
+    pattern: &str,
+    files: &[FileInput],
+    options: &SearchOptions,
+    sink: &mut S,
+) -> Result<SinkOutcome, SearchError> {
+    let compiled = compile_patterns(&[pattern], options)?;
+    let mut progress = ScanProgress {
+        skip: options.offset,
+        ..Default::default()
+    };
+
+    for f in files {
+        if options.skips_file(f) {
+            continue;
+        }
+        let mut found = 0;
+        let stop = scan_file(f, &compiled, options, &mut progress, |scan, line_idx, m| {
+            found += 1;
+            sink.matched(f, &scan.borrowed_match(line_idx, m))
+        });
+        progress.found += found;
+        match stop {
+            Some(Stop::Interrupted) => return Ok(SinkOutcome::Interrupted),
+            Some(Stop::TotalLimit) => return Ok(SinkOutcome::Truncated),
+            Some(Stop::Timeout) => return Ok(SinkOutcome::TimedOut),
+            Some(Stop::Cancelled) => return Ok(SinkOutcome::Cancelled),
+            Some(Stop::FileLimit) | None => {}
+        }
+    }
+
+    Ok(SinkOutcome::Finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    #[cfg(feature = "normalization")]
+    use crate::{ColumnUnit, search_with_options};
+
+    /// ファイルごとのマッチ数を数える
+    struct Counter {
+        counts: Vec<(String, usize)>,
+        limit: usize,
+    }
+
+    impl Sink for Counter {
+        fn matched(&mut self, file: &FileInput, _m: &Match) -> ControlFlow<()> {
+            match self.counts.last_mut() {
+                Some((path, count)) if *path == file.path => *count += 1,
+                _ => self.counts.push((file.path.clone(), 1)),
+            }
+            if self.counts.iter().map(|(_, count)| count).sum::<usize>() == self.limit {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    }
+
+    fn files() -> Vec<FileInput> {
+        vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "x\ny x".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "😀x".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_search_sink_interrupt_and_limits() {
+        let files = files();
+        let mut counter = Counter {
+            counts: Vec::new(),
+            limit: usize::MAX,
+        };
+        let outcome = search_sink("x", &files, &SearchOptions::new(), &mut counter).unwrap();
+        assert_eq!(outcome, SinkOutcome::Finished);
+        assert_eq!(
+            counter.counts,
+            vec![("a.txt".to_string(), 2), ("b.txt".to_string(), 1)]
+        );
+
+        let mut counter = Counter {
+            counts: Vec::new(),
+            limit: 2,
+        };
+        let outcome = search_sink("x", &files, &SearchOptions::new(), &mut counter).unwrap();
+        assert_eq!(outcome, SinkOutcome::Interrupted);
+        assert_eq!(counter.counts, vec![("a.txt".to_string(), 2)]);
+
+        let mut counter = Counter {
+            counts: Vec::new(),
+            limit: usize::MAX,
+        };
+        let options = SearchOptions::new().offset(1).max_results(1);
+        let outcome = search_sink("x", &files, &options, &mut counter).unwrap();
+        assert_eq!(outcome, SinkOutcome::Truncated);
+        assert_eq!(counter.counts, vec![("a.txt".to_string(), 1)]);
+    }
+
+    #[cfg(feature = "normalization")]
+    #[test]
+    fn test_search_sink_positions_match_search_results() {
+        let files = files();
+        let options = SearchOptions::new()
+            .column_unit(ColumnUnit::Utf16)
+            .fold_width(true);
+        let output = search_with_options("ｘ", &files, &options).unwrap();
+        let mut matches = Vec::new();
+        search_sink("ｘ", &files, &options, &mut |_: &FileInput, m: &Match| {
+            matches.push((
+                m.line,
+                m.column,
+                m.end_column,
+                m.byte_offset,
+                m.match_text.to_string(),
+            ));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        let expected: Vec<_> = output
+            .results
+            .iter()
+            .map(|r| {
+                (
+                    r.line,
+                    r.column,
+                    r.end_column,
+                    r.byte_offset,
+                    r.match_text.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(expected.len(), 3);
+        assert_eq!(matches, expected);
+    }
+}
@@ -0,0 +1,127 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::cmp::Reverse;
+
+use crate::options::SortOrder;
+use crate::{FileMatches, MatchRef, MatchResult};
+
+/// 並べ替えのキーを取り出せる検索結果
+pub(crate) trait SortKey {
+    /// 並べ替えの間も持っておけるファイルのパス（結果を借用しないもの）
+    type PathKey: Ord + Borrow<str>;
+
+    /// ファイルのパス
+    fn path(&self) -> &str;
+    /// 文字列をコピーせずに取り出したファイルのパス
+    fn path_key(&self) -> Self::PathKey;
+    /// パス・行・列・パターンの位置の組
+    fn position(&self) -> (&str, u32, u32, usize);
+    /// 関連度のスコア
+    fn score(&self) -> i64;
+}
+
+impl SortKey for MatchResult {
+    type PathKey = Arc<str>;
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn path_key(&self) -> Arc<str> {
+        Arc::clone(&self.path)
+    }
+
+    fn position(&self) -> (&str, u32, u32, usize) {
+        (&self.path, self.line, self.column, self.pattern_index)
+    }
+
+    fn score(&self) -> i64 {
+        self.score
+    }
+}
+
+impl<'a> SortKey for MatchRef<'a> {
+    type PathKey = &'a str;
+
+    fn path(&self) -> &str {
+        self.path
+    }
+
+    fn path_key(&self) -> &'a str {
+        self.path
+    }
+
+    fn position(&self) -> (&str, u32, u32, usize) {
+        (self.path, self.line, self.column, self.pattern_index)
+    }
+
+    fn score(&self) -> i64 {
+        0
+    }
+}
+
+/// 検索結果を `order` に従って並べ替える
+///
+/// 検索関数は `SearchOptions::sort` に従ってこの関数で結果を並べるため、Web Worker などで
+/// ファイルを分けて検索した結果をつなげてから呼ぶと、1回で検索した場合と同じ順になる
+/// （`SortOrder::Input` は何もしないため、つなげる順が入力順になる）。
+/// 並べ替えは安定で、各 `SortOrder` の説明にあるキーが等しい結果は元の順を保つ。
+///
+/// # Arguments
+///
+/// * `results` - 並べ替える検索結果
+/// * `order` - 並び順
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, SortOrder, search_with_options, sort_results};
+///
+/// let options = SearchOptions::new();
+/// let search = |path: &str| {
+///     let files = vec![FileInput { path: path.to_string(), content: "x\nx".to_string() }];
+///     search_with_options("x", &files, &options).unwrap().results
+/// };
+/// let mut results = search("b.txt");
+/// results.extend(search("a.txt"));
+/// sort_results(&mut results, SortOrder::Path);
+/// let order: Vec<(&str, u32)> = results.iter().map(|r| (&*r.path, r.line)).collect();
+/// assert_eq!(order, vec![("a.txt", 1), ("a.txt", 2), ("b.txt", 1), ("b.txt", 2)]);
+/// ```
+pub fn sort_results(results: &mut [MatchResult], order: SortOrder) {
+    sort_by_order(results, order);
+}
+
+/// `sort_results` と同じ規則で、キーを取り出せる検索結果を並べ替える
+pub(crate) fn sort_by_order<T: SortKey>(results: &mut [T], order: SortOrder) {
+    match order {
+        SortOrder::Input => {}
+        SortOrder::Path => results.sort_by(|a, b| a.position().cmp(&b.position())),
+        SortOrder::MatchCount => {
+            let mut counts: BTreeMap<T::PathKey, usize> = BTreeMap::new();
+            for result in results.iter() {
+                *counts.entry(result.path_key()).or_default() += 1;
+            }
+            results.sort_by_cached_key(|result| Reverse(counts[result.path()]));
+        }
+        SortOrder::Relevance => results.sort_by_key(|result| Reverse(result.score())),
+    }
+}
+
+/// ファイルごとにまとめた検索結果を `order` に従って並べ替える
+///
+/// `Relevance` の場合はファイル内の結果も関連度の順にし、最も関連度の高い結果でファイルを並べる。
+/// それ以外ではファイル内の結果は行・列の順のままにする。
+pub(crate) fn sort_file_matches(files: &mut [FileMatches], order: SortOrder) {
+    match order {
+        SortOrder::Input => {}
+        SortOrder::Path => files.sort_by(|a, b| a.path.cmp(&b.path)),
+        SortOrder::MatchCount => files.sort_by_key(|f| Reverse(f.matches.len())),
+        SortOrder::Relevance => {
+            for f in files.iter_mut() {
+                f.matches
+                    .sort_by_key(|line_match| Reverse(line_match.score));
+            }
+            files.sort_by_key(|f| Reverse(f.matches.first().map_or(0, |m| m.score)));
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+
+use memchr::memmem;
+
+use crate::{
+    CompiledSearch, MatchResult, RecordSeparator, ScanProgress, SearchError, SearchOptions, Stop,
+    compile_patterns, scan_text,
+};
+
+/// ファイルの内容を少しずつ受け取りながら検索する
+///
+/// ネットワークやディスクから読み込んだ巨大なファイルを、全体をメモリに載せずに検索するために使う。
+/// `push_chunk` で受け取った内容のうち、終わりまで揃った行だけを検索してその結果を返し、
+/// 途中で切れている最後の行は次のチャンクまで持ち越す。行番号とバイトオフセットはファイル
+/// 全体での位置になり、チャンクの境目が行や `\r\n` の途中にあっても一度に検索した場合と
+/// 同じ結果になる。最後に `finish` を呼ぶと、残りの行を検索する。
+///
+/// 前後のコンテキストは返さず、結果は見つかった順に返すため `sort`・`relevance` は無視する。
+/// `RecordSeparator::Paragraph` と空の `RecordSeparator::Custom` は区切りの位置を決められない
+/// ため、`finish` まで内容をためてから検索する。
+///
+/// ```
+/// use simple_find_core::{ChunkedSearch, SearchOptions};
+///
+/// let mut search = ChunkedSearch::new("needle", "big.log", &SearchOptions::new()).unwrap();
+/// assert!(search.push_chunk("first line\nneed").is_empty());
+/// let results = search.push_chunk("le here\nlast ");
+/// assert_eq!((results[0].line, results[0].byte_offset), (2, 11));
+/// assert!(search.push_chunk("needle").is_empty());
+/// assert_eq!(search.finish()[0].line, 3);
+/// ```
+pub struct ChunkedSearch {
+    /// コンパイル済みのパターン
+    compiled: CompiledSearch,
+    /// 検索オプション（コンテキストと上限は取り除いたもの）
+    options: SearchOptions,
+    /// 結果に入れるファイルのパス
+    path: Arc<str>,
+    /// まだ検索していない内容
+    pending: String,
+    /// `pending` の先頭からこの長さまでには区切りがないことを確かめてある
+    searched: usize,
+    /// `pending` の先頭の行番号（1ベース）
+    line: usize,
+    /// `pending` の先頭のファイル先頭からの位置（バイトオフセット）
+    offset: usize,
+    /// `offset` の読み飛ばしや走査した行数など、チャンクをまたいで引き継ぐ進み具合
+    progress: ScanProgress,
+    /// 返す結果の数の上限（`max_count_per_file` と `max_results` の小さい方）
+    limit: usize,
+    /// これまでに返した結果の数
+    found: usize,
+    /// 上限に達してまだマッチが残っていたかどうか
+    truncated: bool,
+    /// `timeout_ms` の時間を過ぎたかどうか
+    timed_out: bool,
+    /// `cancel_token` で打ち切りを求められたかどうか
+    cancelled: bool,
+    /// `finish` を呼んだかどうか
+    finished: bool,
+}
+
+impl ChunkedSearch {
+    /// パターンをコンパイルして、1ファイル分の検索を始める
+    ///
+    /// `timeout_ms` の制限時間はここから数える。
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - 検索する正規表現パターン
+    /// * `path` - 結果に入れるファイルのパス
+    /// * `options` - 検索オプション
+    ///
+    /// # Returns
+    ///
+    /// 検索の状態、または正規表現パターンが無効な場合のエラー
+    pub fn new(pattern: &str, path: &str, options: &SearchOptions) -> Result<Self, SearchError> {
+        let compiled = compile_patterns(&[pattern], options)?;
+        let limit = options
+            .max_count_per_file
+            .unwrap_or(usize::MAX)
+            .min(options.max_results.unwrap_or(usize::MAX));
+        let options = options.clone().before_context(0).after_context(0);
+        Ok(Self {
+            progress: ScanProgress {
+                skip: options.offset,
+                ..Default::default()
+            },
+            compiled,
+            options: SearchOptions {
+                max_count_per_file: None,
+                max_results: None,
+                ..options
+            },
+            path: Arc::from(path),
+            pending: String::new(),
+            searched: 0,
+            line: 1,
+            offset: 0,
+            limit,
+            found: 0,
+            truncated: false,
+            timed_out: false,
+            cancelled: false,
+            finished: false,
+        })
+    }
+
+    /// 内容の続きを受け取り、終わりまで揃った行の検索結果を返す
+    ///
+    /// 上限や制限時間、`cancel_token` で打ち切った後や `finish` の後に受け取った内容は検索しない。
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - 内容の続き
+    ///
+    /// # Returns
+    ///
+    /// 新しく見つかった検索結果のリスト
+    pub fn push_chunk(&mut self, chunk: &str) -> Vec<MatchResult> {
+        if self.is_done() {
+            return Vec::new();
+        }
+        self.pending.push_str(chunk);
+        let Some(end) = self.complete_len() else {
+            return Vec::new();
+        };
+        let rest = self.pending.split_off(end);
+        let text = core::mem::replace(&mut self.pending, rest);
+        self.scan(&text)
+    }
+
+    /// 持ち越していた最後の行を検索し、その結果を返す
+    ///
+    /// # Returns
+    ///
+    /// 新しく見つかった検索結果のリスト
+    pub fn finish(&mut self) -> Vec<MatchResult> {
+        if self.is_done() {
+            return Vec::new();
+        }
+        self.finished = true;
+        let text = core::mem::take(&mut self.pending);
+        self.scan(&text)
+    }
+
+    /// 上限に達してまだマッチが残っていたため、検索を打ち切ったかどうかを返す
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// `timeout_ms` の時間を過ぎたため、検索を打ち切ったかどうかを返す
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// `cancel_token` で打ち切りを求められたため、検索を打ち切ったかどうかを返す
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// これ以上検索しないかどうかを返す
+    fn is_done(&self) -> bool {
+        self.finished || self.truncated || self.timed_out || self.cancelled
+    }
+
+    /// `pending` のうち、終わりまで揃ったレコードの長さを返す
+    ///
+    /// 前回までに確かめた部分は探し直さず、チャンクの境目で切れた区切りも見つかるように
+    /// 区切りの長さより1バイト短い分だけ戻ってから、新しく受け取った部分を探す。
+    /// 呼び出し側は、返した長さの分を `pending` から取り除く。
+    fn complete_len(&mut self) -> Option<usize> {
+        let separator = match &self.options.record_separator {
+            RecordSeparator::Line => self.options.line_terminator.pattern(),
+            RecordSeparator::Custom(separator) if !separator.is_empty() => separator.clone(),
+            RecordSeparator::Paragraph | RecordSeparator::Custom(_) => return None,
+        };
+        let start = self.searched.saturating_sub(separator.len() - 1);
+        let end = memmem::rfind(&self.pending.as_bytes()[start..], separator.as_bytes())
+            .map(|pos| start + pos + separator.len());
+        // 最後の区切りより後ろには区切りがない
+        self.searched = self.pending.len() - end.unwrap_or(0);
+        end
+    }
+
+    /// 揃ったレコードを検索し、行番号と位置をファイル全体でのものにした結果を返す
+    fn scan(&mut self, text: &str) -> Vec<MatchResult> {
+        let mut results = Vec::new();
+        let Self {
+            compiled,
+            options,
+            path,
+            line,
+            offset,
+            progress,
+            limit,
+            found,
+            truncated,
+            ..
+        } = self;
+        let stop = scan_text(
+            text,
+            *line,
+            compiled,
+            options,
+            progress,
+            |scan, line_idx, line_match| {
+                if *found == *limit {
+                    *truncated = true;
+                    return ControlFlow::Break(());
+                }
+                *found += 1;
+                let mut result = scan
+                    .build_line_match(line_idx, line_match)
+                    .into_result(Arc::clone(path));
+                result.byte_offset += *offset;
+                results.push(result);
+                ControlFlow::Continue(())
+            },
+        );
+        match stop {
+            Some(Stop::Timeout) => self.timed_out = true,
+            Some(Stop::Cancelled) => self.cancelled = true,
+            _ => {}
+        }
+        self.line += self.options.line_terminator.count_in(text);
+        self.offset += text.len();
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::{FileInput, LineTerminator, search_with_options};
+
+    /// 内容を `size` バイトずつ（文字の境目に合わせて）渡した結果を返す
+    fn search_in_chunks(
+        pattern: &str,
+        content: &str,
+        size: usize,
+        options: &SearchOptions,
+    ) -> Vec<MatchResult> {
+        let mut search = ChunkedSearch::new(pattern, "a.txt", options).unwrap();
+        let mut results = Vec::new();
+        let mut rest = content;
+        while !rest.is_empty() {
+            let mut end = size.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            results.extend(search.push_chunk(&rest[..end]));
+            rest = &rest[end..];
+        }
+        results.extend(search.finish());
+        results
+    }
+
+    #[test]
+    fn test_chunked_search_matches_whole_search() {
+        let content = "foo\r\nbar foo\r\n\r\n日本foo\nlast foo";
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: content.to_string(),
+        }];
+        let cases = [
+            SearchOptions::new(),
+            SearchOptions::new().line_terminator(LineTerminator::Crlf),
+            SearchOptions::new().record_separator(RecordSeparator::Custom("\r\n".to_string())),
+            SearchOptions::new().record_separator(RecordSeparator::Custom("o\r\n".to_string())),
+            SearchOptions::new().record_separator(RecordSeparator::Paragraph),
+            SearchOptions::new().line_range(2..=4).offset(1),
+            SearchOptions::new().invert_match(true),
+        ];
+        for options in &cases {
+            let expected = search_with_options("fo+$", &files, options)
+                .unwrap()
+                .results;
+            for size in [1, 2, 3, 7, 100] {
+                assert_eq!(
+                    search_in_chunks("fo+$", content, size, options),
+                    expected,
+                    "size {} with {:?}",
+                    size,
+                    options
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunked_search_finds_separator_split_across_chunks() {
+        let options =
+            SearchOptions::new().record_separator(RecordSeparator::Custom("<>".to_string()));
+        let mut search = ChunkedSearch::new("x", "a.txt", &options).unwrap();
+        assert!(search.push_chunk("a x<").is_empty());
+        // 前回探した部分の末尾で切れていた区切りが揃った時点で結果を返す
+        let results = search.push_chunk(">b");
+        assert_eq!(results.len(), 1);
+        assert_eq!(&*results[0].line_text, "a x");
+        assert!(search.push_chunk("<").is_empty());
+        assert!(search.push_chunk("x>").is_empty());
+        assert_eq!(search.finish()[0].byte_offset, 7);
+    }
+
+    #[test]
+    fn test_chunked_search_stops_at_limit() {
+        let options = SearchOptions::new().max_results(2);
+        let mut search = ChunkedSearch::new("x", "a.txt", &options).unwrap();
+        assert_eq!(search.push_chunk("x\nx").len(), 1);
+        assert!(!search.truncated());
+        assert_eq!(search.push_chunk("\nx\n").len(), 1);
+        assert!(search.truncated());
+        assert!(search.push_chunk("x\n").is_empty());
+        assert!(search.finish().is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "fancy-regex")]
+use crate::RegexEngine;
+use crate::engine::parser_builder;
+use crate::{PatternSet, SearchOptions, escape_literal};
+
+/// 検索に使えるパターンの情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternInfo {
+    /// キャプチャグループの数（マッチ全体を表す0番は含まない）
+    pub capture_count: usize,
+    /// 名前付きキャプチャグループの名前（出現順）
+    pub capture_names: Vec<String>,
+    /// 正規表現の記号を含まず、文字列そのものにマッチするかどうか
+    pub is_literal: bool,
+    /// 空文字列にマッチし得るかどうか（`a*` など、すべての位置でマッチする）
+    pub matches_empty: bool,
+}
+
+/// パターンが無効な理由と位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    /// 人が読むための無効な理由
+    pub message: String,
+    /// パターン内の無効な部分（0ベースのバイト位置、位置を特定できない場合は `None`）
+    pub span: Option<Range<usize>>,
+    /// 無効な部分の開始列番号（1ベース、`SearchOptions::column_unit` の単位）
+    pub column: Option<u32>,
+    /// 無効な部分の直後の列番号（1ベース、`SearchOptions::column_unit` の単位）
+    pub end_column: Option<u32>,
+    /// 修正したパターンの候補（いずれも有効なことを確かめたもの、見込みの高い順）
+    pub suggestions: Vec<String>,
+}
+
+impl PatternError {
+    /// 位置のないエラーを作る
+    pub(crate) fn new(message: String) -> Self {
+        Self {
+            message,
+            span: None,
+            column: None,
+            end_column: None,
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+/// パターンを検索と同じ規則でコンパイルし、使えるかどうかを確かめる
+///
+/// 無効な場合は理由と、パターン内の問題のある範囲を返すため、検索ボックスで入力中に
+/// 該当する文字へ下線を引くことができる。位置はユーザーが入力したパターンに対するもので、
+/// 列番号は `SearchOptions::column_unit` に従う（JavaScript の文字列には `ColumnUnit::Utf16`）。
+/// 大きすぎるパターン（`size_limit` を超えるもの）など、位置を特定できないエラーでは
+/// `span` は `None` になる。`literal` と `max_edit_distance` では正規表現として解釈しない。
+/// `validate` フィーチャーで使える。
+///
+/// 無効な場合は、よくある誤りを直したパターン（問題の文字をエスケープしたもの、括弧の対応を
+/// 取ったもの、全体をリテラルとしてエスケープしたもの）のうち有効なものを `suggestions` に入れるため、
+/// UI で「もしかして」としてワンクリックで修正できる。
+///
+/// # Arguments
+///
+/// * `pattern` - 確かめるパターン
+/// * `options` - 検索オプション
+///
+/// # Returns
+///
+/// パターンの情報、または無効な理由と位置
+///
+/// ```
+/// use simple_find_core::{SearchOptions, validate_pattern};
+///
+/// let error = validate_pattern("price (usd", &SearchOptions::new()).unwrap_err();
+/// assert_eq!(error.span, Some(6..7));
+/// assert_eq!(error.column, Some(7));
+/// assert_eq!(error.suggestions, vec![r"price \(usd", "price (usd)"]);
+///
+/// let info = validate_pattern(r"(?<year>\d{4})-(\d{2})", &SearchOptions::new()).unwrap();
+/// assert_eq!(info.capture_count, 2);
+/// assert_eq!(info.capture_names, vec!["year"]);
+/// ```
+pub fn validate_pattern(
+    pattern: &str,
+    options: &SearchOptions,
+) -> Result<PatternInfo, PatternError> {
+    check(pattern, options).map_err(|mut error| {
+        error.suggestions = suggest_repairs(pattern, error.span.as_ref(), options);
+        error
+    })
+}
+
+/// 修正したパターンの候補のうち、有効なものを返す
+fn suggest_repairs(
+    pattern: &str,
+    span: Option<&Range<usize>>,
+    options: &SearchOptions,
+) -> Vec<String> {
+    let mut candidates = Vec::new();
+    if let Some(span) = span
+        && pattern[span.start..].starts_with(|c: char| regex_syntax::is_meta_character(c))
+    {
+        candidates.push(format!(
+            r"{}\{}",
+            &pattern[..span.start],
+            &pattern[span.start..]
+        ));
+    }
+    candidates.push(balance_brackets(pattern));
+    candidates.push(escape_literal(pattern));
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if candidate != pattern
+            && !suggestions.contains(&candidate)
+            && check(&candidate, options).is_ok()
+        {
+            suggestions.push(candidate);
+        }
+    }
+    suggestions
+}
+
+/// 対応する `(` のない `)` の前に `(` を補い、閉じていない `[` と `(` を末尾で閉じる
+fn balance_brackets(pattern: &str) -> String {
+    let mut open_groups = 0;
+    let mut unopened_groups = 0;
+    let mut in_class = false;
+    let mut escaped = false;
+    for c in pattern.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ']' if in_class => in_class = false,
+            _ if in_class => {}
+            '[' => in_class = true,
+            '(' => open_groups += 1,
+            ')' if open_groups > 0 => open_groups -= 1,
+            ')' => unopened_groups += 1,
+            _ => {}
+        }
+    }
+
+    let mut balanced = "(".repeat(unopened_groups);
+    balanced.push_str(pattern);
+    if escaped {
+        balanced.push('\\');
+    }
+    if in_class {
+        balanced.push(']');
+    }
+    balanced.push_str(&")".repeat(open_groups));
+    balanced
+}
+
+/// パターンを検索と同じ規則でコンパイルする
+fn check(pattern: &str, options: &SearchOptions) -> Result<PatternInfo, PatternError> {
+    let as_regex = !options.literal && options.max_edit_distance.is_none();
+    #[cfg(feature = "fancy-regex")]
+    if as_regex && options.regex_engine == RegexEngine::Fancy {
+        return crate::fancy::check(pattern, options).map_err(|e| PatternError::new(e.to_string()));
+    }
+    if as_regex && let Err(error) = parser_builder(options).build().parse(pattern) {
+        let (message, span) = match &error {
+            regex_syntax::Error::Parse(e) => (e.kind().to_string(), e.span()),
+            regex_syntax::Error::Translate(e) => (e.kind().to_string(), e.span()),
+            _ => return Err(PatternError::new(error.to_string())),
+        };
+        let span = span.start.offset..span.end.offset;
+        return Err(PatternError {
+            message,
+            column: Some(options.column_unit.column(pattern, span.start)),
+            end_column: Some(options.column_unit.column(pattern, span.end)),
+            span: Some(span),
+            suggestions: Vec::new(),
+        });
+    }
+
+    let patterns =
+        PatternSet::new(&[pattern], options).map_err(|e| PatternError::new(e.to_string()))?;
+    let PatternSet::Regex { regexes, .. } = &patterns else {
+        return Ok(PatternInfo {
+            capture_count: 0,
+            capture_names: Vec::new(),
+            is_literal: true,
+            matches_empty: false,
+        });
+    };
+    let regex = &regexes[0];
+    let (is_literal, matches_empty) = if as_regex {
+        let hir = parser_builder(options)
+            .build()
+            .parse(pattern)
+            .map_err(|e| PatternError::new(e.to_string()))?;
+        let properties = hir.properties();
+        (properties.is_literal(), properties.minimum_len() == Some(0))
+    } else {
+        (true, pattern.is_empty())
+    };
+
+    Ok(PatternInfo {
+        capture_count: regex.captures_len() - 1,
+        capture_names: regex
+            .capture_names()
+            .flatten()
+            .map(str::to_string)
+            .collect(),
+        is_literal,
+        matches_empty,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::ColumnUnit;
+
+    #[test]
+    fn test_validate_pattern_reports_position_in_column_unit() {
+        let options = SearchOptions::new().column_unit(ColumnUnit::Utf16);
+        let error = validate_pattern("😀[a-", &options).unwrap_err();
+        assert_eq!(error.span, Some(4..5));
+        assert_eq!((error.column, error.end_column), (Some(3), Some(4)));
+        assert!(!error.message.is_empty());
+
+        // 翻訳時のエラー（存在しない Unicode クラス）も位置を返す
+        let error = validate_pattern(r"a\p{Nope}", &SearchOptions::new()).unwrap_err();
+        assert_eq!(error.span, Some(1..9));
+    }
+
+    #[test]
+    fn test_validate_pattern_suggestions() {
+        let suggestions = |pattern: &str| {
+            validate_pattern(pattern, &SearchOptions::new())
+                .unwrap_err()
+                .suggestions
+        };
+        assert_eq!(suggestions("[abc"), vec![r"\[abc", "[abc]"]);
+        assert_eq!(suggestions("a)b"), vec![r"a\)b", "(a)b"]);
+        assert_eq!(suggestions("*.log"), vec![r"\*.log", r"\*\.log"]);
+        assert_eq!(suggestions(r"tab\"), vec![r"tab\\"]);
+        // 修正しても有効にならない候補は含めない
+        assert_eq!(suggestions(r"\p{Nope}"), vec![r"\\p\{Nope\}"]);
+    }
+
+    #[test]
+    fn test_validate_pattern_info() {
+        let info = validate_pattern("a*", &SearchOptions::new()).unwrap();
+        assert!(info.matches_empty);
+        assert!(!info.is_literal);
+        assert!(
+            validate_pattern("abc", &SearchOptions::new())
+                .unwrap()
+                .is_literal
+        );
+
+        // リテラルとして扱う場合は記号もそのまま
+        let options = SearchOptions::new().literal(true);
+        let info = validate_pattern("(a", &options).unwrap();
+        assert!(info.is_literal);
+        assert_eq!(info.capture_count, 0);
+
+        // 位置を特定できないエラー
+        let options = SearchOptions::new().size_limit(10);
+        let error = validate_pattern(r"\w{100}", &options).unwrap_err();
+        assert_eq!(error.span, None);
+        assert!(error.message.contains("size limit"));
+    }
+}
@@ -0,0 +1,244 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::ops::Range;
+use std::sync::Mutex;
+
+use self_cell::self_cell;
+use vectorscan_rs::{BlockDatabase, BlockScanner, Flag, Pattern, Scan};
+
+use crate::normalize::normalize_pattern;
+use crate::{Anchor, Matcher, SearchError, SearchOptions, SpanMatch, engine, is_word_bounded};
+
+/// 使い終わったスキャナー（スレッドごとの作業領域）を使い回すための置き場
+type Scanners<'db> = Mutex<Vec<BlockScanner<'db>>>;
+
+self_cell!(
+    /// コンパイル済みのデータベースと、それを参照するスキャナーの置き場
+    struct ScannerPool {
+        owner: BlockDatabase,
+
+        #[not_covariant]
+        dependent: Scanners,
+    }
+
+    impl {Debug}
+);
+
+/// Vectorscan（Hyperscan の移植）でコンパイルしたパターンで行内のマッチを探すマッチャー
+///
+/// すべてのパターンを1つのデータベースにまとめ、SIMD を使って1回の走査で探すため、
+/// 大量のログを数百から数千のパターンで検索する場合に向く。`Matcher` を実装しているため、
+/// `search_with_matcher` や `Searcher::with_matcher` に渡して使う。`vectorscan` フィーチャーで使える
+/// （C++ のライブラリをビルドするため、ネイティブ向けのビルドのみ）。
+///
+/// パターンは `case_sensitive`・`whole_word`・`literal`・`anchor`・`regex_flags` と
+/// 正規化のオプションに従ってコンパイルする。Vectorscan はマッチの終わりをすべて報告するため、
+/// パターンごとに左端で最長のマッチから重ならないものを選んで返す（`swap_greed` は結果に影響しない）。
+/// キャプチャグループ、後方参照、先読み・後読みは使えず、空文字列にマッチするパターンはエラーになる。
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, VectorscanMatcher, search_with_matcher};
+///
+/// let files = vec![FileInput {
+///     path: "app.log".to_string(),
+///     content: "INFO start\nERROR disk full\nWARN retry".to_string(),
+/// }];
+/// let options = SearchOptions::new();
+/// let matcher = VectorscanMatcher::new(&["ERROR", "WARN"], &options).unwrap();
+/// let output = search_with_matcher(matcher, &files, &options).unwrap();
+/// assert_eq!(output.results.len(), 2);
+/// assert_eq!(output.results[1].pattern_index, 1);
+/// ```
+#[derive(Debug)]
+pub struct VectorscanMatcher {
+    /// コンパイル済みのデータベースとスキャナー
+    pool: ScannerPool,
+    /// 単語単位でのみマッチさせるかどうか
+    whole_word: bool,
+    /// マッチの位置の固定方法
+    anchor: Anchor,
+}
+
+impl VectorscanMatcher {
+    /// オプションに従ってパターンを1つのデータベースにコンパイルする
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - パターンのリスト（`SpanMatch::pattern_index` はこの順の位置）
+    /// * `options` - 検索オプション
+    ///
+    /// # Returns
+    ///
+    /// マッチャー、またはいずれかのパターンをコンパイルできなかった場合のエラー
+    pub fn new(patterns: &[&str], options: &SearchOptions) -> Result<Self, SearchError> {
+        let flags = vectorscan_flags(options);
+        let patterns = patterns
+            .iter()
+            .enumerate()
+            .map(|(index, pattern)| {
+                let expression = build_vectorscan_pattern(pattern, options);
+                Pattern::new(expression.into_bytes(), flags, Some(index as u32))
+            })
+            .collect();
+        let database = BlockDatabase::new(patterns)
+            .map_err(|source| SearchError::VectorscanMatcher(Arc::new(source)))?;
+
+        Ok(Self {
+            pool: ScannerPool::new(database, |_| Mutex::new(Vec::new())),
+            whole_word: options.whole_word,
+            anchor: options.anchor,
+        })
+    }
+
+    /// 行を走査し、見つかったマッチ（パターンの位置と範囲）ごとに `on_match` を呼ぶ
+    ///
+    /// スキャナーは空いているものを使い、なければ作る。走査に失敗した場合は
+    /// それ以降のマッチがないものとして扱う。
+    fn scan(&self, line: &str, mut on_match: impl FnMut(usize, Range<usize>) -> Scan) {
+        self.pool.with_dependent(|database, scanners| {
+            let idle = scanners.lock().unwrap_or_else(|e| e.into_inner()).pop();
+            let Some(mut scanner) = idle.or_else(|| BlockScanner::new(database).ok()) else {
+                return;
+            };
+            let _ = scanner.scan(line.as_bytes(), |id, from, to, _flags| {
+                on_match(id as usize, from as usize..to as usize)
+            });
+            scanners
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(scanner);
+        });
+    }
+
+    /// マッチの範囲が `whole_word` と `anchor` の条件を満たすかどうかを返す
+    fn accepts(&self, line: &str, span: &Range<usize>) -> bool {
+        (!self.whole_word || is_word_bounded(line, span.clone())) && self.anchor.accepts(line, span)
+    }
+}
+
+impl Matcher for VectorscanMatcher {
+    fn find_in_line(&self, line: &str) -> Vec<SpanMatch> {
+        let mut candidates = Vec::new();
+        self.scan(line, |pattern_index, span| {
+            if self.accepts(line, &span) {
+                candidates.push(SpanMatch::new(pattern_index, span));
+            }
+            Scan::Continue
+        });
+
+        // パターンごとに、左端で最長のマッチから重ならないものを選ぶ
+        candidates.sort_by_key(|m| (m.pattern_index, m.span.start, Reverse(m.span.end)));
+        let mut found: Vec<SpanMatch> = Vec::new();
+        for candidate in candidates {
+            let overlaps = found.last().is_some_and(|last| {
+                last.pattern_index == candidate.pattern_index
+                    && candidate.span.start < last.span.end
+            });
+            if !overlaps {
+                found.push(candidate);
+            }
+        }
+        found.sort_by_key(|m| (m.span.start, m.pattern_index));
+        found
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        let mut matched = false;
+        self.scan(line, |_, span| {
+            if self.accepts(line, &span) {
+                matched = true;
+                Scan::Terminate
+            } else {
+                Scan::Continue
+            }
+        });
+        matched
+    }
+}
+
+/// オプションに従って Vectorscan のフラグを組み立てる
+///
+/// 開始位置を得るため、常に `SOM_LEFTMOST` を付ける。
+fn vectorscan_flags(options: &SearchOptions) -> Flag {
+    let flags = &options.regex_flags;
+    let mut vectorscan = Flag::UTF8 | Flag::SOM_LEFTMOST;
+    vectorscan.set(Flag::CASELESS, !options.case_sensitive);
+    vectorscan.set(Flag::MULTILINE, flags.multi_line);
+    vectorscan.set(Flag::DOTALL, flags.dot_all);
+    vectorscan.set(Flag::UCP, flags.unicode);
+    vectorscan
+}
+
+/// オプションに従って Vectorscan のパターン文字列を組み立てる
+///
+/// Vectorscan には先読み・後読みがないため、`whole_word` と `anchor` はパターンには含めず、
+/// 見つかったマッチの範囲で確かめる。`build_regex` と同じく、`literal` モードでは
+/// `ignore_whitespace` を無視する。
+fn build_vectorscan_pattern(pattern: &str, options: &SearchOptions) -> String {
+    let pattern = normalize_pattern(pattern, options);
+    if options.literal {
+        engine::escape(&pattern)
+    } else if options.regex_flags.ignore_whitespace {
+        format!("(?x){}", pattern)
+    } else {
+        pattern
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{FileInput, search_with_matcher};
+
+    #[test]
+    fn test_vectorscan_matcher() {
+        let files = vec![FileInput {
+            path: "a.txt".to_string(),
+            content: "aaa baa\nconcat Cat\nfoo.bar".to_string(),
+        }];
+        let search = |patterns: &[&str], options: &SearchOptions| {
+            let matcher = VectorscanMatcher::new(patterns, options).unwrap();
+            search_with_matcher(matcher, &files, options)
+                .unwrap()
+                .results
+                .iter()
+                .map(|r| (r.line, r.column, r.match_text.clone(), r.pattern_index))
+                .collect::<Vec<_>>()
+        };
+
+        // マッチの終わりごとの報告を、左端で最長の重ならないマッチにまとめる
+        assert_eq!(
+            search(&["a+"], &SearchOptions::new())[..2],
+            [(1, 1, "aaa".to_string(), 0), (1, 6, "aa".to_string(), 0)]
+        );
+
+        // 複数のパターンは列番号順に返す
+        let results = search(&["cat", "con"], &SearchOptions::new());
+        assert_eq!(
+            results,
+            [(2, 1, "con".to_string(), 1), (2, 4, "cat".to_string(), 0)]
+        );
+
+        // 単語単位・大文字小文字の区別・リテラル・位置の固定のオプションが効く
+        let options = SearchOptions::new().case_sensitive(false).whole_word(true);
+        assert_eq!(search(&["cat"], &options), [(2, 8, "Cat".to_string(), 0)]);
+        let options = SearchOptions::new().literal(true);
+        assert_eq!(search(&["o.b"], &options).len(), 1);
+        assert!(search(&["o.c"], &options).is_empty());
+        let options = SearchOptions::new().anchor(Anchor::LineStart);
+        assert!(search(&["ba+"], &options).is_empty());
+
+        // 空文字列にマッチするパターンはコンパイルできない
+        assert!(matches!(
+            VectorscanMatcher::new(&["a*"], &SearchOptions::new()),
+            Err(SearchError::VectorscanMatcher(_))
+        ));
+    }
+}
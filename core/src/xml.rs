@@ -0,0 +1,161 @@
+use alloc::string::String;
+use core::fmt::Write as _;
+use std::io;
+
+use crate::MatchResult;
+
+/// 検索結果を CheckStyle 形式の XML として `writer` に書き出す
+///
+/// CheckStyle の XML を読み込める CI やコードレビューのツール向けの形式で、
+/// ファイルごとの `<file>` 要素に結果ごとの `<error>` 要素（`severity="warning"`）を並べる。
+/// `message` はマッチした文字列（`invert_match` の場合は行のテキスト）、`source` は
+/// `simple_find.pattern{pattern_index}` になる。属性値はエスケープし、XML で使えない
+/// 制御文字は U+FFFD に置き換える。
+///
+/// # Arguments
+///
+/// * `writer` - 書き出し先
+/// * `results` - 検索結果のリスト（同じファイルの結果は連続しているものとする）
+///
+/// # Returns
+///
+/// 書き出しに失敗した場合のエラー
+///
+/// ```
+/// use simple_find_core::{FileInput, SearchOptions, search_with_options, write_checkstyle};
+///
+/// let files = vec![FileInput {
+///     path: "a.c".to_string(),
+///     content: "if (a < b) gets(buf);".to_string(),
+/// }];
+/// let output = search_with_options(r"gets\(", &files, &SearchOptions::new()).unwrap();
+/// let mut out = Vec::new();
+/// write_checkstyle(&mut out, &output.results).unwrap();
+/// assert!(String::from_utf8(out).unwrap().contains(
+///     "<file name=\"a.c\">\n    <error line=\"1\" column=\"12\" severity=\"warning\" \
+///      message=\"gets(\" source=\"simple_find.pattern0\"/>\n  </file>"
+/// ));
+/// ```
+pub fn write_checkstyle<W: io::Write>(mut writer: W, results: &[MatchResult]) -> io::Result<()> {
+    writer
+        .write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n")?;
+
+    let mut out = String::new();
+    let mut current: Option<&str> = None;
+    for result in results {
+        out.clear();
+        if current != Some(&*result.path) {
+            if current.is_some() {
+                out.push_str("  </file>\n");
+            }
+            out.push_str("  <file name=\"");
+            escape_attribute(&result.path, &mut out);
+            out.push_str("\">\n");
+            current = Some(&result.path);
+        }
+        let message: &str = if result.match_text.is_empty() {
+            &result.line_text
+        } else {
+            &result.match_text
+        };
+        let _ = write!(
+            out,
+            "    <error line=\"{}\" column=\"{}\" severity=\"warning\" message=\"",
+            result.line, result.column
+        );
+        escape_attribute(message, &mut out);
+        let _ = writeln!(
+            out,
+            "\" source=\"simple_find.pattern{}\"/>",
+            result.pattern_index
+        );
+        writer.write_all(out.as_bytes())?;
+    }
+    if current.is_some() {
+        writer.write_all(b"  </file>\n")?;
+    }
+
+    writer.write_all(b"</checkstyle>\n")?;
+    writer.flush()
+}
+
+/// 属性値として使えるように文字列をエスケープして `out` に追加する
+///
+/// 改行やタブは属性値の正規化で空白にならないよう文字参照にする。
+fn escape_attribute(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            // XML 1.0 では文字参照を使っても表せない
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {
+                out.push(char::REPLACEMENT_CHARACTER)
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{FileInput, SearchOptions, search_with_options};
+
+    #[test]
+    fn test_escape_attribute() {
+        let mut out = String::new();
+        escape_attribute("<a href=\"x\">'&'\n\t\u{1}</a>", &mut out);
+        assert_eq!(
+            out,
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&#10;&#9;\u{FFFD}&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_write_checkstyle_groups_by_file() {
+        let files = vec![
+            FileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nfoo".to_string(),
+            },
+            FileInput {
+                path: "b.txt".to_string(),
+                content: "bar".to_string(),
+            },
+            FileInput {
+                path: "c&d.txt".to_string(),
+                content: "foo".to_string(),
+            },
+        ];
+        let output = search_with_options("foo", &files, &SearchOptions::new()).unwrap();
+        let mut out = Vec::new();
+        write_checkstyle(&mut out, &output.results).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n  \
+             <file name=\"a.txt\">\n    \
+             <error line=\"1\" column=\"1\" severity=\"warning\" message=\"foo\" source=\"simple_find.pattern0\"/>\n    \
+             <error line=\"2\" column=\"1\" severity=\"warning\" message=\"foo\" source=\"simple_find.pattern0\"/>\n  \
+             </file>\n  <file name=\"c&amp;d.txt\">\n    \
+             <error line=\"1\" column=\"1\" severity=\"warning\" message=\"foo\" source=\"simple_find.pattern0\"/>\n  \
+             </file>\n</checkstyle>\n"
+        );
+
+        let mut out = Vec::new();
+        write_checkstyle(&mut out, &[]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n</checkstyle>\n"
+        );
+    }
+}
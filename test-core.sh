@@ -4,4 +4,7 @@
 set -e
 
 cargo test -p simple_find_core
-
+# std を使わない構成（no_std + alloc）でもテストする
+cargo test -p simple_find_core --no-default-features --features regex,libm,async
+# 正規表現セットのない regex-lite の構成でもテストする
+cargo test -p simple_find_core --no-default-features --features std,regex-lite
@@ -1,7 +1,28 @@
 // wasm/src/lib.rs
-use wasm_bindgen::prelude::*;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::error::Error;
+
 use serde::{Deserialize, Serialize};
-use simple_find_core::{FileInput, MatchResult as CoreMatchResult};
+use simple_find_core::{
+    Anchor, BlockLine as CoreBlockLine, CaptureGroup as CoreCaptureGroup,
+    ChunkedSearch as CoreChunkedSearch, ColumnUnit, ContentProvider,
+    ContextBlock as CoreContextBlock, ContextLine as CoreContextLine, Dedupe, DensityBuckets,
+    EditorRange as CoreEditorRange, FileDensity as CoreFileDensity, FileInput,
+    FileMatches as CoreFileMatches, FileProfile as CoreFileProfile, FuzzyMatch as CoreFuzzyMatch,
+    IncrementalSearch as CoreIncrementalSearch, LineHighlights as CoreLineHighlights,
+    LineMatch as CoreLineMatch, LineTerminator, LongLineMode, MatchResult as CoreMatchResult,
+    MonacoRange as CoreMonacoRange, Normalization, RecordSeparator, RegexEngine, RegexFlags,
+    ReplacePreview as CoreReplacePreview, ReplacedFile as CoreReplacedFile, SearchError,
+    SearchOptions, SearchOutput as CoreSearchOutput, SearchReport as CoreSearchReport,
+    Searcher as CoreSearcher, SortOrder, TermMatch as CoreTermMatch, TextEdit as CoreTextEdit,
+};
+#[cfg(feature = "validate")]
+use simple_find_core::{PatternError as CorePatternError, PatternInfo as CorePatternInfo};
+#[cfg(feature = "explain")]
+use simple_find_core::{PatternNode as CorePatternNode, PatternNodeKind};
+use wasm_bindgen::prelude::*;
 
 /// WebAssembly用のファイル入力構造体
 #[derive(Deserialize, Serialize)]
@@ -19,218 +40,3401 @@ pub struct WasmMatchResult {
     pub path: String,
     /// マッチした行番号（1ベース）
     pub line: u32,
+    /// マッチしたパターンの位置（`search_multi` 以外では常に0）
+    pub pattern_index: usize,
     /// マッチした列番号（1ベース）
     pub column: u32,
+    /// マッチ範囲の直後の列番号（1ベース）
+    pub end_column: u32,
+    /// マッチした文字列
+    pub match_text: String,
+    /// ファイル先頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub byte_offset: usize,
+    /// 行頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub line_byte_offset: usize,
     /// マッチした行のテキスト
     pub line_text: String,
+    /// `line_text` の行内での開始位置（0ベースのバイトオフセット）
+    pub line_text_offset: usize,
+    /// `line_text` が切り詰められたかどうか
+    pub line_truncated: bool,
+    /// `line_text` の中でのマッチの列番号（1ベース）
+    pub line_text_column: u32,
+    /// キャプチャグループにマッチした部分
+    pub captures: Vec<WasmCaptureGroup>,
+    /// 名前付きキャプチャグループの名前とマッチした文字列
+    pub named_captures: BTreeMap<String, String>,
+    /// マッチした行の直前の行
+    pub context_before: Vec<WasmContextLine>,
+    /// マッチした行の直後の行
+    pub context_after: Vec<WasmContextLine>,
+    /// 関連度（`relevance` が無効な場合は0）
+    #[serde(default)]
+    pub score: i64,
 }
 
-impl From<CoreMatchResult> for WasmMatchResult {
-    fn from(m: CoreMatchResult) -> Self {
+/// WebAssembly用のファイルごとにまとめた検索結果の1件を表す構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmLineMatch {
+    /// マッチした行番号（1ベース）
+    pub line: u32,
+    /// マッチしたパターンの位置（`search_multi` 以外では常に0）
+    pub pattern_index: usize,
+    /// マッチした列番号（1ベース）
+    pub column: u32,
+    /// マッチ範囲の直後の列番号（1ベース）
+    pub end_column: u32,
+    /// マッチした文字列
+    pub match_text: String,
+    /// ファイル先頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub byte_offset: usize,
+    /// 行頭からのマッチ開始位置（0ベースのバイトオフセット）
+    pub line_byte_offset: usize,
+    /// マッチした行のテキスト
+    pub line_text: String,
+    /// `line_text` の行内での開始位置（0ベースのバイトオフセット）
+    pub line_text_offset: usize,
+    /// `line_text` が切り詰められたかどうか
+    pub line_truncated: bool,
+    /// `line_text` の中でのマッチの列番号（1ベース）
+    pub line_text_column: u32,
+    /// キャプチャグループにマッチした部分
+    pub captures: Vec<WasmCaptureGroup>,
+    /// 名前付きキャプチャグループの名前とマッチした文字列
+    pub named_captures: BTreeMap<String, String>,
+    /// マッチした行の直前の行
+    pub context_before: Vec<WasmContextLine>,
+    /// マッチした行の直後の行
+    pub context_after: Vec<WasmContextLine>,
+    /// 関連度（`relevance` が無効な場合は0）
+    pub score: i64,
+}
+
+impl From<CoreLineMatch> for WasmLineMatch {
+    fn from(m: CoreLineMatch) -> Self {
         Self {
-            path: m.path,
             line: m.line,
+            pattern_index: m.pattern_index,
             column: m.column,
-            line_text: m.line_text,
+            end_column: m.end_column,
+            match_text: m.match_text,
+            byte_offset: m.byte_offset,
+            line_byte_offset: m.line_byte_offset,
+            line_text: m.line_text.to_string(),
+            line_text_offset: m.line_text_offset,
+            line_truncated: m.line_truncated,
+            line_text_column: m.line_text_column,
+            captures: m.captures.into_iter().map(Into::into).collect(),
+            named_captures: m.named_captures,
+            context_before: m.context_before.into_iter().map(Into::into).collect(),
+            context_after: m.context_after.into_iter().map(Into::into).collect(),
+            score: m.score,
         }
     }
 }
 
-/// パターンでファイルを検索する（WebAssembly用）
+/// WebAssembly用の1ファイル分の検索結果構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmFileMatches {
+    /// マッチしたファイルのパス
+    pub path: String,
+    /// ファイル内の検索結果
+    pub matches: Vec<WasmLineMatch>,
+    /// マッチした行と前後のコンテキストを連続する行ごとにまとめたブロック
+    pub blocks: Vec<WasmContextBlock>,
+}
+
+impl From<CoreFileMatches> for WasmFileMatches {
+    fn from(f: CoreFileMatches) -> Self {
+        Self {
+            path: f.path,
+            matches: f.matches.into_iter().map(Into::into).collect(),
+            blocks: f.blocks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// WebAssembly用のコンテキストブロックの構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmContextBlock {
+    /// ブロック内の行（行番号順）
+    pub lines: Vec<WasmBlockLine>,
+}
+
+impl From<CoreContextBlock> for WasmContextBlock {
+    fn from(b: CoreContextBlock) -> Self {
+        Self {
+            lines: b.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// WebAssembly用のコンテキストブロック内の1行の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmBlockLine {
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// 行のテキスト
+    pub text: String,
+    /// この行のマッチの `matches` 内の位置（コンテキスト行の場合は空）
+    pub matches: Vec<usize>,
+}
+
+impl From<CoreBlockLine> for WasmBlockLine {
+    fn from(l: CoreBlockLine) -> Self {
+        Self {
+            line: l.line,
+            text: l.text,
+            matches: l.matches,
+        }
+    }
+}
+
+/// JavaScript から渡された検索結果をコアの検索結果に戻す
+impl From<WasmMatchResult> for CoreMatchResult {
+    fn from(m: WasmMatchResult) -> Self {
+        Self {
+            path: m.path.into(),
+            line: m.line,
+            pattern_index: m.pattern_index,
+            column: m.column,
+            end_column: m.end_column,
+            match_text: m.match_text,
+            byte_offset: m.byte_offset,
+            line_byte_offset: m.line_byte_offset,
+            line_text: m.line_text.into(),
+            line_text_offset: m.line_text_offset,
+            line_truncated: m.line_truncated,
+            line_text_column: m.line_text_column,
+            captures: m.captures.into_iter().map(Into::into).collect(),
+            named_captures: m.named_captures,
+            context_before: m.context_before.into_iter().map(Into::into).collect(),
+            context_after: m.context_after.into_iter().map(Into::into).collect(),
+            score: m.score,
+        }
+    }
+}
+
+/// WebAssembly用のキャプチャグループ構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmCaptureGroup {
+    /// グループ番号（1ベース）
+    pub index: usize,
+    /// グループにマッチした文字列
+    pub text: String,
+    /// グループの開始列番号（1ベース）
+    pub column: u32,
+    /// グループ範囲の直後の列番号（1ベース）
+    pub end_column: u32,
+}
+
+impl From<CoreCaptureGroup> for WasmCaptureGroup {
+    fn from(c: CoreCaptureGroup) -> Self {
+        Self {
+            index: c.index,
+            text: c.text,
+            column: c.column,
+            end_column: c.end_column,
+        }
+    }
+}
+
+impl From<WasmCaptureGroup> for CoreCaptureGroup {
+    fn from(c: WasmCaptureGroup) -> Self {
+        Self {
+            index: c.index,
+            text: c.text,
+            column: c.column,
+            end_column: c.end_column,
+        }
+    }
+}
+
+impl From<&CoreCaptureGroup> for WasmCaptureGroup {
+    fn from(c: &CoreCaptureGroup) -> Self {
+        Self {
+            index: c.index,
+            text: c.text.clone(),
+            column: c.column,
+            end_column: c.end_column,
+        }
+    }
+}
+
+/// WebAssembly用のコンテキスト行構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmContextLine {
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// 行のテキスト
+    pub text: String,
+}
+
+impl From<CoreContextLine> for WasmContextLine {
+    fn from(c: CoreContextLine) -> Self {
+        Self {
+            line: c.line,
+            text: c.text,
+        }
+    }
+}
+
+impl From<WasmContextLine> for CoreContextLine {
+    fn from(c: WasmContextLine) -> Self {
+        Self {
+            line: c.line,
+            text: c.text,
+        }
+    }
+}
+
+impl From<&CoreContextLine> for WasmContextLine {
+    fn from(c: &CoreContextLine) -> Self {
+        Self {
+            line: c.line,
+            text: c.text.clone(),
+        }
+    }
+}
+
+/// WebAssembly用の検索オプション構造体
 ///
-/// # Arguments
+/// 省略されたフィールドはデフォルト値になる
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct WasmSearchOptions {
+    /// 大文字小文字を区別するかどうか
+    pub case_sensitive: bool,
+    /// 単語単位でのみマッチさせるかどうか
+    pub whole_word: bool,
+    /// パターンを正規表現ではなく文字列そのものとして検索するかどうか
+    pub literal: bool,
+    /// パターンにマッチしない行を返すかどうか
+    pub invert_match: bool,
+    /// 1ファイルあたりの最大マッチ数（省略時は無制限）
+    pub max_count_per_file: Option<usize>,
+    /// 検索全体での最大結果数（省略時は無制限）
+    pub max_results: Option<usize>,
+    /// 集めた検索結果のおおよその大きさの上限（バイト、省略時は無制限）
+    pub max_result_bytes: Option<usize>,
+    /// 先頭から読み飛ばす結果の数（ページの開始位置）
+    pub offset: usize,
+    /// マッチした行の直前に含めるコンテキスト行数
+    pub before_context: usize,
+    /// マッチした行の直後に含めるコンテキスト行数
+    pub after_context: usize,
+    /// 行全体ではなくマッチした部分だけを返すかどうか
+    pub only_matching: bool,
+    /// 除外パターン（この正規表現にマッチする行は結果に含めない）
+    pub exclude_pattern: Option<String>,
+    /// 検索する行の範囲 `[start, end]`（1ベース、両端を含む）
+    pub line_range: Option<[u32; 2]>,
+    /// マッチングの単位となるレコードの区切り方
+    pub record_separator: WasmRecordSeparator,
+    /// 行の終端文字
+    pub line_terminator: WasmLineTerminator,
+    /// 正規表現の詳細なフラグ
+    pub regex_flags: WasmRegexFlags,
+    /// パターンをコンパイルする正規表現エンジン
+    pub regex_engine: WasmRegexEngine,
+    /// コンパイル済み正規表現の大きさの上限（バイト、省略時はコア側のデフォルト）
+    pub size_limit: Option<usize>,
+    /// 遅延 DFA のキャッシュの大きさの上限（バイト、省略時はコア側のデフォルト）
+    pub dfa_size_limit: Option<usize>,
+    /// 検索にかけられる時間の上限（ミリ秒、省略時は無制限）
+    pub timeout_ms: Option<u64>,
+    /// マッチの位置の固定方法
+    pub anchor: WasmAnchor,
+    /// あいまい一致で許容する編集距離（省略時は通常の検索）
+    pub max_edit_distance: Option<usize>,
+    /// マッチングの前に適用する Unicode 正規化の形式
+    pub normalization: WasmNormalization,
+    /// アクセントなどの発音区別符号を無視するかどうか
+    #[cfg(feature = "normalization")]
+    pub fold_diacritics: bool,
+    /// ひらがなとカタカナを区別しないかどうか
+    pub kana_insensitive: bool,
+    /// 全角・半角を区別しないかどうか
+    #[cfg(feature = "normalization")]
+    pub fold_width: bool,
+    /// 結果の列番号を数える単位（デフォルトは UTF-16 のコードユニット）
+    pub column_unit: WasmColumnUnit,
+    /// `column_unit` が `"display_width"` の場合のタブの幅（デフォルトは 8）
+    pub tab_width: usize,
+    /// 長すぎる行とみなす長さ（バイト、`None` の場合は制限しない）
+    pub max_line_length: Option<usize>,
+    /// `max_line_length` を超える行の扱い方
+    pub long_line_mode: WasmLongLineMode,
+    /// `line_text` をマッチを中心に切り詰める文字数（`None` の場合は切り詰めない）
+    pub preview_width: Option<usize>,
+    /// バイナリとみなしたファイルも検索するかどうか
+    pub search_binary: bool,
+    /// 検索結果の並び順
+    pub sort: WasmSortOrder,
+    /// 検索結果に関連度を付けるかどうか
+    pub relevance: bool,
+    /// 同じ行の重複した結果のまとめ方
+    pub dedupe: WasmDedupe,
+    /// 検索の統計を返すかどうか
+    pub report: bool,
+    /// ファイルごとの検索時間とマッチ数を計測するかどうか
+    pub profile: bool,
+}
+
+impl Default for WasmSearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: true,
+            whole_word: false,
+            literal: false,
+            invert_match: false,
+            max_count_per_file: None,
+            max_results: None,
+            max_result_bytes: None,
+            offset: 0,
+            before_context: 0,
+            after_context: 0,
+            only_matching: false,
+            exclude_pattern: None,
+            line_range: None,
+            record_separator: WasmRecordSeparator::Line,
+            line_terminator: WasmLineTerminator::Auto,
+            regex_flags: WasmRegexFlags::default(),
+            regex_engine: WasmRegexEngine::Standard,
+            size_limit: None,
+            dfa_size_limit: None,
+            timeout_ms: None,
+            anchor: WasmAnchor::Unanchored,
+            max_edit_distance: None,
+            normalization: WasmNormalization::None,
+            #[cfg(feature = "normalization")]
+            fold_diacritics: false,
+            kana_insensitive: false,
+            #[cfg(feature = "normalization")]
+            fold_width: false,
+            column_unit: WasmColumnUnit::Utf16,
+            tab_width: 8,
+            max_line_length: None,
+            long_line_mode: WasmLongLineMode::Truncate,
+            preview_width: None,
+            search_binary: false,
+            sort: WasmSortOrder::Input,
+            relevance: false,
+            dedupe: WasmDedupe::None,
+            report: false,
+            profile: false,
+        }
+    }
+}
+
+impl From<WasmSearchOptions> for SearchOptions {
+    fn from(o: WasmSearchOptions) -> Self {
+        let mut options = SearchOptions::new()
+            .case_sensitive(o.case_sensitive)
+            .whole_word(o.whole_word)
+            .literal(o.literal)
+            .invert_match(o.invert_match)
+            .before_context(o.before_context)
+            .after_context(o.after_context)
+            .only_matching(o.only_matching)
+            .record_separator(o.record_separator.into())
+            .line_terminator(o.line_terminator.into())
+            .regex_flags(o.regex_flags.into())
+            .regex_engine(o.regex_engine.into())
+            .anchor(o.anchor.into())
+            .normalization(o.normalization.into())
+            .kana_insensitive(o.kana_insensitive)
+            .column_unit(o.column_unit.to_core(o.tab_width))
+            .long_line_mode(o.long_line_mode.into())
+            .search_binary(o.search_binary)
+            .sort(o.sort.into())
+            .relevance(o.relevance)
+            .dedupe(o.dedupe.into())
+            .report(o.report)
+            .profile(o.profile);
+        #[cfg(feature = "normalization")]
+        {
+            options = options
+                .fold_diacritics(o.fold_diacritics)
+                .fold_width(o.fold_width);
+        }
+        if let Some(max_count) = o.max_count_per_file {
+            options = options.max_count_per_file(max_count);
+        }
+        if let Some(max_results) = o.max_results {
+            options = options.max_results(max_results);
+        }
+        if let Some(bytes) = o.max_result_bytes {
+            options = options.max_result_bytes(bytes);
+        }
+        options = options.offset(o.offset);
+        if let Some(exclude_pattern) = o.exclude_pattern {
+            options = options.exclude_pattern(exclude_pattern);
+        }
+        if let Some([start, end]) = o.line_range {
+            options = options.line_range(start..=end);
+        }
+        if let Some(bytes) = o.size_limit {
+            options = options.size_limit(bytes);
+        }
+        if let Some(bytes) = o.dfa_size_limit {
+            options = options.dfa_size_limit(bytes);
+        }
+        if let Some(timeout_ms) = o.timeout_ms {
+            options = options.timeout_ms(timeout_ms);
+        }
+        if let Some(max_edits) = o.max_edit_distance {
+            options = options.max_edit_distance(max_edits);
+        }
+        if let Some(max_line_length) = o.max_line_length {
+            options = options.max_line_length(max_line_length);
+        }
+        if let Some(width) = o.preview_width {
+            options = options.preview_width(width);
+        }
+        options
+    }
+}
+
+/// WebAssembly用のレコードの区切り方
 ///
-/// * `pattern` - 検索する正規表現パターン
-/// * `files` - 検索対象のファイルリスト（JSON形式）
-/// * `case_sensitive` - 大文字小文字を区別するかどうか
+/// JavaScriptからは `"line"`、`"paragraph"`、`{ "custom": "---" }` の形式で指定する
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmRecordSeparator {
+    /// 1行を1レコードとする
+    #[default]
+    Line,
+    /// 空行で区切られた段落を1レコードとする
+    Paragraph,
+    /// 指定した文字列で区切る
+    Custom(String),
+}
+
+impl From<WasmRecordSeparator> for RecordSeparator {
+    fn from(s: WasmRecordSeparator) -> Self {
+        match s {
+            WasmRecordSeparator::Line => RecordSeparator::Line,
+            WasmRecordSeparator::Paragraph => RecordSeparator::Paragraph,
+            WasmRecordSeparator::Custom(separator) => RecordSeparator::Custom(separator),
+        }
+    }
+}
+
+/// WebAssembly用の行の終端文字
 ///
-/// # Returns
+/// JavaScriptからは `"auto"`、`"lf"`、`"crlf"`、`"cr"`、`{ "byte": 59 }` の形式で指定する
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmLineTerminator {
+    /// LF と CRLF のどちらでも区切る
+    #[default]
+    Auto,
+    /// `\n` だけで区切る
+    Lf,
+    /// `\r\n` だけで区切る
+    Crlf,
+    /// `\r` だけで区切る
+    Cr,
+    /// 任意の1バイト（ASCII）で区切る
+    Byte(u8),
+}
+
+impl From<WasmLineTerminator> for LineTerminator {
+    fn from(t: WasmLineTerminator) -> Self {
+        match t {
+            WasmLineTerminator::Auto => LineTerminator::Auto,
+            WasmLineTerminator::Lf => LineTerminator::Lf,
+            WasmLineTerminator::Crlf => LineTerminator::Crlf,
+            WasmLineTerminator::Cr => LineTerminator::Cr,
+            WasmLineTerminator::Byte(byte) => LineTerminator::Byte(byte),
+        }
+    }
+}
+
+/// WebAssembly用のマッチ位置の固定方法
 ///
-/// 検索結果のリスト（JSON形式）、またはエラー
-#[wasm_bindgen]
-pub fn search(pattern: &str, files: &JsValue, case_sensitive: bool) -> Result<JsValue, JsValue> {
-    let wasm_files: Vec<WasmFileInput> = serde_wasm_bindgen::from_value(files.clone())
-        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize files: {}", e)))?;
+/// JavaScriptからは `"unanchored"`、`"line_start"`、`"whole_line"` のいずれかで指定する
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmAnchor {
+    /// 行内のどこでもマッチする
+    #[default]
+    Unanchored,
+    /// 行頭から始まるマッチだけを返す
+    LineStart,
+    /// 行全体がマッチする場合だけ返す
+    WholeLine,
+}
+
+impl From<WasmAnchor> for Anchor {
+    fn from(a: WasmAnchor) -> Self {
+        match a {
+            WasmAnchor::Unanchored => Anchor::Unanchored,
+            WasmAnchor::LineStart => Anchor::LineStart,
+            WasmAnchor::WholeLine => Anchor::WholeLine,
+        }
+    }
+}
+
+/// WebAssembly用の正規表現エンジン
+///
+/// JavaScriptからは `"standard"` または `"fancy"`（`fancy-regex` フィーチャーでビルドした場合のみ）で指定する
+#[derive(Deserialize, Serialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmRegexEngine {
+    /// 組み込みのエンジン
+    #[default]
+    Standard,
+    /// 先読み・後読みや後方参照を使える fancy-regex
+    #[cfg(feature = "fancy-regex")]
+    Fancy,
+}
+
+impl From<WasmRegexEngine> for RegexEngine {
+    fn from(e: WasmRegexEngine) -> Self {
+        match e {
+            WasmRegexEngine::Standard => RegexEngine::Standard,
+            #[cfg(feature = "fancy-regex")]
+            WasmRegexEngine::Fancy => RegexEngine::Fancy,
+        }
+    }
+}
+
+impl From<RegexEngine> for WasmRegexEngine {
+    fn from(e: RegexEngine) -> Self {
+        match e {
+            RegexEngine::Standard => WasmRegexEngine::Standard,
+            #[cfg(feature = "fancy-regex")]
+            RegexEngine::Fancy => WasmRegexEngine::Fancy,
+            // このクレートのフィーチャーで公開していないエンジンは組み込みのエンジンとして扱う
+            _ => WasmRegexEngine::Standard,
+        }
+    }
+}
+
+/// WebAssembly用の正規表現エンジンが対応している構文と性質の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmEngineFeatures {
+    /// `regex_engine` に指定する値
+    pub engine: WasmRegexEngine,
+    /// エンジンの実装に使うクレートの名前
+    pub name: String,
+    /// 先読み・後読みを使えるかどうか
+    pub lookaround: bool,
+    /// 後方参照を使えるかどうか
+    pub backreferences: bool,
+    /// Unicode の文字クラスを使えるかどうか
+    pub unicode_classes: bool,
+    /// マッチにかかる時間がパターンとテキストの長さに比例することを保証するかどうか
+    pub linear_time: bool,
+}
+
+impl From<RegexEngine> for WasmEngineFeatures {
+    fn from(engine: RegexEngine) -> Self {
+        let features = engine.features();
+        Self {
+            engine: engine.into(),
+            name: engine.name().to_string(),
+            lookaround: features.lookaround,
+            backreferences: features.backreferences,
+            unicode_classes: features.unicode_classes,
+            linear_time: features.linear_time,
+        }
+    }
+}
+
+/// WebAssembly用の長すぎる行の扱い方
+///
+/// JavaScriptからは `"skip"`、`"truncate"`、`"window"` のいずれかで指定する
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmLongLineMode {
+    /// 長すぎる行は検索しない
+    Skip,
+    /// 行頭から切り詰める
+    #[default]
+    Truncate,
+    /// マッチを中心に切り詰める
+    Window,
+}
+
+impl From<WasmLongLineMode> for LongLineMode {
+    fn from(m: WasmLongLineMode) -> Self {
+        match m {
+            WasmLongLineMode::Skip => LongLineMode::Skip,
+            WasmLongLineMode::Truncate => LongLineMode::Truncate,
+            WasmLongLineMode::Window => LongLineMode::Window,
+        }
+    }
+}
+
+/// WebAssembly用の検索結果の並び順
+///
+/// JavaScriptからは `"input"`、`"path"`、`"match_count"`、`"relevance"` のいずれかで指定する
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmSortOrder {
+    /// 入力したファイルの順
+    #[default]
+    Input,
+    /// パスの順
+    Path,
+    /// マッチ数の多いファイルから順
+    MatchCount,
+    /// 関連度の高い順
+    Relevance,
+}
+
+impl From<WasmSortOrder> for SortOrder {
+    fn from(o: WasmSortOrder) -> Self {
+        match o {
+            WasmSortOrder::Input => SortOrder::Input,
+            WasmSortOrder::Path => SortOrder::Path,
+            WasmSortOrder::MatchCount => SortOrder::MatchCount,
+            WasmSortOrder::Relevance => SortOrder::Relevance,
+        }
+    }
+}
+
+/// WebAssembly用の同じ行の重複した結果のまとめ方
+///
+/// JavaScriptからは `"none"`、`"line"`、`"span"` のいずれかで指定する
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmDedupe {
+    /// まとめずに行内のすべてのマッチを返す
+    #[default]
+    None,
+    /// 1行につき最初のマッチだけを返す
+    Line,
+    /// 同じ範囲へのマッチは最初のパターンのものだけを返す
+    Span,
+}
+
+impl From<WasmDedupe> for Dedupe {
+    fn from(d: WasmDedupe) -> Self {
+        match d {
+            WasmDedupe::None => Dedupe::None,
+            WasmDedupe::Line => Dedupe::Line,
+            WasmDedupe::Span => Dedupe::Span,
+        }
+    }
+}
+
+/// WebAssembly用の Unicode 正規化の形式
+///
+/// JavaScriptからは `"none"`、`"nfc"`、`"nfkc"` のいずれかで指定する（`"nfc"` と `"nfkc"` は
+/// `normalization` フィーチャーでビルドした場合のみ）
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmNormalization {
+    /// 正規化しない
+    #[default]
+    None,
+    /// 正規化形式 C
+    #[cfg(feature = "normalization")]
+    Nfc,
+    /// 正規化形式 KC
+    #[cfg(feature = "normalization")]
+    Nfkc,
+}
+
+impl From<WasmNormalization> for Normalization {
+    fn from(n: WasmNormalization) -> Self {
+        match n {
+            WasmNormalization::None => Normalization::None,
+            #[cfg(feature = "normalization")]
+            WasmNormalization::Nfc => Normalization::Nfc,
+            #[cfg(feature = "normalization")]
+            WasmNormalization::Nfkc => Normalization::Nfkc,
+        }
+    }
+}
+
+/// WebAssembly用の列番号の単位
+///
+/// JavaScriptからは `"utf16"`、`"bytes"`、`"chars"`、`"graphemes"`、`"display_width"` のいずれかで指定する。
+/// JavaScript の文字列のインデックスとそのまま対応するよう、デフォルトは UTF-16 のコードユニット。
+/// `"graphemes"` と `"display_width"` はそれぞれ `graphemes` と `display-width` フィーチャーでビルドした場合のみ使える
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmColumnUnit {
+    /// UTF-16 のコードユニットの数
+    #[default]
+    Utf16,
+    /// UTF-8 のバイト数
+    Bytes,
+    /// Unicode のスカラー値の数
+    Chars,
+    /// 書記素クラスタの数
+    #[cfg(feature = "graphemes")]
+    Graphemes,
+    /// 端末での表示幅（タブの幅は `tab_width` で指定する）
+    #[cfg(feature = "display-width")]
+    DisplayWidth,
+}
+
+impl WasmColumnUnit {
+    /// タブの幅を指定してコアの列番号の単位に変換する
+    #[cfg_attr(not(feature = "display-width"), allow(unused_variables))]
+    fn to_core(&self, tab_width: usize) -> ColumnUnit {
+        match self {
+            WasmColumnUnit::Utf16 => ColumnUnit::Utf16,
+            WasmColumnUnit::Bytes => ColumnUnit::Bytes,
+            WasmColumnUnit::Chars => ColumnUnit::Chars,
+            #[cfg(feature = "graphemes")]
+            WasmColumnUnit::Graphemes => ColumnUnit::Graphemes,
+            #[cfg(feature = "display-width")]
+            WasmColumnUnit::DisplayWidth => ColumnUnit::DisplayWidth { tab_width },
+        }
+    }
+}
+
+/// WebAssembly用の正規表現フラグ構造体
+///
+/// 省略されたフィールドはデフォルト値になる
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct WasmRegexFlags {
+    /// `^` と `$` を行頭・行末にもマッチさせるかどうか
+    pub multi_line: bool,
+    /// `.` を改行にもマッチさせるかどうか
+    pub dot_all: bool,
+    /// Unicode モードを有効にするかどうか
+    pub unicode: bool,
+    /// 量指定子の貪欲・非貪欲を入れ替えるかどうか
+    pub swap_greed: bool,
+    /// パターン中の空白とコメントを無視するかどうか
+    pub ignore_whitespace: bool,
+}
+
+impl Default for WasmRegexFlags {
+    fn default() -> Self {
+        Self {
+            multi_line: false,
+            dot_all: false,
+            unicode: true,
+            swap_greed: false,
+            ignore_whitespace: false,
+        }
+    }
+}
+
+impl From<WasmRegexFlags> for RegexFlags {
+    fn from(f: WasmRegexFlags) -> Self {
+        RegexFlags::new()
+            .multi_line(f.multi_line)
+            .dot_all(f.dot_all)
+            .unicode(f.unicode)
+            .swap_greed(f.swap_greed)
+            .ignore_whitespace(f.ignore_whitespace)
+    }
+}
+
+/// WebAssembly用のオプション付き検索の出力構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmSearchOutput<T = WasmMatchResult> {
+    /// 検索結果のリスト（`search_grouped` ではファイルごとにまとめたもの）
+    pub results: Vec<T>,
+    /// 1ファイルあたりの上限に達して結果が打ち切られたファイルのパス
+    pub truncated_files: Vec<String>,
+    /// 検索全体の上限に達して結果が打ち切られたかどうか
+    pub truncated: bool,
+    /// 時間の上限を過ぎて検索が打ち切られたかどうか
+    pub timed_out: bool,
+    /// バイナリとみなして読み飛ばしたファイルのパス
+    pub binary_files: Vec<String>,
+    /// 続きの結果を取得するときに `offset` に渡す値（最後まで検索した場合は `null`）
+    pub next_offset: Option<usize>,
+    /// 検索の統計（`report` が無効な場合は `null`）
+    pub report: Option<WasmSearchReport>,
+}
+
+/// WebAssembly用の検索1回分の統計構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmSearchReport {
+    /// 走査したファイルの数
+    pub files_scanned: usize,
+    /// 走査したファイルの合計バイト数
+    pub bytes_scanned: u64,
+    /// 走査した行の数
+    pub lines_scanned: u64,
+    /// 見つかったマッチの数
+    pub matches_found: usize,
+    /// バイナリとみなして読み飛ばしたファイルの数
+    pub files_skipped: usize,
+    /// 検索にかかった時間（ミリ秒）
+    pub elapsed_ms: f64,
+    /// ファイルごとの計測結果（`profile` が無効な場合は空）
+    pub files: Vec<WasmFileProfile>,
+}
+
+impl From<CoreSearchReport> for WasmSearchReport {
+    fn from(r: CoreSearchReport) -> Self {
+        Self {
+            files_scanned: r.files_scanned,
+            bytes_scanned: r.bytes_scanned,
+            lines_scanned: r.lines_scanned,
+            matches_found: r.matches_found,
+            files_skipped: r.files_skipped,
+            elapsed_ms: r.elapsed.as_secs_f64() * 1000.0,
+            files: r.files.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// WebAssembly用の1ファイル分の計測結果構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmFileProfile {
+    /// ファイルのパス
+    pub path: String,
+    /// ファイルの検索にかかった時間（ミリ秒）
+    pub elapsed_ms: f64,
+    /// 走査した行の数
+    pub lines: u64,
+    /// 見つかったマッチの数
+    pub matches: usize,
+}
+
+impl From<CoreFileProfile> for WasmFileProfile {
+    fn from(p: CoreFileProfile) -> Self {
+        Self {
+            path: p.path,
+            elapsed_ms: p.elapsed.as_secs_f64() * 1000.0,
+            lines: p.lines,
+            matches: p.matches,
+        }
+    }
+}
+
+impl From<CoreSearchOutput> for WasmSearchOutput {
+    fn from(o: CoreSearchOutput) -> Self {
+        Self {
+            results: o.results.into_iter().map(WasmMatchResult::from).collect(),
+            truncated_files: o.truncated_files,
+            truncated: o.truncated,
+            timed_out: o.timed_out,
+            binary_files: o.binary_files,
+            next_offset: o.next_offset,
+            report: o.report.map(Into::into),
+        }
+    }
+}
+
+impl From<CoreSearchOutput<CoreFileMatches>> for WasmSearchOutput<WasmFileMatches> {
+    fn from(o: CoreSearchOutput<CoreFileMatches>) -> Self {
+        Self {
+            results: o.results.into_iter().map(WasmFileMatches::from).collect(),
+            truncated_files: o.truncated_files,
+            truncated: o.truncated,
+            timed_out: o.timed_out,
+            binary_files: o.binary_files,
+            next_offset: o.next_offset,
+            report: o.report.map(Into::into),
+        }
+    }
+}
+
+impl From<CoreMatchResult> for WasmMatchResult {
+    fn from(m: CoreMatchResult) -> Self {
+        Self {
+            path: m.path.to_string(),
+            line: m.line,
+            pattern_index: m.pattern_index,
+            column: m.column,
+            end_column: m.end_column,
+            match_text: m.match_text,
+            byte_offset: m.byte_offset,
+            line_byte_offset: m.line_byte_offset,
+            line_text: m.line_text.to_string(),
+            line_text_offset: m.line_text_offset,
+            line_truncated: m.line_truncated,
+            line_text_column: m.line_text_column,
+            captures: m.captures.into_iter().map(Into::into).collect(),
+            named_captures: m.named_captures,
+            context_before: m.context_before.into_iter().map(Into::into).collect(),
+            context_after: m.context_after.into_iter().map(Into::into).collect(),
+            score: m.score,
+        }
+    }
+}
+
+/// コールバックに渡すために、コアの検索結果を複製して変換する
+impl From<&CoreMatchResult> for WasmMatchResult {
+    fn from(m: &CoreMatchResult) -> Self {
+        Self {
+            path: m.path.to_string(),
+            line: m.line,
+            pattern_index: m.pattern_index,
+            column: m.column,
+            end_column: m.end_column,
+            match_text: m.match_text.clone(),
+            byte_offset: m.byte_offset,
+            line_byte_offset: m.line_byte_offset,
+            line_text: m.line_text.to_string(),
+            line_text_offset: m.line_text_offset,
+            line_truncated: m.line_truncated,
+            line_text_column: m.line_text_column,
+            captures: m.captures.iter().map(Into::into).collect(),
+            named_captures: m.named_captures.clone(),
+            context_before: m.context_before.iter().map(Into::into).collect(),
+            context_after: m.context_after.iter().map(Into::into).collect(),
+            score: m.score,
+        }
+    }
+}
+
+/// WebAssembly用の単語リスト検索の結果構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmTermMatch {
+    /// 見つかった語のリスト内の位置（0ベース）
+    pub term_index: usize,
+    /// 見つかった語
+    pub term: String,
+    /// 検索結果
+    pub result: WasmMatchResult,
+}
+
+impl From<CoreTermMatch> for WasmTermMatch {
+    fn from(m: CoreTermMatch) -> Self {
+        Self {
+            term_index: m.term_index,
+            term: m.term,
+            result: m.result.into(),
+        }
+    }
+}
+
+/// WebAssembly用のあいまい検索の結果構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmFuzzyMatch {
+    /// マッチしたファイルのパス
+    pub path: String,
+    /// マッチした行番号（1ベース）
+    pub line: u32,
+    /// マッチの良さ（大きいほど良い）
+    pub score: i64,
+    /// パターンの各文字がマッチした列番号（1ベース）
+    pub positions: Vec<u32>,
+    /// マッチした行のテキスト
+    pub line_text: String,
+}
+
+impl From<CoreFuzzyMatch> for WasmFuzzyMatch {
+    fn from(m: CoreFuzzyMatch) -> Self {
+        Self {
+            path: m.path,
+            line: m.line,
+            score: m.score,
+            positions: m.positions,
+            line_text: m.line_text,
+        }
+    }
+}
+
+/// WebAssembly用の1行分の強調表示する範囲の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmLineHighlights {
+    /// ファイルのパス
+    pub path: String,
+    /// 行番号（1ベース）
+    pub line: u32,
+    /// 強調表示する範囲（`[column, end_column]` の組、1ベースで終端を含まない）
+    pub spans: Vec<(u32, u32)>,
+}
+
+impl From<CoreLineHighlights> for WasmLineHighlights {
+    fn from(h: CoreLineHighlights) -> Self {
+        Self {
+            path: h.path,
+            line: h.line,
+            spans: h.spans,
+        }
+    }
+}
+
+/// WebAssembly用の Monaco Editor の `IRange` 構造体
+///
+/// フィールド名は Monaco に合わせて camelCase で出力する
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WasmMonacoRange {
+    /// 開始行番号（1ベース）
+    pub start_line_number: u32,
+    /// 開始列番号（1ベース、UTF-16 のコードユニット単位）
+    pub start_column: u32,
+    /// 終了行番号（1ベース）
+    pub end_line_number: u32,
+    /// 終了列番号（1ベース、UTF-16 のコードユニット単位）
+    pub end_column: u32,
+}
+
+impl From<CoreMonacoRange> for WasmMonacoRange {
+    fn from(r: CoreMonacoRange) -> Self {
+        Self {
+            start_line_number: r.start_line_number,
+            start_column: r.start_column,
+            end_line_number: r.end_line_number,
+            end_column: r.end_column,
+        }
+    }
+}
+
+/// WebAssembly用のエディタの装飾に使う範囲の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmEditorRange {
+    /// ファイルのパス
+    pub path: String,
+    /// Monaco Editor の `IRange`
+    pub monaco: WasmMonacoRange,
+    /// CodeMirror 6 のドキュメント先頭からの開始位置
+    pub from: u32,
+    /// CodeMirror 6 のドキュメント先頭からの終了位置
+    pub to: u32,
+}
+
+impl From<CoreEditorRange> for WasmEditorRange {
+    fn from(r: CoreEditorRange) -> Self {
+        Self {
+            path: r.path,
+            monaco: r.monaco.into(),
+            from: r.from,
+            to: r.to,
+        }
+    }
+}
+
+/// WebAssembly用のマッチの密度を数える区間の分け方
+///
+/// JavaScriptからは `{ "lines_per": 100 }` または `{ "count": 20 }` で指定する
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmDensityBuckets {
+    /// 指定した行数ごとに区切る
+    LinesPer(u32),
+    /// ファイルをおよそ指定した数の区間に等分する
+    Count(u32),
+}
+
+impl From<WasmDensityBuckets> for DensityBuckets {
+    fn from(b: WasmDensityBuckets) -> Self {
+        match b {
+            WasmDensityBuckets::LinesPer(n) => DensityBuckets::LinesPer(n),
+            WasmDensityBuckets::Count(n) => DensityBuckets::Count(n),
+        }
+    }
+}
+
+/// WebAssembly用の1ファイル分のマッチの密度の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmFileDensity {
+    /// ファイルのパス
+    pub path: String,
+    /// ファイルの行数
+    pub total_lines: u32,
+    /// 1区間の行数
+    pub lines_per_bucket: u32,
+    /// 区間ごとのマッチ数
+    pub counts: Vec<u32>,
+}
+
+impl From<CoreFileDensity> for WasmFileDensity {
+    fn from(d: CoreFileDensity) -> Self {
+        Self {
+            path: d.path,
+            total_lines: d.total_lines,
+            lines_per_bucket: d.lines_per_bucket,
+            counts: d.counts,
+        }
+    }
+}
+
+/// WebAssembly用の2回の検索結果の差分の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmResultDiff {
+    /// 後の検索結果にだけある結果
+    pub added: Vec<WasmMatchResult>,
+    /// 前の検索結果にだけある結果
+    pub removed: Vec<WasmMatchResult>,
+    /// 両方にある結果の数
+    pub unchanged: usize,
+}
+
+#[cfg(feature = "validate")]
+/// WebAssembly用のパターンの検証結果の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmPatternValidation {
+    /// パターンが有効かどうか
+    pub valid: bool,
+    /// 有効な場合のパターンの情報
+    pub info: Option<WasmPatternInfo>,
+    /// 無効な場合の理由と位置
+    pub error: Option<WasmPatternError>,
+}
+
+#[cfg(feature = "validate")]
+/// WebAssembly用のパターンの情報の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmPatternInfo {
+    /// キャプチャグループの数（マッチ全体を表す0番は含まない）
+    pub capture_count: usize,
+    /// 名前付きキャプチャグループの名前（出現順）
+    pub capture_names: Vec<String>,
+    /// 正規表現の記号を含まず、文字列そのものにマッチするかどうか
+    pub is_literal: bool,
+    /// 空文字列にマッチし得るかどうか
+    pub matches_empty: bool,
+}
+
+#[cfg(feature = "validate")]
+impl From<CorePatternInfo> for WasmPatternInfo {
+    fn from(info: CorePatternInfo) -> Self {
+        Self {
+            capture_count: info.capture_count,
+            capture_names: info.capture_names,
+            is_literal: info.is_literal,
+            matches_empty: info.matches_empty,
+        }
+    }
+}
+
+#[cfg(feature = "validate")]
+/// WebAssembly用のパターンが無効な理由と位置の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmPatternError {
+    /// 人が読むための無効な理由
+    pub message: String,
+    /// 無効な部分の開始列番号（1ベース、位置を特定できない場合は `null`）
+    pub column: Option<u32>,
+    /// 無効な部分の直後の列番号（1ベース、位置を特定できない場合は `null`）
+    pub end_column: Option<u32>,
+    /// 修正したパターンの候補（いずれも有効なもの、見込みの高い順）
+    pub suggestions: Vec<String>,
+}
+
+#[cfg(feature = "validate")]
+impl From<CorePatternError> for WasmPatternError {
+    fn from(error: CorePatternError) -> Self {
+        Self {
+            message: error.message,
+            column: error.column,
+            end_column: error.end_column,
+            suggestions: error.suggestions,
+        }
+    }
+}
+
+#[cfg(feature = "explain")]
+/// WebAssembly用のパターンの説明の木の要素の構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmPatternNode {
+    /// 要素の種類（`"literal"`、`"class"`、`"repetition"` など）と種類ごとの値
+    pub kind: WasmPatternNodeKind,
+    /// 人が読むための英語の説明
+    pub description: String,
+    /// 開始列番号（1ベース）
+    pub column: u32,
+    /// 終了列番号（1ベース、この位置の文字は含まない）
+    pub end_column: u32,
+    /// 子要素
+    pub children: Vec<WasmPatternNode>,
+}
+
+#[cfg(feature = "explain")]
+impl From<CorePatternNode> for WasmPatternNode {
+    fn from(node: CorePatternNode) -> Self {
+        Self {
+            kind: node.kind.into(),
+            description: node.description,
+            column: node.column,
+            end_column: node.end_column,
+            children: node.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "explain")]
+/// WebAssembly用のパターンの説明の木の要素の種類
+///
+/// JavaScriptには `{ "type": "repetition", "min": 1, "max": null, "greedy": true }` のように出力する
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WasmPatternNodeKind {
+    /// 空
+    Empty,
+    /// 文字列そのもの
+    Literal { text: String },
+    /// 任意の1文字
+    AnyChar,
+    /// 文字クラス
+    Class { negated: bool },
+    /// 位置だけにマッチするもの
+    Assertion,
+    /// 繰り返し
+    Repetition {
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+    },
+    /// グループ
+    Group {
+        capture_index: Option<u32>,
+        name: Option<String>,
+    },
+    /// 選択
+    Alternation,
+    /// 連接
+    Concat,
+    /// フラグの設定
+    Flags,
+}
+
+#[cfg(feature = "explain")]
+impl From<PatternNodeKind> for WasmPatternNodeKind {
+    fn from(kind: PatternNodeKind) -> Self {
+        match kind {
+            PatternNodeKind::Empty => Self::Empty,
+            PatternNodeKind::Literal { text } => Self::Literal { text },
+            PatternNodeKind::AnyChar => Self::AnyChar,
+            PatternNodeKind::Class { negated } => Self::Class { negated },
+            PatternNodeKind::Assertion => Self::Assertion,
+            PatternNodeKind::Repetition { min, max, greedy } => {
+                Self::Repetition { min, max, greedy }
+            }
+            PatternNodeKind::Group {
+                capture_index,
+                name,
+            } => Self::Group {
+                capture_index,
+                name,
+            },
+            PatternNodeKind::Alternation => Self::Alternation,
+            PatternNodeKind::Concat => Self::Concat,
+            PatternNodeKind::Flags => Self::Flags,
+        }
+    }
+}
+
+/// WebAssembly用の置換後のファイル構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmReplacedFile {
+    /// ファイルのパス
+    pub path: String,
+    /// 置換後のファイルの内容
+    pub content: String,
+    /// 置換した箇所の数
+    pub replacements: usize,
+}
+
+impl From<CoreReplacedFile> for WasmReplacedFile {
+    fn from(f: CoreReplacedFile) -> Self {
+        Self {
+            path: f.path,
+            content: f.content,
+            replacements: f.replacements,
+        }
+    }
+}
+
+/// WebAssembly用の置換のプレビュー構造体
+#[derive(Serialize, Deserialize)]
+pub struct WasmReplacePreview {
+    /// ファイルのパス
+    pub path: String,
+    /// 置換前後の unified diff
+    pub diff: String,
+    /// 置換する箇所の数
+    pub replacements: usize,
+}
+
+impl From<CoreReplacePreview> for WasmReplacePreview {
+    fn from(p: CoreReplacePreview) -> Self {
+        Self {
+            path: p.path,
+            diff: p.diff,
+            replacements: p.replacements,
+        }
+    }
+}
+
+/// WebAssembly用の置換箇所構造体
+///
+/// 列番号は `column_unit` に従い、デフォルトの UTF-16 なら Monaco の範囲にそのまま使える。
+#[derive(Serialize, Deserialize)]
+pub struct WasmTextEdit {
+    /// 置換するファイルのパス
+    pub path: String,
+    /// 置換範囲の開始行番号（1ベース）
+    pub start_line: u32,
+    /// 置換範囲の開始列番号（1ベース）
+    pub start_col: u32,
+    /// 置換範囲の終了行番号（1ベース）
+    pub end_line: u32,
+    /// 置換範囲の直後の列番号（1ベース）
+    pub end_col: u32,
+    /// 置換範囲と置き換える文字列
+    pub new_text: String,
+}
+
+impl From<CoreTextEdit> for WasmTextEdit {
+    fn from(e: CoreTextEdit) -> Self {
+        Self {
+            path: e.path,
+            start_line: e.start_line,
+            start_col: e.start_col,
+            end_line: e.end_line,
+            end_col: e.end_col,
+            new_text: e.new_text,
+        }
+    }
+}
+
+/// パターンでファイルを検索する（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `case_sensitive` - 大文字小文字を区別するかどうか
+///
+/// # Returns
+///
+/// 検索結果のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search(pattern: &str, files: &JsValue, case_sensitive: bool) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+
+    let options = SearchOptions::from(WasmSearchOptions {
+        case_sensitive,
+        ..Default::default()
+    });
+    let output = simple_find_core::search_with_options(pattern, &core_files, &options)
+        .map_err(search_error)?;
+
+    to_js_results(output.results)
+}
+
+/// オプションを指定してパターンでファイルを検索する（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search_with_options(
+    pattern: &str,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let output = simple_find_core::search_with_options(pattern, &core_files, &core_options)
+        .map_err(search_error)?;
+
+    to_js(&WasmSearchOutput::from(output))
+}
+
+/// JavaScript の関数でファイルの内容を返す提供元
+struct JsContentProvider<'a>(&'a js_sys::Function);
+
+impl ContentProvider for JsContentProvider<'_> {
+    fn content(&self, path: &str) -> Result<Cow<'_, str>, Box<dyn Error + Send + Sync>> {
+        let value = self
+            .0
+            .call1(&JsValue::NULL, &JsValue::from_str(path))
+            .map_err(|e| {
+                e.as_string()
+                    .unwrap_or_else(|| "Provider threw an exception".to_string())
+            })?;
+        let content = value
+            .as_string()
+            .ok_or_else(|| "Provider must return a string".to_string())?;
+        Ok(Cow::Owned(content))
+    }
+}
+
+/// ファイルの内容を JavaScript の関数から1つずつ取得しながら検索する（WebAssembly用）
+///
+/// 関数にはパスが渡され、ファイルの内容を文字列で返す必要がある。すべてのファイルの内容を
+/// 先に配列に詰めずに済むため、大量のファイルを検索する場合でもメモリに載るのは1ファイル分
+/// だけになる。関数が例外を投げた場合や文字列以外を返した場合は、検索を中止してエラーを返す。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `paths` - 検索するファイルのパスのリスト
+/// * `provider` - パスからファイルの内容を返す関数
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search_provider(
+    pattern: &str,
+    paths: Vec<String>,
+    provider: &js_sys::Function,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_options = parse_options(options)?;
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+
+    let output = simple_find_core::search_provider(
+        pattern,
+        &paths,
+        &JsContentProvider(provider),
+        &core_options,
+    )
+    .map_err(search_error)?;
+
+    to_js(&WasmSearchOutput::from(output))
+}
+
+/// パターンを1回だけコンパイルして何度でも検索に使える検索器（WebAssembly用）
+///
+/// インクリメンタル検索のように同じパターンで繰り返し検索する場合に、
+/// `search_with_options` のように呼ぶたびにコンパイルし直すのを避けられる。
+#[wasm_bindgen]
+pub struct Searcher {
+    inner: CoreSearcher,
+}
+
+#[wasm_bindgen]
+impl Searcher {
+    /// パターンをコンパイルして検索器を作る
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - 検索する正規表現パターン
+    /// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+    ///
+    /// # Returns
+    ///
+    /// 検索器、またはエラー
+    #[wasm_bindgen(constructor)]
+    pub fn new(pattern: &str, options: &JsValue) -> Result<Searcher, JsValue> {
+        let core_options = parse_options(options)?;
+        let inner = CoreSearcher::new(pattern, &core_options).map_err(search_error)?;
+
+        Ok(Searcher { inner })
+    }
+
+    /// 複数のパターンを `search_multi` と同じようにまとめてコンパイルして検索器を作る
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - 検索する正規表現パターンのリスト（JSON形式）
+    /// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+    ///
+    /// # Returns
+    ///
+    /// 検索器、またはエラー
+    pub fn multi(patterns: &JsValue, options: &JsValue) -> Result<Searcher, JsValue> {
+        let patterns: Vec<String> = serde_wasm_bindgen::from_value(patterns.clone())
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize patterns: {}", e)))?;
+        let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+        let core_options = parse_options(options)?;
+        let inner = CoreSearcher::multi(&patterns, &core_options).map_err(search_error)?;
+
+        Ok(Searcher { inner })
+    }
+
+    /// コンパイル済みのパターンでファイルを検索する
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - 検索対象のファイルリスト（JSON形式）
+    ///
+    /// # Returns
+    ///
+    /// 検索結果と打ち切り情報（JSON形式）、またはエラー
+    pub fn search(&self, files: &JsValue) -> Result<JsValue, JsValue> {
+        let core_files = parse_files(files)?;
+
+        to_js(&WasmSearchOutput::from(self.inner.search(&core_files)))
+    }
+}
+
+/// ファイルの内容を少しずつ受け取りながら検索する（WebAssembly用）
+///
+/// `fetch` のストリームなどから読み込んだ巨大なファイルを、全体を文字列にせずに検索できる。
+/// 行の途中で切れたチャンクも、次のチャンクと合わせて正しい行番号で検索する。
+#[wasm_bindgen]
+pub struct ChunkedSearch {
+    inner: CoreChunkedSearch,
+}
+
+#[wasm_bindgen]
+impl ChunkedSearch {
+    /// パターンをコンパイルして、1ファイル分の検索を始める
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - 検索する正規表現パターン
+    /// * `path` - 結果に入れるファイルのパス
+    /// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+    ///
+    /// # Returns
+    ///
+    /// 検索の状態、またはエラー
+    #[wasm_bindgen(constructor)]
+    pub fn new(pattern: &str, path: &str, options: &JsValue) -> Result<ChunkedSearch, JsValue> {
+        let core_options = parse_options(options)?;
+        let inner = CoreChunkedSearch::new(pattern, path, &core_options).map_err(search_error)?;
+
+        Ok(ChunkedSearch { inner })
+    }
+
+    /// 内容の続きを受け取り、終わりまで揃った行の検索結果を返す
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - 内容の続き
+    ///
+    /// # Returns
+    ///
+    /// 新しく見つかった検索結果のリスト（JSON形式）、またはエラー
+    pub fn push_chunk(&mut self, chunk: &str) -> Result<JsValue, JsValue> {
+        to_js_results(self.inner.push_chunk(chunk))
+    }
+
+    /// 持ち越していた最後の行を検索し、その結果を返す
+    ///
+    /// # Returns
+    ///
+    /// 新しく見つかった検索結果のリスト（JSON形式）、またはエラー
+    pub fn finish(&mut self) -> Result<JsValue, JsValue> {
+        to_js_results(self.inner.finish())
+    }
+
+    /// 上限に達してまだマッチが残っていたため、検索を打ち切ったかどうかを返す
+    pub fn truncated(&self) -> bool {
+        self.inner.truncated()
+    }
+
+    /// `timeout_ms` の時間を過ぎたため、検索を打ち切ったかどうかを返す
+    pub fn timed_out(&self) -> bool {
+        self.inner.timed_out()
+    }
+}
+
+/// 編集のたびに変わった部分だけを検索し直す1ファイル分の検索結果（WebAssembly用）
+///
+/// エディタの変更イベント（CodeMirror 6 の `from` / `to`、Monaco の `rangeOffset` /
+/// `rangeLength` など）をそのまま `edit` に渡せるよう、位置は UTF-16 のコードユニットで指定する。
+#[wasm_bindgen]
+pub struct IncrementalSearch {
+    inner: CoreIncrementalSearch,
+}
+
+#[wasm_bindgen]
+impl IncrementalSearch {
+    /// パターンをコンパイルしてファイル全体を検索する
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - 検索する正規表現パターン
+    /// * `path` - ファイルのパス
+    /// * `content` - ファイルの内容
+    /// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+    ///
+    /// # Returns
+    ///
+    /// 検索結果を持つ状態、またはエラー
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        pattern: &str,
+        path: &str,
+        content: String,
+        options: &JsValue,
+    ) -> Result<IncrementalSearch, JsValue> {
+        let core_options = parse_options(options)?;
+        let file = FileInput {
+            path: path.to_string(),
+            content,
+        };
+        let inner =
+            CoreIncrementalSearch::new(pattern, file, &core_options).map_err(search_error)?;
+
+        Ok(IncrementalSearch { inner })
+    }
+
+    /// 現在の内容の検索結果を返す
+    ///
+    /// # Returns
+    ///
+    /// 検索結果のリスト（JSON形式）、またはエラー
+    pub fn results(&self) -> Result<JsValue, JsValue> {
+        to_js_results(self.inner.results().to_vec())
+    }
+
+    /// 編集を適用した現在の内容を返す
+    pub fn content(&self) -> String {
+        self.inner.content().to_string()
+    }
+
+    /// 内容の `from` から `to` までを `new_text` に置き換え、検索結果を更新する
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - 置き換える範囲の開始位置（UTF-16 のコードユニット単位）
+    /// * `to` - 置き換える範囲の終了位置（UTF-16 のコードユニット単位）
+    /// * `new_text` - 置き換える文字列
+    ///
+    /// # Returns
+    ///
+    /// 範囲が無効な場合のエラー
+    pub fn edit(&mut self, from: u32, to: u32, new_text: &str) -> Result<(), JsValue> {
+        let content = self.inner.content();
+        let start = utf16_to_byte_offset(content, from as usize);
+        let end = utf16_to_byte_offset(content, to as usize);
+        self.inner.edit(start..end, new_text).map_err(search_error)
+    }
+}
+
+/// UTF-16 のコードユニット単位の位置をバイトオフセットに変換する
+///
+/// サロゲートペアの途中を指す位置や内容より後ろの位置は、そのまま変換できない値にして
+/// コアの範囲の検証でエラーにする。
+fn utf16_to_byte_offset(content: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (idx, c) in content.char_indices() {
+        if units >= offset {
+            return if units == offset { idx } else { usize::MAX };
+        }
+        units += c.len_utf16();
+    }
+    if units == offset {
+        content.len()
+    } else {
+        usize::MAX
+    }
+}
+
+/// オプションを指定してパターンでファイルを検索し、結果をファイルごとにまとめて返す（WebAssembly用）
+///
+/// 結果ごとにパスを持たないため、JavaScript に渡すデータが小さくなる。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// ファイルごとの検索結果と打ち切り情報（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search_grouped(
+    pattern: &str,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let output = simple_find_core::search_grouped(pattern, &core_files, &core_options)
+        .map_err(search_error)?;
+
+    to_js(&WasmSearchOutput::<WasmFileMatches>::from(output))
+}
+
+/// 複数のパターンでファイルを一度に検索する（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `patterns` - 検索する正規表現パターンのリスト（文字列の配列）
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 検索結果と打ち切り情報（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search_multi(
+    patterns: &JsValue,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let patterns: Vec<String> = serde_wasm_bindgen::from_value(patterns.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize patterns: {}", e)))?;
+    let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let output = simple_find_core::search_multi(&patterns, &core_files, &core_options)
+        .map_err(search_error)?;
+
+    to_js(&WasmSearchOutput::from(output))
+}
+
+/// 複数のパターンでファイルを検索し、行ごとの強調表示する範囲を返す（WebAssembly用）
+///
+/// 同じ行のマッチが重なっていても、範囲は列番号順に並べて1つにまとめてある。
+///
+/// # Arguments
+///
+/// * `patterns` - 検索する正規表現パターンのリスト（文字列の配列）
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 行ごとの強調表示する範囲のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search_highlights(
+    patterns: &JsValue,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let patterns: Vec<String> = serde_wasm_bindgen::from_value(patterns.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize patterns: {}", e)))?;
+    let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let output = simple_find_core::search_multi(&patterns, &core_files, &core_options)
+        .map_err(search_error)?;
+    let highlights: Vec<WasmLineHighlights> = simple_find_core::highlight_spans(&output.results)
+        .into_iter()
+        .map(WasmLineHighlights::from)
+        .collect();
+
+    to_js(&highlights)
+}
+
+/// 単語リストでファイルを検索し、各マッチがどの語によるものかを返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `terms` - 検索する語のリスト（JSON形式）
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 語の位置と語を付けた検索結果のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn search_terms(
+    terms: &JsValue,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let terms: Vec<String> = serde_wasm_bindgen::from_value(terms.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize terms: {}", e)))?;
+    let terms: Vec<&str> = terms.iter().map(String::as_str).collect();
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let matches =
+        simple_find_core::search_terms(&terms, &core_files, &core_options).map_err(search_error)?;
+    let wasm_matches: Vec<WasmTermMatch> = matches.into_iter().map(WasmTermMatch::from).collect();
+
+    to_js(&wasm_matches)
+}
+
+/// fzf のようにパターンの文字を順番どおりに含む行をスコア順に返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する文字の並び（正規表現ではない）
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// スコア順の検索結果のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn fuzzy_search(pattern: &str, files: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let matches = simple_find_core::fuzzy_search(pattern, &core_files, &core_options)
+        .map_err(search_error)?;
+    let wasm_matches: Vec<WasmFuzzyMatch> = matches.into_iter().map(WasmFuzzyMatch::from).collect();
+
+    to_js(&wasm_matches)
+}
+
+/// パターンにマッチした部分を置換したファイルの内容を返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト（JSON形式）
+/// * `replacement` - マッチした部分と置き換える文字列（`$1` や `${name}` でキャプチャグループを参照でき、
+///   `\U`、`\L`、`\E`、`\u`、`\l` で大文字小文字を変換できる）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 置換後のファイルのリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn replace(
+    pattern: &str,
+    files: &JsValue,
+    replacement: &str,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let replaced = simple_find_core::replace(pattern, &core_files, replacement, &core_options)
+        .map_err(replace_error)?;
+    let wasm_files: Vec<WasmReplacedFile> =
+        replaced.into_iter().map(WasmReplacedFile::from).collect();
+
+    to_js(&wasm_files)
+}
+
+/// `replace` と同じ置換を置換箇所のリストとして返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト（JSON形式）
+/// * `replacement` - マッチした部分と置き換える文字列（`replace` と同じ書式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 置換箇所のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn replace_edits(
+    pattern: &str,
+    files: &JsValue,
+    replacement: &str,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let edits = simple_find_core::replace_edits(pattern, &core_files, replacement, &core_options)
+        .map_err(replace_error)?;
+    let wasm_edits: Vec<WasmTextEdit> = edits.into_iter().map(WasmTextEdit::from).collect();
+
+    to_js(&wasm_edits)
+}
+
+/// 検索結果の行テキストを、マッチ部分を `<mark>` で囲んだ HTML の断片に変換する（WebAssembly用）
+///
+/// 行のテキストはエスケープされるため、結果をそのまま `innerHTML` に設定できる。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト（`search_with_options` などの `results` と同じ形式）
+/// * `mark_class` - `<mark>` に付けるクラス名（`undefined` の場合は付けない）
+///
+/// # Returns
+///
+/// 結果ごとの HTML の断片のリスト（文字列の配列）、またはエラー
+#[wasm_bindgen]
+pub fn render_html(results: &JsValue, mark_class: Option<String>) -> Result<JsValue, JsValue> {
+    let html: Vec<String> = parse_results(results)?
+        .iter()
+        .map(|result| simple_find_core::render_html(result, mark_class.as_deref()))
+        .collect();
+
+    to_js(&html)
+}
+
+/// 検索結果を Monaco Editor と CodeMirror 6 で装飾に使う範囲に変換する（WebAssembly用）
+///
+/// 位置はファイルの内容から UTF-16 単位で求めるため、`column_unit` に関わらず
+/// `monaco` は `deltaDecorations` に、`from` / `to` は `Decoration.mark(...).range(from, to)` に渡せる。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト（`search_with_options` などの `results` と同じ形式）
+/// * `files` - 検索したファイルのリスト（JSON形式）
+///
+/// # Returns
+///
+/// 検索結果の順の範囲のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn editor_ranges(results: &JsValue, files: &JsValue) -> Result<JsValue, JsValue> {
+    let core_results = parse_results(results)?;
+    let core_files = parse_files(files)?;
+
+    let ranges: Vec<WasmEditorRange> = simple_find_core::editor_ranges(&core_results, &core_files)
+        .into_iter()
+        .map(WasmEditorRange::from)
+        .collect();
+
+    to_js(&ranges)
+}
+
+/// 検索結果を指定した順に並べ替える（WebAssembly用）
+///
+/// Web Worker ごとに検索した結果をつなげてから呼ぶと、1回で検索した場合と同じ順になる。
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト（`search_with_options` などの `results` と同じ形式）
+/// * `order` - 並び順（`"input"`、`"path"`、`"match_count"`、`"relevance"` のいずれか）
+///
+/// # Returns
+///
+/// 並べ替えた検索結果のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn sort_results(results: &JsValue, order: &JsValue) -> Result<JsValue, JsValue> {
+    let mut core_results = parse_results(results)?;
+    let order: WasmSortOrder = serde_wasm_bindgen::from_value(order.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize order: {}", e)))?;
+
+    simple_find_core::sort_results(&mut core_results, order.into());
+    let results: Vec<WasmMatchResult> = core_results.into_iter().map(Into::into).collect();
+    to_js(&results)
+}
+
+/// 2回の検索結果を比べ、増えた結果と減った結果を返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `before` - 前の検索結果のリスト（`search_with_options` などの `results` と同じ形式）
+/// * `after` - 後の検索結果のリスト（同上）
+///
+/// # Returns
+///
+/// 増えた結果・減った結果・変わらなかった結果の数（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn diff_results(before: &JsValue, after: &JsValue) -> Result<JsValue, JsValue> {
+    let before = parse_results(before)?;
+    let after = parse_results(after)?;
+
+    let diff = simple_find_core::diff_results(&before, &after);
+    to_js(&WasmResultDiff {
+        added: diff.added.into_iter().map(Into::into).collect(),
+        removed: diff.removed.into_iter().map(Into::into).collect(),
+        unchanged: diff.unchanged,
+    })
+}
+
+/// パターンが検索に使えるかどうかを確かめる（WebAssembly用）
+///
+/// 入力中のパターンを検証するためのもので、無効なパターンでも例外を投げずに
+/// `valid: false` と理由・列番号を返す。列番号は `column_unit`（デフォルトは UTF-16）に従うため、
+/// 検索ボックスの文字列の `column - 1` から `end_column - 1` までに下線を引ける。
+///
+/// # Arguments
+///
+/// * `pattern` - 確かめるパターン
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 検証結果（JSON形式）、またはエラー（オプションが不正な場合）
+#[cfg(feature = "validate")]
+#[wasm_bindgen]
+pub fn validate_pattern(pattern: &str, options: &JsValue) -> Result<JsValue, JsValue> {
+    let core_options = parse_options(options)?;
+
+    let validation = match simple_find_core::validate_pattern(pattern, &core_options) {
+        Ok(info) => WasmPatternValidation {
+            valid: true,
+            info: Some(info.into()),
+            error: None,
+        },
+        Err(error) => WasmPatternValidation {
+            valid: false,
+            info: None,
+            error: Some(error.into()),
+        },
+    };
+    to_js(&validation)
+}
+
+/// コンパイル済みの正規表現をキャッシュしておく数を設定する（WebAssembly用）
+///
+/// 同じパターンとフラグで続けて検索する場合はコンパイルを省く。`0` でキャッシュを無効にする。
+///
+/// # Arguments
+///
+/// * `capacity` - キャッシュしておくパターンの数（デフォルトは 16）
+#[wasm_bindgen]
+pub fn set_regex_cache_capacity(capacity: usize) {
+    simple_find_core::set_regex_cache_capacity(capacity);
+}
+
+/// キャッシュしておいたコンパイル済みの正規表現をすべて捨てる（WebAssembly用）
+#[wasm_bindgen]
+pub fn clear_regex_cache() {
+    simple_find_core::clear_regex_cache();
+}
+
+/// このビルドで使える正規表現エンジンと、それぞれが対応している構文を返す（WebAssembly用）
+///
+/// # Returns
+///
+/// エンジンごとの対応している構文と性質のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn regex_engines() -> Result<JsValue, JsValue> {
+    let engines: Vec<WasmEngineFeatures> = RegexEngine::available()
+        .iter()
+        .map(|&engine| engine.into())
+        .collect();
+    to_js(&engines)
+}
+
+/// 文字列をそのまま検索する正規表現パターンに変換する（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `text` - 検索する文字列
+///
+/// # Returns
+///
+/// 正規表現の記号をエスケープしたパターン
+#[wasm_bindgen]
+pub fn escape_literal(text: &str) -> String {
+    simple_find_core::escape_literal(text)
+}
+
+/// グロブ（`*.log` など）を同じ意味の正規表現パターンに変換する（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `glob` - 変換するグロブ（`*`、`**`、`?`、`[...]`、`{a,b}` に対応）
+///
+/// # Returns
+///
+/// 正規表現パターン
+#[wasm_bindgen]
+pub fn glob_to_regex(glob: &str) -> String {
+    simple_find_core::glob_to_regex(glob)
+}
+
+/// パターンを解析し、要素ごとの説明の木を返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 説明するパターン
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 説明の木の根（JSON形式）、またはエラー（パターンが無効な場合は `validate_pattern` の `error` と同じ形式）
+#[cfg(feature = "explain")]
+#[wasm_bindgen]
+pub fn explain_pattern(pattern: &str, options: &JsValue) -> Result<JsValue, JsValue> {
+    let core_options = parse_options(options)?;
+
+    match simple_find_core::explain_pattern(pattern, &core_options) {
+        Ok(root) => to_js(&WasmPatternNode::from(root)),
+        Err(error) => Err(to_js(&WasmPatternError::from(error))?),
+    }
+}
+
+/// 検索結果をファイルごとに行の区間に分けて数える（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `results` - 検索結果のリスト（`search_with_options` などの `results` と同じ形式）
+/// * `files` - 検索したファイルのリスト（JSON形式）
+/// * `buckets` - 区間の分け方（`{ "lines_per": 100 }` または `{ "count": 20 }`）
+///
+/// # Returns
+///
+/// ファイルごとのマッチの密度のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn match_density(
+    results: &JsValue,
+    files: &JsValue,
+    buckets: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_results = parse_results(results)?;
+    let core_files = parse_files(files)?;
+    let buckets: WasmDensityBuckets = serde_wasm_bindgen::from_value(buckets.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize buckets: {}", e)))?;
+
+    let density: Vec<WasmFileDensity> =
+        simple_find_core::match_density(&core_results, &core_files, buckets.into())
+            .into_iter()
+            .map(WasmFileDensity::from)
+            .collect();
+
+    to_js(&density)
+}
+
+/// マッチごとに JavaScript の関数を呼び出し、その戻り値で置換したファイルの内容を返す（WebAssembly用）
+///
+/// 関数には検索結果（`search_with_options` の結果と同じ形式）が渡され、文字列を返す必要がある。
+/// 関数が例外を投げた場合や文字列以外を返した場合は、置換を中止してエラーを返す。
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト（JSON形式）
+/// * `replacer` - マッチから置換後の文字列を返す関数
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 置換後のファイルのリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn replace_with(
+    pattern: &str,
+    files: &JsValue,
+    replacer: &js_sys::Function,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    // コアの関数はエラーを返せないため、最初のエラーを記録して後から返す
+    let error = RefCell::new(None);
+    let call = |m: &CoreMatchResult| -> Result<String, JsValue> {
+        let value = to_js(&WasmMatchResult::from(m))?;
+        replacer
+            .call1(&JsValue::NULL, &value)?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("Replacer must return a string"))
+    };
+    let replaced = simple_find_core::replace_with(
+        pattern,
+        &core_files,
+        |m| {
+            if error.borrow().is_some() {
+                return String::new();
+            }
+            call(m).unwrap_or_else(|e| {
+                *error.borrow_mut() = Some(e);
+                String::new()
+            })
+        },
+        &core_options,
+    )
+    .map_err(replace_error)?;
+    if let Some(e) = error.into_inner() {
+        return Err(e);
+    }
+    let wasm_files: Vec<WasmReplacedFile> =
+        replaced.into_iter().map(WasmReplacedFile::from).collect();
+
+    to_js(&wasm_files)
+}
+
+/// 置換を適用せずに、各ファイルの差分を unified diff 形式で返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 置換対象のファイルリスト（JSON形式）
+/// * `replacement` - マッチした部分と置き換える文字列（`replace` と同じ書式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// 置換箇所のあるファイルの差分のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn replace_preview(
+    pattern: &str,
+    files: &JsValue,
+    replacement: &str,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let previews =
+        simple_find_core::replace_preview(pattern, &core_files, replacement, &core_options)
+            .map_err(replace_error)?;
+    let wasm_previews: Vec<WasmReplacePreview> =
+        previews.into_iter().map(WasmReplacePreview::from).collect();
+
+    to_js(&wasm_previews)
+}
+
+/// パターンにマッチするファイルのパスを返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// マッチしたファイルのパスのリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn files_with_matches(
+    pattern: &str,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let paths = simple_find_core::files_with_matches(pattern, &core_files, &core_options)
+        .map_err(search_error)?;
+
+    to_js(&paths)
+}
+
+/// パターンに1件もマッチしないファイルのパスを返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// マッチしなかったファイルのパスのリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn files_without_match(
+    pattern: &str,
+    files: &JsValue,
+    options: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let paths = simple_find_core::files_without_match(pattern, &core_files, &core_options)
+        .map_err(search_error)?;
+
+    to_js(&paths)
+}
+
+/// ファイルごとのマッチ数を返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// `[path, count]` の組のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn count(pattern: &str, files: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let counts =
+        simple_find_core::count(pattern, &core_files, &core_options).map_err(search_error)?;
+
+    to_js(&counts)
+}
+
+/// マッチ数の多いファイルを最大 `n` 件返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+/// * `n` - 返すファイルの最大数
+///
+/// # Returns
+///
+/// マッチ数の多い順の `[path, count]` の組のリスト（JSON形式）、またはエラー
+#[wasm_bindgen]
+pub fn top_files(
+    pattern: &str,
+    files: &JsValue,
+    options: &JsValue,
+    n: usize,
+) -> Result<JsValue, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    let counts = simple_find_core::top_files(pattern, &core_files, &core_options, n)
+        .map_err(search_error)?;
+
+    to_js(&counts)
+}
+
+/// いずれかのファイルにマッチが存在するかどうかを返す（WebAssembly用）
+///
+/// # Arguments
+///
+/// * `pattern` - 検索する正規表現パターン
+/// * `files` - 検索対象のファイルリスト（JSON形式）
+/// * `options` - 検索オプション（JSON形式、`undefined` / `null` の場合はデフォルト値）
+///
+/// # Returns
+///
+/// マッチが存在するかどうか、またはエラー
+#[wasm_bindgen]
+pub fn exists(pattern: &str, files: &JsValue, options: &JsValue) -> Result<bool, JsValue> {
+    let core_files = parse_files(files)?;
+    let core_options = parse_options(options)?;
+
+    simple_find_core::exists(pattern, &core_files, &core_options).map_err(search_error)
+}
+
+/// JSの値をコアのファイル入力リストに変換する
+fn parse_results(results: &JsValue) -> Result<Vec<CoreMatchResult>, JsValue> {
+    let wasm_results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(results.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize results: {}", e)))?;
+
+    Ok(wasm_results
+        .into_iter()
+        .map(CoreMatchResult::from)
+        .collect())
+}
+
+fn parse_files(files: &JsValue) -> Result<Vec<FileInput>, JsValue> {
+    let wasm_files: Vec<WasmFileInput> = serde_wasm_bindgen::from_value(files.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize files: {}", e)))?;
+
+    Ok(wasm_files
+        .into_iter()
+        .map(|f| FileInput {
+            path: f.path,
+            content: f.content,
+        })
+        .collect())
+}
+
+/// JSの値をコアの検索オプションに変換する
+fn parse_options(options: &JsValue) -> Result<SearchOptions, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(WasmSearchOptions::default().into());
+    }
+
+    let wasm_options: WasmSearchOptions = serde_wasm_bindgen::from_value(options.clone())
+        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize options: {}", e)))?;
+
+    Ok(wasm_options.into())
+}
+
+/// コアの検索のエラーを JavaScript に投げる値に変換する
+fn search_error(e: SearchError) -> JsValue {
+    JsValue::from_str(&format!("Search error: {}", e))
+}
+
+/// コアの置換のエラーを JavaScript に投げる値に変換する
+fn replace_error(e: SearchError) -> JsValue {
+    JsValue::from_str(&format!("Replace error: {}", e))
+}
+
+/// 値をJSの値に変換する
+///
+/// マップは `Map` ではなく通常のオブジェクトとして出力する
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true))
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize results: {}", e)))
+}
+
+/// コアの検索結果をJSの値に変換する
+fn to_js_results(results: Vec<CoreMatchResult>) -> Result<JsValue, JsValue> {
+    let wasm_results: Vec<WasmMatchResult> =
+        results.into_iter().map(WasmMatchResult::from).collect();
+
+    to_js(&wasm_results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn create_test_files() -> JsValue {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, world!".to_string(),
+        }];
+        serde_wasm_bindgen::to_value(&files).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_basic_search_match() {
+        let files = create_test_files();
+        let result = search("world", &files, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "test.txt");
+        assert_eq!(results[0].line, 1);
+        assert_eq!(results[0].column, 8);
+        assert_eq!(results[0].line_text, "Hello, world!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_no_match() {
+        let files = create_test_files();
+        let result = search("foo", &files, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_case_insensitive_search() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("world", &files_js, false).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line_text, "Hello, WORLD!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_case_sensitive_search() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("world", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_multiline_file() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "Line 1\nLine 2\nLine 3".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("Line", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].line, 1);
+        assert_eq!(results[1].line, 2);
+        assert_eq!(results[2].line, 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_multiple_files() {
+        let files = vec![
+            WasmFileInput {
+                path: "file1.txt".to_string(),
+                content: "Hello from file1".to_string(),
+            },
+            WasmFileInput {
+                path: "file2.txt".to_string(),
+                content: "Hello from file2".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("Hello", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path, "file1.txt");
+        assert_eq!(results[1].path, "file2.txt");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_multiple_matches_same_line() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "foo bar foo baz".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("foo", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[1].column, 9);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_regex_pattern() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "abc123 def456".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search(r"\d+", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, 4);
+        assert_eq!(results[1].column, 11);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_match_text_and_end_column() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "abc123 def456".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search(r"\d+", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results[0].match_text, "123");
+        assert_eq!(results[0].end_column, 7);
+        assert_eq!(results[1].match_text, "456");
+        assert_eq!(results[1].end_column, 14);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_invalid_regex_pattern() {
+        let files = create_test_files();
+        let result = search("[", &files, true);
+
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().as_string().unwrap();
+        assert!(error_msg.contains("Search error"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_empty_file() {
+        let files = vec![WasmFileInput {
+            path: "empty.txt".to_string(),
+            content: "".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("test", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(results.len(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_invalid_json_input() {
+        let invalid_json = JsValue::from_str("not valid json");
+        let result = search("test", &invalid_json, true);
+
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().as_string().unwrap();
+        assert!(error_msg.contains("Failed to deserialize files"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_case_insensitive() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "Hello, WORLD!".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("world", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        let results = output.results;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 8);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_searcher_reuses_compiled_pattern() {
+        let options = WasmSearchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let searcher = Searcher::new("world", &options_js).unwrap();
+        for content in ["Hello, WORLD!", "world world"] {
+            let files = vec![WasmFileInput {
+                path: "test.txt".to_string(),
+                content: content.to_string(),
+            }];
+            let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+            let output: WasmSearchOutput =
+                serde_wasm_bindgen::from_value(searcher.search(&files_js).unwrap()).unwrap();
+            assert_eq!(output.results.len(), content.matches(['w', 'W']).count());
+        }
+        assert!(Searcher::new("(", &JsValue::UNDEFINED).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_chunked_search_across_chunks() {
+        let mut search = ChunkedSearch::new("needle", "big.log", &JsValue::UNDEFINED).unwrap();
+        let first: Vec<WasmMatchResult> =
+            serde_wasm_bindgen::from_value(search.push_chunk("a\nnee").unwrap()).unwrap();
+        assert!(first.is_empty());
+        let second: Vec<WasmMatchResult> =
+            serde_wasm_bindgen::from_value(search.push_chunk("dle\n").unwrap()).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].line, 2);
+        assert_eq!(second[0].path, "big.log");
+        let rest: Vec<WasmMatchResult> =
+            serde_wasm_bindgen::from_value(search.finish().unwrap()).unwrap();
+        assert!(rest.is_empty());
+        assert!(!search.truncated());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_incremental_search_edit_with_utf16_offsets() {
+        let mut search = IncrementalSearch::new(
+            "todo",
+            "a.txt",
+            "😀 todo\nok".to_string(),
+            &JsValue::UNDEFINED,
+        )
+        .unwrap();
+        // 😀 は UTF-16 で2コードユニット
+        search.edit(8, 10, "todo").unwrap();
+        assert_eq!(search.content(), "😀 todo\ntodo");
+        let results: Vec<WasmMatchResult> =
+            serde_wasm_bindgen::from_value(search.results().unwrap()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].line, 2);
+        assert!(search.edit(1, 1, "x").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_undefined_options() {
+        let files = create_test_files();
+        let result = search_with_options("world", &files, &JsValue::UNDEFINED).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        let results = output.results;
+
+        assert_eq!(results.len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_whole_word() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "cat concatenate".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            whole_word: true,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("cat", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        let results = output.results;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_literal() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "price ($)".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            literal: true,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("($)", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        let results = output.results;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 7);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_max_count_per_file() {
+        let files = vec![WasmFileInput {
+            path: "big.log".to_string(),
+            content: "error\nerror\nerror".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            max_count_per_file: Some(1),
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("error", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.truncated_files, vec!["big.log".to_string()]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_max_results() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "foo foo foo".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            max_results: Some(2),
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("foo", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(output.results.len(), 2);
+        assert!(output.truncated);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_max_result_bytes() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "a\nb\nc".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            max_result_bytes: Some(0),
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(output.results.len(), 1);
+        assert!(output.truncated);
+        assert_eq!(output.next_offset, Some(1));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_report() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "foo\nbar".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            report: true,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("foo", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+
+        let report = output.report.unwrap();
+        assert_eq!(report.files_scanned, 1);
+        assert_eq!(report.lines_scanned, 2);
+        assert_eq!(report.matches_found, 1);
+        assert!(report.files.is_empty());
+
+        let options = WasmSearchOptions {
+            profile: true,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("foo", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.report.unwrap().files[0].matches, 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_with_options_offset() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "foo foo foo".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            max_results: Some(2),
+            offset: 2,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("foo", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
 
-    let core_files: Vec<FileInput> = wasm_files
-        .into_iter()
-        .map(|f| FileInput {
-            path: f.path,
-            content: f.content,
-        })
-        .collect();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.results[0].column, 9);
+        assert_eq!(output.next_offset, None);
+    }
 
-    let results = simple_find_core::search(pattern, &core_files, case_sensitive)
-        .map_err(|e| JsValue::from_str(&format!("Search error: {}", e)))?;
+    #[wasm_bindgen_test]
+    fn test_search_with_options_context_lines() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "one\ntwo\nthree".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            before_context: 1,
+            after_context: 1,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("two", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
 
-    let wasm_results: Vec<WasmMatchResult> =
-        results.into_iter().map(WasmMatchResult::from).collect();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.results[0].context_before[0].text, "one");
+        assert_eq!(output.results[0].context_after[0].text, "three");
+    }
 
-    serde_wasm_bindgen::to_value(&wasm_results)
-        .map_err(|e| JsValue::from_str(&format!("Failed to serialize results: {}", e)))
-}
+    #[wasm_bindgen_test]
+    fn test_files_with_matches() {
+        let files = vec![
+            WasmFileInput {
+                path: "a.txt".to_string(),
+                content: "Hello".to_string(),
+            },
+            WasmFileInput {
+                path: "b.txt".to_string(),
+                content: "Bye".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = files_with_matches("Hello", &files_js, &JsValue::UNDEFINED).unwrap();
+        let paths: Vec<String> = serde_wasm_bindgen::from_value(result).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use wasm_bindgen_test::*;
+        assert_eq!(paths, vec!["a.txt".to_string()]);
+    }
 
-    fn create_test_files() -> JsValue {
+    #[wasm_bindgen_test]
+    fn test_files_without_match() {
         let files = vec![
             WasmFileInput {
-                path: "test.txt".to_string(),
-                content: "Hello, world!".to_string(),
+                path: "a.txt".to_string(),
+                content: "Hello".to_string(),
+            },
+            WasmFileInput {
+                path: "b.txt".to_string(),
+                content: "Bye".to_string(),
             },
         ];
-        serde_wasm_bindgen::to_value(&files).unwrap()
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = files_without_match("Hello", &files_js, &JsValue::UNDEFINED).unwrap();
+        let paths: Vec<String> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(paths, vec!["b.txt".to_string()]);
     }
 
     #[wasm_bindgen_test]
-    fn test_basic_search_match() {
+    fn test_count() {
+        let files = vec![WasmFileInput {
+            path: "a.txt".to_string(),
+            content: "foo foo\nfoo".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = count("foo", &files_js, &JsValue::UNDEFINED).unwrap();
+        let counts: Vec<(String, u64)> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(counts, vec![("a.txt".to_string(), 3)]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_top_files() {
+        let files = vec![
+            WasmFileInput {
+                path: "a.txt".to_string(),
+                content: "foo".to_string(),
+            },
+            WasmFileInput {
+                path: "b.txt".to_string(),
+                content: "foo foo".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = top_files("foo", &files_js, &JsValue::UNDEFINED, 1).unwrap();
+        let counts: Vec<(String, u64)> = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(counts, vec![("b.txt".to_string(), 2)]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_exists() {
         let files = create_test_files();
-        let result = search("world", &files, true).unwrap();
+
+        assert!(exists("world", &files, &JsValue::UNDEFINED).unwrap());
+        assert!(!exists("foo", &files, &JsValue::UNDEFINED).unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_capture_groups() {
+        let files = vec![WasmFileInput {
+            path: "Cargo.toml".to_string(),
+            content: "version = \"1.2.3\"".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search(r#"version = "([\d.]+)""#, &files_js, true).unwrap();
         let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].path, "test.txt");
-        assert_eq!(results[0].line, 1);
-        assert_eq!(results[0].column, 8);
-        assert_eq!(results[0].line_text, "Hello, world!");
+        assert_eq!(results[0].captures.len(), 1);
+        assert_eq!(results[0].captures[0].text, "1.2.3");
     }
 
     #[wasm_bindgen_test]
-    fn test_search_no_match() {
-        let files = create_test_files();
-        let result = search("foo", &files, true).unwrap();
+    fn test_named_capture_groups() {
+        let files = vec![WasmFileInput {
+            path: "config.ini".to_string(),
+            content: "mode=fast".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search(r"(?P<key>\w+)=(?P<value>\S+)", &files_js, true).unwrap();
         let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
 
-        assert_eq!(results.len(), 0);
+        assert_eq!(results[0].named_captures["key"], "mode");
+        assert_eq!(results[0].named_captures["value"], "fast");
     }
 
     #[wasm_bindgen_test]
-    fn test_case_insensitive_search() {
+    fn test_search_multi() {
+        let files = vec![WasmFileInput {
+            path: "app.log".to_string(),
+            content: "ERROR db WARN retry".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let patterns_js = serde_wasm_bindgen::to_value(&vec!["WARN", "ERROR"]).unwrap();
+        let result = search_multi(&patterns_js, &files_js, &JsValue::UNDEFINED).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[0].pattern_index, 1);
+        assert_eq!(output.results[1].pattern_index, 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_terms() {
+        let files = vec![WasmFileInput {
+            path: "doc.md".to_string(),
+            content: "Use the API via HTTP".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let terms_js = serde_wasm_bindgen::to_value(&vec!["HTTP", "API"]).unwrap();
+        let result = search_terms(&terms_js, &files_js, &JsValue::UNDEFINED).unwrap();
+        let matches: Vec<WasmTermMatch> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].term, "API");
+        assert_eq!(matches[0].term_index, 1);
+        assert_eq!(matches[1].result.column, 17);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fuzzy_search() {
+        let files = vec![WasmFileInput {
+            path: "main.rs".to_string(),
+            content: "fn search_with_options() {}\nfn other() {}".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = fuzzy_search("swo", &files_js, &JsValue::UNDEFINED).unwrap();
+        let matches: Vec<WasmFuzzyMatch> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].positions, vec![4, 11, 16]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_replace() {
         let files = vec![WasmFileInput {
             path: "test.txt".to_string(),
-            content: "Hello, WORLD!".to_string(),
+            content: "Hello World\nhello again".to_string(),
         }];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search("world", &files_js, false).unwrap();
-        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        let options = WasmSearchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = replace("hello", &files_js, "Bye", &options_js).unwrap();
+        let replaced: Vec<WasmReplacedFile> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(replaced[0].content, "Bye World\nBye again");
+        assert_eq!(replaced[0].replacements, 2);
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].line_text, "Hello, WORLD!");
+        let result = replace(r"(\w+) (?P<rest>\w+)", &files_js, "${rest} $1", &options_js).unwrap();
+        let replaced: Vec<WasmReplacedFile> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(replaced[0].content, "World Hello\nagain hello");
     }
 
     #[wasm_bindgen_test]
-    fn test_case_sensitive_search() {
+    fn test_replace_edits() {
         let files = vec![WasmFileInput {
             path: "test.txt".to_string(),
-            content: "Hello, WORLD!".to_string(),
+            content: "😀 old\nold".to_string(),
         }];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search("world", &files_js, true).unwrap();
-        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        let result = replace_edits("old", &files_js, "new", &JsValue::UNDEFINED).unwrap();
+        let edits: Vec<WasmTextEdit> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(edits.len(), 2);
+        // 絵文字はサロゲートペアで2つに数える
+        assert_eq!((edits[0].start_line, edits[0].start_col), (1, 4));
+        assert_eq!((edits[1].end_line, edits[1].end_col), (2, 4));
+        assert_eq!(edits[1].new_text, "new");
+    }
 
-        assert_eq!(results.len(), 0);
+    #[wasm_bindgen_test]
+    fn test_search_provider() {
+        let provider = js_sys::Function::new_with_args(
+            "path",
+            "if (path === 'a.txt') return 'foo\\nbar foo'; return 1;",
+        );
+        let paths = vec!["a.txt".to_string()];
+        let result = search_provider("foo", paths, &provider, &JsValue::UNDEFINED).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[1].line, 2);
+
+        let paths = vec!["b.txt".to_string()];
+        assert!(search_provider("foo", paths, &provider, &JsValue::UNDEFINED).is_err());
     }
 
     #[wasm_bindgen_test]
-    fn test_multiline_file() {
+    fn test_replace_with() {
         let files = vec![WasmFileInput {
             path: "test.txt".to_string(),
-            content: "Line 1\nLine 2\nLine 3".to_string(),
+            content: "a1 b22".to_string(),
         }];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search("Line", &files_js, true).unwrap();
-        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        let replacer = js_sys::Function::new_with_args("m", "return String(m.match_text.length);");
+        let result = replace_with(r"\d+", &files_js, &replacer, &JsValue::UNDEFINED).unwrap();
+        let replaced: Vec<WasmReplacedFile> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(replaced[0].content, "a1 b2");
 
-        assert_eq!(results.len(), 3);
-        assert_eq!(results[0].line, 1);
-        assert_eq!(results[1].line, 2);
-        assert_eq!(results[2].line, 3);
+        let replacer = js_sys::Function::new_with_args("m", "return 1;");
+        assert!(replace_with(r"\d+", &files_js, &replacer, &JsValue::UNDEFINED).is_err());
     }
 
     #[wasm_bindgen_test]
-    fn test_multiple_files() {
+    fn test_replace_preview() {
         let files = vec![
             WasmFileInput {
-                path: "file1.txt".to_string(),
-                content: "Hello from file1".to_string(),
+                path: "a.txt".to_string(),
+                content: "foo\nbar\n".to_string(),
             },
             WasmFileInput {
-                path: "file2.txt".to_string(),
-                content: "Hello from file2".to_string(),
+                path: "b.txt".to_string(),
+                content: "bar\n".to_string(),
             },
         ];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search("Hello", &files_js, true).unwrap();
-        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
-
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0].path, "file1.txt");
-        assert_eq!(results[1].path, "file2.txt");
+        let result = replace_preview("foo", &files_js, "baz", &JsValue::UNDEFINED).unwrap();
+        let previews: Vec<WasmReplacePreview> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(previews.len(), 1);
+        assert_eq!(
+            previews[0].diff,
+            "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-foo\n+baz\n bar\n"
+        );
     }
 
     #[wasm_bindgen_test]
-    fn test_multiple_matches_same_line() {
+    fn test_preview_width() {
         let files = vec![WasmFileInput {
             path: "test.txt".to_string(),
-            content: "foo bar foo baz".to_string(),
+            content: "0123456789 ERROR 0123456789".to_string(),
         }];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search("foo", &files_js, true).unwrap();
-        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        let options = WasmSearchOptions {
+            preview_width: Some(11),
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("ERROR", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results[0].line_text, "…89 ERROR 01…");
+        assert_eq!(output.results[0].line_text_column, 5);
+        assert!(output.results[0].line_truncated);
+    }
 
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0].column, 1);
-        assert_eq!(results[1].column, 9);
+    #[wasm_bindgen_test]
+    fn test_search_highlights() {
+        let files = vec![WasmFileInput {
+            path: "a.txt".to_string(),
+            content: "foobar baz".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let patterns_js = serde_wasm_bindgen::to_value(&vec!["foo", "oba", "baz"]).unwrap();
+        let result = search_highlights(&patterns_js, &files_js, &JsValue::UNDEFINED).unwrap();
+        let lines: Vec<WasmLineHighlights> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans, vec![(1, 6), (8, 11)]);
     }
 
     #[wasm_bindgen_test]
-    fn test_regex_pattern() {
+    fn test_editor_ranges() {
         let files = vec![WasmFileInput {
-            path: "test.txt".to_string(),
-            content: "abc123 def456".to_string(),
+            path: "a.txt".to_string(),
+            content: "first\r\n😀 target".to_string(),
         }];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search(r"\d+", &files_js, true).unwrap();
-        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        let result = search("target", &files_js, true).unwrap();
+        let ranges = editor_ranges(&result, &files_js).unwrap();
+        let ranges: Vec<WasmEditorRange> = serde_wasm_bindgen::from_value(ranges).unwrap();
+        assert_eq!(ranges[0].monaco.start_line_number, 2);
+        assert_eq!(ranges[0].monaco.start_column, 4);
+        assert_eq!((ranges[0].from, ranges[0].to), (9, 15));
+    }
 
-        assert_eq!(results.len(), 2);
-        assert_eq!(results[0].column, 4);
-        assert_eq!(results[1].column, 11);
+    #[wasm_bindgen_test]
+    fn test_sort_results() {
+        let files = vec![
+            WasmFileInput {
+                path: "b.txt".to_string(),
+                content: "x".to_string(),
+            },
+            WasmFileInput {
+                path: "a.txt".to_string(),
+                content: "x".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("x", &files_js, true).unwrap();
+        let order = serde_wasm_bindgen::to_value(&WasmSortOrder::Path).unwrap();
+        let sorted = sort_results(&result, &order).unwrap();
+        let sorted: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(sorted).unwrap();
+        assert_eq!(sorted[0].path, "a.txt");
     }
 
     #[wasm_bindgen_test]
-    fn test_invalid_regex_pattern() {
-        let files = create_test_files();
-        let result = search("[", &files, true);
+    fn test_diff_results() {
+        let before = vec![WasmFileInput {
+            path: "a.txt".to_string(),
+            content: "TODO a\nTODO b".to_string(),
+        }];
+        let after = vec![WasmFileInput {
+            path: "a.txt".to_string(),
+            content: "x\nTODO b\nTODO c".to_string(),
+        }];
+        let before_js = serde_wasm_bindgen::to_value(&before).unwrap();
+        let after_js = serde_wasm_bindgen::to_value(&after).unwrap();
+        let old = search("TODO", &before_js, true).unwrap();
+        let new = search("TODO", &after_js, true).unwrap();
+        let diff = diff_results(&old, &new).unwrap();
+        let diff: WasmResultDiff = serde_wasm_bindgen::from_value(diff).unwrap();
+        assert_eq!(diff.added[0].line_text, "TODO c");
+        assert_eq!(diff.removed[0].line_text, "TODO a");
+        assert_eq!(diff.unchanged, 1);
+    }
 
-        assert!(result.is_err());
-        let error_msg = result.unwrap_err().as_string().unwrap();
-        assert!(error_msg.contains("Search error"));
+    #[cfg(feature = "validate")]
+    #[wasm_bindgen_test]
+    fn test_validate_pattern() {
+        let result = validate_pattern("😀(a", &JsValue::UNDEFINED).unwrap();
+        let validation: WasmPatternValidation = serde_wasm_bindgen::from_value(result).unwrap();
+        assert!(!validation.valid);
+        let error = validation.error.unwrap();
+        // 列番号は UTF-16 単位
+        assert_eq!((error.column, error.end_column), (Some(3), Some(4)));
+        assert_eq!(error.suggestions, vec![r"😀\(a", "😀(a)"]);
+
+        let result = validate_pattern(r"(?<id>\d+)", &JsValue::UNDEFINED).unwrap();
+        let validation: WasmPatternValidation = serde_wasm_bindgen::from_value(result).unwrap();
+        assert!(validation.valid);
+        assert_eq!(validation.info.unwrap().capture_names, vec!["id"]);
     }
 
+    #[cfg(feature = "explain")]
     #[wasm_bindgen_test]
-    fn test_empty_file() {
+    fn test_explain_pattern() {
+        let result = explain_pattern(r"a\d+", &JsValue::UNDEFINED).unwrap();
+        let root: WasmPatternNode = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(root.kind, WasmPatternNodeKind::Concat);
+        assert_eq!(
+            root.children[1].kind,
+            WasmPatternNodeKind::Repetition {
+                min: 1,
+                max: None,
+                greedy: true
+            }
+        );
+        assert_eq!(root.children[1].children[0].description, "a digit");
+
+        let error = explain_pattern("a(", &JsValue::UNDEFINED).unwrap_err();
+        let error: WasmPatternError = serde_wasm_bindgen::from_value(error).unwrap();
+        assert_eq!(error.column, Some(2));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_escape_literal_and_glob_to_regex() {
         let files = vec![WasmFileInput {
-            path: "empty.txt".to_string(),
-            content: "".to_string(),
+            path: "a.txt".to_string(),
+            content: "price ($)\nsee error.log".to_string(),
         }];
         let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
-        let result = search("test", &files_js, true).unwrap();
+
+        let result = search(&escape_literal("($)"), &files_js, true).unwrap();
         let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(results[0].line, 1);
 
-        assert_eq!(results.len(), 0);
+        let result = search(&glob_to_regex("*.log"), &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(results[0].match_text, "see error.log");
     }
 
     #[wasm_bindgen_test]
-    fn test_invalid_json_input() {
-        let invalid_json = JsValue::from_str("not valid json");
-        let result = search("test", &invalid_json, true);
+    fn test_match_density() {
+        let files = vec![WasmFileInput {
+            path: "a.txt".to_string(),
+            content: "x\n\n\nx x".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("x", &files_js, true).unwrap();
+        let buckets = serde_wasm_bindgen::to_value(&WasmDensityBuckets::Count(2)).unwrap();
+        let density = match_density(&result, &files_js, &buckets).unwrap();
+        let density: Vec<WasmFileDensity> = serde_wasm_bindgen::from_value(density).unwrap();
+        assert_eq!(density[0].counts, vec![1, 2]);
+    }
 
-        assert!(result.is_err());
-        let error_msg = result.unwrap_err().as_string().unwrap();
-        assert!(error_msg.contains("Failed to deserialize files"));
+    #[wasm_bindgen_test]
+    fn test_render_html() {
+        let files = vec![WasmFileInput {
+            path: "a.html".to_string(),
+            content: "<b>target</b>".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("target", &files_js, true).unwrap();
+        let html = render_html(&result, Some("hit".to_string())).unwrap();
+        let html: Vec<String> = serde_wasm_bindgen::from_value(html).unwrap();
+        assert_eq!(
+            html,
+            vec!["&lt;b&gt;<mark class=\"hit\">target</mark>&lt;/b&gt;"]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sort_by_match_count() {
+        let files = vec![
+            WasmFileInput {
+                path: "a.txt".to_string(),
+                content: "x".to_string(),
+            },
+            WasmFileInput {
+                path: "b.txt".to_string(),
+                content: "x x".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            sort: WasmSortOrder::MatchCount,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("x", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results[0].path, "b.txt");
+        assert_eq!(output.results[2].path, "a.txt");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_dedupe_line() {
+        let files = vec![WasmFileInput {
+            path: "a.txt".to_string(),
+            content: "x x\nx".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            dedupe: WasmDedupe::Line,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("x", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results.len(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_relevance_sort() {
+        let files = vec![
+            WasmFileInput {
+                path: "notes.txt".to_string(),
+                content: "a\nb\nc\ntodo".to_string(),
+            },
+            WasmFileInput {
+                path: "todo.md".to_string(),
+                content: "todo".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options = WasmSearchOptions {
+            sort: WasmSortOrder::Relevance,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("todo", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results[0].path, "todo.md");
+        assert!(output.results[0].score > output.results[1].score);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_grouped() {
+        let files = vec![
+            WasmFileInput {
+                path: "a.txt".to_string(),
+                content: "foo\nfoo".to_string(),
+            },
+            WasmFileInput {
+                path: "b.txt".to_string(),
+                content: "bar".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search_grouped("foo", &files_js, &JsValue::UNDEFINED).unwrap();
+        let output: WasmSearchOutput<WasmFileMatches> =
+            serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.results[0].path, "a.txt");
+        assert_eq!(output.results[0].matches[1].line, 2);
+        assert!(output.results[0].blocks.is_empty());
+
+        let options = WasmSearchOptions {
+            after_context: 1,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_grouped("foo", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput<WasmFileMatches> =
+            serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results[0].blocks.len(), 1);
+        assert_eq!(output.results[0].blocks[0].lines[1].matches, vec![1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_binary_files_are_skipped() {
+        let files = vec![
+            WasmFileInput {
+                path: "data.bin".to_string(),
+                content: "target\0\0\0".to_string(),
+            },
+            WasmFileInput {
+                path: "test.txt".to_string(),
+                content: "target".to_string(),
+            },
+        ];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let options_js = serde_wasm_bindgen::to_value(&WasmSearchOptions::default()).unwrap();
+        let result = search_with_options("target", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.binary_files, vec!["data.bin"]);
+
+        let options = WasmSearchOptions {
+            search_binary: true,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("target", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results.len(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_utf16_columns_by_default() {
+        let files = vec![WasmFileInput {
+            path: "test.txt".to_string(),
+            content: "😀 日本語 target".to_string(),
+        }];
+        let files_js = serde_wasm_bindgen::to_value(&files).unwrap();
+        let result = search("target", &files_js, true).unwrap();
+        let results: Vec<WasmMatchResult> = serde_wasm_bindgen::from_value(result).unwrap();
+        // JavaScript の `"😀 日本語 target".indexOf("target")` は 7
+        assert_eq!(results[0].column, 8);
+
+        let options = WasmSearchOptions {
+            column_unit: WasmColumnUnit::Bytes,
+            ..Default::default()
+        };
+        let options_js = serde_wasm_bindgen::to_value(&options).unwrap();
+        let result = search_with_options("target", &files_js, &options_js).unwrap();
+        let output: WasmSearchOutput = serde_wasm_bindgen::from_value(result).unwrap();
+        assert_eq!(output.results[0].column, 16);
     }
-}
\ No newline at end of file
+}